@@ -1,16 +1,33 @@
 
 use anyhow::{anyhow, Context, Result};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use html5ever::parse_document;
 use html5ever::tendril::TendrilSink;
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{Cursor, Read, Write};
 use std::path::PathBuf;
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Docx,
+    Odt,
+}
+
+impl OutputFormat {
+    /// Infers the format from `path`'s extension (case-insensitively), defaulting to `Docx` for
+    /// anything else so an unrecognized or missing extension still produces a usable document.
+    fn from_extension(path: &std::path::Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("odt") => OutputFormat::Odt,
+            _ => OutputFormat::Docx,
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 struct Args {
@@ -18,20 +35,40 @@ struct Args {
     #[arg(long)]
     html_file: PathBuf,
 
-    /// Output .docx path.
+    /// Output document path.
     #[arg(long)]
     out: PathBuf,
 
+    /// Output format: WordprocessingML (.docx) or OpenDocument Text (.odt). Defaults to whatever
+    /// `--out`'s file extension implies, falling back to .docx if that's not recognized.
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+
     /// Optional document title (currently unused; accepted for compatibility with the test harness).
     #[arg(long)]
     title: Option<String>,
+
+    /// Syntax-highlighting theme for fenced code blocks (e.g. "InspiredGitHub"). Only takes
+    /// effect when built with the `syntect` feature; otherwise code blocks stay plain monospace.
+    #[arg(long)]
+    highlight: Option<String>,
+
+    /// Insert an auto-generated Table of Contents field at the top of the document, built from
+    /// the Heading1/Heading2 outline levels. DOCX only; ignored for `--format odt`.
+    #[arg(long)]
+    toc: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct RunStyle {
     bold: bool,
     italic: bool,
     code: bool,
+    underline: bool,
+    strike: bool,
+    color: Option<String>,      // RRGGBB
+    highlight: Option<String>,  // RRGGBB background
+    size_half_pt: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -40,6 +77,12 @@ enum Segment {
     LinkText { text: String, style: RunStyle, href: String },
     Break,
     Omml(String),
+    Image {
+        data: Vec<u8>,
+        extension: String,
+        width_emu: i64,
+        height_emu: i64,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -50,10 +93,96 @@ enum ParagraphStyle {
     CodeBlock,
 }
 
+/// `<w:numFmt>` values a list level can render as. `Bullet` covers `<ul>`; the rest come from
+/// an `<ol type="...">` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListNumFmt {
+    Bullet,
+    Decimal,
+    LowerLetter,
+    UpperLetter,
+    LowerRoman,
+    UpperRoman,
+}
+
+impl ListNumFmt {
+    fn from_ol_type(type_attr: &str) -> Self {
+        match type_attr {
+            "a" => ListNumFmt::LowerLetter,
+            "A" => ListNumFmt::UpperLetter,
+            "i" => ListNumFmt::LowerRoman,
+            "I" => ListNumFmt::UpperRoman,
+            _ => ListNumFmt::Decimal,
+        }
+    }
+
+    fn docx_num_fmt(self) -> &'static str {
+        match self {
+            ListNumFmt::Bullet => "bullet",
+            ListNumFmt::Decimal => "decimal",
+            ListNumFmt::LowerLetter => "lowerLetter",
+            ListNumFmt::UpperLetter => "upperLetter",
+            ListNumFmt::LowerRoman => "lowerRoman",
+            ListNumFmt::UpperRoman => "upperRoman",
+        }
+    }
+
+    fn docx_lvl_text(self, ilvl: u32) -> String {
+        match self {
+            ListNumFmt::Bullet => "•".to_string(),
+            _ => format!("%{}.", ilvl + 1),
+        }
+    }
+}
+
+/// One `<ul>`/`<ol>` instance encountered while walking the HTML tree. Every instance gets its
+/// own `num_id` (allocated in `BuildCtx::next_list_num_id`) so sibling lists number
+/// independently instead of sharing restart state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct ListInfo {
-    num_id: u32, // 1 = bullet, 2 = decimal
-    ilvl: u32,   // nesting level
+    num_id: u32,
+    ilvl: u32, // nesting level
+    fmt: ListNumFmt,
+    start: u32, // from `<ol start="N">`, 1 if unset or for `<ul>`
+}
+
+/// A distinct `<w:num>`/`abstractNum` pair to emit into `word/numbering.xml`, collected from
+/// the `ListInfo` carried by the blocks that actually use it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ListDef {
+    num_id: u32,
+    fmt: ListNumFmt,
+    start: u32,
+}
+
+/// Allocates the `w:id`/`w:name` pairs `<w:bookmarkStart>`/`<w:bookmarkEnd>` need while writing
+/// `word/document.xml`, disambiguating headings whose slugified text collides (e.g. two
+/// "Overview" sections) by suffixing a running count onto the repeat.
+struct BookmarkState {
+    next_id: u32,
+    slug_counts: BTreeMap<String, u32>,
+}
+
+impl BookmarkState {
+    fn new() -> Self {
+        Self {
+            next_id: 1,
+            slug_counts: BTreeMap::new(),
+        }
+    }
+
+    fn allocate(&mut self, base_slug: &str) -> (u32, String) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let count = self.slug_counts.entry(base_slug.to_string()).or_insert(0);
+        *count += 1;
+        let name = if *count == 1 {
+            base_slug.to_string()
+        } else {
+            format!("{}-{}", base_slug, count)
+        };
+        (id, name)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -61,11 +190,19 @@ struct Paragraph {
     style: ParagraphStyle,
     list: Option<ListInfo>,
     segments: Vec<Segment>,
+    /// Language hint from `<code class="language-xyz">`/`data-lang` for `CodeBlock` paragraphs,
+    /// used to drive syntax highlighting. `None` for every other paragraph style.
+    code_lang: Option<String>,
+    /// Slugified heading text for Heading1/Heading2 paragraphs, giving intra-document
+    /// `href="#..."` links something to target. `None` for every other paragraph style.
+    heading_anchor: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 struct TableCell {
     paragraphs: Vec<Paragraph>,
+    col_span: u32,
+    row_span: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -180,6 +317,304 @@ fn attr_get(attrs: &[(String, String)], name: &str) -> Option<String> {
     None
 }
 
+fn parse_style_decls(style: &str) -> Vec<(String, String)> {
+    style
+        .split(';')
+        .filter_map(|decl| {
+            let mut parts = decl.splitn(2, ':');
+            let prop = parts.next()?.trim().to_ascii_lowercase();
+            let val = parts.next()?.trim().to_string();
+            if prop.is_empty() || val.is_empty() {
+                None
+            } else {
+                Some((prop, val))
+            }
+        })
+        .collect()
+}
+
+/// Normalizes a CSS `#rgb`/`#rrggbb`/`rgb(r, g, b)` color into bare uppercase `RRGGBB` hex, the
+/// form OOXML's `w:color`/`w:fill` attributes expect. Named CSS colors (e.g. `red`) aren't
+/// resolved since pasted HTML overwhelmingly carries hex or rgb() from its inline styles.
+fn css_color_to_hex(value: &str) -> Option<String> {
+    let v = value.trim();
+    if let Some(hex) = v.strip_prefix('#') {
+        if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Some(hex.to_ascii_uppercase());
+        }
+        if hex.len() == 3 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            let mut out = String::with_capacity(6);
+            for c in hex.chars() {
+                out.push(c);
+                out.push(c);
+            }
+            return Some(out.to_ascii_uppercase());
+        }
+        return None;
+    }
+    let inner = v.strip_prefix("rgb(")?.strip_suffix(')')?;
+    let parts: Vec<&str> = inner.split(',').map(|p| p.trim()).collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    let mut out = String::with_capacity(6);
+    for p in parts {
+        let n: u32 = p.parse().ok()?;
+        out.push_str(&format!("{:02X}", n.min(255)));
+    }
+    Some(out)
+}
+
+/// Converts a CSS `font-size` (`px` or `pt`) into OOXML half-points (`w:sz`'s unit), using the
+/// standard 96dpi CSS px-to-pt ratio (1px = 0.75pt).
+fn css_font_size_to_half_pt(value: &str) -> Option<u32> {
+    let v = value.trim().to_ascii_lowercase();
+    let (num_str, is_px) = if let Some(n) = v.strip_suffix("px") {
+        (n, true)
+    } else if let Some(n) = v.strip_suffix("pt") {
+        (n, false)
+    } else {
+        return None;
+    };
+    let num: f64 = num_str.trim().parse().ok()?;
+    let pt = if is_px { num * 0.75 } else { num };
+    if pt <= 0.0 {
+        return None;
+    }
+    Some((pt * 2.0).round() as u32)
+}
+
+/// Maps a handful of common `RRGGBB` colors to OOXML's closed `w:highlight` enum so plain
+/// `<mark>`/pure primary backgrounds render as a real highlight instead of shading; anything
+/// else falls back to `<w:shd>` in `run_properties_xml`.
+fn highlight_name_for_hex(hex: &str) -> Option<&'static str> {
+    match hex {
+        "FFFF00" => Some("yellow"),
+        "00FF00" => Some("green"),
+        "00FFFF" => Some("cyan"),
+        "FF00FF" => Some("magenta"),
+        "0000FF" => Some("blue"),
+        "FF0000" => Some("red"),
+        "000080" => Some("darkBlue"),
+        "008080" => Some("darkCyan"),
+        "008000" => Some("darkGreen"),
+        "800080" => Some("darkMagenta"),
+        "800000" => Some("darkRed"),
+        "808000" => Some("darkYellow"),
+        "808080" => Some("darkGray"),
+        "C0C0C0" => Some("lightGray"),
+        "000000" => Some("black"),
+        _ => None,
+    }
+}
+
+/// Decodes a base64 string (standard alphabet, `=` padding). Tolerant of embedded whitespace
+/// since `<img>` `data:` URIs are often hand-wrapped across lines in pasted HTML. There's no
+/// base64 crate in this workspace, so this is hand-rolled rather than pulling one in for a
+/// single call site.
+fn base64_decode(input: &str) -> Option<Vec<u8>> {
+    fn val(c: u8) -> Option<u8> {
+        match c {
+            b'A'..=b'Z' => Some(c - b'A'),
+            b'a'..=b'z' => Some(c - b'a' + 26),
+            b'0'..=b'9' => Some(c - b'0' + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let mut out = Vec::with_capacity(input.len() / 4 * 3);
+    let mut buf = [0u8; 4];
+    let mut buf_len = 0usize;
+    for b in input.bytes() {
+        if b.is_ascii_whitespace() {
+            continue;
+        }
+        if b == b'=' {
+            break;
+        }
+        let v = val(b)?;
+        buf[buf_len] = v;
+        buf_len += 1;
+        if buf_len == 4 {
+            out.push((buf[0] << 2) | (buf[1] >> 4));
+            out.push((buf[1] << 4) | (buf[2] >> 2));
+            out.push((buf[2] << 6) | buf[3]);
+            buf_len = 0;
+        }
+    }
+    match buf_len {
+        0 => {}
+        2 => out.push((buf[0] << 2) | (buf[1] >> 4)),
+        3 => {
+            out.push((buf[0] << 2) | (buf[1] >> 4));
+            out.push((buf[1] << 4) | (buf[2] >> 2));
+        }
+        _ => return None,
+    }
+    Some(out)
+}
+
+/// Maps an `<img>` extension/mime subtype onto the canonical extension this crate stores media
+/// under, so the same image referenced with different casing (`JPG` vs `jpeg`) dedups correctly.
+fn normalize_image_extension(ext: &str) -> Option<String> {
+    match ext.to_ascii_lowercase().as_str() {
+        "png" => Some("png".to_string()),
+        "jpg" | "jpeg" => Some("jpeg".to_string()),
+        "gif" => Some("gif".to_string()),
+        "bmp" => Some("bmp".to_string()),
+        _ => None,
+    }
+}
+
+fn png_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.len() < 24 || &data[0..8] != b"\x89PNG\r\n\x1a\n" {
+        return None;
+    }
+    let width = u32::from_be_bytes(data[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(data[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+fn gif_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.len() < 10 || (&data[0..6] != b"GIF87a" && &data[0..6] != b"GIF89a") {
+        return None;
+    }
+    let width = u16::from_le_bytes(data[6..8].try_into().ok()?) as u32;
+    let height = u16::from_le_bytes(data[8..10].try_into().ok()?) as u32;
+    Some((width, height))
+}
+
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.len() < 4 || data[0] != 0xFF || data[1] != 0xD8 {
+        return None;
+    }
+    let mut pos = 2usize;
+    while pos + 9 <= data.len() {
+        if data[pos] != 0xFF {
+            pos += 1;
+            continue;
+        }
+        let marker = data[pos + 1];
+        if marker == 0xD8 || marker == 0x01 || (0xD0..=0xD9).contains(&marker) {
+            pos += 2;
+            continue;
+        }
+        let seg_len = u16::from_be_bytes(data[pos + 2..pos + 4].try_into().ok()?) as usize;
+        let is_sof = (0xC0..=0xCF).contains(&marker)
+            && marker != 0xC4
+            && marker != 0xC8
+            && marker != 0xCC;
+        if is_sof {
+            if pos + 9 > data.len() {
+                return None;
+            }
+            let height = u16::from_be_bytes(data[pos + 5..pos + 7].try_into().ok()?) as u32;
+            let width = u16::from_be_bytes(data[pos + 7..pos + 9].try_into().ok()?) as u32;
+            return Some((width, height));
+        }
+        pos += 2 + seg_len;
+    }
+    None
+}
+
+/// Reads the intrinsic pixel dimensions straight out of an image's own header, for `<img>` tags
+/// pasted without explicit `width`/`height` attributes.
+fn image_intrinsic_size_px(data: &[u8]) -> Option<(u32, u32)> {
+    png_dimensions(data)
+        .or_else(|| gif_dimensions(data))
+        .or_else(|| jpeg_dimensions(data))
+}
+
+/// CSS px to OOXML EMUs, at the standard 96dpi assumption (1px = 9525 EMU).
+fn px_to_emu(px: u32) -> i64 {
+    px as i64 * 9525
+}
+
+fn parse_px_attr(attrs: &[(String, String)], name: &str) -> Option<u32> {
+    attr_get(attrs, name)?
+        .trim()
+        .trim_end_matches("px")
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .map(|v| v.round().max(0.0) as u32)
+}
+
+/// Resolves an `<img src>` into raw bytes plus a normalized extension. Handles `data:` URIs and
+/// local filesystem paths (`file://` or relative/absolute); `http(s)://` URLs are skipped since
+/// this CLI never makes network requests.
+fn fetch_image_bytes(src: &str) -> Option<(Vec<u8>, String)> {
+    if let Some(rest) = src.strip_prefix("data:image/") {
+        let (mime, rest) = rest.split_once(';')?;
+        let (encoding, data) = rest.split_once(',')?;
+        if encoding != "base64" {
+            return None;
+        }
+        let ext = normalize_image_extension(mime)?;
+        let bytes = base64_decode(data)?;
+        return Some((bytes, ext));
+    }
+    if src.starts_with("http://") || src.starts_with("https://") {
+        return None;
+    }
+    let path = src.strip_prefix("file://").unwrap_or(src);
+    let ext = normalize_image_extension(std::path::Path::new(path).extension()?.to_str()?)?;
+    let bytes = std::fs::read(path).ok()?;
+    Some((bytes, ext))
+}
+
+/// Builds a `Segment::Image` from an `<img>` tag's attributes, resolving its bytes, extension,
+/// and rendered size in EMUs. Falls back to the image's intrinsic pixel size when `width`/
+/// `height` attributes are absent (preserving aspect ratio if only one is given), and finally to
+/// a 300x200px placeholder size when neither the attributes nor the image header give us one.
+fn load_image_segment(attrs: &[(String, String)]) -> Option<Segment> {
+    let src = attr_get(attrs, "src")?;
+    let (data, extension) = fetch_image_bytes(&src)?;
+    let intrinsic = image_intrinsic_size_px(&data);
+
+    let attr_width = parse_px_attr(attrs, "width");
+    let attr_height = parse_px_attr(attrs, "height");
+
+    let (width_px, height_px) = match (attr_width, attr_height, intrinsic) {
+        (Some(w), Some(h), _) => (w, h),
+        (Some(w), None, Some((iw, ih))) if iw > 0 => (w, ((w as f64) * (ih as f64) / (iw as f64)).round() as u32),
+        (None, Some(h), Some((iw, ih))) if ih > 0 => (((h as f64) * (iw as f64) / (ih as f64)).round() as u32, h),
+        (Some(w), None, _) => (w, w),
+        (None, Some(h), _) => (h, h),
+        (None, None, Some((iw, ih))) => (iw, ih),
+        (None, None, None) => (300, 200),
+    };
+
+    Some(Segment::Image {
+        data,
+        extension,
+        width_emu: px_to_emu(width_px.max(1)),
+        height_emu: px_to_emu(height_px.max(1)),
+    })
+}
+
+/// Content-hash key used to dedup identical images embedded multiple times in the source HTML,
+/// mirroring how hyperlink `href`s are deduped via `BTreeMap` in `gather_hrefs`/`link_to_rid`.
+fn image_media_key(data: &[u8]) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    format!("image{:016x}", hasher.finish())
+}
+
+fn image_mime_for_extension(ext: &str) -> &'static str {
+    match ext {
+        "png" => "image/png",
+        "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        _ => "application/octet-stream",
+    }
+}
+
 fn collapse_ws(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
     let mut in_ws = false;
@@ -203,9 +638,16 @@ struct BuildCtx {
     italic_depth: u32,
     code_depth: u32,
     pre_depth: u32,
+    underline_depth: u32,
+    strike_depth: u32,
+    mark_depth: u32,
     link_stack: Vec<Option<String>>,
-    list_stack: Vec<u32>,            // 1 bullet, 2 decimal
+    list_stack: Vec<ListInfo>,
     li_list_stack: Vec<Option<ListInfo>>,
+    next_list_num_id: u32,
+    color_stack: Vec<Option<String>>,
+    highlight_stack: Vec<Option<String>>,
+    size_stack: Vec<Option<u32>>,
 }
 
 impl BuildCtx {
@@ -215,9 +657,16 @@ impl BuildCtx {
             italic_depth: 0,
             code_depth: 0,
             pre_depth: 0,
+            underline_depth: 0,
+            strike_depth: 0,
+            mark_depth: 0,
             link_stack: Vec::new(),
             list_stack: Vec::new(),
             li_list_stack: Vec::new(),
+            next_list_num_id: 1,
+            color_stack: Vec::new(),
+            highlight_stack: Vec::new(),
+            size_stack: Vec::new(),
         }
     }
 
@@ -228,6 +677,18 @@ impl BuildCtx {
     fn current_list(&self) -> Option<ListInfo> {
         self.li_list_stack.last().cloned().unwrap_or(None)
     }
+
+    fn current_color(&self) -> Option<&String> {
+        self.color_stack.iter().rev().find_map(|c| c.as_ref())
+    }
+
+    fn current_highlight(&self) -> Option<&String> {
+        self.highlight_stack.iter().rev().find_map(|c| c.as_ref())
+    }
+
+    fn current_size(&self) -> Option<u32> {
+        self.size_stack.iter().rev().find_map(|c| *c)
+    }
 }
 
 fn paragraph_has_content(p: &Paragraph) -> bool {
@@ -236,16 +697,51 @@ fn paragraph_has_content(p: &Paragraph) -> bool {
         Segment::LinkText { text, .. } => !text.trim().is_empty(),
         Segment::Break => true,
         Segment::Omml(_) => true,
+        Segment::Image { .. } => true,
     })
 }
 
+/// Concatenates a paragraph's text-bearing segments, for slugifying a heading into a bookmark
+/// name. Not used for rendering, so it's fine that it drops breaks/images/math.
+fn paragraph_plain_text(p: &Paragraph) -> String {
+    let mut out = String::new();
+    for seg in &p.segments {
+        match seg {
+            Segment::Text { text, .. } | Segment::LinkText { text, .. } => out.push_str(text),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Turns heading text into a `[a-z0-9-]` bookmark name, collapsing runs of non-alphanumerics
+/// into a single `-` the way most static-site heading slugs look (so a hand-written
+/// `href="#my-heading"` has a decent chance of matching without us seeing the source `id`).
+fn slugify_heading(text: &str) -> String {
+    let mut out = String::new();
+    for ch in text.trim().chars() {
+        if ch.is_alphanumeric() {
+            out.extend(ch.to_lowercase());
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_string()
+}
+
 fn flush_paragraph(blocks: &mut Vec<Block>, current: &mut Paragraph) {
     if paragraph_has_content(current) {
+        if matches!(current.style, ParagraphStyle::Heading1 | ParagraphStyle::Heading2) {
+            let slug = slugify_heading(&paragraph_plain_text(current));
+            current.heading_anchor = (!slug.is_empty()).then_some(slug);
+        }
         blocks.push(Block::Paragraph(current.clone()));
     }
     current.style = ParagraphStyle::Normal;
     current.list = None;
     current.segments.clear();
+    current.code_lang = None;
+    current.heading_anchor = None;
 }
 
 fn start_paragraph(
@@ -260,7 +756,7 @@ fn start_paragraph(
     current.segments.clear();
 }
 
-fn emit_text(current: &mut Paragraph, ctx: &BuildCtx, raw: &str) {
+fn emit_text(blocks: &mut Vec<Block>, current: &mut Paragraph, ctx: &BuildCtx, raw: &str) {
     if raw.is_empty() {
         return;
     }
@@ -285,20 +781,55 @@ fn emit_text(current: &mut Paragraph, ctx: &BuildCtx, raw: &str) {
         bold: ctx.bold_depth > 0,
         italic: ctx.italic_depth > 0,
         code: (ctx.code_depth > 0) || (ctx.pre_depth > 0),
+        underline: ctx.underline_depth > 0,
+        strike: ctx.strike_depth > 0,
+        color: ctx.current_color().cloned(),
+        highlight: ctx
+            .current_highlight()
+            .cloned()
+            .or_else(|| (ctx.mark_depth > 0).then(|| "FFFF00".to_string())),
+        size_half_pt: ctx.current_size(),
     };
 
     let push_text = |s: String, current: &mut Paragraph| {
         if let Some(href) = ctx.current_href() {
             current.segments.push(Segment::LinkText {
                 text: s,
-                style,
+                style: style.clone(),
                 href: href.to_string(),
             });
         } else {
-            current.segments.push(Segment::Text { text: s, style });
+            current.segments.push(Segment::Text {
+                text: s,
+                style: style.clone(),
+            });
         }
     };
 
+    if !preserve_space && (text.contains('$') || text.contains('\\')) {
+        let spans = split_latex_math_spans(&text);
+        if spans.iter().any(|s| !matches!(s, MathSpan::Text(_))) {
+            for span in spans {
+                match span {
+                    MathSpan::Text(t) => {
+                        if !t.is_empty() {
+                            push_text(t, current);
+                        }
+                    }
+                    MathSpan::Inline(tex) => {
+                        current.segments.push(Segment::Omml(latex_to_inline_omml(&tex)));
+                    }
+                    MathSpan::Display(tex) => {
+                        flush_paragraph(blocks, current);
+                        current.segments.push(Segment::Omml(latex_to_display_omml(&tex)));
+                        flush_paragraph(blocks, current);
+                    }
+                }
+            }
+            return;
+        }
+    }
+
     if preserve_space && text.contains('\n') {
         let mut first = true;
         for line in text.split('\n') {
@@ -318,6 +849,509 @@ fn emit_text(current: &mut Paragraph, ctx: &BuildCtx, raw: &str) {
     }
 }
 
+fn mathml_text_content(node: &Handle) -> String {
+    let mut out = String::new();
+    for c in node.children.borrow().iter() {
+        if let NodeData::Text { contents } = &c.data {
+            out.push_str(&contents.borrow());
+        }
+    }
+    out
+}
+
+fn mathml_element_children(node: &Handle) -> Vec<Handle> {
+    node.children
+        .borrow()
+        .iter()
+        .filter(|c| matches!(c.data, NodeData::Element { .. }))
+        .cloned()
+        .collect()
+}
+
+/// Recursively translates one MathML node (and its element children) into OMML. Unknown
+/// elements (e.g. `mstyle`, `mpadded`) aren't given their own mapping - we just recurse into
+/// their children so the `mi`/`mn`/`mo` runs inside still come through instead of being dropped.
+fn mathml_node_to_omml(node: &Handle) -> String {
+    let Some(tag) = tag_lower(node) else {
+        return String::new();
+    };
+
+    match tag.as_str() {
+        "mi" | "mn" | "mtext" | "mo" => {
+            let text = mathml_text_content(node);
+            format!("<m:r><m:t>{}</m:t></m:r>", xml_escape_text(&text))
+        }
+        "mrow" | "math" | "semantics" => mathml_element_children(node)
+            .iter()
+            .map(mathml_node_to_omml)
+            .collect(),
+        "msup" => {
+            let kids = mathml_element_children(node);
+            let base = kids.first().map(mathml_node_to_omml).unwrap_or_default();
+            let sup = kids.get(1).map(mathml_node_to_omml).unwrap_or_default();
+            format!("<m:sSup><m:e>{base}</m:e><m:sup>{sup}</m:sup></m:sSup>")
+        }
+        "msub" => {
+            let kids = mathml_element_children(node);
+            let base = kids.first().map(mathml_node_to_omml).unwrap_or_default();
+            let sub = kids.get(1).map(mathml_node_to_omml).unwrap_or_default();
+            format!("<m:sSub><m:e>{base}</m:e><m:sub>{sub}</m:sub></m:sSub>")
+        }
+        "msubsup" => {
+            let kids = mathml_element_children(node);
+            let base = kids.first().map(mathml_node_to_omml).unwrap_or_default();
+            let sub = kids.get(1).map(mathml_node_to_omml).unwrap_or_default();
+            let sup = kids.get(2).map(mathml_node_to_omml).unwrap_or_default();
+            format!(
+                "<m:sSubSup><m:e>{base}</m:e><m:sub>{sub}</m:sub><m:sup>{sup}</m:sup></m:sSubSup>"
+            )
+        }
+        "mfrac" => {
+            let kids = mathml_element_children(node);
+            let num = kids.first().map(mathml_node_to_omml).unwrap_or_default();
+            let den = kids.get(1).map(mathml_node_to_omml).unwrap_or_default();
+            format!("<m:f><m:num>{num}</m:num><m:den>{den}</m:den></m:f>")
+        }
+        "msqrt" => {
+            let inner: String = mathml_element_children(node)
+                .iter()
+                .map(mathml_node_to_omml)
+                .collect();
+            format!(r#"<m:rad><m:degHide m:val="1"/><m:e>{inner}</m:e></m:rad>"#)
+        }
+        "mroot" => {
+            let kids = mathml_element_children(node);
+            let base = kids.first().map(mathml_node_to_omml).unwrap_or_default();
+            let deg = kids.get(1).map(mathml_node_to_omml).unwrap_or_default();
+            format!("<m:rad><m:deg>{deg}</m:deg><m:e>{base}</m:e></m:rad>")
+        }
+        "mfenced" => {
+            let inner: String = mathml_element_children(node)
+                .iter()
+                .map(mathml_node_to_omml)
+                .collect();
+            format!("<m:d><m:e>{inner}</m:e></m:d>")
+        }
+        _ => mathml_element_children(node)
+            .iter()
+            .map(mathml_node_to_omml)
+            .collect(),
+    }
+}
+
+/// Translates a `<math>` subtree into `m:`-namespaced OMML wrapped in `<m:oMath>`, reusing
+/// `normalize_omml_case`/`normalize_omml_namespace` the same way the msEquation comment path
+/// does so both sources of OMML end up with identical casing/namespacing.
+fn mathml_to_omml(node: &Handle) -> String {
+    let inner = mathml_node_to_omml(node);
+    let omml = format!("<m:oMath>{inner}</m:oMath>");
+    normalize_omml_namespace(&normalize_omml_case(&omml))
+}
+
+/// Maps a handful of common LaTeX macros (Greek letters, relations, arrows, set operators) to
+/// their Unicode code point, for use as a plain `<m:r><m:t>` run. Unknown macros are left for the
+/// caller to fall back to literal text so nothing silently disappears.
+fn latex_symbol_unicode(name: &str) -> Option<char> {
+    Some(match name {
+        "alpha" => '\u{03B1}',
+        "beta" => '\u{03B2}',
+        "gamma" => '\u{03B3}',
+        "delta" => '\u{03B4}',
+        "epsilon" => '\u{03B5}',
+        "zeta" => '\u{03B6}',
+        "eta" => '\u{03B7}',
+        "theta" => '\u{03B8}',
+        "iota" => '\u{03B9}',
+        "kappa" => '\u{03BA}',
+        "lambda" => '\u{03BB}',
+        "mu" => '\u{03BC}',
+        "nu" => '\u{03BD}',
+        "xi" => '\u{03BE}',
+        "pi" => '\u{03C0}',
+        "rho" => '\u{03C1}',
+        "sigma" => '\u{03C3}',
+        "tau" => '\u{03C4}',
+        "upsilon" => '\u{03C5}',
+        "phi" => '\u{03C6}',
+        "chi" => '\u{03C7}',
+        "psi" => '\u{03C8}',
+        "omega" => '\u{03C9}',
+        "Gamma" => '\u{0393}',
+        "Delta" => '\u{0394}',
+        "Theta" => '\u{0398}',
+        "Lambda" => '\u{039B}',
+        "Xi" => '\u{039E}',
+        "Pi" => '\u{03A0}',
+        "Sigma" => '\u{03A3}',
+        "Phi" => '\u{03A6}',
+        "Psi" => '\u{03A8}',
+        "Omega" => '\u{03A9}',
+        "leq" | "le" => '\u{2264}',
+        "geq" | "ge" => '\u{2265}',
+        "neq" | "ne" => '\u{2260}',
+        "approx" => '\u{2248}',
+        "equiv" => '\u{2261}',
+        "sim" => '\u{223C}',
+        "propto" => '\u{221D}',
+        "times" => '\u{00D7}',
+        "cdot" => '\u{22C5}',
+        "div" => '\u{00F7}',
+        "pm" => '\u{00B1}',
+        "mp" => '\u{2213}',
+        "infty" => '\u{221E}',
+        "partial" => '\u{2202}',
+        "nabla" => '\u{2207}',
+        "to" | "rightarrow" => '\u{2192}',
+        "leftarrow" => '\u{2190}',
+        "Rightarrow" => '\u{21D2}',
+        "Leftarrow" => '\u{21D0}',
+        "leftrightarrow" => '\u{2194}',
+        "in" => '\u{2208}',
+        "notin" => '\u{2209}',
+        "subset" => '\u{2282}',
+        "subseteq" => '\u{2286}',
+        "cup" => '\u{222A}',
+        "cap" => '\u{2229}',
+        "emptyset" => '\u{2205}',
+        "forall" => '\u{2200}',
+        "exists" => '\u{2203}',
+        "cdots" => '\u{22EF}',
+        "ldots" | "dots" => '\u{2026}',
+        "vdots" => '\u{22EE}',
+        "ddots" => '\u{22F1}',
+        _ => return None,
+    })
+}
+
+/// Maps a LaTeX "big operator" macro to the Unicode glyph `<m:nary>` displays as its symbol.
+fn latex_nary_unicode(name: &str) -> Option<char> {
+    Some(match name {
+        "sum" => '\u{2211}',
+        "prod" => '\u{220F}',
+        "int" => '\u{222B}',
+        "oint" => '\u{222E}',
+        "bigcup" => '\u{22C3}',
+        "bigcap" => '\u{22C2}',
+        "bigoplus" => '\u{2A01}',
+        "bigotimes" => '\u{2A02}',
+        _ => return None,
+    })
+}
+
+fn omml_text_run(text: &str) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    format!(
+        "<m:r><m:t xml:space=\"preserve\">{}</m:t></m:r>",
+        xml_escape_text(text)
+    )
+}
+
+/// Walks a LaTeX string one Unicode scalar at a time. Kept separate from the byte-oriented
+/// `&str` helpers used elsewhere in this file since LaTeX math needs single-character lookahead
+/// for things like consecutive `^`/`_` scripts.
+struct LatexCursor {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl LatexCursor {
+    fn new(tex: &str) -> Self {
+        LatexCursor {
+            chars: tex.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.pos += 1;
+        }
+        c
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+}
+
+fn latex_is_plain_char(c: char) -> bool {
+    !matches!(c, '{' | '}' | '\\' | '^' | '_') && !c.is_whitespace()
+}
+
+fn latex_read_command_name(cur: &mut LatexCursor) -> String {
+    match cur.peek() {
+        Some(c) if c.is_alphabetic() => {
+            let mut name = String::new();
+            while matches!(cur.peek(), Some(c) if c.is_alphabetic()) {
+                name.push(cur.bump().unwrap());
+            }
+            name
+        }
+        Some(c) => {
+            cur.bump();
+            c.to_string()
+        }
+        None => String::new(),
+    }
+}
+
+/// Parses exactly one LaTeX atom - a `{...}` group, a `\command` (which may itself consume
+/// further arguments, e.g. `\frac`), or a single literal character - and returns it as inline
+/// OMML. `stop_at_brace` is threaded through so a `\sum`/`\int` encountered here still knows
+/// where its own implicit body should stop.
+fn latex_next_atom(cur: &mut LatexCursor, stop_at_brace: bool) -> String {
+    cur.skip_ws();
+    match cur.peek() {
+        Some('{') => {
+            cur.bump();
+            let inner = latex_parse_sequence(cur, true);
+            if cur.peek() == Some('}') {
+                cur.bump();
+            }
+            inner
+        }
+        Some('\\') => {
+            cur.bump();
+            let name = latex_read_command_name(cur);
+            latex_render_command(cur, &name, stop_at_brace)
+        }
+        Some(c) => {
+            cur.bump();
+            omml_text_run(&c.to_string())
+        }
+        None => String::new(),
+    }
+}
+
+/// After parsing `base`, checks for a following `^`/`_` (in either order) and wraps it in the
+/// matching `m:sSup`/`m:sSub`/`m:sSubSup`. Returns `base` unchanged when no script follows.
+fn latex_apply_scripts(cur: &mut LatexCursor, base: String) -> String {
+    cur.skip_ws();
+    let first_is_sup = match cur.peek() {
+        Some('^') => true,
+        Some('_') => false,
+        _ => return base,
+    };
+    cur.bump();
+    let first_arg = latex_next_atom(cur, true);
+    cur.skip_ws();
+    let second_arg = match (first_is_sup, cur.peek()) {
+        (true, Some('_')) | (false, Some('^')) => {
+            cur.bump();
+            Some(latex_next_atom(cur, true))
+        }
+        _ => None,
+    };
+
+    match (first_is_sup, second_arg) {
+        (true, Some(sub)) => format!(
+            "<m:sSubSup><m:e>{base}</m:e><m:sub>{sub}</m:sub><m:sup>{sup}</m:sup></m:sSubSup>",
+            base = base,
+            sub = sub,
+            sup = first_arg
+        ),
+        (false, Some(sup)) => format!(
+            "<m:sSubSup><m:e>{base}</m:e><m:sub>{sub}</m:sub><m:sup>{sup}</m:sup></m:sSubSup>",
+            base = base,
+            sub = first_arg,
+            sup = sup
+        ),
+        (true, None) => format!(
+            "<m:sSup><m:e>{base}</m:e><m:sup>{sup}</m:sup></m:sSup>",
+            base = base,
+            sup = first_arg
+        ),
+        (false, None) => format!(
+            "<m:sSub><m:e>{base}</m:e><m:sub>{sub}</m:sub></m:sSub>",
+            base = base,
+            sub = first_arg
+        ),
+    }
+}
+
+/// Renders a `\sum`/`\int`/etc. as `<m:nary>`, reading an immediately following `_{..}`/`^{..}`
+/// pair (in either order) as its limits. The summand/integrand has no explicit LaTeX delimiter,
+/// so - matching how these macros behave in running text - it's taken to be everything else
+/// remaining in the current scope (the rest of the `{...}` group, or the rest of the expression
+/// at top level).
+fn latex_render_nary(cur: &mut LatexCursor, name: &str, stop_at_brace: bool) -> String {
+    let chr = latex_nary_unicode(name).unwrap_or('\u{2211}');
+    let mut sub = String::new();
+    let mut sup = String::new();
+    loop {
+        cur.skip_ws();
+        match cur.peek() {
+            Some('_') if sub.is_empty() => {
+                cur.bump();
+                sub = latex_next_atom(cur, true);
+            }
+            Some('^') if sup.is_empty() => {
+                cur.bump();
+                sup = latex_next_atom(cur, true);
+            }
+            _ => break,
+        }
+    }
+    let body = latex_parse_sequence(cur, stop_at_brace);
+    let sub_hide = if sub.is_empty() { "1" } else { "0" };
+    let sup_hide = if sup.is_empty() { "1" } else { "0" };
+    format!(
+        "<m:nary><m:naryPr><m:chr m:val=\"{chr}\"/><m:limLoc m:val=\"subSup\"/><m:subHide m:val=\"{sub_hide}\"/><m:supHide m:val=\"{sup_hide}\"/></m:naryPr><m:sub>{sub}</m:sub><m:sup>{sup}</m:sup><m:e>{body}</m:e></m:nary>",
+        chr = chr,
+        sub_hide = sub_hide,
+        sup_hide = sup_hide,
+        sub = sub,
+        sup = sup,
+        body = body
+    )
+}
+
+/// Renders one LaTeX control sequence (name already consumed, without its leading `\`) into
+/// OMML. Unknown macros fall back to their literal spelling (`\foo` becomes the text `\foo`)
+/// rather than being silently dropped.
+fn latex_render_command(cur: &mut LatexCursor, name: &str, stop_at_brace: bool) -> String {
+    match name {
+        "frac" | "dfrac" | "tfrac" => {
+            let num = latex_next_atom(cur, true);
+            let den = latex_next_atom(cur, true);
+            format!("<m:f><m:num>{num}</m:num><m:den>{den}</m:den></m:f>")
+        }
+        "sqrt" => {
+            let radicand = latex_next_atom(cur, true);
+            format!("<m:rad><m:degHide m:val=\"1\"/><m:e>{radicand}</m:e></m:rad>")
+        }
+        "sum" | "prod" | "int" | "oint" | "bigcup" | "bigcap" | "bigoplus" | "bigotimes" => {
+            latex_render_nary(cur, name, stop_at_brace)
+        }
+        "left" | "right" => String::new(),
+        "," | ";" | " " | "quad" | "qquad" => omml_text_run(" "),
+        "" => String::new(),
+        _ => {
+            if let Some(ch) = latex_symbol_unicode(name) {
+                omml_text_run(&ch.to_string())
+            } else {
+                omml_text_run(&format!("\\{name}"))
+            }
+        }
+    }
+}
+
+/// Parses a run of LaTeX atoms, stopping at end of input or (when `stop_at_brace`) at an
+/// unmatched `}`. Consecutive plain characters are merged into a single `<m:r><m:t>` run rather
+/// than one per character, unless a `^`/`_` immediately follows, in which case only the last
+/// character becomes the scripted base.
+fn latex_parse_sequence(cur: &mut LatexCursor, stop_at_brace: bool) -> String {
+    let mut out = String::new();
+    loop {
+        cur.skip_ws();
+        match cur.peek() {
+            None => break,
+            Some('}') if stop_at_brace => break,
+            Some(c) if latex_is_plain_char(c) => {
+                let mut buf = String::new();
+                loop {
+                    match cur.peek() {
+                        Some(c) if latex_is_plain_char(c) => buf.push(cur.bump().unwrap()),
+                        _ => break,
+                    }
+                    if matches!(cur.peek(), Some('^') | Some('_')) {
+                        break;
+                    }
+                }
+                let atom = latex_apply_scripts(cur, omml_text_run(&buf));
+                out.push_str(&atom);
+            }
+            _ => {
+                let atom = latex_next_atom(cur, stop_at_brace);
+                let atom = latex_apply_scripts(cur, atom);
+                out.push_str(&atom);
+            }
+        }
+    }
+    out
+}
+
+/// Converts a LaTeX expression (the text between `$...$`/`\(...\)` delimiters) into an inline
+/// `<m:oMath>` run group, for embedding directly in the run stream alongside regular text.
+fn latex_to_inline_omml(tex: &str) -> String {
+    let mut cur = LatexCursor::new(tex);
+    format!("<m:oMath>{}</m:oMath>", latex_parse_sequence(&mut cur, false))
+}
+
+/// Converts a LaTeX expression (the text between `$$...$$`/`\[...\]` delimiters) into a
+/// standalone display-mode equation (`<m:oMathPara>`), emitted as its own paragraph.
+fn latex_to_display_omml(tex: &str) -> String {
+    let mut cur = LatexCursor::new(tex);
+    format!(
+        "<m:oMathPara><m:oMath>{}</m:oMath></m:oMathPara>",
+        latex_parse_sequence(&mut cur, false)
+    )
+}
+
+/// One chunk of text produced by splitting on LaTeX math delimiters - either plain text to
+/// render as normal runs, or a LaTeX expression to hand off to the OMML emitter above.
+enum MathSpan {
+    Text(String),
+    Inline(String),
+    Display(String),
+}
+
+/// Splits `text` on `$$...$$`/`\[...\]` (display math) and `$...$`/`\(...\)` (inline math)
+/// delimiters into alternating plain-text and math chunks. An opening delimiter with no matching
+/// close is left as literal text rather than swallowing the rest of the paragraph looking for
+/// one.
+fn split_latex_math_spans(text: &str) -> Vec<MathSpan> {
+    let mut spans = Vec::new();
+    let mut plain_start = 0usize;
+    let mut i = 0usize;
+    while i < text.len() {
+        let rest = &text[i..];
+        let (close, is_display, open_len) = if rest.starts_with("$$") {
+            ("$$", true, 2)
+        } else if rest.starts_with("\\[") {
+            ("\\]", true, 2)
+        } else if rest.starts_with("\\(") {
+            ("\\)", false, 2)
+        } else if rest.starts_with('$') {
+            ("$", false, 1)
+        } else {
+            i += rest.chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+            continue;
+        };
+
+        let search_start = i + open_len;
+        if let Some(rel_end) = text.get(search_start..).and_then(|s| s.find(close)) {
+            let math_end = search_start + rel_end;
+            if plain_start < i {
+                spans.push(MathSpan::Text(text[plain_start..i].to_string()));
+            }
+            let inner = &text[search_start..math_end];
+            spans.push(if is_display {
+                MathSpan::Display(inner.to_string())
+            } else {
+                MathSpan::Inline(inner.to_string())
+            });
+            i = math_end + close.len();
+            plain_start = i;
+        } else {
+            i += open_len;
+        }
+    }
+    if plain_start < text.len() {
+        spans.push(MathSpan::Text(text[plain_start..].to_string()));
+    }
+    spans
+}
+
 fn parse_table(node: &Handle) -> Table {
     fn find_children(node: &Handle, name: &str, out: &mut Vec<Handle>) {
         if let Some(tag) = tag_lower(node) {
@@ -353,9 +1387,26 @@ fn parse_table(node: &Handle) -> Table {
                     style: ParagraphStyle::Normal,
                     list: None,
                     segments: Vec::new(),
+                    code_lang: None,
+                    heading_anchor: None,
                 });
             }
-            cells.push(TableCell { paragraphs: paras });
+
+            let cell_attrs = attrs_vec(c);
+            let col_span = attr_get(&cell_attrs, "colspan")
+                .and_then(|v| v.trim().parse::<u32>().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(1);
+            let row_span = attr_get(&cell_attrs, "rowspan")
+                .and_then(|v| v.trim().parse::<u32>().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(1);
+
+            cells.push(TableCell {
+                paragraphs: paras,
+                col_span,
+                row_span,
+            });
         }
         if !cells.is_empty() {
             rows.push(TableRow { cells });
@@ -371,6 +1422,8 @@ fn build_blocks_from_nodes(nodes: &[Handle], allow_tables: bool) -> Vec<Block> {
         style: ParagraphStyle::Normal,
         list: None,
         segments: Vec::new(),
+        code_lang: None,
+        heading_anchor: None,
     };
     let mut ctx = BuildCtx::new();
 
@@ -384,7 +1437,7 @@ fn build_blocks_from_nodes(nodes: &[Handle], allow_tables: bool) -> Vec<Block> {
         match &node.data {
             NodeData::Text { contents } => {
                 let s = contents.borrow().to_string();
-                emit_text(current, ctx, &s);
+                emit_text(blocks, current, ctx, &s);
             }
             NodeData::Comment { contents } => {
                 if let Some(omml) = comment_extract_ms_equation_omml(&contents.to_string()) {
@@ -401,9 +1454,48 @@ fn build_blocks_from_nodes(nodes: &[Handle], allow_tables: bool) -> Vec<Block> {
                     return;
                 }
 
-                if tag == "math" || tag == "annotation" {
+                if tag == "annotation" {
+                    return;
+                }
+                if tag == "math" {
+                    current.segments.push(Segment::Omml(mathml_to_omml(node)));
                     return;
                 }
+                if tag == "img" {
+                    if let Some(seg) = load_image_segment(&attrs) {
+                        current.segments.push(seg);
+                    }
+                    return;
+                }
+
+                let decls = parse_style_decls(&attr_get(&attrs, "style").unwrap_or_default());
+                let mut color_here = None;
+                let mut highlight_here = None;
+                let mut size_here = None;
+                let mut style_underline = false;
+                let mut style_strike = false;
+                for (prop, val) in &decls {
+                    match prop.as_str() {
+                        "color" => color_here = css_color_to_hex(val),
+                        "background-color" => highlight_here = css_color_to_hex(val),
+                        "font-size" => size_here = css_font_size_to_half_pt(val),
+                        "text-decoration" => {
+                            let v = val.to_ascii_lowercase();
+                            style_underline = style_underline || v.contains("underline");
+                            style_strike = style_strike || v.contains("line-through");
+                        }
+                        _ => {}
+                    }
+                }
+                ctx.color_stack.push(color_here);
+                ctx.highlight_stack.push(highlight_here);
+                ctx.size_stack.push(size_here);
+                if style_underline {
+                    ctx.underline_depth += 1;
+                }
+                if style_strike {
+                    ctx.strike_depth += 1;
+                }
 
                 match tag.as_str() {
                     "h1" => start_paragraph(blocks, current, ParagraphStyle::Heading1, ctx.current_list()),
@@ -417,21 +1509,48 @@ fn build_blocks_from_nodes(nodes: &[Handle], allow_tables: bool) -> Vec<Block> {
                     }
                     "br" => current.segments.push(Segment::Break),
                     "hr" => flush_paragraph(blocks, current),
-                    "ul" => ctx.list_stack.push(1),
-                    "ol" => ctx.list_stack.push(2),
+                    "ul" => {
+                        let num_id = ctx.next_list_num_id;
+                        ctx.next_list_num_id += 1;
+                        let ilvl = ctx.list_stack.len() as u32;
+                        ctx.list_stack.push(ListInfo { num_id, ilvl, fmt: ListNumFmt::Bullet, start: 1 });
+                    }
+                    "ol" => {
+                        let num_id = ctx.next_list_num_id;
+                        ctx.next_list_num_id += 1;
+                        let ilvl = ctx.list_stack.len() as u32;
+                        let fmt = attr_get(&attrs, "type")
+                            .map(|t| ListNumFmt::from_ol_type(&t))
+                            .unwrap_or(ListNumFmt::Decimal);
+                        let start = attr_get(&attrs, "start")
+                            .and_then(|s| s.trim().parse::<u32>().ok())
+                            .unwrap_or(1);
+                        ctx.list_stack.push(ListInfo { num_id, ilvl, fmt, start });
+                    }
                     "li" => {
-                        let num_id = ctx.list_stack.last().cloned().unwrap_or(1);
-                        let ilvl = ctx.list_stack.len().saturating_sub(1) as u32;
-                        ctx.li_list_stack.push(Some(ListInfo { num_id, ilvl }));
+                        ctx.li_list_stack.push(ctx.list_stack.last().cloned());
                         start_paragraph(blocks, current, ParagraphStyle::Normal, ctx.current_list());
                     }
                     "a" => {
                         let href = attr_get(&attrs, "href").and_then(|h| sanitize_href(&h));
                         ctx.link_stack.push(href);
                     }
-                    "code" => ctx.code_depth += 1,
+                    "code" => {
+                        ctx.code_depth += 1;
+                        if ctx.pre_depth > 0 && current.code_lang.is_none() {
+                            current.code_lang = attr_get(&attrs, "class")
+                                .and_then(|c| {
+                                    c.split_whitespace()
+                                        .find_map(|cls| cls.strip_prefix("language-").map(str::to_string))
+                                })
+                                .or_else(|| attr_get(&attrs, "data-lang"));
+                        }
+                    }
                     "b" | "strong" => ctx.bold_depth += 1,
                     "i" | "em" => ctx.italic_depth += 1,
+                    "u" => ctx.underline_depth += 1,
+                    "s" | "del" => ctx.strike_depth += 1,
+                    "mark" => ctx.mark_depth += 1,
                     _ => {}
                 }
 
@@ -459,8 +1578,21 @@ fn build_blocks_from_nodes(nodes: &[Handle], allow_tables: bool) -> Vec<Block> {
                     "code" => ctx.code_depth = ctx.code_depth.saturating_sub(1),
                     "b" | "strong" => ctx.bold_depth = ctx.bold_depth.saturating_sub(1),
                     "i" | "em" => ctx.italic_depth = ctx.italic_depth.saturating_sub(1),
+                    "u" => ctx.underline_depth = ctx.underline_depth.saturating_sub(1),
+                    "s" | "del" => ctx.strike_depth = ctx.strike_depth.saturating_sub(1),
+                    "mark" => ctx.mark_depth = ctx.mark_depth.saturating_sub(1),
                     _ => {}
                 }
+
+                if style_strike {
+                    ctx.strike_depth = ctx.strike_depth.saturating_sub(1);
+                }
+                if style_underline {
+                    ctx.underline_depth = ctx.underline_depth.saturating_sub(1);
+                }
+                ctx.size_stack.pop();
+                ctx.highlight_stack.pop();
+                ctx.color_stack.pop();
             }
             _ => {}
         }
@@ -505,22 +1637,49 @@ fn build_blocks_from_html(input_html: &str) -> Vec<Block> {
     build_blocks_from_nodes(&body_children, true)
 }
 
-fn hyperlink_run_xml(text: &str, style: RunStyle) -> String {
-    if text.is_empty() {
-        return String::new();
-    }
-    let escaped = xml_escape_text(text);
+fn run_properties_xml(style: &RunStyle) -> String {
     let mut out = String::new();
-    out.push_str("<w:r><w:rPr>");
     if style.bold {
         out.push_str("<w:b/>");
     }
     if style.italic {
         out.push_str("<w:i/>");
     }
+    if style.strike {
+        out.push_str("<w:strike/>");
+    }
+    if style.underline {
+        out.push_str("<w:u w:val=\"single\"/>");
+    }
+    if let Some(color) = &style.color {
+        out.push_str(&format!("<w:color w:val=\"{color}\"/>"));
+    }
+    if let Some(half_pt) = style.size_half_pt {
+        out.push_str(&format!("<w:sz w:val=\"{half_pt}\"/><w:szCs w:val=\"{half_pt}\"/>"));
+    }
+    if let Some(highlight) = &style.highlight {
+        if let Some(name) = highlight_name_for_hex(highlight) {
+            out.push_str(&format!("<w:highlight w:val=\"{name}\"/>"));
+        } else {
+            out.push_str(&format!(
+                "<w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"{highlight}\"/>"
+            ));
+        }
+    }
     if style.code {
         out.push_str("<w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\" w:cs=\"Consolas\"/>");
     }
+    out
+}
+
+fn hyperlink_run_xml(text: &str, style: &RunStyle) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    let escaped = xml_escape_text(text);
+    let mut out = String::new();
+    out.push_str("<w:r><w:rPr>");
+    out.push_str(&run_properties_xml(style));
     out.push_str("</w:rPr>");
     out.push_str("<w:t xml:space=\"preserve\">");
     out.push_str(&escaped);
@@ -528,24 +1687,17 @@ fn hyperlink_run_xml(text: &str, style: RunStyle) -> String {
     out
 }
 
-fn run_xml(text: &str, style: RunStyle) -> String {
+fn run_xml(text: &str, style: &RunStyle) -> String {
     if text.is_empty() {
         return String::new();
     }
     let escaped = xml_escape_text(text);
+    let props = run_properties_xml(style);
     let mut out = String::new();
     out.push_str("<w:r>");
-    if style.bold || style.italic || style.code {
+    if !props.is_empty() {
         out.push_str("<w:rPr>");
-        if style.bold {
-            out.push_str("<w:b/>");
-        }
-        if style.italic {
-            out.push_str("<w:i/>");
-        }
-        if style.code {
-            out.push_str("<w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\" w:cs=\"Consolas\"/>");
-        }
+        out.push_str(&props);
         out.push_str("</w:rPr>");
     }
     out.push_str("<w:t xml:space=\"preserve\">");
@@ -554,7 +1706,32 @@ fn run_xml(text: &str, style: RunStyle) -> String {
     out
 }
 
-fn paragraph_xml(p: &Paragraph, link_to_rid: &BTreeMap<String, String>) -> String {
+/// Emits an inline `<w:drawing>` run embedding the image at `rid`, using `pic_id` as both the
+/// `wp:docPr`/`pic:nvPicPr` id (must be unique within the document, hence the caller-owned
+/// counter) and the picture's display name.
+fn drawing_run_xml(rid: &str, pic_id: u32, width_emu: i64, height_emu: i64) -> String {
+    format!(
+        "<w:r><w:drawing><wp:inline distT=\"0\" distB=\"0\" distL=\"0\" distR=\"0\">\
+<wp:extent cx=\"{width_emu}\" cy=\"{height_emu}\"/>\
+<wp:docPr id=\"{pic_id}\" name=\"Picture {pic_id}\"/>\
+<a:graphic xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\">\
+<a:graphicData uri=\"http://schemas.openxmlformats.org/drawingml/2006/picture\">\
+<pic:pic xmlns:pic=\"http://schemas.openxmlformats.org/drawingml/2006/picture\">\
+<pic:nvPicPr><pic:cNvPr id=\"{pic_id}\" name=\"Picture {pic_id}\"/><pic:cNvPicPr/></pic:nvPicPr>\
+<pic:blipFill><a:blip r:embed=\"{rid}\"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill>\
+<pic:spPr><a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"{width_emu}\" cy=\"{height_emu}\"/></a:xfrm>\
+<a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom></pic:spPr>\
+</pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>"
+    )
+}
+
+fn paragraph_xml(
+    p: &Paragraph,
+    link_to_rid: &BTreeMap<String, String>,
+    media_to_rid: &BTreeMap<String, String>,
+    pic_id: &mut u32,
+    bookmarks: &mut BookmarkState,
+) -> String {
     let mut out = String::new();
     out.push_str("<w:p>");
 
@@ -575,6 +1752,16 @@ fn paragraph_xml(p: &Paragraph, link_to_rid: &BTreeMap<String, String>) -> Strin
         out.push_str("</w:pPr>");
     }
 
+    let bookmark_id = p.heading_anchor.as_deref().map(|slug| {
+        let (id, name) = bookmarks.allocate(slug);
+        out.push_str(&format!(
+            "<w:bookmarkStart w:id=\"{}\" w:name=\"{}\"/>",
+            id,
+            xml_escape_text(&name)
+        ));
+        id
+    });
+
     let mut in_link: Option<(String, RunStyle, String)> = None;
     let flush_link =
         |out: &mut String, st: &mut Option<(String, RunStyle, String)>| {
@@ -582,15 +1769,22 @@ fn paragraph_xml(p: &Paragraph, link_to_rid: &BTreeMap<String, String>) -> Strin
                 if buf.is_empty() {
                     return;
                 }
-                if let Some(rid) = link_to_rid.get(&href) {
+                if let Some(anchor) = href.strip_prefix('#') {
+                    out.push_str(&format!(
+                        "<w:hyperlink w:anchor=\"{}\" w:history=\"1\">",
+                        xml_escape_text(anchor)
+                    ));
+                    out.push_str(&hyperlink_run_xml(&buf, &style));
+                    out.push_str("</w:hyperlink>");
+                } else if let Some(rid) = link_to_rid.get(&href) {
                     out.push_str(&format!(
                         "<w:hyperlink r:id=\"{}\" w:history=\"1\">",
                         rid
                     ));
-                    out.push_str(&hyperlink_run_xml(&buf, style));
+                    out.push_str(&hyperlink_run_xml(&buf, &style));
                     out.push_str("</w:hyperlink>");
                 } else {
-                    out.push_str(&run_xml(&buf, style));
+                    out.push_str(&run_xml(&buf, &style));
                 }
             }
         };
@@ -605,30 +1799,154 @@ fn paragraph_xml(p: &Paragraph, link_to_rid: &BTreeMap<String, String>) -> Strin
                 flush_link(&mut out, &mut in_link);
                 out.push_str(xml);
             }
+            Segment::Image {
+                data,
+                width_emu,
+                height_emu,
+                ..
+            } => {
+                flush_link(&mut out, &mut in_link);
+                if let Some(rid) = media_to_rid.get(&image_media_key(data)) {
+                    *pic_id += 1;
+                    out.push_str(&drawing_run_xml(rid, *pic_id, *width_emu, *height_emu));
+                }
+            }
             Segment::Text { text, style } => {
                 flush_link(&mut out, &mut in_link);
-                out.push_str(&run_xml(text, *style));
+                out.push_str(&run_xml(text, style));
             }
             Segment::LinkText { text, style, href } => match &mut in_link {
                 Some((buf, cur_style, cur_href))
-                    if cur_href == href && *cur_style == *style =>
+                    if cur_href == href && cur_style == style =>
                 {
                     buf.push_str(text);
                 }
                 _ => {
                     flush_link(&mut out, &mut in_link);
-                    in_link = Some((text.clone(), *style, href.clone()));
+                    in_link = Some((text.clone(), style.clone(), href.clone()));
                 }
             },
         }
     }
     flush_link(&mut out, &mut in_link);
 
+    if let Some(id) = bookmark_id {
+        out.push_str(&format!("<w:bookmarkEnd w:id=\"{}\"/>", id));
+    }
+
     out.push_str("</w:p>");
     out
 }
 
-fn table_xml(t: &Table, link_to_rid: &BTreeMap<String, String>) -> String {
+fn table_cell_tc_xml(
+    cell: &TableCell,
+    link_to_rid: &BTreeMap<String, String>,
+    media_to_rid: &BTreeMap<String, String>,
+    pic_id: &mut u32,
+    bookmarks: &mut BookmarkState,
+) -> String {
+    let mut out = String::new();
+    out.push_str("<w:tc><w:tcPr><w:tcW w:w=\"0\" w:type=\"auto\"/>");
+    if cell.col_span > 1 {
+        out.push_str(&format!("<w:gridSpan w:val=\"{}\"/>", cell.col_span));
+    }
+    if cell.row_span > 1 {
+        out.push_str("<w:vMerge w:val=\"restart\"/>");
+    }
+    out.push_str("</w:tcPr>");
+    for p in &cell.paragraphs {
+        out.push_str(&paragraph_xml(p, link_to_rid, media_to_rid, pic_id, bookmarks));
+    }
+    out.push_str("</w:tc>");
+    out
+}
+
+/// A `<w:tc>` placeholder for a grid column a rowspan from an earlier row still covers. Word
+/// requires one `<w:tc>` per row for every merged region, even the rows that contribute no
+/// content to it.
+fn table_vmerge_continuation_tc_xml(col_span: u32) -> String {
+    let mut out = String::new();
+    out.push_str("<w:tc><w:tcPr><w:tcW w:w=\"0\" w:type=\"auto\"/>");
+    if col_span > 1 {
+        out.push_str(&format!("<w:gridSpan w:val=\"{}\"/>", col_span));
+    }
+    out.push_str("<w:vMerge/></w:tcPr><w:p/></w:tc>");
+    out
+}
+
+/// A rowspan that's still active when this row is built, tracked so the next row knows which
+/// grid columns it must emit a `<w:vMerge/>` continuation for instead of a real cell.
+struct PendingRowSpan {
+    col_start: u32,
+    col_span: u32,
+    rows_left: u32,
+}
+
+fn table_xml(
+    t: &Table,
+    link_to_rid: &BTreeMap<String, String>,
+    media_to_rid: &BTreeMap<String, String>,
+    pic_id: &mut u32,
+    bookmarks: &mut BookmarkState,
+) -> String {
+    let mut pending: Vec<PendingRowSpan> = Vec::new();
+    let mut grid_cols: u32 = 0;
+    let mut rows_xml = String::new();
+
+    for row in &t.rows {
+        let mut col: u32 = 0;
+        let mut next_pending: Vec<PendingRowSpan> = Vec::new();
+        let mut cell_iter = row.cells.iter();
+        let mut current_cell = cell_iter.next();
+        let mut row_xml = String::new();
+
+        loop {
+            if let Some(idx) = pending.iter().position(|p| p.col_start == col) {
+                let p = pending.remove(idx);
+                row_xml.push_str(&table_vmerge_continuation_tc_xml(p.col_span));
+                col += p.col_span;
+                if p.rows_left > 1 {
+                    next_pending.push(PendingRowSpan {
+                        col_start: p.col_start,
+                        col_span: p.col_span,
+                        rows_left: p.rows_left - 1,
+                    });
+                }
+                continue;
+            }
+            let Some(cell) = current_cell else { break };
+            row_xml.push_str(&table_cell_tc_xml(cell, link_to_rid, media_to_rid, pic_id, bookmarks));
+            if cell.row_span > 1 {
+                next_pending.push(PendingRowSpan {
+                    col_start: col,
+                    col_span: cell.col_span,
+                    rows_left: cell.row_span - 1,
+                });
+            }
+            col += cell.col_span;
+            current_cell = cell_iter.next();
+        }
+
+        // Rowspans further right than any cell this row has left still need to continue.
+        for p in pending.drain(..) {
+            row_xml.push_str(&table_vmerge_continuation_tc_xml(p.col_span));
+            col = col.max(p.col_start + p.col_span);
+            if p.rows_left > 1 {
+                next_pending.push(PendingRowSpan {
+                    col_start: p.col_start,
+                    col_span: p.col_span,
+                    rows_left: p.rows_left - 1,
+                });
+            }
+        }
+
+        grid_cols = grid_cols.max(col);
+        rows_xml.push_str("<w:tr>");
+        rows_xml.push_str(&row_xml);
+        rows_xml.push_str("</w:tr>");
+        pending = next_pending;
+    }
+
     let mut out = String::new();
     out.push_str("<w:tbl>");
     out.push_str("<w:tblPr>");
@@ -645,29 +1963,43 @@ fn table_xml(t: &Table, link_to_rid: &BTreeMap<String, String>) -> String {
     );
     out.push_str("</w:tblPr>");
 
-    for row in &t.rows {
-        out.push_str("<w:tr>");
-        for cell in &row.cells {
-            out.push_str("<w:tc>");
-            out.push_str("<w:tcPr><w:tcW w:w=\"0\" w:type=\"auto\"/></w:tcPr>");
-            for p in &cell.paragraphs {
-                out.push_str(&paragraph_xml(p, link_to_rid));
-            }
-            out.push_str("</w:tc>");
-        }
-        out.push_str("</w:tr>");
+    out.push_str("<w:tblGrid>");
+    for _ in 0..grid_cols.max(1) {
+        out.push_str("<w:gridCol/>");
     }
+    out.push_str("</w:tblGrid>");
 
+    out.push_str(&rows_xml);
     out.push_str("</w:tbl>");
     out
 }
 
-fn document_xml(blocks: &[Block], link_to_rid: &BTreeMap<String, String>) -> String {
+/// A field-code paragraph Word replaces with a clickable, updatable heading outline the first
+/// time the document is opened (or on F9). `\o "1-3"` pulls outline levels 1-3, which is all
+/// Heading1/Heading2 ever set; `\h` makes entries hyperlinks, `\z` hides tab leaders in Web
+/// Layout. The cached "result" run only matters until Word regenerates the field.
+fn toc_field_paragraph_xml() -> &'static str {
+    r#"<w:p><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText xml:space="preserve"> TOC \o "1-3" \h \z </w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>Right-click and select "Update Field" to generate the table of contents.</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>"#
+}
+
+fn document_xml(
+    blocks: &[Block],
+    link_to_rid: &BTreeMap<String, String>,
+    media_to_rid: &BTreeMap<String, String>,
+    include_toc: bool,
+) -> String {
     let mut body = String::new();
+    if include_toc {
+        body.push_str(toc_field_paragraph_xml());
+    }
+    let mut pic_id: u32 = 0;
+    let mut bookmarks = BookmarkState::new();
     for b in blocks {
         match b {
-            Block::Paragraph(p) => body.push_str(&paragraph_xml(p, link_to_rid)),
-            Block::Table(t) => body.push_str(&table_xml(t, link_to_rid)),
+            Block::Paragraph(p) => {
+                body.push_str(&paragraph_xml(p, link_to_rid, media_to_rid, &mut pic_id, &mut bookmarks))
+            }
+            Block::Table(t) => body.push_str(&table_xml(t, link_to_rid, media_to_rid, &mut pic_id, &mut bookmarks)),
         }
     }
 
@@ -704,7 +2036,7 @@ fn document_xml(blocks: &[Block], link_to_rid: &BTreeMap<String, String>) -> Str
     )
 }
 
-fn content_types_xml(has_numbering: bool) -> String {
+fn content_types_xml(has_numbering: bool, image_extensions: &BTreeSet<String>) -> String {
     let mut out = String::new();
     out.push_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
     out.push('\n');
@@ -716,6 +2048,14 @@ fn content_types_xml(has_numbering: bool) -> String {
     out.push('\n');
     out.push_str(r#"  <Default Extension="xml" ContentType="application/xml"/>"#);
     out.push('\n');
+    for ext in image_extensions {
+        out.push_str(&format!(
+            r#"  <Default Extension="{ext}" ContentType="{mime}"/>"#,
+            ext = ext,
+            mime = image_mime_for_extension(ext),
+        ));
+        out.push('\n');
+    }
     out.push_str(r#"  <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>"#);
     out.push('\n');
     out.push_str(r#"  <Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>"#);
@@ -735,7 +2075,11 @@ fn rels_xml() -> &'static str {
 </Relationships>"#
 }
 
-fn document_rels_xml(link_to_rid: &BTreeMap<String, String>) -> String {
+fn document_rels_xml(
+    link_to_rid: &BTreeMap<String, String>,
+    images: &BTreeMap<String, (Vec<u8>, String)>,
+    media_to_rid: &BTreeMap<String, String>,
+) -> String {
     let mut out = String::new();
     out.push_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
     out.push('\n');
@@ -749,6 +2093,16 @@ fn document_rels_xml(link_to_rid: &BTreeMap<String, String>) -> String {
         ));
         out.push('\n');
     }
+    for (key, (_, extension)) in images {
+        let Some(rid) = media_to_rid.get(key) else { continue };
+        out.push_str(&format!(
+            r#"  <Relationship Id="{rid}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/{key}.{extension}"/>"#,
+            rid = rid,
+            key = key,
+            extension = extension,
+        ));
+        out.push('\n');
+    }
     out.push_str("</Relationships>");
     out
 }
@@ -808,38 +2162,49 @@ fn styles_xml() -> &'static str {
 </w:styles>"#
 }
 
-fn numbering_xml() -> &'static str {
-    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
-  <w:abstractNum w:abstractNumId="1">
-    <w:multiLevelType w:val="hybridMultilevel"/>
-    <w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="1"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="2"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="3"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="4"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="5"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="6"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="7"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="8"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/></w:lvl>
-  </w:abstractNum>
-  <w:abstractNum w:abstractNumId="2">
-    <w:multiLevelType w:val="hybridMultilevel"/>
-    <w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%1."/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="1"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%2."/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="2"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%3."/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="3"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%4."/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="4"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%5."/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="5"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%6."/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="6"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%7."/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="7"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%8."/><w:lvlJc w:val="left"/></w:lvl>
-    <w:lvl w:ilvl="8"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%9."/><w:lvlJc w:val="left"/></w:lvl>
-  </w:abstractNum>
-  <w:num w:numId="1"><w:abstractNumId w:val="1"/></w:num>
-  <w:num w:numId="2"><w:abstractNumId w:val="2"/></w:num>
-</w:numbering>"#
+/// Builds `word/numbering.xml` from the list definitions `collect_list_defs` found in use,
+/// one `abstractNum`/`num` pair per logical list instance (so sibling `<ol>`/`<ul>` elements
+/// restart independently instead of sharing a single shared `numId`). Only the level the list
+/// actually renders at gets its real `start`; the other eight levels of the hybrid multilevel
+/// definition repeat the same format so the part stays well-formed if nesting goes deeper.
+fn numbering_xml(list_defs: &[ListDef]) -> String {
+    let mut out = String::new();
+    out.push_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
+    out.push('\n');
+    out.push_str(r#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#);
+    out.push('\n');
+
+    for def in list_defs {
+        out.push_str(&format!(
+            "  <w:abstractNum w:abstractNumId=\"{}\">\n    <w:multiLevelType w:val=\"hybridMultilevel\"/>\n",
+            def.num_id
+        ));
+        for ilvl in 0..9u32 {
+            let start = if ilvl == 0 { def.start } else { 1 };
+            out.push_str(&format!(
+                "    <w:lvl w:ilvl=\"{}\"><w:start w:val=\"{}\"/><w:numFmt w:val=\"{}\"/><w:lvlText w:val=\"{}\"/><w:lvlJc w:val=\"left\"/></w:lvl>\n",
+                ilvl,
+                start,
+                def.fmt.docx_num_fmt(),
+                def.fmt.docx_lvl_text(ilvl),
+            ));
+        }
+        out.push_str("  </w:abstractNum>\n");
+    }
+    for def in list_defs {
+        out.push_str(&format!(
+            "  <w:num w:numId=\"{}\"><w:abstractNumId w:val=\"{}\"/></w:num>\n",
+            def.num_id, def.num_id
+        ));
+    }
+
+    out.push_str("</w:numbering>");
+    out
 }
 
+/// Collects every external hyperlink target, for building `word/_rels/document.xml.rels`.
+/// Fragment links (`href="#..."`) are excluded since they become `w:anchor` references to a
+/// heading's bookmark instead of a relationship.
 fn gather_hrefs(blocks: &[Block]) -> BTreeSet<String> {
     let mut out = BTreeSet::new();
     for b in blocks {
@@ -847,7 +2212,9 @@ fn gather_hrefs(blocks: &[Block]) -> BTreeSet<String> {
             Block::Paragraph(p) => {
                 for s in &p.segments {
                     if let Segment::LinkText { href, .. } = s {
-                        out.insert(href.to_string());
+                        if !href.starts_with('#') {
+                            out.insert(href.to_string());
+                        }
                     }
                 }
             }
@@ -857,7 +2224,9 @@ fn gather_hrefs(blocks: &[Block]) -> BTreeSet<String> {
                         for p in &cell.paragraphs {
                             for s in &p.segments {
                                 if let Segment::LinkText { href, .. } = s {
-                                    out.insert(href.to_string());
+                                    if !href.starts_with('#') {
+                                        out.insert(href.to_string());
+                                    }
                                 }
                             }
                         }
@@ -869,20 +2238,27 @@ fn gather_hrefs(blocks: &[Block]) -> BTreeSet<String> {
     out
 }
 
-fn blocks_need_numbering(blocks: &[Block]) -> bool {
+/// Collects every embedded image across the document, keyed by content hash so the same image
+/// pasted more than once shares a single media part/rId, mirroring `gather_hrefs` above.
+fn gather_images(blocks: &[Block]) -> BTreeMap<String, (Vec<u8>, String)> {
+    let mut out = BTreeMap::new();
     for b in blocks {
         match b {
             Block::Paragraph(p) => {
-                if p.list.is_some() {
-                    return true;
+                for s in &p.segments {
+                    if let Segment::Image { data, extension, .. } = s {
+                        out.insert(image_media_key(data), (data.clone(), extension.clone()));
+                    }
                 }
             }
             Block::Table(t) => {
                 for row in &t.rows {
                     for cell in &row.cells {
                         for p in &cell.paragraphs {
-                            if p.list.is_some() {
-                                return true;
+                            for s in &p.segments {
+                                if let Segment::Image { data, extension, .. } = s {
+                                    out.insert(image_media_key(data), (data.clone(), extension.clone()));
+                                }
                             }
                         }
                     }
@@ -890,41 +2266,518 @@ fn blocks_need_numbering(blocks: &[Block]) -> bool {
             }
         }
     }
-    false
+    out
+}
+
+/// Walks every paragraph (including table cells) and returns one `ListDef` per distinct
+/// `num_id` actually used, in `num_id` order. `write_docx` uses an empty result to skip
+/// `word/numbering.xml` entirely, same as the old boolean `blocks_need_numbering` did.
+fn collect_list_defs(blocks: &[Block]) -> Vec<ListDef> {
+    fn note(p: &Paragraph, out: &mut BTreeMap<u32, ListDef>) {
+        if let Some(li) = p.list {
+            out.entry(li.num_id).or_insert(ListDef {
+                num_id: li.num_id,
+                fmt: li.fmt,
+                start: li.start,
+            });
+        }
+    }
+
+    let mut defs = BTreeMap::new();
+    for b in blocks {
+        match b {
+            Block::Paragraph(p) => note(p, &mut defs),
+            Block::Table(t) => {
+                for row in &t.rows {
+                    for cell in &row.cells {
+                        for p in &cell.paragraphs {
+                            note(p, &mut defs);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    defs.into_values().collect()
 }
 
-fn write_docx(
-    out_path: &PathBuf,
+/// Common interface for turning a parsed `Block` tree into bytes for a particular document
+/// format, so `main` doesn't need to know the OOXML/ODT zip layouts to pick one.
+trait DocumentWriter {
+    fn write(&self, blocks: &[Block], out: &mut impl Write, include_toc: bool) -> Result<()>;
+}
+
+fn write_docx_zip(
     document_xml: &str,
     doc_rels_xml: &str,
-    has_numbering: bool,
-) -> Result<()> {
-    let f = File::create(out_path).with_context(|| format!("create {}", out_path.display()))?;
-    let mut zip = ZipWriter::new(f);
-    let opts = SimpleFileOptions::default();
+    list_defs: &[ListDef],
+    images: &BTreeMap<String, (Vec<u8>, String)>,
+) -> Result<Vec<u8>> {
+    let has_numbering = !list_defs.is_empty();
+    let image_extensions: BTreeSet<String> =
+        images.values().map(|(_, ext)| ext.clone()).collect();
 
-    zip.start_file("[Content_Types].xml", opts)?;
-    zip.write_all(content_types_xml(has_numbering).as_bytes())?;
+    let mut buf = Cursor::new(Vec::new());
+    {
+        let mut zip = ZipWriter::new(&mut buf);
+        let opts = SimpleFileOptions::default();
 
-    zip.start_file("_rels/.rels", opts)?;
-    zip.write_all(rels_xml().as_bytes())?;
+        zip.start_file("[Content_Types].xml", opts)?;
+        zip.write_all(content_types_xml(has_numbering, &image_extensions).as_bytes())?;
 
-    zip.start_file("word/document.xml", opts)?;
-    zip.write_all(document_xml.as_bytes())?;
+        zip.start_file("_rels/.rels", opts)?;
+        zip.write_all(rels_xml().as_bytes())?;
 
-    zip.start_file("word/styles.xml", opts)?;
-    zip.write_all(styles_xml().as_bytes())?;
+        zip.start_file("word/document.xml", opts)?;
+        zip.write_all(document_xml.as_bytes())?;
 
-    if has_numbering {
-        zip.start_file("word/numbering.xml", opts)?;
-        zip.write_all(numbering_xml().as_bytes())?;
+        zip.start_file("word/styles.xml", opts)?;
+        zip.write_all(styles_xml().as_bytes())?;
+
+        if has_numbering {
+            zip.start_file("word/numbering.xml", opts)?;
+            zip.write_all(numbering_xml(list_defs).as_bytes())?;
+        }
+
+        zip.start_file("word/_rels/document.xml.rels", opts)?;
+        zip.write_all(doc_rels_xml.as_bytes())?;
+
+        for (key, (data, extension)) in images {
+            zip.start_file(format!("word/media/{}.{}", key, extension), opts)?;
+            zip.write_all(data)?;
+        }
+
+        zip.finish()?;
     }
+    Ok(buf.into_inner())
+}
 
-    zip.start_file("word/_rels/document.xml.rels", opts)?;
-    zip.write_all(doc_rels_xml.as_bytes())?;
+struct DocxWriter;
 
-    zip.finish()?;
-    Ok(())
+impl DocumentWriter for DocxWriter {
+    fn write(&self, blocks: &[Block], out: &mut impl Write, include_toc: bool) -> Result<()> {
+        let hrefs = gather_hrefs(blocks);
+        let mut link_to_rid = BTreeMap::new();
+        let mut rid_counter: u32 = 10;
+        for href in hrefs {
+            link_to_rid.insert(href, format!("rId{}", rid_counter));
+            rid_counter += 1;
+        }
+
+        let images = gather_images(blocks);
+        let mut media_to_rid = BTreeMap::new();
+        for key in images.keys() {
+            media_to_rid.insert(key.clone(), format!("rId{}", rid_counter));
+            rid_counter += 1;
+        }
+
+        let doc_xml = document_xml(blocks, &link_to_rid, &media_to_rid, include_toc);
+        let doc_rels = document_rels_xml(&link_to_rid, &images, &media_to_rid);
+        let list_defs = collect_list_defs(blocks);
+
+        let bytes = write_docx_zip(&doc_xml, &doc_rels, &list_defs, &images)?;
+        out.write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+fn odt_run_style_name(style: &RunStyle) -> &'static str {
+    match (style.bold, style.italic, style.code) {
+        (false, false, false) => "T_B0_I0_C0",
+        (true, false, false) => "T_B1_I0_C0",
+        (false, true, false) => "T_B0_I1_C0",
+        (false, false, true) => "T_B0_I0_C1",
+        (true, true, false) => "T_B1_I1_C0",
+        (true, false, true) => "T_B1_I0_C1",
+        (false, true, true) => "T_B0_I1_C1",
+        (true, true, true) => "T_B1_I1_C1",
+    }
+}
+
+fn odt_run_xml(text: &str, style: &RunStyle) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    let escaped = xml_escape_text(text);
+    if !style.bold && !style.italic && !style.code {
+        escaped
+    } else {
+        format!(
+            r#"<text:span text:style-name="{}">{}</text:span>"#,
+            odt_run_style_name(style),
+            escaped
+        )
+    }
+}
+
+fn odt_link_xml(text: &str, href: &str, style: &RunStyle) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    format!(
+        r#"<text:a xlink:type="simple" xlink:href="{}">{}</text:a>"#,
+        xml_escape_text(href),
+        odt_run_xml(text, style)
+    )
+}
+
+fn odt_paragraph_xml(p: &Paragraph) -> String {
+    let (tag, style_name, outline_level) = match p.style {
+        ParagraphStyle::Normal => ("text:p", "Standard", None),
+        ParagraphStyle::Heading1 => ("text:h", "Heading1", Some(1)),
+        ParagraphStyle::Heading2 => ("text:h", "Heading2", Some(2)),
+        ParagraphStyle::CodeBlock => ("text:p", "Preformatted", None),
+    };
+
+    let mut out = String::new();
+    out.push_str(&format!(r#"<{tag} text:style-name="{style_name}""#));
+    if let Some(level) = outline_level {
+        out.push_str(&format!(r#" text:outline-level="{level}""#));
+    }
+    out.push('>');
+
+    for seg in &p.segments {
+        match seg {
+            Segment::Break => out.push_str("<text:line-break/>"),
+            Segment::Text { text, style } => out.push_str(&odt_run_xml(text, style)),
+            Segment::LinkText { text, style, href } => out.push_str(&odt_link_xml(text, href, style)),
+            Segment::Omml(_) => {
+                // OMML is an OOXML construct with no ODT equivalent wired up here, so inline math
+                // pasted as <math> is dropped from the .odt output rather than emitting it raw.
+            }
+            Segment::Image { .. } => {
+                // Image embedding is only wired up for the DOCX media/rels pipeline so far; ODT
+                // output drops embedded images rather than emitting them without a real part.
+            }
+        }
+    }
+
+    out.push_str(&format!("</{tag}>"));
+    out
+}
+
+fn odt_table_xml(t: &Table, index: u32) -> String {
+    let cols = t.rows.iter().map(|r| r.cells.len()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        r#"<table:table table:name="Table{}" table:style-name="OdtTable">"#,
+        index + 1
+    ));
+    for _ in 0..cols {
+        out.push_str(r#"<table:table-column table:style-name="OdtTableColumn"/>"#);
+    }
+    for row in &t.rows {
+        out.push_str("<table:table-row>");
+        for cell in &row.cells {
+            out.push_str(r#"<table:table-cell office:value-type="string">"#);
+            if cell.paragraphs.is_empty() {
+                out.push_str(r#"<text:p text:style-name="Standard"/>"#);
+            } else {
+                for p in &cell.paragraphs {
+                    out.push_str(&odt_paragraph_xml(p));
+                }
+            }
+            out.push_str("</table:table-cell>");
+        }
+        out.push_str("</table:table-row>");
+    }
+    out.push_str("</table:table>");
+    out
+}
+
+fn odt_list_style_name(fmt: ListNumFmt) -> &'static str {
+    if fmt == ListNumFmt::Bullet {
+        "OdtListBullet"
+    } else {
+        "OdtListNumber"
+    }
+}
+
+/// Closes open `<text:list-item><text:list>` pairs down to `target_depth` nesting levels.
+fn odt_close_lists_to(out: &mut String, stack: &mut Vec<u32>, target_depth: usize) {
+    while stack.len() > target_depth {
+        out.push_str("</text:list-item></text:list>");
+        stack.pop();
+    }
+}
+
+/// Renders the block sequence into ODT body markup, turning runs of paragraphs carrying
+/// `ListInfo` into `<text:list>`/`<text:list-item>` nesting that mirrors `ListInfo.ilvl`
+/// (`build_blocks_from_html`'s own `list_stack` only ever grows/shrinks by one level at a
+/// time, so a single new `<text:list>` per missing depth is always enough here).
+fn odt_body_xml(blocks: &[Block]) -> String {
+    let mut out = String::new();
+    let mut stack: Vec<u32> = Vec::new();
+    let mut table_index: u32 = 0;
+
+    for block in blocks {
+        match block {
+            Block::Paragraph(p) => {
+                if let Some(list) = p.list {
+                    let target_depth = list.ilvl as usize + 1;
+                    if stack.len() >= target_depth {
+                        odt_close_lists_to(&mut out, &mut stack, target_depth);
+                        out.push_str("</text:list-item>");
+                    } else {
+                        while stack.len() < target_depth {
+                            out.push_str(&format!(
+                                r#"<text:list text:style-name="{}">"#,
+                                odt_list_style_name(list.fmt)
+                            ));
+                            stack.push(list.ilvl);
+                        }
+                    }
+                    out.push_str("<text:list-item>");
+                    out.push_str(&odt_paragraph_xml(p));
+                } else {
+                    odt_close_lists_to(&mut out, &mut stack, 0);
+                    out.push_str(&odt_paragraph_xml(p));
+                }
+            }
+            Block::Table(t) => {
+                odt_close_lists_to(&mut out, &mut stack, 0);
+                out.push_str(&odt_table_xml(t, table_index));
+                table_index += 1;
+            }
+        }
+    }
+
+    odt_close_lists_to(&mut out, &mut stack, 0);
+    out
+}
+
+fn odt_automatic_styles_xml() -> &'static str {
+    r#"<style:style style:name="T_B0_I0_C0" style:family="text"/>
+<style:style style:name="T_B1_I0_C0" style:family="text"><style:text-properties fo:font-weight="bold" style:font-weight-asian="bold" style:font-weight-complex="bold"/></style:style>
+<style:style style:name="T_B0_I1_C0" style:family="text"><style:text-properties fo:font-style="italic" style:font-style-asian="italic" style:font-style-complex="italic"/></style:style>
+<style:style style:name="T_B0_I0_C1" style:family="text"><style:text-properties style:font-name="Consolas"/></style:style>
+<style:style style:name="T_B1_I1_C0" style:family="text"><style:text-properties fo:font-weight="bold" style:font-weight-asian="bold" style:font-weight-complex="bold" fo:font-style="italic" style:font-style-asian="italic" style:font-style-complex="italic"/></style:style>
+<style:style style:name="T_B1_I0_C1" style:family="text"><style:text-properties fo:font-weight="bold" style:font-weight-asian="bold" style:font-weight-complex="bold" style:font-name="Consolas"/></style:style>
+<style:style style:name="T_B0_I1_C1" style:family="text"><style:text-properties fo:font-style="italic" style:font-style-asian="italic" style:font-style-complex="italic" style:font-name="Consolas"/></style:style>
+<style:style style:name="T_B1_I1_C1" style:family="text"><style:text-properties fo:font-weight="bold" style:font-weight-asian="bold" style:font-weight-complex="bold" fo:font-style="italic" style:font-style-asian="italic" style:font-style-complex="italic" style:font-name="Consolas"/></style:style>"#
+}
+
+fn odt_content_xml(body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" xmlns:xlink="http://www.w3.org/1999/xlink" office:version="1.2">
+  <office:automatic-styles>
+    {auto_styles}
+  </office:automatic-styles>
+  <office:body>
+    <office:text>
+      {body}
+    </office:text>
+  </office:body>
+</office:document-content>"#,
+        auto_styles = odt_automatic_styles_xml(),
+        body = body
+    )
+}
+
+fn odt_styles_xml() -> &'static str {
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-styles xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" office:version="1.2">
+  <office:styles>
+    <style:style style:name="Standard" style:family="paragraph" style:class="text"/>
+    <style:style style:name="Heading1" style:family="paragraph" style:parent-style-name="Standard">
+      <style:text-properties fo:font-weight="bold" style:font-weight-asian="bold" style:font-weight-complex="bold" fo:font-size="20pt"/>
+    </style:style>
+    <style:style style:name="Heading2" style:family="paragraph" style:parent-style-name="Standard">
+      <style:text-properties fo:font-weight="bold" style:font-weight-asian="bold" style:font-weight-complex="bold" fo:font-size="16pt"/>
+    </style:style>
+    <style:style style:name="Preformatted" style:family="paragraph" style:parent-style-name="Standard">
+      <style:text-properties style:font-name="Consolas" fo:font-size="10pt"/>
+    </style:style>
+    <text:list-style style:name="OdtListBullet">
+      <text:list-level-style-bullet text:level="1" text:bullet-char="&#8226;"><style:list-level-properties text:space-before="0.25in" text:min-label-width="0.25in"/></text:list-level-style-bullet>
+      <text:list-level-style-bullet text:level="2" text:bullet-char="&#8226;"><style:list-level-properties text:space-before="0.5in" text:min-label-width="0.25in"/></text:list-level-style-bullet>
+    </text:list-style>
+    <text:list-style style:name="OdtListNumber">
+      <text:list-level-style-number text:level="1" style:num-format="1" text:display-levels="1"><style:list-level-properties text:space-before="0.25in" text:min-label-width="0.25in"/></text:list-level-style-number>
+      <text:list-level-style-number text:level="2" style:num-format="1" text:display-levels="1"><style:list-level-properties text:space-before="0.5in" text:min-label-width="0.25in"/></text:list-level-style-number>
+    </text:list-style>
+    <style:style style:name="OdtTableColumn" style:family="table-column"/>
+    <style:style style:name="OdtTable" style:family="table"/>
+  </office:styles>
+</office:document-styles>"#
+}
+
+fn odt_manifest_xml() -> &'static str {
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.2">
+  <manifest:file-entry manifest:full-path="/" manifest:version="1.2" manifest:media-type="application/vnd.oasis.opendocument.text"/>
+  <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+  <manifest:file-entry manifest:full-path="styles.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>"#
+}
+
+fn write_odt_zip(blocks: &[Block]) -> Result<Vec<u8>> {
+    let content = odt_content_xml(&odt_body_xml(blocks));
+
+    let mut buf = Cursor::new(Vec::new());
+    {
+        let mut zip = ZipWriter::new(&mut buf);
+        // The mimetype entry must be first and stored (uncompressed) for ODF's "magic bytes"
+        // sniffing to work before any zip central-directory parsing happens.
+        let stored = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        let deflated = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+        zip.start_file("mimetype", stored)?;
+        zip.write_all(b"application/vnd.oasis.opendocument.text")?;
+
+        zip.start_file("META-INF/manifest.xml", deflated)?;
+        zip.write_all(odt_manifest_xml().as_bytes())?;
+
+        zip.start_file("content.xml", deflated)?;
+        zip.write_all(content.as_bytes())?;
+
+        zip.start_file("styles.xml", deflated)?;
+        zip.write_all(odt_styles_xml().as_bytes())?;
+
+        zip.finish()?;
+    }
+    Ok(buf.into_inner())
+}
+
+struct OdtWriter;
+
+impl DocumentWriter for OdtWriter {
+    fn write(&self, blocks: &[Block], out: &mut impl Write, _include_toc: bool) -> Result<()> {
+        // TODO: the ODT backend has no TOC field equivalent yet; `--toc` is a no-op here.
+        let bytes = write_odt_zip(blocks)?;
+        out.write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+/// Tokenizes one line of source for `lang` into `(text, color)` spans, `color` being `RRGGBB` or
+/// `None` for the theme's default foreground. Lets a real tokenizer (syntect, behind a cargo
+/// feature) be swapped in without `highlight_code_blocks` knowing about it.
+trait Highlighter {
+    fn spans(&self, lang: &str, line: &str) -> Vec<(String, Option<String>)>;
+}
+
+struct PlainHighlighter;
+
+impl Highlighter for PlainHighlighter {
+    fn spans(&self, _lang: &str, line: &str) -> Vec<(String, Option<String>)> {
+        vec![(line.to_string(), None)]
+    }
+}
+
+#[cfg(feature = "syntect")]
+mod syntect_highlight {
+    use super::Highlighter;
+    use syntect::easy::HighlightLines;
+    use syntect::highlighting::{Theme, ThemeSet};
+    use syntect::parsing::SyntaxSet;
+
+    pub struct SyntectHighlighter {
+        syntax_set: SyntaxSet,
+        theme: Theme,
+    }
+
+    impl SyntectHighlighter {
+        pub fn new(theme_name: &str) -> Self {
+            let syntax_set = SyntaxSet::load_defaults_newlines();
+            let theme_set = ThemeSet::load_defaults();
+            let theme = theme_set
+                .themes
+                .get(theme_name)
+                .cloned()
+                .unwrap_or_else(|| theme_set.themes["InspiredGitHub"].clone());
+            Self { syntax_set, theme }
+        }
+    }
+
+    impl Highlighter for SyntectHighlighter {
+        fn spans(&self, lang: &str, line: &str) -> Vec<(String, Option<String>)> {
+            let syntax = self
+                .syntax_set
+                .find_syntax_by_token(lang)
+                .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+            let mut highlighter = HighlightLines::new(syntax, &self.theme);
+            let Ok(ranges) = highlighter.highlight_line(line, &self.syntax_set) else {
+                return vec![(line.to_string(), None)];
+            };
+            ranges
+                .into_iter()
+                .map(|(style, text)| {
+                    let color = format!(
+                        "{:02X}{:02X}{:02X}",
+                        style.foreground.r, style.foreground.g, style.foreground.b
+                    );
+                    (text.to_string(), Some(color))
+                })
+                .collect()
+        }
+    }
+}
+
+fn make_highlighter(_theme: Option<&str>) -> Box<dyn Highlighter> {
+    #[cfg(feature = "syntect")]
+    if let Some(theme) = _theme {
+        return Box::new(syntect_highlight::SyntectHighlighter::new(theme));
+    }
+    Box::new(PlainHighlighter)
+}
+
+/// Re-tokenizes each line of every `CodeBlock` paragraph that carries a `code_lang` hint through
+/// `highlighter`, replacing the single plain-text line the HTML walker produced with one colored
+/// `Segment::Text` run per token. Runs after `build_blocks_from_html` so the walker itself stays
+/// oblivious to highlighting; blocks with no language hint are left untouched.
+fn highlight_code_blocks(blocks: &mut [Block], highlighter: &dyn Highlighter) {
+    for block in blocks.iter_mut() {
+        let Block::Paragraph(p) = block else { continue };
+        if p.style != ParagraphStyle::CodeBlock {
+            continue;
+        }
+        let Some(lang) = p.code_lang.clone() else {
+            continue;
+        };
+
+        let mut new_segments = Vec::with_capacity(p.segments.len());
+        let mut line = String::new();
+        let flush_line = |line: &mut String, out: &mut Vec<Segment>| {
+            for (token, color) in highlighter.spans(&lang, line) {
+                if token.is_empty() {
+                    continue;
+                }
+                out.push(Segment::Text {
+                    text: token,
+                    style: RunStyle {
+                        bold: false,
+                        italic: false,
+                        code: true,
+                        underline: false,
+                        strike: false,
+                        color,
+                        highlight: None,
+                        size_half_pt: None,
+                    },
+                });
+            }
+            line.clear();
+        };
+
+        for seg in std::mem::take(&mut p.segments) {
+            match seg {
+                Segment::Text { text, .. } => line.push_str(&text),
+                Segment::Break => {
+                    flush_line(&mut line, &mut new_segments);
+                    new_segments.push(Segment::Break);
+                }
+                other => {
+                    flush_line(&mut line, &mut new_segments);
+                    new_segments.push(other);
+                }
+            }
+        }
+        flush_line(&mut line, &mut new_segments);
+        p.segments = new_segments;
+    }
 }
 
 fn main() -> Result<()> {
@@ -940,20 +2793,18 @@ fn main() -> Result<()> {
         return Err(anyhow!("empty html"));
     }
 
-    let blocks = build_blocks_from_html(&html);
-
-    let hrefs = gather_hrefs(&blocks);
-    let mut link_to_rid = BTreeMap::new();
-    let mut rid_counter: u32 = 10;
-    for href in hrefs {
-        link_to_rid.insert(href, format!("rId{}", rid_counter));
-        rid_counter += 1;
+    let mut blocks = build_blocks_from_html(&html);
+    let highlighter = make_highlighter(args.highlight.as_deref());
+    highlight_code_blocks(&mut blocks, highlighter.as_ref());
+
+    let mut out_file =
+        File::create(&args.out).with_context(|| format!("create {}", args.out.display()))?;
+    let format = args
+        .format
+        .unwrap_or_else(|| OutputFormat::from_extension(&args.out));
+    match format {
+        OutputFormat::Docx => DocxWriter.write(&blocks, &mut out_file, args.toc)?,
+        OutputFormat::Odt => OdtWriter.write(&blocks, &mut out_file, args.toc)?,
     }
-
-    let doc_xml = document_xml(&blocks, &link_to_rid);
-    let doc_rels = document_rels_xml(&link_to_rid);
-    let has_numbering = blocks_need_numbering(&blocks);
-
-    write_docx(&args.out, &doc_xml, &doc_rels, has_numbering)?;
     Ok(())
 }
@@ -1,26 +1,129 @@
 use anyhow::{anyhow, Context, Result};
 use clap::Parser;
 use regex::Regex;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
 
+/// Output package format: `Docx` writes the existing WordprocessingML package, `Odt` writes an
+/// OpenDocument Text package (see `write_odt`) from the same `Vec<Paragraph>`/`Footnotes`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum OutputFormat {
+    #[default]
+    Docx,
+    Odt,
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 struct Args {
     /// Input HTML file (expects a full document; body will be extracted if present).
+    /// Exactly one of --html-file / --markdown-file must be given.
+    #[arg(long)]
+    html_file: Option<PathBuf>,
+
+    /// Input Markdown file (CommonMark, plus GFM tables, fenced code, and autolinks).
+    /// Exactly one of --html-file / --markdown-file must be given.
     #[arg(long)]
-    html_file: PathBuf,
+    markdown_file: Option<PathBuf>,
 
-    /// Output .docx path.
+    /// Output package format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Docx)]
+    format: OutputFormat,
+
+    /// Output .docx/.odt path.
     #[arg(long)]
     out: PathBuf,
 
-    /// Document title metadata (optional).
+    /// Document title metadata (optional); written to docProps/core.xml as dc:title.
     #[arg(long)]
     title: Option<String>,
+
+    /// Document author metadata (optional); written as both dc:creator and cp:lastModifiedBy.
+    #[arg(long)]
+    author: Option<String>,
+
+    /// Document subject metadata (optional); written to docProps/core.xml as dc:subject.
+    #[arg(long)]
+    subject: Option<String>,
+
+    /// Skip syntax highlighting in fenced/`<pre>` code blocks; emit plain monospace runs.
+    #[arg(long)]
+    no_highlight: bool,
+
+    /// Token color palette for syntax-highlighted code blocks (ignored with --no-highlight).
+    #[arg(long, value_enum, default_value_t = Theme::Dark)]
+    theme: Theme,
+
+    /// Reverse mode: read an existing .docx instead of writing one. When given, all of the
+    /// forward-conversion flags above (--html-file/--markdown-file/--format/...) are ignored;
+    /// --out is the HTML or Markdown file to write (chosen by its extension - see `import_docx`).
+    #[arg(long)]
+    import: Option<PathBuf>,
+
+    /// Zip compression method for the generated package's parts.
+    #[arg(long, value_enum, default_value_t = Compression::Deflate)]
+    compression: Compression,
+
+    /// Deflate level (0-9, higher = smaller but slower); ignored with `--compression store` and
+    /// with the zip crate's own default level when unset.
+    #[arg(long)]
+    compression_level: Option<i64>,
+
+    /// Store `[Content_Types].xml`/`.rels` parts uncompressed regardless of `--compression`:
+    /// they're a few hundred bytes, so deflating them costs CPU for no real size win.
+    #[arg(long)]
+    store_small_parts: bool,
+
+    /// Batch mode: recursively convert every `.html`/`.md` file under this directory instead of
+    /// a single --html-file/--markdown-file. When given, --out is the output directory (mirroring
+    /// the input tree) rather than a single output file - see `run_batch`.
+    #[arg(long)]
+    input_dir: Option<PathBuf>,
+}
+
+/// `--compression`'s method: `Deflate` is the default (smaller output); `Store` skips
+/// compression entirely, trading size for speed - useful when batch-generating many documents
+/// where CPU, not disk, is the bottleneck.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Compression {
+    #[default]
+    Deflate,
+    Store,
+}
+
+impl From<Compression> for zip::CompressionMethod {
+    fn from(c: Compression) -> Self {
+        match c {
+            Compression::Deflate => zip::CompressionMethod::Deflated,
+            Compression::Store => zip::CompressionMethod::Stored,
+        }
+    }
+}
+
+/// Per-entry zip options `write_docx`/`write_odt` build from `Args`: `bulk` is used for most
+/// parts, `small` is forced to `CompressionMethod::Stored` for the tiny fixed-boilerplate parts
+/// named in `--store-small-parts`'s doc comment (falls back to `bulk` when that flag isn't given).
+#[derive(Debug, Clone, Copy)]
+struct ZipOptions {
+    bulk: SimpleFileOptions,
+    small: SimpleFileOptions,
+}
+
+fn zip_options_from_args(args: &Args) -> ZipOptions {
+    let mut bulk = SimpleFileOptions::default().compression_method(args.compression.into());
+    if args.compression_level.is_some() {
+        bulk = bulk.compression_level(args.compression_level);
+    }
+    let small = if args.store_small_parts {
+        SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored)
+    } else {
+        bulk
+    };
+    ZipOptions { bulk, small }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -35,6 +138,17 @@ enum Segment {
     Text { text: String, style: RunStyle },
     Break,
     Omml(String),
+    LinkText { text: String, href: String, style: RunStyle },
+    /// Inline `$...$`/`$$...$$` math captured verbatim from Markdown. Rendered as italic
+    /// literal LaTeX for now - turning it into OMML needs the tex-to-mathml conversion the
+    /// wasm crate exposes, which this native CLI doesn't link against.
+    Math(String),
+    /// A `[^name]` footnote marker from Markdown, resolved to the numeric id its footnote
+    /// occupies in `word/footnotes.xml` (2+ - ids 0/1 are reserved, see `footnotes_xml`).
+    FootnoteRef { id: u32 },
+    /// A decoded `<img>` payload: `index` into the `Images` vec collected while parsing (see
+    /// `ImageAsset`), `cx`/`cy` the drawing extent in EMUs (1 px = 9525 EMU).
+    Image { index: usize, cx: i64, cy: i64 },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -43,13 +157,75 @@ enum ParagraphStyle {
     Heading1,
     Heading2,
     CodeBlock,
-    Bullet,
+}
+
+/// Word list numbering reference: `num_id` selects which list definition in `numbering.xml`
+/// a paragraph belongs to (1 = bullet, 2 = ordered/decimal), `ilvl` is the 0-based nesting depth.
+#[derive(Debug, Clone, Copy)]
+struct ListInfo {
+    num_id: u32,
+    ilvl: u32,
 }
 
 #[derive(Debug, Clone)]
 struct Paragraph {
     style: ParagraphStyle,
     segments: Vec<Segment>,
+    list: Option<ListInfo>,
+    /// Fenced-code-block language (`language-rust` class / ```` ```rust ```` info string),
+    /// `None` for plain `<pre>`/indented code. Only meaningful when `style` is `CodeBlock`; see
+    /// `highlight_code_paragraph`.
+    code_lang: Option<String>,
+}
+
+/// One table cell: the paragraphs making up its content (almost always one), plus the
+/// `colspan`/`rowspan` `render_table`/`odt_table_xml` need to decide between a plain `<w:tc>`, a
+/// `<w:gridSpan>`, and a `<w:vMerge>` continuation. Header-row cells (GFM header row, HTML `<th>`)
+/// arrive with their runs already forced bold by `force_bold` rather than carrying a separate
+/// flag, so rendering doesn't need to special-case them.
+#[derive(Debug, Clone)]
+struct TableCell {
+    paragraphs: Vec<Paragraph>,
+    colspan: u32,
+    rowspan: u32,
+}
+
+/// A table: `rows[r][c]` is one cell, in document order - `<thead>`/`<tbody>`/`<tfoot>` rows (HTML)
+/// or header/body rows (GFM) are flattened together the same way.
+#[derive(Debug, Clone)]
+struct Table {
+    rows: Vec<Vec<TableCell>>,
+}
+
+#[derive(Debug, Clone)]
+enum Block {
+    Para(Paragraph),
+    Table(Table),
+}
+
+/// Footnote bodies in id order, as handed to `footnotes_xml`/`write_docx`.
+type Footnotes = Vec<(u32, Vec<Paragraph>)>;
+
+/// A decoded `<img>` payload awaiting a `word/media/imageN.ext` slot: `N` is the position in the
+/// `Images` vec (1-based) and `ext` picks both the file's extension and its `[Content_Types].xml`
+/// default content type.
+#[derive(Debug, Clone)]
+struct ImageAsset {
+    bytes: Vec<u8>,
+    ext: &'static str,
+}
+
+/// Images in encounter order, as handed to `word_rels_xml`/`content_types_xml`/`write_docx`.
+/// `Segment::Image::index` is the position of its `ImageAsset` in this vec.
+type Images = Vec<ImageAsset>;
+
+/// User-supplied `docProps/core.xml` fields; created/modified timestamps are stamped separately
+/// (with the current time) rather than taken from the CLI, since there's no "when" to pass in.
+#[derive(Debug, Clone, Default)]
+struct DocMetadata {
+    title: Option<String>,
+    author: Option<String>,
+    subject: Option<String>,
 }
 
 fn normalize_omml_case(xml: &str) -> String {
@@ -86,6 +262,269 @@ fn xml_escape_text(s: &str) -> String {
     out
 }
 
+/// The HTML4/XHTML1 named character reference set (`&amp;name;` -> Unicode), sorted by name for
+/// `decode_entities_basic`'s binary search. Covers the ISO-8859-1 block, the full Greek alphabet,
+/// mathematical/technical symbols, arrows, and the markup-significant/typographic punctuation set
+/// (dashes, quotes, `&euro;`, etc.) - the entities GPT/browser output actually produces, without
+/// pulling in a full ~2000-entry HTML5 table (or a dependency) for the long tail HTML5 added on
+/// top of it. `ThickSpace` and `fjlig` are the two entries here that expand to more than one code
+/// point; every other entry is a single `char`.
+static NAMED_ENTITIES: &[(&str, &str)] = &[
+    ("AElig", "\u{00C6}"),
+    ("Aacute", "\u{00C1}"),
+    ("Acirc", "\u{00C2}"),
+    ("Agrave", "\u{00C0}"),
+    ("Alpha", "\u{0391}"),
+    ("Aring", "\u{00C5}"),
+    ("Atilde", "\u{00C3}"),
+    ("Auml", "\u{00C4}"),
+    ("Beta", "\u{0392}"),
+    ("Ccedil", "\u{00C7}"),
+    ("Chi", "\u{03A7}"),
+    ("Dagger", "\u{2021}"),
+    ("Delta", "\u{0394}"),
+    ("ETH", "\u{00D0}"),
+    ("Eacute", "\u{00C9}"),
+    ("Ecirc", "\u{00CA}"),
+    ("Egrave", "\u{00C8}"),
+    ("Epsilon", "\u{0395}"),
+    ("Eta", "\u{0397}"),
+    ("Euml", "\u{00CB}"),
+    ("Gamma", "\u{0393}"),
+    ("Iacute", "\u{00CD}"),
+    ("Icirc", "\u{00CE}"),
+    ("Igrave", "\u{00CC}"),
+    ("Iota", "\u{0399}"),
+    ("Iuml", "\u{00CF}"),
+    ("Kappa", "\u{039A}"),
+    ("Lambda", "\u{039B}"),
+    ("Mu", "\u{039C}"),
+    ("Ntilde", "\u{00D1}"),
+    ("Nu", "\u{039D}"),
+    ("OElig", "\u{0152}"),
+    ("Oacute", "\u{00D3}"),
+    ("Ocirc", "\u{00D4}"),
+    ("Ograve", "\u{00D2}"),
+    ("Omega", "\u{03A9}"),
+    ("Omicron", "\u{039F}"),
+    ("Oslash", "\u{00D8}"),
+    ("Otilde", "\u{00D5}"),
+    ("Ouml", "\u{00D6}"),
+    ("Phi", "\u{03A6}"),
+    ("Pi", "\u{03A0}"),
+    ("Prime", "\u{2033}"),
+    ("Psi", "\u{03A8}"),
+    ("Rho", "\u{03A1}"),
+    ("Scaron", "\u{0160}"),
+    ("Sigma", "\u{03A3}"),
+    ("THORN", "\u{00DE}"),
+    ("Tau", "\u{03A4}"),
+    ("Theta", "\u{0398}"),
+    ("ThickSpace", "\u{205F}\u{200A}"),
+    ("Uacute", "\u{00DA}"),
+    ("Ucirc", "\u{00DB}"),
+    ("Ugrave", "\u{00D9}"),
+    ("Upsilon", "\u{03A5}"),
+    ("Uuml", "\u{00DC}"),
+    ("Xi", "\u{039E}"),
+    ("Yacute", "\u{00DD}"),
+    ("Yuml", "\u{0178}"),
+    ("Zeta", "\u{0396}"),
+    ("aacute", "\u{00E1}"),
+    ("acirc", "\u{00E2}"),
+    ("acute", "\u{00B4}"),
+    ("aelig", "\u{00E6}"),
+    ("agrave", "\u{00E0}"),
+    ("alefsym", "\u{2135}"),
+    ("alpha", "\u{03B1}"),
+    ("amp", "&"),
+    ("and", "\u{2227}"),
+    ("ang", "\u{2220}"),
+    ("apos", "'"),
+    ("aring", "\u{00E5}"),
+    ("asymp", "\u{2248}"),
+    ("atilde", "\u{00E3}"),
+    ("auml", "\u{00E4}"),
+    ("bdquo", "\u{201E}"),
+    ("beta", "\u{03B2}"),
+    ("brvbar", "\u{00A6}"),
+    ("bull", "\u{2022}"),
+    ("cap", "\u{2229}"),
+    ("ccedil", "\u{00E7}"),
+    ("cedil", "\u{00B8}"),
+    ("cent", "\u{00A2}"),
+    ("chi", "\u{03C7}"),
+    ("circ", "\u{02C6}"),
+    ("clubs", "\u{2663}"),
+    ("cong", "\u{2245}"),
+    ("copy", "\u{00A9}"),
+    ("crarr", "\u{21B5}"),
+    ("cup", "\u{222A}"),
+    ("curren", "\u{00A4}"),
+    ("dArr", "\u{21D3}"),
+    ("dagger", "\u{2020}"),
+    ("darr", "\u{2193}"),
+    ("deg", "\u{00B0}"),
+    ("delta", "\u{03B4}"),
+    ("diams", "\u{2666}"),
+    ("divide", "\u{00F7}"),
+    ("eacute", "\u{00E9}"),
+    ("ecirc", "\u{00EA}"),
+    ("egrave", "\u{00E8}"),
+    ("emsp", "\u{2003}"),
+    ("ensp", "\u{2002}"),
+    ("epsilon", "\u{03B5}"),
+    ("equiv", "\u{2261}"),
+    ("eta", "\u{03B7}"),
+    ("eth", "\u{00F0}"),
+    ("euml", "\u{00EB}"),
+    ("euro", "\u{20AC}"),
+    ("exist", "\u{2203}"),
+    ("fjlig", "fj"),
+    ("forall", "\u{2200}"),
+    ("frac12", "\u{00BD}"),
+    ("frac14", "\u{00BC}"),
+    ("frac34", "\u{00BE}"),
+    ("frasl", "\u{2044}"),
+    ("gamma", "\u{03B3}"),
+    ("ge", "\u{2265}"),
+    ("gt", ">"),
+    ("hArr", "\u{21D4}"),
+    ("harr", "\u{2194}"),
+    ("hearts", "\u{2665}"),
+    ("hellip", "\u{2026}"),
+    ("iacute", "\u{00ED}"),
+    ("icirc", "\u{00EE}"),
+    ("iexcl", "\u{00A1}"),
+    ("igrave", "\u{00EC}"),
+    ("image", "\u{2111}"),
+    ("infin", "\u{221E}"),
+    ("int", "\u{222B}"),
+    ("iota", "\u{03B9}"),
+    ("iquest", "\u{00BF}"),
+    ("isin", "\u{2208}"),
+    ("iuml", "\u{00EF}"),
+    ("kappa", "\u{03BA}"),
+    ("lArr", "\u{21D0}"),
+    ("lambda", "\u{03BB}"),
+    ("lang", "\u{27E8}"),
+    ("laquo", "\u{00AB}"),
+    ("larr", "\u{2190}"),
+    ("lceil", "\u{2308}"),
+    ("ldquo", "\u{201C}"),
+    ("le", "\u{2264}"),
+    ("lfloor", "\u{230A}"),
+    ("lowast", "\u{2217}"),
+    ("loz", "\u{25CA}"),
+    ("lrm", "\u{200E}"),
+    ("lsaquo", "\u{2039}"),
+    ("lsquo", "\u{2018}"),
+    ("lt", "<"),
+    ("macr", "\u{00AF}"),
+    ("mdash", "\u{2014}"),
+    ("micro", "\u{00B5}"),
+    ("middot", "\u{00B7}"),
+    ("minus", "\u{2212}"),
+    ("mu", "\u{03BC}"),
+    ("nabla", "\u{2207}"),
+    ("nbsp", "\u{00A0}"),
+    ("ndash", "\u{2013}"),
+    ("ne", "\u{2260}"),
+    ("ni", "\u{220B}"),
+    ("not", "\u{00AC}"),
+    ("notin", "\u{2209}"),
+    ("nsub", "\u{2284}"),
+    ("ntilde", "\u{00F1}"),
+    ("nu", "\u{03BD}"),
+    ("oacute", "\u{00F3}"),
+    ("ocirc", "\u{00F4}"),
+    ("oelig", "\u{0153}"),
+    ("ograve", "\u{00F2}"),
+    ("oline", "\u{203E}"),
+    ("omega", "\u{03C9}"),
+    ("omicron", "\u{03BF}"),
+    ("oplus", "\u{2295}"),
+    ("or", "\u{2228}"),
+    ("ordf", "\u{00AA}"),
+    ("ordm", "\u{00BA}"),
+    ("oslash", "\u{00F8}"),
+    ("otilde", "\u{00F5}"),
+    ("otimes", "\u{2297}"),
+    ("ouml", "\u{00F6}"),
+    ("para", "\u{00B6}"),
+    ("part", "\u{2202}"),
+    ("permil", "\u{2030}"),
+    ("perp", "\u{22A5}"),
+    ("phi", "\u{03C6}"),
+    ("pi", "\u{03C0}"),
+    ("piv", "\u{03D6}"),
+    ("plusmn", "\u{00B1}"),
+    ("pound", "\u{00A3}"),
+    ("prime", "\u{2032}"),
+    ("prod", "\u{220F}"),
+    ("prop", "\u{221D}"),
+    ("psi", "\u{03C8}"),
+    ("quot", "\""),
+    ("rArr", "\u{21D2}"),
+    ("radic", "\u{221A}"),
+    ("rang", "\u{27E9}"),
+    ("raquo", "\u{00BB}"),
+    ("rarr", "\u{2192}"),
+    ("rceil", "\u{2309}"),
+    ("rdquo", "\u{201D}"),
+    ("real", "\u{211C}"),
+    ("reg", "\u{00AE}"),
+    ("rfloor", "\u{230B}"),
+    ("rho", "\u{03C1}"),
+    ("rlm", "\u{200F}"),
+    ("rsaquo", "\u{203A}"),
+    ("rsquo", "\u{2019}"),
+    ("sbquo", "\u{201A}"),
+    ("scaron", "\u{0161}"),
+    ("sdot", "\u{22C5}"),
+    ("sect", "\u{00A7}"),
+    ("shy", "\u{00AD}"),
+    ("sigma", "\u{03C3}"),
+    ("sigmaf", "\u{03C2}"),
+    ("sim", "\u{223C}"),
+    ("spades", "\u{2660}"),
+    ("sub", "\u{2282}"),
+    ("sube", "\u{2286}"),
+    ("sum", "\u{2211}"),
+    ("sup", "\u{2283}"),
+    ("sup1", "\u{00B9}"),
+    ("sup2", "\u{00B2}"),
+    ("sup3", "\u{00B3}"),
+    ("supe", "\u{2287}"),
+    ("szlig", "\u{00DF}"),
+    ("tau", "\u{03C4}"),
+    ("there4", "\u{2234}"),
+    ("theta", "\u{03B8}"),
+    ("thetasym", "\u{03D1}"),
+    ("thinsp", "\u{2009}"),
+    ("thorn", "\u{00FE}"),
+    ("tilde", "\u{02DC}"),
+    ("times", "\u{00D7}"),
+    ("trade", "\u{2122}"),
+    ("uArr", "\u{21D1}"),
+    ("uacute", "\u{00FA}"),
+    ("uarr", "\u{2191}"),
+    ("ucirc", "\u{00FB}"),
+    ("ugrave", "\u{00F9}"),
+    ("uml", "\u{00A8}"),
+    ("upsih", "\u{03D2}"),
+    ("upsilon", "\u{03C5}"),
+    ("uuml", "\u{00FC}"),
+    ("weierp", "\u{2118}"),
+    ("xi", "\u{03BE}"),
+    ("yacute", "\u{00FD}"),
+    ("yen", "\u{00A5}"),
+    ("yuml", "\u{00FF}"),
+    ("zeta", "\u{03B6}"),
+    ("zwj", "\u{200D}"),
+    ("zwnj", "\u{200C}"),
+];
+
 fn decode_entities_basic(s: &str) -> String {
     // Minimal deterministic decode; preserves UTF-8 (do NOT iterate bytes).
     let mut out = String::with_capacity(s.len());
@@ -112,18 +551,8 @@ fn decode_entities_basic(s: &str) -> String {
             }
         }
 
-        let decoded: Option<char> = match ent.as_str() {
-            "nbsp" => Some(' '),
-            "lt" => Some('<'),
-            "gt" => Some('>'),
-            "amp" => Some('&'),
-            "quot" => Some('"'),
-            "apos" => Some('\''),
-            _ => None,
-        };
-
-        if let Some(c) = decoded {
-            out.push(c);
+        if let Ok(idx) = NAMED_ENTITIES.binary_search_by_key(&ent.as_str(), |(name, _)| name) {
+            out.push_str(NAMED_ENTITIES[idx].1);
             continue;
         }
 
@@ -269,25 +698,327 @@ fn collapse_whitespace(s: &str) -> String {
     out
 }
 
-fn build_paragraphs_from_html(body_html: &str) -> Result<Vec<Paragraph>> {
+/// 1 px = 9525 EMU (English Metric Units), the unit `<wp:extent>` expects.
+const EMU_PER_PX: i64 = 9525;
+
+/// Fallback drawing size (in px, before EMU conversion) when an image's pixel dimensions can't be
+/// determined - keeps the page layout sane rather than embedding at an unknown/zero size.
+const DEFAULT_IMAGE_WIDTH_PX: u32 = 300;
+const DEFAULT_IMAGE_HEIGHT_PX: u32 = 200;
+
+/// Mirrors the hand-rolled codec in `tex_to_mathml_wasm/src/normalize.rs` - there's no shared
+/// crate for it and no `base64` dependency in this one either.
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let clean: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if clean.is_empty() || clean.len() % 4 != 0 {
+        return None;
+    }
+    let value_of = |b: u8| -> Option<u8> {
+        if b == b'=' {
+            Some(0)
+        } else {
+            BASE64_ALPHABET.iter().position(|&c| c == b).map(|p| p as u8)
+        }
+    };
+    let mut out = Vec::with_capacity(clean.len() / 4 * 3);
+    for chunk in clean.chunks(4) {
+        let b0 = value_of(chunk[0])?;
+        let b1 = value_of(chunk[1])?;
+        let b2 = value_of(chunk[2])?;
+        let b3 = value_of(chunk[3])?;
+        out.push((b0 << 2) | (b1 >> 4));
+        if chunk[2] != b'=' {
+            out.push((b1 << 4) | (b2 >> 2));
+        }
+        if chunk[3] != b'=' {
+            out.push((b2 << 6) | b3);
+        }
+    }
+    Some(out)
+}
+
+/// Decodes `src="data:image/png;base64,..."`/`data:image/jpeg;base64,...` URIs, or falls back to
+/// reading `src` as a local file path (resolved relative to the current working directory, since
+/// this CLI has no notion of the source document's own base path). Returns `None` for unsupported
+/// mime types, malformed base64, or a path that can't be read - the caller then leaves the `<img>`
+/// out entirely, same as today.
+fn resolve_image_src(src: &str) -> Option<(Vec<u8>, &'static str)> {
+    if let Some(rest) = src.strip_prefix("data:") {
+        let comma = rest.find(',')?;
+        let meta = &rest[..comma];
+        let payload = &rest[comma + 1..];
+        let mime = meta.strip_suffix(";base64")?;
+        let ext = match mime {
+            "image/png" => "png",
+            "image/jpeg" | "image/jpg" => "jpeg",
+            _ => return None,
+        };
+        let bytes = base64_decode(payload)?;
+        return Some((bytes, ext));
+    }
+
+    let path = PathBuf::from(src);
+    let ext = match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()) {
+        Some(e) if e == "png" => "png",
+        Some(e) if e == "jpg" || e == "jpeg" => "jpeg",
+        _ => return None,
+    };
+    let bytes = std::fs::read(&path).ok()?;
+    Some((bytes, ext))
+}
+
+/// PNG pixel dimensions from the IHDR chunk, which always starts at byte 16 (8-byte signature +
+/// 4-byte chunk length + 4-byte "IHDR" tag): width then height as big-endian u32s.
+fn parse_png_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.len() < 24 || &bytes[0..8] != b"\x89PNG\r\n\x1a\n" {
+        return None;
+    }
+    let w = u32::from_be_bytes(bytes[16..20].try_into().ok()?);
+    let h = u32::from_be_bytes(bytes[20..24].try_into().ok()?);
+    Some((w, h))
+}
+
+/// JPEG pixel dimensions from the first SOF (start-of-frame) marker segment: height then width as
+/// big-endian u16s, 5 bytes into the segment (2-byte length + 1-byte sample precision).
+fn parse_jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.len() < 4 || bytes[0] != 0xFF || bytes[1] != 0xD8 {
+        return None;
+    }
+    let mut i = 2;
+    while i + 1 < bytes.len() {
+        if bytes[i] != 0xFF {
+            i += 1;
+            continue;
+        }
+        let marker = bytes[i + 1];
+        // Standalone markers (no length field follows): start-of-image, restart markers, ...
+        if marker == 0xD8 || marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            i += 2;
+            continue;
+        }
+        if marker == 0xD9 || i + 3 >= bytes.len() {
+            break;
+        }
+        let seg_len = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+        let is_sof = matches!(marker, 0xC0..=0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF);
+        if is_sof {
+            if i + 9 > bytes.len() {
+                return None;
+            }
+            let h = u16::from_be_bytes([bytes[i + 5], bytes[i + 6]]) as u32;
+            let w = u16::from_be_bytes([bytes[i + 7], bytes[i + 8]]) as u32;
+            return Some((w, h));
+        }
+        i += 2 + seg_len;
+    }
+    None
+}
+
+/// Finds `attr="value"`/`attr='value'` inside a raw (unescaped) tag body like
+/// `img src="..." alt="..."`, requiring `attr` to start right after whitespace so e.g. `data-src`
+/// doesn't match a lookup for `src`.
+fn extract_attr(tag_body: &str, attr: &str) -> Option<String> {
+    let lower = tag_body.to_ascii_lowercase();
+    let needle = format!("{attr}=");
+    let mut search_from = 0;
+    while let Some(rel) = lower[search_from..].find(needle.as_str()) {
+        let pos = search_from + rel;
+        let boundary_ok = pos == 0 || lower.as_bytes()[pos - 1].is_ascii_whitespace();
+        if !boundary_ok {
+            search_from = pos + needle.len();
+            continue;
+        }
+        let after = pos + needle.len();
+        let (open, close) = match tag_body.as_bytes().get(after) {
+            Some(b'"') => (after + 1, '"'),
+            Some(b'\'') => (after + 1, '\''),
+            _ => return None,
+        };
+        let end_rel = tag_body[open..].find(close)?;
+        return Some(decode_entities_basic(&tag_body[open..open + end_rel]));
+    }
+    None
+}
+
+/// Finds the `<` starting the `</tag>` that matches a `<tag ...>`/`<tag>` open tag whose content
+/// begins at `start` (right after its own `>`), accounting for any further same-named tag nested
+/// inside. Searches a lowercased copy while indexing the original string, the same trick
+/// `extract_body` uses - safe here since the only bytes that matter (`<`, `>`, ASCII tag names)
+/// never change length under `to_ascii_lowercase`.
+fn find_balanced_close(html: &str, start: usize, tag: &str) -> Option<usize> {
+    let lower = html.to_ascii_lowercase();
+    let open_needle = format!("<{tag}");
+    let close_needle = format!("</{tag}>");
+    let mut depth = 1usize;
+    let mut i = start;
+    loop {
+        let next_open = lower[i..].find(&open_needle).map(|p| i + p);
+        let next_close = lower[i..].find(&close_needle).map(|p| i + p);
+        match (next_open, next_close) {
+            (Some(o), Some(c)) if o < c => {
+                let after = o + open_needle.len();
+                let boundary = html.as_bytes().get(after).is_none_or(|&b| b == b'>' || b == b'/' || b.is_ascii_whitespace());
+                if boundary {
+                    depth += 1;
+                }
+                i = after;
+            }
+            (_, Some(c)) => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(c);
+                }
+                i = c + close_needle.len();
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Parses a `<table>`'s inner HTML (everything between its `<table ...>` and matching `</table>`)
+/// into a `Table`, picking out `<tr>` elements wherever they occur - `<thead>`/`<tbody>`/`<tfoot>`
+/// wrappers need no special handling since they fall through to the default "skip this tag" case
+/// and the `<tr>`s inside them are found all the same. `images` is the whole document's image
+/// list, threaded through so `Segment::Image::index` values minted while parsing cells keep
+/// pointing at the right entry.
+fn parse_html_table(inner_html: &str, images: &mut Images) -> Result<Table> {
+    let mut rows: Vec<Vec<TableCell>> = Vec::new();
+    let bytes = inner_html.as_bytes();
+    let mut i = 0usize;
+
+    while i < bytes.len() {
+        let Some(lt_rel) = inner_html[i..].find('<') else { break };
+        let lt = i + lt_rel;
+        let Some(gt_rel) = inner_html[lt..].find('>') else { break };
+        let gt = lt + gt_rel;
+        let raw = inner_html[lt + 1..gt].trim();
+        if raw.is_empty() || raw.starts_with('!') {
+            i = gt + 1;
+            continue;
+        }
+        let is_end = raw.starts_with('/');
+        let raw2 = raw.trim_start_matches('/').trim().trim_end_matches('/');
+        let name = raw2.split_whitespace().next().unwrap_or("").to_ascii_lowercase();
+
+        if !is_end && name == "tr" {
+            match find_balanced_close(inner_html, gt + 1, "tr") {
+                Some(close) => {
+                    rows.push(parse_html_table_row(&inner_html[gt + 1..close], images)?);
+                    i = close + "</tr>".len();
+                }
+                None => i = gt + 1,
+            }
+            continue;
+        }
+
+        i = gt + 1;
+    }
+
+    Ok(Table { rows })
+}
+
+/// Parses one `<tr>`'s inner HTML into its `<td>`/`<th>` cells. Each cell's inner HTML is run back
+/// through `build_blocks_from_html` - the same entry point the top-level document uses - so a cell
+/// can contain lists, images, or formatting just like any other block; `<th>` cells come back with
+/// their runs forced bold, and a nested `<table>` a cell happens to contain is dropped rather than
+/// corrupting the parent table's row/column shape.
+fn parse_html_table_row(row_html: &str, images: &mut Images) -> Result<Vec<TableCell>> {
+    let mut cells = Vec::new();
+    let bytes = row_html.as_bytes();
+    let mut i = 0usize;
+
+    while i < bytes.len() {
+        let Some(lt_rel) = row_html[i..].find('<') else { break };
+        let lt = i + lt_rel;
+        let Some(gt_rel) = row_html[lt..].find('>') else { break };
+        let gt = lt + gt_rel;
+        let raw = row_html[lt + 1..gt].trim();
+        if raw.is_empty() || raw.starts_with('!') {
+            i = gt + 1;
+            continue;
+        }
+        let is_end = raw.starts_with('/');
+        let raw2 = raw.trim_start_matches('/').trim().trim_end_matches('/');
+        let name = raw2.split_whitespace().next().unwrap_or("").to_ascii_lowercase();
+
+        if !is_end && (name == "td" || name == "th") {
+            match find_balanced_close(row_html, gt + 1, &name) {
+                Some(close) => {
+                    let (cell_blocks, cell_images) =
+                        build_blocks_from_html(&row_html[gt + 1..close])?;
+                    let offset = images.len();
+                    images.extend(cell_images);
+                    let mut paragraphs: Vec<Paragraph> = cell_blocks
+                        .into_iter()
+                        .filter_map(|b| match b {
+                            Block::Para(mut p) => {
+                                for seg in &mut p.segments {
+                                    if let Segment::Image { index, .. } = seg {
+                                        *index += offset;
+                                    }
+                                }
+                                Some(p)
+                            }
+                            Block::Table(_) => None,
+                        })
+                        .collect();
+                    if name == "th" {
+                        force_bold(&mut paragraphs);
+                    }
+                    let colspan = extract_attr(raw2, "colspan")
+                        .and_then(|v| v.trim().parse::<u32>().ok())
+                        .filter(|n| *n > 0)
+                        .unwrap_or(1);
+                    let rowspan = extract_attr(raw2, "rowspan")
+                        .and_then(|v| v.trim().parse::<u32>().ok())
+                        .filter(|n| *n > 0)
+                        .unwrap_or(1);
+                    cells.push(TableCell { paragraphs, colspan, rowspan });
+                    i = close + format!("</{name}>").len();
+                }
+                None => i = gt + 1,
+            }
+            continue;
+        }
+
+        i = gt + 1;
+    }
+
+    Ok(cells)
+}
+
+fn build_blocks_from_html(body_html: &str) -> Result<(Vec<Block>, Images)> {
     let (html, omml_blocks) = preprocess_html(body_html)?;
     let token_re = Regex::new(r"__OMML_(\d+)__")?;
 
-    let mut paragraphs: Vec<Paragraph> = Vec::new();
+    let mut blocks: Vec<Block> = Vec::new();
     let mut current = Paragraph {
         style: ParagraphStyle::Normal,
         segments: Vec::new(),
+        list: None,
+        code_lang: None,
     };
 
     let mut bold_depth: u32 = 0;
     let mut italic_depth: u32 = 0;
     let mut code_depth: u32 = 0;
     let mut pre_depth: u32 = 0;
+    // Same single-slot convention `build_blocks_from_markdown` uses for its own `link_href`:
+    // the last-opened `<a href>` wins rather than tracking a stack, since nested anchors aren't
+    // valid HTML anyway.
+    let mut link_href: Option<String> = None;
+
+    // One entry per open `<ul>`/`<ol>`: the num_id (1 = bullet, 2 = ordered) that level's `<li>`s
+    // use - same convention `build_blocks_from_markdown`'s `list_stack` uses for `numbering.xml`.
+    let mut list_stack: Vec<u32> = Vec::new();
+    let mut images: Images = Vec::new();
 
     let mut i: usize = 0;
     let bytes = html.as_bytes();
 
-    let flush = |paragraphs: &mut Vec<Paragraph>, current: &mut Paragraph| {
+    let flush = |blocks: &mut Vec<Block>, current: &mut Paragraph| {
         // Trim whitespace-only text at edges (but keep for code blocks).
         if current.style != ParagraphStyle::CodeBlock {
             while let Some(Segment::Text { text, .. }) = current.segments.first() {
@@ -308,34 +1039,29 @@ fn build_paragraphs_from_html(body_html: &str) -> Result<Vec<Paragraph>> {
 
         let has_content = current.segments.iter().any(|s| match s {
             Segment::Text { text, .. } => !text.is_empty(),
+            Segment::LinkText { text, .. } => !text.is_empty(),
             Segment::Break => true,
             Segment::Omml(_) => true,
+            Segment::Math(_) => true,
+            Segment::FootnoteRef { .. } => true,
+            Segment::Image { .. } => true,
         });
 
         if has_content {
-            paragraphs.push(current.clone());
+            blocks.push(Block::Para(current.clone()));
         }
 
         current.style = ParagraphStyle::Normal;
         current.segments.clear();
+        current.list = None;
+        current.code_lang = None;
     };
 
     let start_new_paragraph =
-        |style: ParagraphStyle, paragraphs: &mut Vec<Paragraph>, current: &mut Paragraph| {
-        flush(paragraphs, current);
+        |style: ParagraphStyle, blocks: &mut Vec<Block>, current: &mut Paragraph| {
+        flush(blocks, current);
         current.style = style;
         current.segments.clear();
-        if style == ParagraphStyle::Bullet {
-            let style = RunStyle {
-                bold: false,
-                italic: false,
-                code: false,
-            };
-            current.segments.push(Segment::Text {
-                text: "â€¢ ".to_string(),
-                style,
-            });
-        }
     };
 
     let emit_text = |raw: &str,
@@ -343,7 +1069,8 @@ fn build_paragraphs_from_html(body_html: &str) -> Result<Vec<Paragraph>> {
                          bold_depth: u32,
                          italic_depth: u32,
                          code_depth: u32,
-                         pre_depth: u32|
+                         pre_depth: u32,
+                         link_href: &Option<String>|
      -> Result<()> {
         if raw.is_empty() {
             return Ok(());
@@ -390,30 +1117,17 @@ fn build_paragraphs_from_html(body_html: &str) -> Result<Vec<Paragraph>> {
         for caps in token_re.captures_iter(&text) {
             let m = caps.get(0).unwrap();
             let before = &text[cursor..m.start()];
-            if !before.is_empty() {
-                current.segments.push(Segment::Text {
-                    text: before.to_string(),
-                    style: run_style,
-                });
-            }
+            push_plain_run(current, before, run_style, link_href);
             let idx: usize = caps.get(1).unwrap().as_str().parse().unwrap_or(usize::MAX);
             if idx < omml_blocks.len() {
                 current.segments.push(Segment::Omml(omml_blocks[idx].clone()));
             } else {
-                current.segments.push(Segment::Text {
-                    text: m.as_str().to_string(),
-                    style: run_style,
-                });
+                push_plain_run(current, m.as_str(), run_style, link_href);
             }
             cursor = m.end();
         }
         let tail = &text[cursor..];
-        if !tail.is_empty() {
-            current.segments.push(Segment::Text {
-                text: tail.to_string(),
-                style: run_style,
-            });
-        }
+        push_plain_run(current, tail, run_style, link_href);
 
         Ok(())
     };
@@ -468,33 +1182,83 @@ fn build_paragraphs_from_html(body_html: &str) -> Result<Vec<Paragraph>> {
 
             match (is_end, name.as_str()) {
                 (false, "br") => current.segments.push(Segment::Break),
-                (false, "hr") => flush(&mut paragraphs, &mut current),
+                (false, "hr") => flush(&mut blocks, &mut current),
                 (false, "p" | "div" | "user-query-content" | "message-content") => {
-                    start_new_paragraph(ParagraphStyle::Normal, &mut paragraphs, &mut current);
+                    start_new_paragraph(ParagraphStyle::Normal, &mut blocks, &mut current);
                 }
                 (true, "p" | "div" | "user-query-content" | "message-content") => {
-                    flush(&mut paragraphs, &mut current);
-                }
-                (false, "li") => start_new_paragraph(ParagraphStyle::Bullet, &mut paragraphs, &mut current),
-                (true, "li") => flush(&mut paragraphs, &mut current),
-                (false, "h1") => start_new_paragraph(ParagraphStyle::Heading1, &mut paragraphs, &mut current),
-                (true, "h1") => flush(&mut paragraphs, &mut current),
-                (false, "h2" | "h3") => start_new_paragraph(ParagraphStyle::Heading2, &mut paragraphs, &mut current),
-                (true, "h2" | "h3") => flush(&mut paragraphs, &mut current),
+                    flush(&mut blocks, &mut current);
+                }
+                (false, "ul") => list_stack.push(1),
+                (false, "ol") => list_stack.push(2),
+                (true, "ul" | "ol") => {
+                    list_stack.pop();
+                }
+                (false, "li") => {
+                    start_new_paragraph(ParagraphStyle::Normal, &mut blocks, &mut current);
+                    current.list = Some(ListInfo {
+                        num_id: *list_stack.last().unwrap_or(&1),
+                        ilvl: list_stack.len().saturating_sub(1) as u32,
+                    });
+                }
+                (true, "li") => flush(&mut blocks, &mut current),
+                (false, "h1") => start_new_paragraph(ParagraphStyle::Heading1, &mut blocks, &mut current),
+                (true, "h1") => flush(&mut blocks, &mut current),
+                (false, "h2" | "h3") => start_new_paragraph(ParagraphStyle::Heading2, &mut blocks, &mut current),
+                (true, "h2" | "h3") => flush(&mut blocks, &mut current),
                 (false, "pre") => {
                     pre_depth += 1;
-                    start_new_paragraph(ParagraphStyle::CodeBlock, &mut paragraphs, &mut current);
+                    start_new_paragraph(ParagraphStyle::CodeBlock, &mut blocks, &mut current);
                 }
                 (true, "pre") => {
-                    flush(&mut paragraphs, &mut current);
+                    flush(&mut blocks, &mut current);
                     pre_depth = pre_depth.saturating_sub(1);
                 }
-                (false, "code") => code_depth += 1,
+                (false, "code") => {
+                    code_depth += 1;
+                    if current.style == ParagraphStyle::CodeBlock && current.code_lang.is_none() {
+                        current.code_lang = extract_attr(raw2, "class").and_then(|class| {
+                            class
+                                .split_whitespace()
+                                .find_map(|c| c.strip_prefix("language-"))
+                                .map(str::to_string)
+                        });
+                    }
+                }
                 (true, "code") => code_depth = code_depth.saturating_sub(1),
                 (false, "strong" | "b") => bold_depth += 1,
                 (true, "strong" | "b") => bold_depth = bold_depth.saturating_sub(1),
                 (false, "em" | "i") => italic_depth += 1,
                 (true, "em" | "i") => italic_depth = italic_depth.saturating_sub(1),
+                (false, "a") => link_href = extract_attr(raw2, "href"),
+                (true, "a") => link_href = None,
+                (false, "img") => {
+                    if let Some(src) = extract_attr(raw2, "src") {
+                        if let Some((bytes, ext)) = resolve_image_src(&src) {
+                            let (w_px, h_px) = match ext {
+                                "png" => parse_png_dimensions(&bytes),
+                                "jpeg" => parse_jpeg_dimensions(&bytes),
+                                _ => None,
+                            }
+                            .unwrap_or((DEFAULT_IMAGE_WIDTH_PX, DEFAULT_IMAGE_HEIGHT_PX));
+                            let index = images.len();
+                            images.push(ImageAsset { bytes, ext });
+                            current.segments.push(Segment::Image {
+                                index,
+                                cx: w_px as i64 * EMU_PER_PX,
+                                cy: h_px as i64 * EMU_PER_PX,
+                            });
+                        }
+                    }
+                }
+                (false, "table") => {
+                    flush(&mut blocks, &mut current);
+                    if let Some(close) = find_balanced_close(&html, i, "table") {
+                        let table = parse_html_table(&html[i..close], &mut images)?;
+                        blocks.push(Block::Table(table));
+                        i = close + "</table>".len();
+                    }
+                }
                 _ => {}
             }
         } else {
@@ -511,125 +1275,1040 @@ fn build_paragraphs_from_html(body_html: &str) -> Result<Vec<Paragraph>> {
                 italic_depth,
                 code_depth,
                 pre_depth,
+                &link_href,
             )?;
         }
     }
 
-    flush(&mut paragraphs, &mut current);
-    Ok(paragraphs)
+    flush(&mut blocks, &mut current);
+    Ok((blocks, images))
 }
 
-fn build_document_xml(paragraphs: &[Paragraph]) -> String {
-    // Minimal WordprocessingML with basic formatting + OMML support.
-    let mut body = String::new();
-    for p in paragraphs {
-        body.push_str("<w:p>");
+fn take_paragraph(current: &mut Paragraph) -> Option<Paragraph> {
+    if current.segments.is_empty() {
+        current.style = ParagraphStyle::Normal;
+        current.list = None;
+        current.code_lang = None;
+        return None;
+    }
+    let done = current.clone();
+    current.style = ParagraphStyle::Normal;
+    current.segments.clear();
+    current.list = None;
+    current.code_lang = None;
+    Some(done)
+}
 
-        match p.style {
-            ParagraphStyle::Normal | ParagraphStyle::Bullet => {}
-            ParagraphStyle::Heading1 => {
-                body.push_str(r#"<w:pPr><w:pStyle w:val="Heading1"/></w:pPr>"#);
+/// Forces every text run in `paragraphs` bold, regardless of markup inside it - used for
+/// header-row table cells (GFM header row, HTML `<th>`), which Word renders bold by convention.
+fn force_bold(paragraphs: &mut [Paragraph]) {
+    for p in paragraphs {
+        for seg in &mut p.segments {
+            match seg {
+                Segment::Text { style, .. } | Segment::LinkText { style, .. } => style.bold = true,
+                _ => {}
             }
-            ParagraphStyle::Heading2 => {
-                body.push_str(r#"<w:pPr><w:pStyle w:val="Heading2"/></w:pPr>"#);
+        }
+    }
+}
+
+fn push_plain_run(current: &mut Paragraph, text: &str, style: RunStyle, link_href: &Option<String>) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some(href) = link_href {
+        current.segments.push(Segment::LinkText {
+            text: text.to_string(),
+            href: href.clone(),
+            style,
+        });
+    } else {
+        current.segments.push(Segment::Text {
+            text: text.to_string(),
+            style,
+        });
+    }
+}
+
+fn push_markdown_text(current: &mut Paragraph, text: &str, style: RunStyle, link_href: &Option<String>) {
+    // `$$...$$` (display) and `$...$` (inline) math spans are captured verbatim and kept as
+    // `Segment::Math`, everything else goes through unchanged as plain/linked text runs.
+    let mut rest = text;
+    while let Some(rel) = rest.find('$') {
+        push_plain_run(current, &rest[..rel], style, link_href);
+        let after = &rest[rel + 1..];
+
+        if let Some(tail) = after.strip_prefix('$') {
+            match tail.find("$$") {
+                Some(end) => {
+                    current.segments.push(Segment::Math(tail[..end].to_string()));
+                    rest = &tail[end + 2..];
+                }
+                None => {
+                    push_plain_run(current, "$$", style, link_href);
+                    rest = tail;
+                }
             }
-            ParagraphStyle::CodeBlock => {
-                body.push_str(r#"<w:pPr><w:pStyle w:val="CodeBlock"/></w:pPr>"#);
+        } else {
+            match after.find('$') {
+                Some(end) => {
+                    current.segments.push(Segment::Math(after[..end].to_string()));
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    push_plain_run(current, "$", style, link_href);
+                    rest = after;
+                }
             }
         }
+    }
+    push_plain_run(current, rest, style, link_href);
+}
 
-        for seg in &p.segments {
-            match seg {
-                Segment::Break => {
-                    body.push_str("<w:r><w:br/></w:r>");
+/// Sibling to `build_blocks_from_html` for assistant output that hasn't been rendered to
+/// HTML yet: parses CommonMark (GFM tables, fenced code, autolinks, `[^name]` footnotes) and maps
+/// the event stream directly onto the same `Block`/`Paragraph`/`Segment`/`RunStyle` types the
+/// HTML front end produces, so `build_document_xml` doesn't need to know which front end a
+/// document came from. Footnote bodies are gathered into the second return value the same way
+/// `render_segments` gathers link hrefs into `link_targets` - as a side list keyed by the id
+/// the reference occupies, rather than inline in the block stream.
+///
+/// This walks `pulldown_cmark`'s event stream rather than building an arena-allocated AST first:
+/// every node this function cares about (headings, lists, code blocks, emphasis, inline `$...$`
+/// math via `push_markdown_text`) is lowered the moment its start/end event arrives, so there's
+/// no tree to hold once a node is done with - the same one-pass shape `build_blocks_from_html`
+/// uses for its own byte-scanning walk.
+fn build_blocks_from_markdown(input: &str) -> (Vec<Block>, Footnotes) {
+    use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut current = Paragraph {
+        style: ParagraphStyle::Normal,
+        segments: Vec::new(),
+        list: None,
+        code_lang: None,
+    };
+
+    let mut bold_depth: u32 = 0;
+    let mut italic_depth: u32 = 0;
+    let mut in_code_block = false;
+    let mut link_href: Option<String> = None;
+
+    // One entry per nesting level: the num_id (1 = bullet, 2 = ordered) that level's items use.
+    let mut list_stack: Vec<u32> = Vec::new();
+
+    let mut table_rows: Vec<Vec<TableCell>> = Vec::new();
+    let mut current_row: Vec<TableCell> = Vec::new();
+    let mut in_header_row = false;
+
+    // Footnote ids are handed out in first-reference order starting at 2 (0/1 are reserved, see
+    // `footnotes_xml`). `[^name]: body` definitions can appear before or after their first
+    // reference in the source, so bodies are collected into `footnote_defs` keyed by name and
+    // only assembled into the returned, id-ordered list once the whole document has been walked.
+    let mut footnote_ids: HashMap<String, u32> = HashMap::new();
+    let mut next_footnote_id: u32 = 2;
+    let mut footnote_defs: HashMap<String, Vec<Paragraph>> = HashMap::new();
+    let mut in_footnote_def: Option<String> = None;
+    let mut footnote_def_paragraphs: Vec<Paragraph> = Vec::new();
+    let mut current_cell: Vec<Paragraph> = Vec::new();
+
+    for event in Parser::new_ext(input, options) {
+        match event {
+            Event::Start(tag) => match tag {
+                Tag::Heading { level, .. } => {
+                    if let Some(p) = take_paragraph(&mut current) {
+                        if in_footnote_def.is_some() {
+                            footnote_def_paragraphs.push(p);
+                        } else {
+                            blocks.push(Block::Para(p));
+                        }
+                    }
+                    current.style = match level {
+                        HeadingLevel::H1 => ParagraphStyle::Heading1,
+                        _ => ParagraphStyle::Heading2,
+                    };
                 }
-                Segment::Text { text, style } => {
-                    if text.is_empty() {
-                        continue;
+                Tag::CodeBlock(kind) => {
+                    if let Some(p) = take_paragraph(&mut current) {
+                        if in_footnote_def.is_some() {
+                            footnote_def_paragraphs.push(p);
+                        } else {
+                            blocks.push(Block::Para(p));
+                        }
                     }
-                    let escaped = xml_escape_text(text);
-                    body.push_str("<w:r>");
-                    if style.bold || style.italic || style.code {
-                        body.push_str("<w:rPr>");
-                        if style.bold {
-                            body.push_str("<w:b/>");
+                    current.style = ParagraphStyle::CodeBlock;
+                    current.code_lang = match kind {
+                        pulldown_cmark::CodeBlockKind::Fenced(info) if !info.is_empty() => {
+                            Some(info.split_whitespace().next().unwrap_or("").to_string())
+                        }
+                        _ => None,
+                    };
+                    in_code_block = true;
+                }
+                Tag::List(start) => {
+                    list_stack.push(if start.is_some() { 2 } else { 1 });
+                }
+                Tag::Item => {
+                    if let Some(p) = take_paragraph(&mut current) {
+                        if in_footnote_def.is_some() {
+                            footnote_def_paragraphs.push(p);
+                        } else {
+                            blocks.push(Block::Para(p));
                         }
-                        if style.italic {
-                            body.push_str("<w:i/>");
+                    }
+                    current.list = Some(ListInfo {
+                        num_id: *list_stack.last().unwrap_or(&1),
+                        ilvl: list_stack.len().saturating_sub(1) as u32,
+                    });
+                }
+                Tag::Strong => bold_depth += 1,
+                Tag::Emphasis => italic_depth += 1,
+                Tag::Link { dest_url, .. } => link_href = Some(dest_url.to_string()),
+                Tag::Table(_) => table_rows.clear(),
+                Tag::TableRow => current_row.clear(),
+                Tag::TableHead => {
+                    current_row.clear();
+                    in_header_row = true;
+                }
+                Tag::TableCell => current_cell.clear(),
+                Tag::FootnoteDefinition(name) => {
+                    if let Some(p) = take_paragraph(&mut current) {
+                        blocks.push(Block::Para(p));
+                    }
+                    in_footnote_def = Some(name.to_string());
+                }
+                _ => {}
+            },
+            Event::End(tag_end) => match tag_end {
+                TagEnd::Heading(_) | TagEnd::Paragraph => {
+                    if let Some(p) = take_paragraph(&mut current) {
+                        if in_footnote_def.is_some() {
+                            footnote_def_paragraphs.push(p);
+                        } else {
+                            blocks.push(Block::Para(p));
                         }
-                        if style.code {
-                            body.push_str(r#"<w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/>"#);
+                    }
+                }
+                TagEnd::CodeBlock => {
+                    if let Some(p) = take_paragraph(&mut current) {
+                        if in_footnote_def.is_some() {
+                            footnote_def_paragraphs.push(p);
+                        } else {
+                            blocks.push(Block::Para(p));
                         }
-                        body.push_str("</w:rPr>");
                     }
-                    body.push_str(r#"<w:t xml:space="preserve">"#);
-                    body.push_str(&escaped);
-                    body.push_str("</w:t></w:r>");
+                    in_code_block = false;
                 }
-                Segment::Omml(xml) => {
-                    // Insert as-is; assumes valid OMML (m: namespace is declared at root).
-                    body.push_str(xml);
+                TagEnd::List(_) => {
+                    list_stack.pop();
+                }
+                TagEnd::Item => {
+                    if let Some(p) = take_paragraph(&mut current) {
+                        if in_footnote_def.is_some() {
+                            footnote_def_paragraphs.push(p);
+                        } else {
+                            blocks.push(Block::Para(p));
+                        }
+                    }
+                }
+                TagEnd::Strong => bold_depth = bold_depth.saturating_sub(1),
+                TagEnd::Emphasis => italic_depth = italic_depth.saturating_sub(1),
+                TagEnd::Link => link_href = None,
+                TagEnd::Table => {
+                    let table = Block::Table(Table {
+                        rows: std::mem::take(&mut table_rows),
+                    });
+                    // Footnote bodies only model a paragraph list (see `Footnotes`), so a table
+                    // inside a footnote definition has nowhere to go; drop it rather than
+                    // leaking it into the main document at an unrelated position.
+                    if in_footnote_def.is_none() {
+                        blocks.push(table);
+                    }
+                }
+                TagEnd::TableRow => {
+                    table_rows.push(std::mem::take(&mut current_row));
+                }
+                TagEnd::TableHead => {
+                    table_rows.push(std::mem::take(&mut current_row));
+                    in_header_row = false;
+                }
+                TagEnd::TableCell => {
+                    if let Some(p) = take_paragraph(&mut current) {
+                        current_cell.push(p);
+                    }
+                    let mut paragraphs = std::mem::take(&mut current_cell);
+                    if in_header_row {
+                        force_bold(&mut paragraphs);
+                    }
+                    current_row.push(TableCell { paragraphs, colspan: 1, rowspan: 1 });
+                }
+                TagEnd::FootnoteDefinition => {
+                    if let Some(p) = take_paragraph(&mut current) {
+                        footnote_def_paragraphs.push(p);
+                    }
+                    if let Some(name) = in_footnote_def.take() {
+                        footnote_defs.insert(name, std::mem::take(&mut footnote_def_paragraphs));
+                    }
+                }
+                _ => {}
+            },
+            Event::Code(text) => {
+                let style = RunStyle {
+                    bold: bold_depth > 0,
+                    italic: italic_depth > 0,
+                    code: true,
+                };
+                push_plain_run(&mut current, &text, style, &link_href);
+            }
+            Event::Text(text) => {
+                let style = RunStyle {
+                    bold: bold_depth > 0,
+                    italic: italic_depth > 0,
+                    code: in_code_block,
+                };
+                if in_code_block {
+                    let mut lines = text.split('\n');
+                    if let Some(first) = lines.next() {
+                        push_plain_run(&mut current, first, style, &None);
+                    }
+                    for line in lines {
+                        current.segments.push(Segment::Break);
+                        push_plain_run(&mut current, line, style, &None);
+                    }
+                } else {
+                    push_markdown_text(&mut current, &text, style, &link_href);
                 }
             }
+            Event::SoftBreak => {
+                let style = RunStyle {
+                    bold: bold_depth > 0,
+                    italic: italic_depth > 0,
+                    code: in_code_block,
+                };
+                push_plain_run(&mut current, " ", style, &link_href);
+            }
+            Event::HardBreak => current.segments.push(Segment::Break),
+            Event::FootnoteReference(name) => {
+                let id = *footnote_ids.entry(name.to_string()).or_insert_with(|| {
+                    let id = next_footnote_id;
+                    next_footnote_id += 1;
+                    id
+                });
+                current.segments.push(Segment::FootnoteRef { id });
+            }
+            _ => {}
         }
+    }
 
-        body.push_str("</w:p>");
+    if let Some(p) = take_paragraph(&mut current) {
+        blocks.push(Block::Para(p));
     }
 
-    format!(
-        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<w:document xmlns:wpc="http://schemas.microsoft.com/office/word/2010/wordprocessingCanvas"
- xmlns:mc="http://schemas.openxmlformats.org/markup-compatibility/2006"
- xmlns:o="urn:schemas-microsoft-com:office:office"
- xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"
- xmlns:m="http://schemas.openxmlformats.org/officeDocument/2006/math"
- xmlns:v="urn:schemas-microsoft-com:vml"
- xmlns:wp14="http://schemas.microsoft.com/office/word/2010/wordprocessingDrawing"
- xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"
- xmlns:w10="urn:schemas-microsoft-com:office:word"
- xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
- xmlns:w14="http://schemas.microsoft.com/office/word/2010/wordprocessingml"
- xmlns:w15="http://schemas.microsoft.com/office/word/2012/wordprocessingml"
- xmlns:wpg="http://schemas.microsoft.com/office/word/2010/wordprocessingGroup"
- xmlns:wpi="http://schemas.microsoft.com/office/word/2010/wordprocessingInk"
- xmlns:wne="http://schemas.microsoft.com/office/word/2006/wordml"
- xmlns:wps="http://schemas.microsoft.com/office/word/2010/wordprocessingShape"
- mc:Ignorable="w14 w15 wp14">
-  <w:body>
-    {body}
-    <w:sectPr>
-      <w:pgSz w:w="12240" w:h="15840"/>
-      <w:pgMar w:top="1440" w:right="1440" w:bottom="1440" w:left="1440" w:header="708" w:footer="708" w:gutter="0"/>
+    let mut footnotes: Footnotes = footnote_ids
+        .into_iter()
+        .map(|(name, id)| (id, footnote_defs.remove(&name).unwrap_or_default()))
+        .collect();
+    footnotes.sort_by_key(|(id, _)| *id);
+
+    (blocks, footnotes)
+}
+
+/// Token categories `tokenize_code_line` tells apart. `Plain` covers identifiers that aren't
+/// keywords, punctuation, and whitespace - anything `theme_colors` leaves at the paragraph's
+/// default run color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Plain,
+    Keyword,
+    String,
+    Comment,
+    Number,
+}
+
+/// `--theme` selects one of these small, hand-picked token-color palettes for highlighted code
+/// blocks - not a full editor-theme format, since the only thing a palette here ever drives is a
+/// `<w:color>` per run.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+fn token_color(theme: Theme, kind: TokenKind) -> Option<&'static str> {
+    match (theme, kind) {
+        (_, TokenKind::Plain) => None,
+        (Theme::Dark, TokenKind::Keyword) => Some("C586C0"),
+        (Theme::Dark, TokenKind::String) => Some("CE9178"),
+        (Theme::Dark, TokenKind::Comment) => Some("6A9955"),
+        (Theme::Dark, TokenKind::Number) => Some("B5CEA8"),
+        (Theme::Light, TokenKind::Keyword) => Some("AF00DB"),
+        (Theme::Light, TokenKind::String) => Some("A31515"),
+        (Theme::Light, TokenKind::Comment) => Some("008000"),
+        (Theme::Light, TokenKind::Number) => Some("098658"),
+    }
+}
+
+/// Keywords recognized for a fenced-code-block language tag (`language-rust`, ```` ```py ````,
+/// ...). An empty slice (any language not listed) means `highlight_code_paragraph` only picks out
+/// strings/comments/numbers for that language, or skips highlighting entirely if
+/// `line_comment_prefix` doesn't know it either - see `is_known_language`.
+fn keywords_for(language: &str) -> &'static [&'static str] {
+    match language {
+        "rust" | "rs" => &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod",
+            "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+            "trait", "true", "type", "unsafe", "use", "where", "while",
+        ],
+        "python" | "py" => &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+            "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+            "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+            "with", "yield", "None", "True", "False",
+        ],
+        "javascript" | "js" | "jsx" | "typescript" | "ts" | "tsx" => &[
+            "break", "case", "catch", "class", "const", "continue", "debugger", "default",
+            "delete", "do", "else", "export", "extends", "finally", "for", "function", "if",
+            "import", "in", "instanceof", "let", "new", "return", "super", "switch", "this",
+            "throw", "try", "typeof", "var", "void", "while", "with", "yield", "async", "await",
+            "true", "false", "null", "undefined", "interface", "type",
+        ],
+        "go" => &[
+            "break", "case", "chan", "const", "continue", "default", "defer", "else",
+            "fallthrough", "for", "func", "go", "goto", "if", "import", "interface", "map",
+            "package", "range", "return", "select", "struct", "switch", "type", "var", "true",
+            "false", "nil",
+        ],
+        "c" | "cpp" | "c++" | "h" | "hpp" => &[
+            "auto", "break", "case", "char", "const", "continue", "default", "do", "double",
+            "else", "enum", "extern", "float", "for", "goto", "if", "int", "long", "register",
+            "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef",
+            "union", "unsigned", "void", "volatile", "while", "class", "namespace", "public",
+            "private", "protected", "template", "new", "delete", "true", "false", "nullptr",
+        ],
+        "json" => &["true", "false", "null"],
+        "bash" | "sh" | "shell" => &[
+            "if", "then", "else", "elif", "fi", "for", "while", "do", "done", "function",
+            "return", "local", "export", "case", "esac", "in",
+        ],
+        _ => &[],
+    }
+}
+
+/// `//`/`#`-style line-comment marker for a fenced-code-block language, or `None` if this table
+/// doesn't know one (or the language uses block comments only, which this tokenizer doesn't
+/// attempt).
+fn line_comment_prefix(language: &str) -> Option<&'static str> {
+    match language {
+        "python" | "py" | "bash" | "sh" | "shell" | "yaml" | "yml" | "ruby" | "rb" => Some("#"),
+        "rust" | "rs" | "javascript" | "js" | "jsx" | "typescript" | "ts" | "tsx" | "go" | "c"
+        | "cpp" | "c++" | "h" | "hpp" | "java" | "csharp" | "cs" => Some("//"),
+        _ => None,
+    }
+}
+
+/// Whether `highlight_code_paragraph` recognizes `language` well enough to bother tokenizing -
+/// either it has a keyword list, a line-comment marker, or both. Unrecognized languages (or a
+/// code block with no language tag at all) render as plain monospace text, same as before
+/// highlighting existed.
+fn is_known_language(language: &str) -> bool {
+    !keywords_for(language).is_empty() || line_comment_prefix(language).is_some()
+}
+
+/// Splits one line of source into `(TokenKind, text)` runs: a line comment (rest of the line once
+/// its marker is seen), `"..."`/`'...'` string literals (backslash-escaped, unterminated ones run
+/// to end of line), digit runs (including `.`/`_` separators), identifiers (checked against
+/// `keywords_for`), and everything else collapsed into `Plain` runs. This is a best-effort
+/// lexer, not a real one - no nested comments, no raw/triple-quoted strings, no per-language
+/// numeric-literal syntax - good enough to make code blocks look highlighted without a crate full
+/// of grammars.
+fn tokenize_code_line(line: &str, language: &str) -> Vec<(TokenKind, &str)> {
+    let keywords = keywords_for(language);
+    let comment_prefix = line_comment_prefix(language);
+    let mut tokens = Vec::new();
+    let mut i = 0usize;
+
+    while i < line.len() {
+        if let Some(prefix) = comment_prefix {
+            if line[i..].starts_with(prefix) {
+                tokens.push((TokenKind::Comment, &line[i..]));
+                break;
+            }
+        }
+
+        let ch = line[i..].chars().next().unwrap();
+
+        if ch == '"' || ch == '\'' {
+            let start = i;
+            let mut j = i + ch.len_utf8();
+            while j < line.len() {
+                let c = line[j..].chars().next().unwrap();
+                j += c.len_utf8();
+                if c == '\\' {
+                    if let Some(escaped) = line[j..].chars().next() {
+                        j += escaped.len_utf8();
+                    }
+                    continue;
+                }
+                if c == ch {
+                    break;
+                }
+            }
+            tokens.push((TokenKind::String, &line[start..j]));
+            i = j;
+        } else if ch.is_ascii_digit() {
+            let start = i;
+            let mut j = i;
+            while let Some(c) = line[j..].chars().next() {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+                    j += c.len_utf8();
+                } else {
+                    break;
+                }
+            }
+            tokens.push((TokenKind::Number, &line[start..j]));
+            i = j;
+        } else if ch.is_alphabetic() || ch == '_' {
+            let start = i;
+            let mut j = i;
+            while let Some(c) = line[j..].chars().next() {
+                if c.is_alphanumeric() || c == '_' {
+                    j += c.len_utf8();
+                } else {
+                    break;
+                }
+            }
+            let word = &line[start..j];
+            let kind = if keywords.contains(&word) { TokenKind::Keyword } else { TokenKind::Plain };
+            tokens.push((kind, word));
+            i = j;
+        } else {
+            let start = i;
+            let mut j = i + ch.len_utf8();
+            while let Some(c) = line[j..].chars().next() {
+                if c.is_alphanumeric() || c == '_' || c == '"' || c == '\'' {
+                    break;
+                }
+                if comment_prefix.is_some_and(|prefix| line[j..].starts_with(prefix)) {
+                    break;
+                }
+                j += c.len_utf8();
+            }
+            tokens.push((TokenKind::Plain, &line[start..j]));
+            i = j;
+        }
+    }
+
+    tokens
+}
+
+/// Renders a `CodeBlock` paragraph's segments with one `<w:r>` per token, colored via
+/// `token_color`, instead of `render_segments`' one run per `Segment::Text`. Only `Segment::Text`
+/// gets tokenized; other segment kinds (`Break`, stray `Omml`/`Math`, ...) fall through to
+/// `render_segments` unchanged, since a code block can't produce links or images but the type
+/// doesn't rule it out.
+fn render_highlighted_code(segments: &[Segment], language: &str, theme: Theme, body: &mut String, images_len: usize) {
+    for seg in segments {
+        match seg {
+            Segment::Text { text, style } => {
+                if text.is_empty() {
+                    continue;
+                }
+                for (kind, token) in tokenize_code_line(text, language) {
+                    if token.is_empty() {
+                        continue;
+                    }
+                    body.push_str("<w:r><w:rPr>");
+                    if style.bold {
+                        body.push_str("<w:b/>");
+                    }
+                    if style.italic {
+                        body.push_str("<w:i/>");
+                    }
+                    body.push_str(r#"<w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/>"#);
+                    if let Some(color) = token_color(theme, kind) {
+                        body.push_str(&format!(r#"<w:color w:val="{color}"/>"#));
+                    }
+                    body.push_str("</w:rPr>");
+                    body.push_str(r#"<w:t xml:space="preserve">"#);
+                    body.push_str(&xml_escape_text(token));
+                    body.push_str("</w:t></w:r>");
+                }
+            }
+            other => render_segments(std::slice::from_ref(other), body, None, images_len),
+        }
+    }
+}
+
+fn render_paragraph(
+    p: &Paragraph,
+    body: &mut String,
+    link_targets: &mut Vec<String>,
+    images_len: usize,
+    theme: Option<Theme>,
+) {
+    body.push_str("<w:p>");
+
+    let pstyle_id = match p.style {
+        ParagraphStyle::Normal => None,
+        ParagraphStyle::Heading1 => Some("Heading1"),
+        ParagraphStyle::Heading2 => Some("Heading2"),
+        ParagraphStyle::CodeBlock => Some("CodeBlock"),
+    };
+
+    if pstyle_id.is_some() || p.list.is_some() {
+        body.push_str("<w:pPr>");
+        if let Some(style_id) = pstyle_id {
+            body.push_str(&format!(r#"<w:pStyle w:val="{style_id}"/>"#));
+        }
+        if let Some(list) = p.list {
+            body.push_str(&format!(
+                r#"<w:numPr><w:ilvl w:val="{}"/><w:numId w:val="{}"/></w:numPr>"#,
+                list.ilvl, list.num_id
+            ));
+        }
+        body.push_str("</w:pPr>");
+    }
+
+    match (p.style, theme, p.code_lang.as_deref()) {
+        (ParagraphStyle::CodeBlock, Some(theme), Some(lang)) if is_known_language(lang) => {
+            render_highlighted_code(&p.segments, lang, theme, body, images_len);
+        }
+        _ => render_segments(&p.segments, body, Some(link_targets), images_len),
+    }
+
+    body.push_str("</w:p>");
+}
+
+/// Shared by `render_paragraph` (document body) and `footnotes_xml` (footnote bodies). Pass
+/// `None` for `link_targets` when the caller has nowhere to record an `r:id` (footnote bodies
+/// have no `word/_rels/footnotes.xml.rels`) - `Segment::LinkText` then degrades to a plain
+/// styled run instead of a `w:hyperlink` pointing at a relationship that doesn't exist.
+/// `images_len` is the number of image relationships `word_rels_xml` reserves before the
+/// hyperlink ones, so a `Segment::LinkText`'s `r:id` lands after them (see `word_rels_xml`).
+fn render_segments(
+    segments: &[Segment],
+    body: &mut String,
+    mut link_targets: Option<&mut Vec<String>>,
+    images_len: usize,
+) {
+    for seg in segments {
+        match seg {
+            Segment::Break => {
+                body.push_str("<w:r><w:br/></w:r>");
+            }
+            Segment::Text { text, style } => {
+                if text.is_empty() {
+                    continue;
+                }
+                let escaped = xml_escape_text(text);
+                body.push_str("<w:r>");
+                if style.bold || style.italic || style.code {
+                    body.push_str("<w:rPr>");
+                    if style.bold {
+                        body.push_str("<w:b/>");
+                    }
+                    if style.italic {
+                        body.push_str("<w:i/>");
+                    }
+                    if style.code {
+                        body.push_str(r#"<w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/>"#);
+                    }
+                    body.push_str("</w:rPr>");
+                }
+                body.push_str(r#"<w:t xml:space="preserve">"#);
+                body.push_str(&escaped);
+                body.push_str("</w:t></w:r>");
+            }
+            Segment::Omml(xml) => {
+                // Insert as-is; assumes valid OMML (m: namespace is declared at root).
+                body.push_str(xml);
+            }
+            Segment::Math(latex) => {
+                let escaped = xml_escape_text(latex);
+                body.push_str(r#"<w:r><w:rPr><w:i/></w:rPr><w:t xml:space="preserve">"#);
+                body.push_str(&escaped);
+                body.push_str("</w:t></w:r>");
+            }
+            Segment::LinkText { text, href, style } => {
+                if text.is_empty() {
+                    continue;
+                }
+                let rid = link_targets.as_deref_mut().map(|targets| {
+                    targets.push(href.clone());
+                    format!("rId{}", targets.len() + 3 + images_len)
+                });
+                let escaped = xml_escape_text(text);
+                if let Some(rid) = &rid {
+                    body.push_str(&format!(r#"<w:hyperlink r:id="{rid}">"#));
+                }
+                body.push_str("<w:r><w:rPr>");
+                if style.bold {
+                    body.push_str("<w:b/>");
+                }
+                if style.italic {
+                    body.push_str("<w:i/>");
+                }
+                if style.code {
+                    body.push_str(r#"<w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/>"#);
+                }
+                body.push_str(r#"<w:rStyle w:val="Hyperlink"/>"#);
+                body.push_str("</w:rPr>");
+                body.push_str(r#"<w:t xml:space="preserve">"#);
+                body.push_str(&escaped);
+                body.push_str("</w:t></w:r>");
+                if rid.is_some() {
+                    body.push_str("</w:hyperlink>");
+                }
+            }
+            Segment::FootnoteRef { id } => {
+                body.push_str(&format!(
+                    r#"<w:r><w:rPr><w:vertAlign w:val="superscript"/></w:rPr><w:footnoteReference w:id="{id}"/></w:r>"#
+                ));
+            }
+            Segment::Image { index, cx, cy } => {
+                // Relationship ids for images are reserved up front at `index + 4` (see
+                // `word_rels_xml`), so no mutable counter is needed here the way hyperlinks need one.
+                let rid = format!("rId{}", index + 4);
+                let doc_pr_id = index + 1;
+                body.push_str(&format!(
+                    r#"<w:r><w:drawing><wp:inline distT="0" distB="0" distL="0" distR="0"><wp:extent cx="{cx}" cy="{cy}"/><wp:docPr id="{doc_pr_id}" name="Picture {doc_pr_id}"/><a:graphic xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/picture"><pic:pic xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture"><pic:nvPicPr><pic:cNvPr id="{doc_pr_id}" name="Picture {doc_pr_id}"/><pic:cNvPicPr/></pic:nvPicPr><pic:blipFill><a:blip r:embed="{rid}"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill><pic:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom></pic:spPr></pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>"#
+                ));
+            }
+        }
+    }
+}
+
+/// Renders a `Table` as `<w:tbl>`: a `<w:tblGrid>` sized to the widest row, one `<w:tr>` per row,
+/// and one `<w:tc>` per cell - honoring `colspan` via `<w:gridSpan>` and `rowspan` via
+/// `<w:vMerge>`, with `vmerge_remaining` tracking which columns owe a continuation placeholder on
+/// later rows. Mirrors `tex_to_mathml_wasm`'s `process_table`, which renders the same shape from a
+/// DOM walk rather than this crate's own `Vec<Block>`.
+fn render_table(table: &Table, body: &mut String, link_targets: &mut Vec<String>, images_len: usize, theme: Option<Theme>) {
+    let max_cols = table
+        .rows
+        .iter()
+        .map(|row| row.iter().map(|c| c.colspan as usize).sum::<usize>())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    body.push_str(r#"<w:tbl><w:tblPr><w:tblStyle w:val="TableGrid"/><w:tblW w:w="0" w:type="auto"/><w:tblBorders>"#);
+    for edge in ["top", "left", "bottom", "right", "insideH", "insideV"] {
+        body.push_str(&format!(
+            r#"<w:{edge} w:val="single" w:sz="4" w:space="0" w:color="auto"/>"#
+        ));
+    }
+    body.push_str("</w:tblBorders></w:tblPr><w:tblGrid>");
+    for _ in 0..max_cols {
+        body.push_str("<w:gridCol/>");
+    }
+    body.push_str("</w:tblGrid>");
+
+    let mut vmerge_remaining: Vec<usize> = vec![0; max_cols];
+    for row in &table.rows {
+        body.push_str("<w:tr>");
+        let mut col = 0usize;
+        let mut cells = row.iter();
+        let mut next_cell = cells.next();
+        while col < max_cols {
+            if vmerge_remaining[col] > 0 {
+                vmerge_remaining[col] -= 1;
+                body.push_str(r#"<w:tc><w:tcPr><w:tcW w:w="0" w:type="auto"/><w:vMerge/></w:tcPr><w:p/></w:tc>"#);
+                col += 1;
+                continue;
+            }
+            let Some(cell) = next_cell else { break };
+            let colspan = (cell.colspan as usize).max(1).min(max_cols - col);
+            let rowspan = (cell.rowspan as usize).max(1);
+
+            body.push_str(r#"<w:tc><w:tcPr><w:tcW w:w="0" w:type="auto"/>"#);
+            if colspan > 1 {
+                body.push_str(&format!(r#"<w:gridSpan w:val="{colspan}"/>"#));
+            }
+            if rowspan > 1 {
+                body.push_str(r#"<w:vMerge w:val="restart"/>"#);
+                for c in col..col + colspan {
+                    vmerge_remaining[c] = rowspan - 1;
+                }
+            }
+            body.push_str("</w:tcPr>");
+            if cell.paragraphs.is_empty() {
+                body.push_str("<w:p/>");
+            } else {
+                for p in &cell.paragraphs {
+                    render_paragraph(p, body, link_targets, images_len, theme);
+                }
+            }
+            body.push_str("</w:tc>");
+
+            col += colspan;
+            next_cell = cells.next();
+        }
+        body.push_str("</w:tr>");
+    }
+
+    body.push_str("</w:tbl>");
+}
+
+fn build_document_xml(blocks: &[Block], images: &Images, theme: Option<Theme>) -> (String, Vec<String>) {
+    // Minimal WordprocessingML with basic formatting + OMML support.
+    let mut body = String::new();
+    let mut link_targets: Vec<String> = Vec::new();
+    let images_len = images.len();
+
+    for block in blocks {
+        match block {
+            Block::Para(p) => render_paragraph(p, &mut body, &mut link_targets, images_len, theme),
+            Block::Table(t) => render_table(t, &mut body, &mut link_targets, images_len, theme),
+        }
+    }
+
+    let document = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:wpc="http://schemas.microsoft.com/office/word/2010/wordprocessingCanvas"
+ xmlns:mc="http://schemas.openxmlformats.org/markup-compatibility/2006"
+ xmlns:o="urn:schemas-microsoft-com:office:office"
+ xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"
+ xmlns:m="http://schemas.openxmlformats.org/officeDocument/2006/math"
+ xmlns:v="urn:schemas-microsoft-com:vml"
+ xmlns:wp14="http://schemas.microsoft.com/office/word/2010/wordprocessingDrawing"
+ xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"
+ xmlns:w10="urn:schemas-microsoft-com:office:word"
+ xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+ xmlns:w14="http://schemas.microsoft.com/office/word/2010/wordprocessingml"
+ xmlns:w15="http://schemas.microsoft.com/office/word/2012/wordprocessingml"
+ xmlns:wpg="http://schemas.microsoft.com/office/word/2010/wordprocessingGroup"
+ xmlns:wpi="http://schemas.microsoft.com/office/word/2010/wordprocessingInk"
+ xmlns:wne="http://schemas.microsoft.com/office/word/2006/wordml"
+ xmlns:wps="http://schemas.microsoft.com/office/word/2010/wordprocessingShape"
+ mc:Ignorable="w14 w15 wp14">
+  <w:body>
+    {body}
+    <w:sectPr>
+      <w:pgSz w:w="12240" w:h="15840"/>
+      <w:pgMar w:top="1440" w:right="1440" w:bottom="1440" w:left="1440" w:header="708" w:footer="708" w:gutter="0"/>
       <w:cols w:space="708"/>
       <w:docGrid w:linePitch="360"/>
     </w:sectPr>
   </w:body>
 </w:document>"#
-    )
+    );
+
+    (document, link_targets)
 }
 
-fn content_types_xml() -> &'static str {
-    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+/// A `Default Extension="..."` per distinct image extension actually embedded, so `word/media/`
+/// parts resolve to a content type without one `Override` per file.
+fn content_types_xml(images: &Images) -> String {
+    let mut image_defaults = String::new();
+    let mut seen_exts: Vec<&str> = Vec::new();
+    for image in images {
+        if !seen_exts.contains(&image.ext) {
+            seen_exts.push(image.ext);
+            image_defaults.push_str(&format!(
+                r#"  <Default Extension="{0}" ContentType="image/{0}"/>
+"#,
+                image.ext
+            ));
+        }
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
   <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
   <Default Extension="xml" ContentType="application/xml"/>
-  <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+{image_defaults}  <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
   <Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+  <Override PartName="/word/numbering.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml"/>
+  <Override PartName="/word/footnotes.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.footnotes+xml"/>
+  <Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>
+  <Override PartName="/docProps/app.xml" ContentType="application/vnd.openxmlformats-officedocument.extended-properties+xml"/>
 </Types>"#
+    )
 }
 
 fn rels_xml() -> &'static str {
     r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
   <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+  <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>
+  <Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/extended-properties" Target="docProps/app.xml"/>
 </Relationships>"#
 }
 
-fn word_rels_xml() -> &'static str {
-    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+/// Current UTC time as a W3CDTF timestamp (`YYYY-MM-DDTHH:MM:SSZ`), the format
+/// `dcterms:created`/`dcterms:modified` require in `docProps/core.xml`.
+fn w3cdtf_now() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let days = (secs / 86_400) as i64;
+    let time_of_day = secs % 86_400;
+    let (y, m, d) = civil_from_days(days);
+    let (h, mi, s) = (time_of_day / 3600, (time_of_day % 3600) / 60, time_of_day % 60);
+    format!("{y:04}-{m:02}-{d:02}T{h:02}:{mi:02}:{s:02}Z")
+}
+
+/// Days-since-1970-01-01 to (year, month, day), per Howard Hinnant's `civil_from_days`
+/// (public domain: http://howardhinnant.github.io/date_algorithms.html). A hand-rolled
+/// calendar conversion avoids pulling in a full date/time crate for one timestamp.
+fn civil_from_days(days_since_epoch: i64) -> (i64, u32, u32) {
+    let z = (days_since_epoch + 719_468) as u64;
+    let era = z / 146_097;
+    let doe = z - era * 146_097; // [0, 146096]
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365; // [0, 399]
+    let y = yoe as i64 + era as i64 * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // [0, 365]
+    let mp = (5 * doy + 2) / 153; // [0, 11]
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32; // [1, 31]
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32; // [1, 12]
+    (if m <= 2 { y + 1 } else { y }, m, d)
+}
+
+/// Builds `docProps/core.xml`'s `<cp:coreProperties>`. `--title`/`--author` (plus a Markdown
+/// file's front matter, see `extract_front_matter`) land here as `dc:title`/`dc:creator`; the
+/// `Content_Types`/`_rels` registration this part needs, and the sibling `docProps/app.xml`, were
+/// wired up alongside it rather than separately - see `content_types_xml`, `rels_xml`, `app_xml`.
+fn core_xml(title: Option<&str>, author: Option<&str>, subject: Option<&str>, timestamp: &str) -> String {
+    let title = title.map(xml_escape_text).unwrap_or_default();
+    let author = author.map(xml_escape_text).unwrap_or_default();
+    let subject = subject.map(xml_escape_text).unwrap_or_default();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <dc:title>{title}</dc:title>
+  <dc:subject>{subject}</dc:subject>
+  <dc:creator>{author}</dc:creator>
+  <cp:lastModifiedBy>{author}</cp:lastModifiedBy>
+  <dcterms:created xsi:type="dcterms:W3CDTF">{timestamp}</dcterms:created>
+  <dcterms:modified xsi:type="dcterms:W3CDTF">{timestamp}</dcterms:modified>
+</cp:coreProperties>"#
+    )
+}
+
+fn app_xml(words: usize, characters: usize) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties">
+  <Application>docx_from_html</Application>
+  <Company></Company>
+  <Words>{words}</Words>
+  <Characters>{characters}</Characters>
+</Properties>"#
+    )
+}
+
+/// Computes the `<Words>`/`<Characters>` counts `app_xml` reports: words are whitespace-separated
+/// runs in `Segment::Text`/`Segment::LinkText`, characters are those runs' length with whitespace
+/// stripped (Word's own convention). Table cells are walked the same as top-level paragraphs.
+fn word_char_counts(blocks: &[Block]) -> (usize, usize) {
+    fn count_paragraph(p: &Paragraph, words: &mut usize, chars: &mut usize) {
+        for seg in &p.segments {
+            let text = match seg {
+                Segment::Text { text, .. } | Segment::LinkText { text, .. } => text.as_str(),
+                _ => continue,
+            };
+            *words += text.split_whitespace().count();
+            *chars += text.chars().filter(|c| !c.is_whitespace()).count();
+        }
+    }
+
+    let mut words = 0usize;
+    let mut chars = 0usize;
+    for block in blocks {
+        match block {
+            Block::Para(p) => count_paragraph(p, &mut words, &mut chars),
+            Block::Table(t) => {
+                for row in &t.rows {
+                    for cell in row {
+                        for p in &cell.paragraphs {
+                            count_paragraph(p, &mut words, &mut chars);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    (words, chars)
+}
+
+/// `rId1`-`rId3` are the fixed styles/numbering/footnotes parts; images occupy `rId4..` (one per
+/// `Images` entry, in order, matching `Segment::Image::index + 4` in `render_segments`), then
+/// hyperlinks continue from there (matching `render_segments`'s `targets.len() + 3 + images_len`).
+fn word_rels_xml(link_targets: &[String], images: &Images) -> String {
+    let mut rels = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
   <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
-</Relationships>"#
+  <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/numbering" Target="numbering.xml"/>
+  <Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/footnotes" Target="footnotes.xml"/>
+"#,
+    );
+    for (i, image) in images.iter().enumerate() {
+        let rid = i + 4;
+        rels.push_str(&format!(
+            r#"  <Relationship Id="rId{rid}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image{}.{}"/>
+"#,
+            i + 1,
+            image.ext
+        ));
+    }
+    for (i, href) in link_targets.iter().enumerate() {
+        let rid = i + 4 + images.len();
+        rels.push_str(&format!(
+            r#"  <Relationship Id="rId{rid}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="{}" TargetMode="External"/>
+"#,
+            xml_escape_text(href)
+        ));
+    }
+    rels.push_str("</Relationships>");
+    rels
+}
+
+/// Two list definitions, each carried out to the 9 levels Word's own multilevel list templates
+/// go before cycling: abstractNumId 0 (numId 1) is the bullet list HTML `<ul>` and Markdown
+/// unordered items both use, cycling the glyph/font triple Word's built-in "List Bullet" style
+/// uses per level; abstractNumId 1 (numId 2) is the list HTML `<ol>` and Markdown ordered items
+/// use, cycling decimal/lowerLetter/lowerRoman the way Word's built-in "List Number" style does.
+fn numbering_xml() -> String {
+    const BULLET_GLYPHS: [&str; 3] = ["&#8226;", "o", "&#9642;"];
+    const BULLET_FONTS: [&str; 3] = ["Symbol", "Courier New", "Wingdings"];
+    const ORDERED_FORMATS: [&str; 3] = ["decimal", "lowerLetter", "lowerRoman"];
+    const LEVELS: u32 = 9;
+
+    let mut bullet_levels = String::new();
+    let mut ordered_levels = String::new();
+    for ilvl in 0..LEVELS {
+        let indent = 720 * (ilvl + 1);
+        let cycle = (ilvl % 3) as usize;
+
+        bullet_levels.push_str(&format!(
+            r#"<w:lvl w:ilvl="{ilvl}"><w:numFmt w:val="bullet"/><w:lvlText w:val="{}"/><w:pPr><w:ind w:left="{indent}" w:hanging="360"/></w:pPr><w:rPr><w:rFonts w:ascii="{}" w:hAnsi="{}" w:hint="default"/></w:rPr></w:lvl>"#,
+            BULLET_GLYPHS[cycle], BULLET_FONTS[cycle], BULLET_FONTS[cycle],
+        ));
+        ordered_levels.push_str(&format!(
+            r#"<w:lvl w:ilvl="{ilvl}"><w:numFmt w:val="{}"/><w:lvlText w:val="%{}."/><w:pPr><w:ind w:left="{indent}" w:hanging="360"/></w:pPr></w:lvl>"#,
+            ORDERED_FORMATS[cycle], ilvl + 1,
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:abstractNum w:abstractNumId="0">{bullet_levels}</w:abstractNum>
+  <w:abstractNum w:abstractNumId="1">{ordered_levels}</w:abstractNum>
+  <w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>
+  <w:num w:numId="2"><w:abstractNumId w:val="1"/></w:num>
+</w:numbering>"#
+    )
 }
 
 fn styles_xml() -> &'static str {
@@ -684,25 +2363,124 @@ fn styles_xml() -> &'static str {
       <w:sz w:val="20"/>
     </w:rPr>
   </w:style>
+  <w:style w:type="paragraph" w:styleId="FootnoteText">
+    <w:name w:val="footnote text"/>
+    <w:basedOn w:val="Normal"/>
+    <w:semiHidden/>
+    <w:unhideWhenUsed/>
+    <w:pPr>
+      <w:spacing w:after="0"/>
+    </w:pPr>
+    <w:rPr>
+      <w:sz w:val="20"/>
+    </w:rPr>
+  </w:style>
+  <w:style w:type="character" w:styleId="Hyperlink">
+    <w:name w:val="Hyperlink"/>
+    <w:basedOn w:val="DefaultParagraphFont"/>
+    <w:uiPriority w:val="99"/>
+    <w:unhideWhenUsed/>
+    <w:rPr>
+      <w:color w:val="0563C1"/>
+      <w:u w:val="single"/>
+    </w:rPr>
+  </w:style>
+  <w:style w:type="table" w:styleId="TableGrid">
+    <w:name w:val="Table Grid"/>
+    <w:basedOn w:val="TableNormal"/>
+    <w:uiPriority w:val="39"/>
+    <w:tblPr>
+      <w:tblBorders>
+        <w:top w:val="single" w:sz="4" w:space="0" w:color="auto"/>
+        <w:left w:val="single" w:sz="4" w:space="0" w:color="auto"/>
+        <w:bottom w:val="single" w:sz="4" w:space="0" w:color="auto"/>
+        <w:right w:val="single" w:sz="4" w:space="0" w:color="auto"/>
+        <w:insideH w:val="single" w:sz="4" w:space="0" w:color="auto"/>
+        <w:insideV w:val="single" w:sz="4" w:space="0" w:color="auto"/>
+      </w:tblBorders>
+    </w:tblPr>
+  </w:style>
 </w:styles>"#
 }
 
-fn write_docx(out_path: &PathBuf, document_xml: &str) -> Result<()> {
+/// Ids 0 and 1 are the separator/continuation-separator footnotes every `word/footnotes.xml`
+/// must ship (Word renders these itself; they just need to exist). Real footnotes start at 2,
+/// matching the ids `Segment::FootnoteRef` carries.
+fn footnotes_xml(footnotes: &Footnotes) -> String {
+    let mut body = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:footnotes xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:footnote w:type="separator" w:id="0"><w:p><w:pPr><w:spacing w:after="0"/></w:pPr><w:r><w:separator/></w:r></w:p></w:footnote>
+  <w:footnote w:type="continuationSeparator" w:id="1"><w:p><w:pPr><w:spacing w:after="0"/></w:pPr><w:r><w:continuationSeparator/></w:r></w:p></w:footnote>
+"#,
+    );
+
+    for (id, paragraphs) in footnotes {
+        body.push_str(&format!(r#"  <w:footnote w:id="{id}">"#));
+        if paragraphs.is_empty() {
+            body.push_str(r#"<w:p><w:pPr><w:pStyle w:val="FootnoteText"/></w:pPr><w:r><w:footnoteRef/></w:r></w:p>"#);
+        }
+        for (i, p) in paragraphs.iter().enumerate() {
+            body.push_str(r#"<w:p><w:pPr><w:pStyle w:val="FootnoteText"/></w:pPr>"#);
+            if i == 0 {
+                body.push_str(r#"<w:r><w:footnoteRef/></w:r><w:r><w:t xml:space="preserve"> </w:t></w:r>"#);
+            }
+            // `word/footnotes.xml` has no relationships part of its own, so any link here must
+            // degrade to plain text rather than a `w:hyperlink` pointing at a missing rId.
+            // `link_targets: None` means `render_segments` never computes a hyperlink rid, so the
+            // `images_len` offset it would need that rid is irrelevant here; 0 is a placeholder.
+            render_segments(&p.segments, &mut body, None, 0);
+            body.push_str("</w:p>");
+        }
+        body.push_str("</w:footnote>\n");
+    }
+
+    body.push_str("</w:footnotes>");
+    body
+}
+
+fn write_docx(
+    out_path: &PathBuf,
+    document_xml: &str,
+    link_targets: &[String],
+    footnotes: &Footnotes,
+    metadata: &DocMetadata,
+    images: &Images,
+    blocks: &[Block],
+    zip_opts: &ZipOptions,
+) -> Result<()> {
     if let Some(parent) = out_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
 
     let file = File::create(out_path).with_context(|| format!("create {}", out_path.display()))?;
     let mut zip = ZipWriter::new(file);
-    let opt = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let opt = zip_opts.bulk;
 
-    zip.start_file("[Content_Types].xml", opt)?;
-    zip.write_all(content_types_xml().as_bytes())?;
+    zip.start_file("[Content_Types].xml", zip_opts.small)?;
+    zip.write_all(content_types_xml(images).as_bytes())?;
 
     zip.add_directory("_rels/", opt)?;
-    zip.start_file("_rels/.rels", opt)?;
+    zip.start_file("_rels/.rels", zip_opts.small)?;
     zip.write_all(rels_xml().as_bytes())?;
 
+    zip.add_directory("docProps/", opt)?;
+    let timestamp = w3cdtf_now();
+    zip.start_file("docProps/core.xml", opt)?;
+    zip.write_all(
+        core_xml(
+            metadata.title.as_deref(),
+            metadata.author.as_deref(),
+            metadata.subject.as_deref(),
+            &timestamp,
+        )
+        .as_bytes(),
+    )?;
+
+    let (words, characters) = word_char_counts(blocks);
+    zip.start_file("docProps/app.xml", opt)?;
+    zip.write_all(app_xml(words, characters).as_bytes())?;
+
     zip.add_directory("word/", opt)?;
     zip.add_directory("word/_rels/", opt)?;
 
@@ -710,33 +2488,853 @@ fn write_docx(out_path: &PathBuf, document_xml: &str) -> Result<()> {
     zip.write_all(document_xml.as_bytes())?;
 
     zip.start_file("word/_rels/document.xml.rels", opt)?;
-    zip.write_all(word_rels_xml().as_bytes())?;
+    zip.write_all(word_rels_xml(link_targets, images).as_bytes())?;
 
     zip.start_file("word/styles.xml", opt)?;
     zip.write_all(styles_xml().as_bytes())?;
 
+    zip.start_file("word/numbering.xml", opt)?;
+    zip.write_all(numbering_xml().as_bytes())?;
+
+    zip.start_file("word/footnotes.xml", opt)?;
+    zip.write_all(footnotes_xml(footnotes).as_bytes())?;
+
+    if !images.is_empty() {
+        zip.add_directory("word/media/", opt)?;
+        for (i, image) in images.iter().enumerate() {
+            zip.start_file(format!("word/media/image{}.{}", i + 1, image.ext), opt)?;
+            zip.write_all(&image.bytes)?;
+        }
+    }
+
     zip.finish()?;
     Ok(())
 }
 
+/// Maps a run's `(bold, italic, code)` combination onto one of the fixed `text:style-name`s
+/// `odt_styles_xml` declares under `office:automatic-styles` - unlike `numbering.xml`'s per-level
+/// generation, there are only 7 non-default combinations, so they're named and declared up front
+/// rather than deduped per document. `None` means the default run style (no `<text:span>` needed).
+fn odt_run_style_name(style: RunStyle) -> Option<&'static str> {
+    match (style.bold, style.italic, style.code) {
+        (false, false, false) => None,
+        (true, false, false) => Some("Bold"),
+        (false, true, false) => Some("Italic"),
+        (false, false, true) => Some("Code"),
+        (true, true, false) => Some("BoldItalic"),
+        (true, false, true) => Some("BoldCode"),
+        (false, true, true) => Some("ItalicCode"),
+        (true, true, true) => Some("BoldItalicCode"),
+    }
+}
+
+fn odt_list_style_name(num_id: u32) -> &'static str {
+    if num_id == 2 { "ListNumber" } else { "ListBullet" }
+}
+
+/// Opens/closes `<text:list>`/`<text:list-item>` elements to walk `stack` (the num_id currently
+/// open at each depth) towards `target` - `Some((num_id, ilvl))` before a list paragraph, `None`
+/// before a non-list paragraph/table and once more at the end of the document. Mirrors
+/// `render_paragraph`'s flat `w:numPr` in spirit, but ODT (unlike WordprocessingML) represents
+/// nesting structurally: a deeper level's `<text:list>` must be a child of the current
+/// `<text:list-item>`, so the transition has to open/close real XML nesting rather than just
+/// emitting an `ilvl` attribute.
+fn odt_list_transition(out: &mut String, stack: &mut Vec<u32>, target: Option<(u32, u32)>) {
+    let target_depth = target.map(|(_, ilvl)| ilvl as usize + 1).unwrap_or(0);
+    while stack.len() > target_depth {
+        out.push_str("</text:list-item></text:list>");
+        stack.pop();
+    }
+    if let Some((num_id, ilvl)) = target {
+        let depth = ilvl as usize;
+        if stack.len() == depth + 1 {
+            if stack[depth] == num_id {
+                out.push_str("</text:list-item><text:list-item>");
+            } else {
+                out.push_str("</text:list-item></text:list>");
+                out.push_str(&format!(r#"<text:list text:style-name="{}"><text:list-item>"#, odt_list_style_name(num_id)));
+                stack[depth] = num_id;
+            }
+        } else {
+            while stack.len() <= depth {
+                out.push_str(&format!(r#"<text:list text:style-name="{}"><text:list-item>"#, odt_list_style_name(num_id)));
+                stack.push(num_id);
+            }
+        }
+    }
+}
+
+/// Shared by `odt_paragraph_xml` (document body) and footnote bodies. Pass `false` for
+/// `wrap_in_p` when the caller supplies its own `<text:p>`/`<text:h>` wrapper (footnote bodies
+/// need a plain `<text:p>` regardless of the footnote paragraph's own `ParagraphStyle`).
+fn odt_segments_xml(segments: &[Segment], footnotes: &Footnotes) -> String {
+    let mut out = String::new();
+    for seg in segments {
+        match seg {
+            Segment::Break => out.push_str("<text:line-break/>"),
+            Segment::Text { text, style } => {
+                if text.is_empty() {
+                    continue;
+                }
+                let escaped = xml_escape_text(text);
+                match odt_run_style_name(*style) {
+                    Some(name) => out.push_str(&format!(r#"<text:span text:style-name="{name}">{escaped}</text:span>"#)),
+                    None => out.push_str(&escaped),
+                }
+            }
+            // Dropped, for the same reason `render_segments` falls back to literal text for
+            // `Segment::Math`: this native CLI doesn't link the wasm crate's LaTeX-to-MathML
+            // conversion, and there's no raw-OMML escape hatch in ODF the way `w:r` has one.
+            Segment::Omml(_) => {}
+            Segment::Math(latex) => {
+                let escaped = xml_escape_text(latex);
+                out.push_str(&format!(r#"<text:span text:style-name="Italic">{escaped}</text:span>"#));
+            }
+            Segment::LinkText { text, href, style } => {
+                if text.is_empty() {
+                    continue;
+                }
+                let escaped_text = xml_escape_text(text);
+                let escaped_href = xml_escape_text(href);
+                let inner = match odt_run_style_name(*style) {
+                    Some(name) => format!(r#"<text:span text:style-name="{name}">{escaped_text}</text:span>"#),
+                    None => escaped_text,
+                };
+                out.push_str(&format!(r#"<text:a xlink:href="{escaped_href}">{inner}</text:a>"#));
+            }
+            Segment::FootnoteRef { id } => {
+                let body = footnotes
+                    .iter()
+                    .find(|(fid, _)| fid == id)
+                    .map(|(_, paragraphs)| odt_footnote_body_xml(paragraphs, footnotes))
+                    .unwrap_or_default();
+                out.push_str(&format!(
+                    r#"<text:note text:note-class="footnote"><text:note-citation>{id}</text:note-citation><text:note-body>{body}</text:note-body></text:note>"#
+                ));
+            }
+            // Dropped, same as `Segment::Omml` above: embedding the image bytes as an ODF
+            // `<draw:frame>` + `Pictures/` part isn't wired up yet in this path.
+            Segment::Image { .. } => {}
+        }
+    }
+    out
+}
+
+fn odt_footnote_body_xml(paragraphs: &[Paragraph], footnotes: &Footnotes) -> String {
+    if paragraphs.is_empty() {
+        return "<text:p/>".to_string();
+    }
+    let mut out = String::new();
+    for p in paragraphs {
+        out.push_str("<text:p>");
+        out.push_str(&odt_segments_xml(&p.segments, footnotes));
+        out.push_str("</text:p>");
+    }
+    out
+}
+
+/// Renders one non-list-transition paragraph: a `<text:h>` for headings (numbered by
+/// `ParagraphStyle`'s own level, matching `render_paragraph`'s `w:pStyle`) or a `<text:p>`
+/// otherwise, both tagged with the matching named paragraph style from `odt_styles_xml`.
+fn odt_paragraph_xml(p: &Paragraph, footnotes: &Footnotes) -> String {
+    let segments_xml = odt_segments_xml(&p.segments, footnotes);
+    match p.style {
+        ParagraphStyle::Heading1 => format!(r#"<text:h text:style-name="Heading_20_1" text:outline-level="1">{segments_xml}</text:h>"#),
+        ParagraphStyle::Heading2 => format!(r#"<text:h text:style-name="Heading_20_2" text:outline-level="2">{segments_xml}</text:h>"#),
+        ParagraphStyle::CodeBlock => format!(r#"<text:p text:style-name="CodeBlock">{segments_xml}</text:p>"#),
+        ParagraphStyle::Normal => format!(r#"<text:p text:style-name="Standard">{segments_xml}</text:p>"#),
+    }
+}
+
+/// ODT counterpart to `render_table`: `colspan`/`rowspan` become
+/// `table:number-columns-spanned`/`table:number-rows-spanned`, and the vMerge continuation
+/// placeholder becomes a `<table:covered-table-cell/>`, using the same `vmerge_remaining`
+/// bookkeeping.
+fn odt_table_xml(table: &Table, footnotes: &Footnotes) -> String {
+    let max_cols = table
+        .rows
+        .iter()
+        .map(|row| row.iter().map(|c| c.colspan as usize).sum::<usize>())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut out = String::from(r#"<table:table>"#);
+    for _ in 0..max_cols {
+        out.push_str(r#"<table:table-column/>"#);
+    }
+
+    let mut vmerge_remaining: Vec<usize> = vec![0; max_cols];
+    for row in &table.rows {
+        out.push_str("<table:table-row>");
+        let mut col = 0usize;
+        let mut cells = row.iter();
+        let mut next_cell = cells.next();
+        while col < max_cols {
+            if vmerge_remaining[col] > 0 {
+                vmerge_remaining[col] -= 1;
+                out.push_str("<table:covered-table-cell/>");
+                col += 1;
+                continue;
+            }
+            let Some(cell) = next_cell else { break };
+            let colspan = (cell.colspan as usize).max(1).min(max_cols - col);
+            let rowspan = (cell.rowspan as usize).max(1);
+
+            out.push_str("<table:table-cell office:value-type=\"string\"");
+            if colspan > 1 {
+                out.push_str(&format!(r#" table:number-columns-spanned="{colspan}""#));
+            }
+            if rowspan > 1 {
+                out.push_str(&format!(r#" table:number-rows-spanned="{rowspan}""#));
+                for c in col..col + colspan {
+                    vmerge_remaining[c] = rowspan - 1;
+                }
+            }
+            out.push('>');
+            if cell.paragraphs.is_empty() {
+                out.push_str("<text:p/>");
+            } else {
+                for p in &cell.paragraphs {
+                    out.push_str(&odt_paragraph_xml(p, footnotes));
+                }
+            }
+            out.push_str("</table:table-cell>");
+
+            col += colspan;
+            next_cell = cells.next();
+        }
+        out.push_str("</table:table-row>");
+    }
+    out.push_str("</table:table>");
+    out
+}
+
+fn odt_content_xml(blocks: &[Block], footnotes: &Footnotes) -> String {
+    let mut body = String::new();
+    let mut list_stack: Vec<u32> = Vec::new();
+
+    for block in blocks {
+        match block {
+            Block::Para(p) => {
+                odt_list_transition(&mut body, &mut list_stack, p.list.map(|l| (l.num_id, l.ilvl)));
+                body.push_str(&odt_paragraph_xml(p, footnotes));
+            }
+            Block::Table(t) => {
+                odt_list_transition(&mut body, &mut list_stack, None);
+                body.push_str(&odt_table_xml(t, footnotes));
+            }
+        }
+    }
+    odt_list_transition(&mut body, &mut list_stack, None);
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+ xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0"
+ xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0"
+ xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0"
+ xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0"
+ xmlns:xlink="http://www.w3.org/1999/xlink"
+ office:version="1.2">
+  <office:automatic-styles>
+    <style:style style:name="Bold" style:family="text"><style:text-properties fo:font-weight="bold"/></style:style>
+    <style:style style:name="Italic" style:family="text"><style:text-properties fo:font-style="italic"/></style:style>
+    <style:style style:name="Code" style:family="text"><style:text-properties style:font-name="Consolas"/></style:style>
+    <style:style style:name="BoldItalic" style:family="text"><style:text-properties fo:font-weight="bold" fo:font-style="italic"/></style:style>
+    <style:style style:name="BoldCode" style:family="text"><style:text-properties fo:font-weight="bold" style:font-name="Consolas"/></style:style>
+    <style:style style:name="ItalicCode" style:family="text"><style:text-properties fo:font-style="italic" style:font-name="Consolas"/></style:style>
+    <style:style style:name="BoldItalicCode" style:family="text"><style:text-properties fo:font-weight="bold" fo:font-style="italic" style:font-name="Consolas"/></style:style>
+  </office:automatic-styles>
+  <office:body>
+    <office:text>
+      {body}
+    </office:text>
+  </office:body>
+</office:document-content>"#
+    )
+}
+
+/// Named paragraph/text/list styles referenced by `odt_content_xml` (`Standard`, `Heading_20_1`/
+/// `Heading_20_2`, `CodeBlock`, `ListBullet`, `ListNumber`) - the ODT counterpart of `styles_xml`.
+/// The two list styles are carried out to 9 levels cycling the same glyph/format triples
+/// `numbering_xml` uses, for parity between the two output formats.
+fn odt_styles_xml() -> String {
+    const BULLET_GLYPHS: [&str; 3] = ["\u{2022}", "o", "\u{25AA}"];
+    const BULLET_FONTS: [&str; 3] = ["OpenSymbol", "Courier New", "OpenSymbol"];
+    const ORDERED_FORMATS: [(&str, &str); 3] = [("1", "."), ("a", "."), ("i", ".")];
+    const LEVELS: u32 = 9;
+
+    let mut bullet_levels = String::new();
+    let mut ordered_levels = String::new();
+    for ilvl in 0..LEVELS {
+        let level = ilvl + 1;
+        let indent_cm = 0.5 * (ilvl + 1) as f64;
+        let cycle = (ilvl % 3) as usize;
+
+        bullet_levels.push_str(&format!(
+            r#"<text:list-level-style-bullet text:level="{level}" text:bullet-char="{}"><style:list-level-properties text:space-before="{indent_cm:.2}cm" text:min-label-width="0.5cm"/><style:text-properties style:font-name="{}"/></text:list-level-style-bullet>"#,
+            BULLET_GLYPHS[cycle], BULLET_FONTS[cycle],
+        ));
+        let (num_format, num_suffix) = ORDERED_FORMATS[cycle];
+        ordered_levels.push_str(&format!(
+            r#"<text:list-level-style-number text:level="{level}" style:num-format="{num_format}" style:num-suffix="{num_suffix}"><style:list-level-properties text:space-before="{indent_cm:.2}cm" text:min-label-width="0.5cm"/></text:list-level-style-number>"#
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<office:document-styles xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+ xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0"
+ xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0"
+ xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0"
+ office:version="1.2">
+  <office:styles>
+    <style:style style:name="Standard" style:family="paragraph" style:class="text"/>
+    <style:style style:name="Heading_20_1" style:display-name="Heading 1" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Standard" style:class="text">
+      <style:text-properties fo:font-weight="bold" fo:font-size="16pt"/>
+    </style:style>
+    <style:style style:name="Heading_20_2" style:display-name="Heading 2" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Standard" style:class="text">
+      <style:text-properties fo:font-weight="bold" fo:font-size="14pt"/>
+    </style:style>
+    <style:style style:name="CodeBlock" style:display-name="Code Block" style:family="paragraph" style:parent-style-name="Standard" style:class="text">
+      <style:text-properties style:font-name="Consolas" fo:font-size="10pt"/>
+    </style:style>
+    <text:list-style style:name="ListBullet">{bullet_levels}</text:list-style>
+    <text:list-style style:name="ListNumber">{ordered_levels}</text:list-style>
+  </office:styles>
+</office:document-styles>"#
+    )
+}
+
+fn odt_meta_xml(metadata: &DocMetadata, timestamp: &str) -> String {
+    let title = metadata.title.as_deref().map(xml_escape_text).unwrap_or_default();
+    let author = metadata.author.as_deref().map(xml_escape_text).unwrap_or_default();
+    let subject = metadata.subject.as_deref().map(xml_escape_text).unwrap_or_default();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<office:document-meta xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0" office:version="1.2">
+  <office:meta>
+    <dc:title>{title}</dc:title>
+    <dc:subject>{subject}</dc:subject>
+    <dc:creator>{author}</dc:creator>
+    <meta:creation-date>{timestamp}</meta:creation-date>
+    <dc:date>{timestamp}</dc:date>
+  </office:meta>
+</office:document-meta>"#
+    )
+}
+
+fn odt_manifest_xml() -> &'static str {
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.2">
+  <manifest:file-entry manifest:full-path="/" manifest:version="1.2" manifest:media-type="application/vnd.oasis.opendocument.text"/>
+  <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+  <manifest:file-entry manifest:full-path="styles.xml" manifest:media-type="text/xml"/>
+  <manifest:file-entry manifest:full-path="meta.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>"#
+}
+
+/// Writes an OpenDocument Text (`.odt`) package: `mimetype` stored uncompressed and first (per
+/// the ODF spec, so tools that peek at the first archive entry can identify the format without
+/// inflating anything), then `META-INF/manifest.xml`, `content.xml`, `styles.xml`, `meta.xml`.
+/// Mirrors `write_docx`'s structure but from the same `Vec<Block>`/`Footnotes` `build_document_xml`
+/// consumes, rather than from pre-rendered WordprocessingML.
+fn write_odt(
+    out_path: &PathBuf,
+    blocks: &[Block],
+    footnotes: &Footnotes,
+    metadata: &DocMetadata,
+    zip_opts: &ZipOptions,
+) -> Result<()> {
+    if let Some(parent) = out_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let file = File::create(out_path).with_context(|| format!("create {}", out_path.display()))?;
+    let mut zip = ZipWriter::new(file);
+
+    // `mimetype` must stay Stored-and-first per the ODF spec regardless of --compression, so
+    // tools that peek at the first archive entry can identify the format without inflating it.
+    zip.start_file("mimetype", SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored))?;
+    zip.write_all(b"application/vnd.oasis.opendocument.text")?;
+
+    let opt = zip_opts.bulk;
+
+    zip.add_directory("META-INF/", opt)?;
+    zip.start_file("META-INF/manifest.xml", zip_opts.small)?;
+    zip.write_all(odt_manifest_xml().as_bytes())?;
+
+    zip.start_file("content.xml", opt)?;
+    zip.write_all(odt_content_xml(blocks, footnotes).as_bytes())?;
+
+    zip.start_file("styles.xml", opt)?;
+    zip.write_all(odt_styles_xml().as_bytes())?;
+
+    zip.start_file("meta.xml", opt)?;
+    zip.write_all(odt_meta_xml(metadata, &w3cdtf_now()).as_bytes())?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// `title`/`author`/`date` pulled from a Markdown file's leading YAML front-matter block (see
+/// `extract_front_matter`). `date` has nowhere to go yet - `DocMetadata` stamps `created`/
+/// `modified` from the current time rather than a user-supplied one - so it's parsed and then
+/// dropped; kept as a field rather than discarded outright so that hook is obvious when needed.
+#[derive(Debug, Clone, Default)]
+struct FrontMatter {
+    title: Option<String>,
+    author: Option<String>,
+    #[allow(dead_code)]
+    date: Option<String>,
+}
+
+/// Peels a leading `---`-delimited YAML front-matter block off a Markdown file, if present, and
+/// pulls out `title`/`author`/`date`. Only flat `key: value` lines are understood (no nested
+/// maps/lists, no quoting rules beyond stripping a matching pair of `"`/`'`) - everything this
+/// crate's own front matter is ever likely to carry - rather than pulling in a full YAML parser
+/// for three scalar fields. Returns the front matter found (empty if there was none) and the
+/// remaining Markdown body with the block removed.
+fn extract_front_matter(input: &str) -> (FrontMatter, &str) {
+    let mut front_matter = FrontMatter::default();
+
+    let Some(rest) = input.strip_prefix("---\n") else {
+        return (front_matter, input);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (front_matter, input);
+    };
+    let block = &rest[..end];
+    let after_marker = end + "\n---".len();
+    let body = match rest[after_marker..].find('\n') {
+        Some(nl) => &rest[after_marker + nl + 1..],
+        None => "",
+    };
+
+    for line in block.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = value.trim().trim_matches('"').trim_matches('\'');
+        if value.is_empty() {
+            continue;
+        }
+        match key {
+            "title" => front_matter.title = Some(value.to_string()),
+            "author" => front_matter.author = Some(value.to_string()),
+            "date" => front_matter.date = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    (front_matter, body)
+}
+
+/// One paragraph reconstructed from `word/document.xml` by `import_docx`: `style` mirrors
+/// `ParagraphStyle` and `runs` is `(text, bold, italic)` in document order - a deliberately
+/// smaller model than `Paragraph`/`Segment`, since lists, tables, images, and math don't
+/// round-trip (yet); a run belonging to one of those is simply dropped rather than guessed at.
+#[derive(Debug, Clone)]
+struct ImportedParagraph {
+    style: ParagraphStyle,
+    runs: Vec<(String, bool, bool)>,
+}
+
+/// Reads `word/document.xml` out of `path` (a `.docx` file) and walks its `<w:p>`/`<w:r>`/`<w:t>`
+/// tree into `ImportedParagraph`s - the reverse of `render_paragraph`. Only recognizes what
+/// `render_paragraph` itself writes: a `w:pStyle` val of Heading1/Heading2/CodeBlock, `w:b`/`w:i`
+/// run properties, `w:t` text, and `w:br` line breaks. Anything else (tables, images, fields) in
+/// a hand-edited or foreign .docx is skipped rather than guessed at.
+fn import_docx(path: &Path) -> Result<Vec<ImportedParagraph>> {
+    let file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file).context("read docx as zip")?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .context("docx has no word/document.xml")?
+        .read_to_string(&mut xml)
+        .context("read word/document.xml")?;
+    // Word and some other tools prefix this part with a UTF-8 BOM; strip it before scanning.
+    let xml = xml.strip_prefix('\u{feff}').unwrap_or(&xml);
+
+    let mut paragraphs = Vec::new();
+    let mut style = ParagraphStyle::Normal;
+    let mut runs: Vec<(String, bool, bool)> = Vec::new();
+    let mut run_bold = false;
+    let mut run_italic = false;
+    let mut run_text = String::new();
+    let mut in_text = false;
+
+    let bytes = xml.as_bytes();
+    let mut i = 0usize;
+    while i < bytes.len() {
+        let Some(lt_rel) = bytes[i..].iter().position(|&c| c == b'<') else {
+            break;
+        };
+        let lt = i + lt_rel;
+        if in_text {
+            run_text.push_str(&decode_entities_basic(&xml[i..lt]));
+        }
+        let Some(gt_rel) = xml[lt..].find('>') else {
+            break;
+        };
+        let gt = lt + gt_rel;
+        let raw = &xml[lt + 1..gt];
+        let is_end = raw.starts_with('/');
+        let tag_body = raw.trim_start_matches('/').trim_end_matches('/').trim();
+        let name_end = tag_body.find(|c: char| c.is_whitespace()).unwrap_or(tag_body.len());
+        let name = &tag_body[..name_end];
+
+        match (name, is_end) {
+            ("w:p", false) => {
+                style = ParagraphStyle::Normal;
+                runs.clear();
+            }
+            ("w:p", true) => {
+                paragraphs.push(ImportedParagraph { style, runs: std::mem::take(&mut runs) });
+            }
+            ("w:pStyle", _) => {
+                if let Some(val) = extract_attr(tag_body, "w:val") {
+                    style = match val.as_str() {
+                        "Heading1" => ParagraphStyle::Heading1,
+                        "Heading2" => ParagraphStyle::Heading2,
+                        "CodeBlock" => ParagraphStyle::CodeBlock,
+                        _ => ParagraphStyle::Normal,
+                    };
+                }
+            }
+            ("w:r", false) => {
+                run_bold = false;
+                run_italic = false;
+            }
+            ("w:b", _) => run_bold = true,
+            ("w:i", _) => run_italic = true,
+            ("w:t", false) => in_text = true,
+            ("w:t", true) => {
+                in_text = false;
+                if !run_text.is_empty() {
+                    runs.push((std::mem::take(&mut run_text), run_bold, run_italic));
+                }
+            }
+            ("w:br", _) => runs.push(("\n".to_string(), run_bold, run_italic)),
+            _ => {}
+        }
+
+        i = gt + 1;
+    }
+
+    Ok(paragraphs)
+}
+
+/// Escapes Markdown's own inline-formatting characters in imported run text, so e.g. a literal
+/// `*` from the source document doesn't turn into stray emphasis once wrapped in `**`/`*`.
+fn escape_markdown_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '\\' | '*' | '_' | '`' | '[' | ']') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn imported_runs_to_markdown(runs: &[(String, bool, bool)]) -> String {
+    let mut out = String::new();
+    for (text, bold, italic) in runs {
+        let escaped = escape_markdown_inline(text);
+        match (bold, italic) {
+            (true, true) => out.push_str(&format!("***{escaped}***")),
+            (true, false) => out.push_str(&format!("**{escaped}**")),
+            (false, true) => out.push_str(&format!("*{escaped}*")),
+            (false, false) => out.push_str(&escaped),
+        }
+    }
+    out
+}
+
+fn imported_paragraphs_to_markdown(paragraphs: &[ImportedParagraph]) -> String {
+    let mut out = String::new();
+    for p in paragraphs {
+        if p.runs.is_empty() {
+            continue;
+        }
+        match p.style {
+            ParagraphStyle::Heading1 => {
+                out.push_str("# ");
+                out.push_str(&imported_runs_to_markdown(&p.runs));
+                out.push_str("\n\n");
+            }
+            ParagraphStyle::Heading2 => {
+                out.push_str("## ");
+                out.push_str(&imported_runs_to_markdown(&p.runs));
+                out.push_str("\n\n");
+            }
+            ParagraphStyle::CodeBlock => {
+                out.push_str("```\n");
+                for (text, ..) in &p.runs {
+                    out.push_str(text);
+                }
+                out.push_str("\n```\n\n");
+            }
+            ParagraphStyle::Normal => {
+                out.push_str(&imported_runs_to_markdown(&p.runs));
+                out.push_str("\n\n");
+            }
+        }
+    }
+    out
+}
+
+fn imported_runs_to_html(runs: &[(String, bool, bool)]) -> String {
+    let mut out = String::new();
+    for (text, bold, italic) in runs {
+        let escaped = xml_escape_text(text);
+        match (bold, italic) {
+            (true, true) => out.push_str(&format!("<strong><em>{escaped}</em></strong>")),
+            (true, false) => out.push_str(&format!("<strong>{escaped}</strong>")),
+            (false, true) => out.push_str(&format!("<em>{escaped}</em>")),
+            (false, false) => out.push_str(&escaped),
+        }
+    }
+    out
+}
+
+fn imported_paragraphs_to_html(paragraphs: &[ImportedParagraph]) -> String {
+    let mut out = String::new();
+    for p in paragraphs {
+        if p.runs.is_empty() {
+            continue;
+        }
+        match p.style {
+            ParagraphStyle::Heading1 => {
+                out.push_str(&format!("<h1>{}</h1>\n", imported_runs_to_html(&p.runs)))
+            }
+            ParagraphStyle::Heading2 => {
+                out.push_str(&format!("<h2>{}</h2>\n", imported_runs_to_html(&p.runs)))
+            }
+            ParagraphStyle::CodeBlock => {
+                let mut code = String::new();
+                for (text, ..) in &p.runs {
+                    code.push_str(&xml_escape_text(text));
+                }
+                out.push_str(&format!("<pre><code>{code}</code></pre>\n"));
+            }
+            ParagraphStyle::Normal => {
+                out.push_str(&format!("<p>{}</p>\n", imported_runs_to_html(&p.runs)))
+            }
+        }
+    }
+    out
+}
+
+/// Runs `--import`: reads `docx_path` and writes Markdown or HTML to `out_path`, choosing the
+/// format from `out_path`'s extension (`.html`/`.htm` -> HTML, everything else -> Markdown, the
+/// same "Markdown unless told otherwise" default `--markdown-file` uses on the way in).
+fn run_import(docx_path: &Path, out_path: &Path) -> Result<()> {
+    let paragraphs = import_docx(docx_path)
+        .with_context(|| format!("import {}", docx_path.display()))?;
+    let is_html = matches!(
+        out_path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref(),
+        Some("html") | Some("htm")
+    );
+    let rendered = if is_html {
+        imported_paragraphs_to_html(&paragraphs)
+    } else {
+        imported_paragraphs_to_markdown(&paragraphs)
+    };
+    std::fs::write(out_path, rendered)
+        .with_context(|| format!("write {}", out_path.display()))?;
+    Ok(())
+}
+
+/// Recursively collects every `.html`/`.htm`/`.md`/`.markdown` file under `dir`, sorted for
+/// deterministic batch-run output (matching the stable iteration order the rest of this crate
+/// relies on elsewhere, e.g. `NAMED_ENTITIES`'s binary search).
+fn collect_convertible_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_convertible_files_into(dir, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_convertible_files_into(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir).with_context(|| format!("read dir {}", dir.display()))? {
+        let path = entry.with_context(|| format!("read dir {}", dir.display()))?.path();
+        if path.is_dir() {
+            collect_convertible_files_into(&path, out)?;
+            continue;
+        }
+        let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+        if matches!(ext.as_deref(), Some("html") | Some("htm") | Some("md") | Some("markdown")) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Same job as `blocks_from_args`'s two match arms, but keyed off a file's own extension rather
+/// than which of `--html-file`/`--markdown-file` was passed - `run_batch` has neither, just a
+/// path it found under `--input-dir`.
+fn blocks_from_path(path: &Path) -> Result<(Vec<Block>, Footnotes, Images, FrontMatter)> {
+    let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("md") | Some("markdown") => {
+            let mut markdown = String::new();
+            File::open(path)
+                .with_context(|| format!("open {}", path.display()))?
+                .read_to_string(&mut markdown)
+                .context("read markdown")?;
+            let (front_matter, body) = extract_front_matter(&markdown);
+            let (blocks, footnotes) = build_blocks_from_markdown(body);
+            Ok((blocks, footnotes, Vec::new(), front_matter))
+        }
+        _ => {
+            let mut html = String::new();
+            File::open(path)
+                .with_context(|| format!("open {}", path.display()))?
+                .read_to_string(&mut html)
+                .context("read html")?;
+            let body = extract_body(&html);
+            let (blocks, images) =
+                build_blocks_from_html(body).context("parse html into blocks")?;
+            Ok((blocks, Vec::new(), images, FrontMatter::default()))
+        }
+    }
+}
+
+/// Converts one input file (chosen by `blocks_from_path`'s extension sniffing) to `out_path`,
+/// the same metadata/theme/zip-option plumbing `main` does for a single `--html-file`/
+/// `--markdown-file` run.
+fn convert_one_file(
+    input_path: &Path,
+    out_path: &Path,
+    args: &Args,
+    zip_opts: &ZipOptions,
+) -> Result<()> {
+    let (blocks, footnotes, images, front_matter) = blocks_from_path(input_path)?;
+    if blocks.is_empty() {
+        return Err(anyhow!("no content produced from input"));
+    }
+
+    let metadata = DocMetadata {
+        title: args.title.clone().or(front_matter.title),
+        author: args.author.clone().or(front_matter.author),
+        subject: args.subject.clone(),
+    };
+    let theme = if args.no_highlight { None } else { Some(args.theme) };
+    let out_path = out_path.to_path_buf();
+
+    match args.format {
+        OutputFormat::Docx => {
+            let (document_xml, link_targets) = build_document_xml(&blocks, &images, theme);
+            write_docx(&out_path, &document_xml, &link_targets, &footnotes, &metadata, &images, &blocks, zip_opts)?;
+        }
+        OutputFormat::Odt => {
+            write_odt(&out_path, &blocks, &footnotes, &metadata, zip_opts)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs `--input-dir`: converts every file `collect_convertible_files` finds under `input_dir`
+/// into `out_dir`, mirroring the input tree's relative paths (`write_docx`/`write_odt`'s own
+/// `create_dir_all(parent)` creates whatever nested subdirectories that needs) and swapping the
+/// extension for `--format`'s own (`.docx`/`.odt`). A single file's conversion error is reported
+/// to stderr and counted rather than aborting the rest of the batch.
+fn run_batch(input_dir: &Path, out_dir: &Path, args: &Args, zip_opts: &ZipOptions) -> Result<()> {
+    let files = collect_convertible_files(input_dir)?;
+    let out_ext = match args.format {
+        OutputFormat::Docx => "docx",
+        OutputFormat::Odt => "odt",
+    };
+
+    let mut failures = 0usize;
+    for input_path in &files {
+        let rel = input_path.strip_prefix(input_dir).unwrap_or(input_path);
+        let out_path = out_dir.join(rel).with_extension(out_ext);
+        if let Err(err) = convert_one_file(input_path, &out_path, args, zip_opts) {
+            eprintln!("error converting {}: {err:#}", input_path.display());
+            failures += 1;
+        }
+    }
+
+    if failures > 0 {
+        eprintln!("{failures} of {} file(s) failed to convert", files.len());
+    }
+
+    Ok(())
+}
+
+fn blocks_from_args(args: &Args) -> Result<(Vec<Block>, Footnotes, Images, FrontMatter)> {
+    match (&args.html_file, &args.markdown_file) {
+        (Some(html_file), None) => {
+            let mut html = String::new();
+            File::open(html_file)
+                .with_context(|| format!("open {}", html_file.display()))?
+                .read_to_string(&mut html)
+                .context("read html")?;
+            let body = extract_body(&html);
+            let (blocks, images) =
+                build_blocks_from_html(body).context("parse html into blocks")?;
+            // The HTML front end has no footnote markup convention (yet), so there's nothing to gather.
+            Ok((blocks, Vec::new(), images, FrontMatter::default()))
+        }
+        (None, Some(markdown_file)) => {
+            let mut markdown = String::new();
+            File::open(markdown_file)
+                .with_context(|| format!("open {}", markdown_file.display()))?
+                .read_to_string(&mut markdown)
+                .context("read markdown")?;
+            let (front_matter, body) = extract_front_matter(&markdown);
+            let (blocks, footnotes) = build_blocks_from_markdown(body);
+            // The Markdown front end has no `![alt](src)` embedding wired up (yet), so nothing to gather.
+            Ok((blocks, footnotes, Vec::new(), front_matter))
+        }
+        (Some(_), Some(_)) => Err(anyhow!("pass only one of --html-file or --markdown-file")),
+        (None, None) => Err(anyhow!("pass one of --html-file or --markdown-file")),
+    }
+}
+
 fn main() -> Result<()> {
     let args = Args::parse();
-    let mut html = String::new();
-    File::open(&args.html_file)
-        .with_context(|| format!("open {}", args.html_file.display()))?
-        .read_to_string(&mut html)
-        .context("read html")?;
-
-    let body = extract_body(&html);
-    let paragraphs = build_paragraphs_from_html(body).context("parse html into paragraphs")?;
-    if paragraphs.is_empty() {
-        return Err(anyhow!("no paragraphs produced from input"));
+
+    if let Some(docx_path) = &args.import {
+        return run_import(docx_path, &args.out);
+    }
+
+    if let Some(input_dir) = &args.input_dir {
+        let zip_opts = zip_options_from_args(&args);
+        return run_batch(input_dir, &args.out, &args, &zip_opts);
+    }
+
+    let (blocks, footnotes, images, front_matter) = blocks_from_args(&args)?;
+    if blocks.is_empty() {
+        return Err(anyhow!("no content produced from input"));
     }
 
-    let document_xml = build_document_xml(&paragraphs);
-    write_docx(&args.out, &document_xml)?;
+    // CLI flags win over front matter when both are given.
+    let metadata = DocMetadata {
+        title: args.title.clone().or(front_matter.title),
+        author: args.author.clone().or(front_matter.author),
+        subject: args.subject.clone(),
+    };
+
+    let theme = if args.no_highlight { None } else { Some(args.theme) };
+    let zip_opts = zip_options_from_args(&args);
+
+    match args.format {
+        OutputFormat::Docx => {
+            let (document_xml, link_targets) = build_document_xml(&blocks, &images, theme);
+            write_docx(&args.out, &document_xml, &link_targets, &footnotes, &metadata, &images, &blocks, &zip_opts)?;
+        }
+        OutputFormat::Odt => {
+            write_odt(&args.out, &blocks, &footnotes, &metadata, &zip_opts)?;
+        }
+    }
 
-    // Title is currently unused; kept for future core-properties support.
-    let _ = args.title;
     Ok(())
 }
@@ -0,0 +1,240 @@
+// Structured parse-tree dumps for integrators who want to inspect exactly what the converters see
+// (tests against structure instead of serialized strings, custom emitters) without re-parsing the
+// crate's own string output. `html_to_ast_json` walks the same `RcDom` html5ever parse
+// `pipeline::parse_to_dom`/`sanitize::parse_to_dom` use; `markdown_to_ast_json` walks the same
+// comrak AST `markdown::markdown_to_html_string`'s extension set produces. Both serialize via the
+// shared `JsonNode` shape (a `type` tag, optional `text`, a string-keyed `attrs` map, and a
+// `children` array) rather than a per-kind enum, so the JSON stays a flat, predictable tree no
+// matter which parser produced it.
+
+use std::collections::BTreeMap;
+
+use comrak::nodes::{AstNode, ListType, NodeValue};
+use comrak::{parse_document, Arena, Options as ComrakOptions};
+use html5ever::parse_document as parse_html_document;
+use html5ever::tendril::TendrilSink;
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use serde::Serialize;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct JsonNode {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    attrs: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    children: Vec<JsonNode>,
+}
+
+impl JsonNode {
+    fn new(kind: &str) -> Self {
+        JsonNode { kind: kind.to_string(), text: None, attrs: BTreeMap::new(), children: Vec::new() }
+    }
+}
+
+fn parse_to_dom(input: &str) -> RcDom {
+    parse_html_document(RcDom::default(), Default::default()).one(input)
+}
+
+fn html_node_to_json(node: &Handle) -> JsonNode {
+    let mut out = match &node.data {
+        NodeData::Document => JsonNode::new("document"),
+        NodeData::Doctype { name, .. } => {
+            let mut n = JsonNode::new("doctype");
+            n.text = Some(name.to_string());
+            n
+        }
+        NodeData::Text { contents } => {
+            let mut n = JsonNode::new("text");
+            n.text = Some(contents.borrow().to_string());
+            n
+        }
+        NodeData::Comment { contents } => {
+            let mut n = JsonNode::new("comment");
+            n.text = Some(contents.to_string());
+            n
+        }
+        NodeData::ProcessingInstruction { .. } => JsonNode::new("processing_instruction"),
+        NodeData::Element { name, attrs, .. } => {
+            let mut n = JsonNode::new("element");
+            n.attrs.insert("tag".to_string(), name.local.to_string());
+            for a in attrs.borrow().iter() {
+                n.attrs.insert(a.name.local.to_string(), a.value.to_string());
+            }
+            n
+        }
+    };
+    for child in node.children.borrow().iter() {
+        out.children.push(html_node_to_json(child));
+    }
+    out
+}
+
+/// Parses `html` and dumps the resulting `RcDom` tree as JSON: `document`/`element`/`text`/
+/// `comment`/`doctype` nodes, an element's tag name under `attrs["tag"]` alongside its own
+/// attributes, and a `children` array in document order.
+pub fn html_to_ast_json(html: &str) -> String {
+    let dom = parse_to_dom(html);
+    let tree = html_node_to_json(&dom.document);
+    serde_json::to_string(&tree).unwrap_or_else(|_| "{\"type\":\"document\",\"children\":[]}".to_string())
+}
+
+fn markdown_extension_options() -> ComrakOptions {
+    let mut opts = ComrakOptions::default();
+    opts.extension.table = true;
+    opts.extension.strikethrough = true;
+    opts.extension.tasklist = true;
+    opts.extension.footnotes = true;
+    opts
+}
+
+fn markdown_node_to_json<'a>(node: &'a AstNode<'a>) -> JsonNode {
+    let mut attrs = BTreeMap::new();
+    let mut text = None;
+    let value = &node.data.borrow().value;
+    let kind = match value {
+        NodeValue::Document => "document",
+        NodeValue::Paragraph => "paragraph",
+        NodeValue::Heading(h) => {
+            attrs.insert("level".to_string(), h.level.to_string());
+            "heading"
+        }
+        NodeValue::List(l) => {
+            attrs.insert("ordered".to_string(), (l.list_type == ListType::Ordered).to_string());
+            if l.list_type == ListType::Ordered {
+                attrs.insert("start".to_string(), l.start.to_string());
+            }
+            "list"
+        }
+        NodeValue::Item(_) => "item",
+        NodeValue::TaskItem(checked) => {
+            attrs.insert("checked".to_string(), checked.is_some().to_string());
+            "task_item"
+        }
+        NodeValue::BlockQuote => "block_quote",
+        NodeValue::CodeBlock(cb) => {
+            if !cb.info.is_empty() {
+                attrs.insert("info".to_string(), cb.info.clone());
+            }
+            text = Some(cb.literal.clone());
+            "code_block"
+        }
+        NodeValue::Code(c) => {
+            text = Some(c.literal.clone());
+            "code"
+        }
+        NodeValue::HtmlBlock(h) => {
+            text = Some(h.literal.clone());
+            "html_block"
+        }
+        NodeValue::HtmlInline(s) => {
+            text = Some(s.clone());
+            "html_inline"
+        }
+        NodeValue::Text(s) => {
+            text = Some(s.clone());
+            "text"
+        }
+        NodeValue::Emph => "emph",
+        NodeValue::Strong => "strong",
+        NodeValue::Strikethrough => "strikethrough",
+        NodeValue::Link(l) => {
+            attrs.insert("href".to_string(), l.url.clone());
+            if !l.title.is_empty() {
+                attrs.insert("title".to_string(), l.title.clone());
+            }
+            "link"
+        }
+        NodeValue::Image(l) => {
+            attrs.insert("src".to_string(), l.url.clone());
+            if !l.title.is_empty() {
+                attrs.insert("title".to_string(), l.title.clone());
+            }
+            "image"
+        }
+        NodeValue::ThematicBreak => "thematic_break",
+        NodeValue::SoftBreak => "soft_break",
+        NodeValue::LineBreak => "line_break",
+        NodeValue::Table(_) => "table",
+        NodeValue::TableRow(is_header) => {
+            attrs.insert("header".to_string(), is_header.to_string());
+            "table_row"
+        }
+        NodeValue::TableCell => "table_cell",
+        other => {
+            // The long tail of comrak variants this crate never otherwise touches (footnote
+            // defs/refs, description lists, ...): fall back to comrak's own debug name rather than
+            // hand-mapping every one.
+            let debug = format!("{other:?}");
+            let variant = debug.split(['(', ' ']).next().unwrap_or("unknown");
+            attrs.insert("comrak_variant".to_string(), variant.to_string());
+            "other"
+        }
+    };
+
+    let mut out = JsonNode { kind: kind.to_string(), text, attrs, children: Vec::new() };
+    for child in node.children() {
+        out.children.push(markdown_node_to_json(child));
+    }
+    out
+}
+
+/// Parses `md` with the same CommonMark extensions `markdown::markdown_to_html_string` enables
+/// (tables, strikethrough, tasklists, footnotes) and dumps the comrak AST as JSON: block/inline
+/// kinds like `heading`/`paragraph`/`list`/`code`/`emph`/`strong`/`link` carrying their relevant
+/// attributes (heading level, list ordering, link/image href, ...) and a `children` array.
+pub fn markdown_to_ast_json(md: &str) -> String {
+    let arena = Arena::new();
+    let opts = markdown_extension_options();
+    let root = parse_document(&arena, md, &opts);
+    let tree = markdown_node_to_json(root);
+    serde_json::to_string(&tree).unwrap_or_else(|_| "{\"type\":\"document\",\"children\":[]}".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_ast_captures_element_attrs_and_text() {
+        let json = html_to_ast_json("<p class=\"x\">Hi <b>there</b></p>");
+        assert!(json.contains("\"type\":\"element\""));
+        assert!(json.contains("\"tag\":\"p\""));
+        assert!(json.contains("\"class\":\"x\""));
+        assert!(json.contains("\"type\":\"text\""));
+        assert!(json.contains("\"text\":\"Hi \""));
+        assert!(json.contains("\"tag\":\"b\""));
+    }
+
+    #[test]
+    fn html_ast_captures_comments() {
+        let json = html_to_ast_json("<!-- note --><p>x</p>");
+        assert!(json.contains("\"type\":\"comment\""));
+        assert!(json.contains("\"text\":\" note \""));
+    }
+
+    #[test]
+    fn markdown_ast_captures_heading_and_emphasis() {
+        let json = markdown_to_ast_json("# Title\n\nSome *em* and **strong** text with a [link](http://x).");
+        assert!(json.contains("\"type\":\"heading\""));
+        assert!(json.contains("\"level\":\"1\""));
+        assert!(json.contains("\"type\":\"paragraph\""));
+        assert!(json.contains("\"type\":\"emph\""));
+        assert!(json.contains("\"type\":\"strong\""));
+        assert!(json.contains("\"type\":\"link\""));
+        assert!(json.contains("\"href\":\"http://x\""));
+    }
+
+    #[test]
+    fn markdown_ast_captures_lists_and_code() {
+        let json = markdown_to_ast_json("- one\n- two\n\n```rust\nfn x() {}\n```\n");
+        assert!(json.contains("\"type\":\"list\""));
+        assert!(json.contains("\"ordered\":\"false\""));
+        assert!(json.contains("\"type\":\"item\""));
+        assert!(json.contains("\"type\":\"code_block\""));
+        assert!(json.contains("\"info\":\"rust\""));
+        assert!(json.contains("fn x() {}"));
+    }
+}
@@ -1,9 +1,14 @@
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
 use html5ever::parse_document;
 use html5ever::tendril::TendrilSink;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Reader as QuickXmlReader;
+use quick_xml::Writer as QuickXmlWriter;
+use zip::read::ZipArchive;
 use zip::write::{FileOptions, ZipWriter};
 use zip::CompressionMethod;
-use std::io::{Cursor, Write};
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read, Write};
 
 // Word XML namespaces
 const NS_W: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
@@ -11,24 +16,110 @@ const NS_R: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relati
 const NS_M: &str = "http://schemas.openxmlformats.org/officeDocument/2006/math";
 const NS_CT: &str = "http://schemas.openxmlformats.org/package/2006/content-types";
 const NS_RELS: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+const NS_WP: &str = "http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing";
+const NS_A: &str = "http://schemas.openxmlformats.org/drawingml/2006/main";
+const NS_PIC: &str = "http://schemas.openxmlformats.org/drawingml/2006/picture";
+const REL_TYPE_IMAGE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/image";
+const REL_TYPE_HYPERLINK: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink";
 
-fn escape_xml_text_simple(text: &str) -> String {
-    text.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&apos;")
+/// One `<img>` decoded into bytes ready to become a `word/media/imageN.ext` part, plus the
+/// `r:id` its `<a:blip>` refers back to. `index` is the part's position among images only (the
+/// `N` in `imageN.ext` and the `docPr` id), kept separate from `rid` since `rid`s are now shared
+/// with hyperlink relationships and can interleave with them.
+struct MediaAsset {
+    rid: String,
+    index: usize,
+    extension: String,
+    content_type: String,
+    bytes: Vec<u8>,
 }
 
-pub fn generate_content_types_xml() -> String {
+/// One `<a href>` resolved to an external-target relationship.
+struct HyperlinkRel {
+    rid: String,
+    href: String,
+}
+
+/// Mutable registry threaded through `process_node`/`process_run_node`/`process_table` alongside
+/// `omml_idx`, so every `<img>`/`<a href>` the walk encounters gets a unique `rIdN`.
+/// `generate_content_types_xml`/`generate_document_rels_xml`/`html_with_omml_to_docx` read it back
+/// once the walk is done to materialize the zip parts.
+struct Assets {
+    images: Vec<MediaAsset>,
+    hyperlinks: Vec<HyperlinkRel>,
+    next_id: usize,
+}
+
+impl Assets {
+    fn new() -> Self {
+        Self { images: Vec::new(), hyperlinks: Vec::new(), next_id: 1 }
+    }
+
+    /// Allocates the next `rIdN` - shared between images and hyperlinks so every relationship in
+    /// `word/_rels/document.xml.rels` gets a distinct id regardless of which kind registered it
+    /// first (the package-level `rId1` in `_rels/.rels` lives in a separate namespace, so starting
+    /// back at `rId1` here is safe).
+    fn next_rid(&mut self) -> String {
+        let id = self.next_id;
+        self.next_id += 1;
+        format!("rId{id}")
+    }
+
+    /// Registers a decoded image and returns its `(r:id, docPr id)`.
+    fn add_image(&mut self, extension: &str, content_type: &str, bytes: Vec<u8>) -> (String, usize) {
+        let index = self.images.len() + 1;
+        let rid = self.next_rid();
+        self.images.push(MediaAsset {
+            rid: rid.clone(),
+            index,
+            extension: extension.to_string(),
+            content_type: content_type.to_string(),
+            bytes,
+        });
+        (rid, index)
+    }
+
+    /// Registers an external hyperlink target and returns the `r:id` its `<w:hyperlink>` should use.
+    fn add_hyperlink(&mut self, href: &str) -> String {
+        let rid = self.next_rid();
+        self.hyperlinks.push(HyperlinkRel { rid: rid.clone(), href: href.to_string() });
+        rid
+    }
+}
+
+fn escape_xml_attr(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn generate_content_types_xml(images: &[MediaAsset]) -> String {
+    let mut defaults = String::new();
+    let mut seen_extensions = std::collections::BTreeSet::new();
+    for img in images {
+        if seen_extensions.insert(img.extension.clone()) {
+            defaults.push_str(&format!(
+                "\n    <Default Extension=\"{}\" ContentType=\"{}\"/>",
+                img.extension, img.content_type
+            ));
+        }
+    }
     format!(
         r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Types xmlns="{}">
     <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
-    <Default Extension="xml" ContentType="application/xml"/>
+    <Default Extension="xml" ContentType="application/xml"/>{}
     <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
 </Types>"#,
-        NS_CT
+        NS_CT, defaults
     )
 }
 
@@ -42,29 +133,119 @@ pub fn generate_rels_xml() -> String {
     )
 }
 
-pub fn generate_document_rels_xml() -> String {
+pub fn generate_document_rels_xml(assets: &Assets) -> String {
+    let mut rels = String::new();
+    for img in &assets.images {
+        rels.push_str(&format!(
+            "\n    <Relationship Id=\"{}\" Type=\"{}\" Target=\"media/image{}.{}\"/>",
+            img.rid, REL_TYPE_IMAGE, img.index, img.extension
+        ));
+    }
+    for link in &assets.hyperlinks {
+        rels.push_str(&format!(
+            "\n    <Relationship Id=\"{}\" Type=\"{}\" TargetMode=\"External\" Target=\"{}\"/>",
+            link.rid,
+            REL_TYPE_HYPERLINK,
+            escape_xml_attr(&link.href)
+        ));
+    }
     format!(
         r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<Relationships xmlns="{}">
+<Relationships xmlns="{}">{}
 </Relationships>"#,
-        NS_RELS
+        NS_RELS, rels
     )
 }
 
+// Mirrors the small hand-rolled codec in `translation_wasm/src/utils.rs` - there's no shared
+// crate for it and no `base64` dependency in this one, so a `data:` URI's payload is decoded
+// locally rather than pulling in an external dependency for a few dozen lines.
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_decode(s: &str) -> Result<Vec<u8>, String> {
+    let clean: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if clean.len() % 4 != 0 || clean.is_empty() {
+        return Err("invalid base64 length".to_string());
+    }
+    let value_of = |b: u8| -> Result<u8, String> {
+        if b == b'=' {
+            Ok(0)
+        } else {
+            BASE64_ALPHABET
+                .iter()
+                .position(|&c| c == b)
+                .map(|p| p as u8)
+                .ok_or_else(|| format!("invalid base64 byte: {b}"))
+        }
+    };
+    let mut out = Vec::with_capacity(clean.len() / 4 * 3);
+    for chunk in clean.chunks(4) {
+        let b0 = value_of(chunk[0])?;
+        let b1 = value_of(chunk[1])?;
+        let b2 = value_of(chunk[2])?;
+        let b3 = value_of(chunk[3])?;
+        out.push((b0 << 2) | (b1 >> 4));
+        if chunk[2] != b'=' {
+            out.push((b1 << 4) | (b2 >> 2));
+        }
+        if chunk[3] != b'=' {
+            out.push((b2 << 6) | b3);
+        }
+    }
+    Ok(out)
+}
+
+/// Decodes a `src="data:image/png;base64,..."` URI into `(extension, content_type, bytes)`.
+/// `None` for anything that isn't a base64-encoded `image/*` data URI (e.g. a remote `http(s)://`
+/// `src`, which this pipeline has no network access to fetch).
+fn decode_data_uri_image(src: &str) -> Option<(String, String, Vec<u8>)> {
+    let rest = src.strip_prefix("data:")?;
+    let (meta, payload) = rest.split_once(',')?;
+    let content_type = meta.strip_suffix(";base64")?;
+    let extension = match content_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/bmp" => "bmp",
+        _ => return None,
+    };
+    let bytes = base64_decode(payload).ok()?;
+    Some((extension.to_string(), content_type.to_string(), bytes))
+}
+
+/// Parses a leading integer off an HTML length attribute (`"300"`, `"300px"`) and ignores
+/// anything else (`"50%"`, empty, garbage) - callers fall back to `DEFAULT_IMAGE_PX` for those.
+fn parse_px(value: &str) -> Option<i64> {
+    let digits: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.is_empty() {
+        None
+    } else {
+        digits.parse::<i64>().ok()
+    }
+}
+
+const DEFAULT_IMAGE_PX: i64 = 300;
+
+// Word's DrawingML measures everything in EMUs; at the 96 DPI HTML `width`/`height` attributes
+// assume, 1px = 914400/96 = 9525 EMU.
+fn px_to_emu(px: i64) -> i64 {
+    px * 9525
+}
+
 fn extract_omml_from_html(html: &str) -> Vec<String> {
     // Extract OMML from conditional comments like <!--[if gte msEquation 12]><m:oMath>...</m:oMath><![endif]-->
     // Or from <!--[if mso]><m:oMath>...</m:oMath><![endif]-->
     let mut omml_chunks = Vec::new();
     let mut i = 0;
-    
+
     while i < html.len() {
         // Look for <!--[if gte msEquation 12]> or <!--[if mso]>
         let pattern1 = "<!--[if gte msEquation 12]>";
         let pattern2 = "<!--[if mso]>";
-        
+
         let pos1 = html[i..].find(pattern1);
         let pos2 = html[i..].find(pattern2);
-        
+
         let (pattern, pos_opt) = match (pos1, pos2) {
             (Some(p1), Some(p2)) => {
                 if p1 < p2 {
@@ -77,20 +258,20 @@ fn extract_omml_from_html(html: &str) -> Vec<String> {
             (None, Some(p)) => (pattern2, Some(p)),
             (None, None) => break,
         };
-        
+
         if let Some(comment_start) = pos_opt {
             let start_pos = i + comment_start;
             let after_comment = start_pos + pattern.len();
-            
+
             // Look for <![endif]--> to find the end of the conditional
             if let Some(end_comment) = html[after_comment..].find("<![endif]-->") {
                 let omml_content = html[after_comment..after_comment + end_comment].trim();
-                
+
                 // Extract OMML - should start with <m:oMath> or <oMath
                 if omml_content.starts_with("<m:oMath") || omml_content.starts_with("<oMath") {
                     omml_chunks.push(omml_content.to_string());
                 }
-                
+
                 i = after_comment + end_comment + "<![endif]-->".len();
             } else {
                 i = after_comment;
@@ -99,42 +280,137 @@ fn extract_omml_from_html(html: &str) -> Vec<String> {
             break;
         }
     }
-    
+
     omml_chunks
 }
 
-fn html_to_word_xml(html: &str) -> Result<String, String> {
+/// Thin wrapper over a `quick_xml::Writer` buffering into memory, so `process_node` and friends
+/// emit proper `Event`s (correctly escaped text/attributes) instead of hand-concatenating strings.
+/// `raw` is the one escape hatch: OMML chunks are already-serialized XML lifted verbatim out of a
+/// `<!--[if ...]-->` comment, so they're written as bytes rather than run through escaping again.
+struct XmlOut {
+    writer: QuickXmlWriter<Cursor<Vec<u8>>>,
+}
+
+impl XmlOut {
+    fn new() -> Self {
+        Self { writer: QuickXmlWriter::new(Cursor::new(Vec::new())) }
+    }
+
+    fn xml_err(e: quick_xml::Error) -> String {
+        format!("XML error: {e}")
+    }
+
+    fn decl(&mut self) -> Result<(), String> {
+        self.writer
+            .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), Some("yes"))))
+            .map_err(Self::xml_err)
+    }
+
+    fn start_with_attrs(&mut self, tag: &str, attrs: &[(&str, &str)]) -> Result<(), String> {
+        let mut start = BytesStart::new(tag);
+        for (k, v) in attrs {
+            start.push_attribute((*k, *v));
+        }
+        self.writer.write_event(Event::Start(start)).map_err(Self::xml_err)
+    }
+
+    fn start(&mut self, tag: &str) -> Result<(), String> {
+        self.start_with_attrs(tag, &[])
+    }
+
+    fn end(&mut self, tag: &str) -> Result<(), String> {
+        self.writer.write_event(Event::End(BytesEnd::new(tag))).map_err(Self::xml_err)
+    }
+
+    fn empty_with_attrs(&mut self, tag: &str, attrs: &[(&str, &str)]) -> Result<(), String> {
+        let mut start = BytesStart::new(tag);
+        for (k, v) in attrs {
+            start.push_attribute((*k, *v));
+        }
+        self.writer.write_event(Event::Empty(start)).map_err(Self::xml_err)
+    }
+
+    fn empty(&mut self, tag: &str) -> Result<(), String> {
+        self.empty_with_attrs(tag, &[])
+    }
+
+    /// Escaped text content (the normal path for any HTML text node).
+    fn text(&mut self, text: &str) -> Result<(), String> {
+        self.writer.write_event(Event::Text(BytesText::new(text))).map_err(Self::xml_err)
+    }
+
+    /// Writes `xml` as already-serialized, pre-escaped markup (an OMML chunk) rather than escaping
+    /// it as character data - `BytesText::from_escaped` is quick-xml's documented way to pass
+    /// through raw XML without a second escaping pass.
+    fn raw(&mut self, xml: &str) -> Result<(), String> {
+        self.writer
+            .write_event(Event::Text(BytesText::from_escaped(xml)))
+            .map_err(Self::xml_err)
+    }
+
+    fn into_string(self) -> Result<String, String> {
+        String::from_utf8(self.writer.into_inner().into_inner())
+            .map_err(|e| format!("UTF-8 error: {e}"))
+    }
+}
+
+fn html_to_word_xml(html: &str) -> Result<(String, Assets), String> {
     // Parse HTML
     let dom = parse_document(RcDom::default(), Default::default()).one(html);
-    
+
     // Extract OMML chunks
     let omml_chunks = extract_omml_from_html(html);
-    
-    // Build Word XML document - for now, use a simple approach
-    // TODO: Integrate OMML chunks properly during node processing
-    let mut doc_xml = String::new();
-    doc_xml.push_str(&format!(
-        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<w:document xmlns:w="{}" xmlns:m="{}" xmlns:r="{}">
-<w:body>
-"#,
-        NS_W, NS_M, NS_R
-    ));
-    
+
+    // Build Word XML document - for now, use a simple approach.
+    // OMML chunks are spliced back in by process_node/process_run_node as they walk past the
+    // conditional comment each one came from, via omml_idx below.
+    let mut xml = XmlOut::new();
+    xml.decl()?;
+    xml.start_with_attrs(
+        "w:document",
+        &[
+            ("xmlns:w", NS_W),
+            ("xmlns:m", NS_M),
+            ("xmlns:r", NS_R),
+            ("xmlns:wp", NS_WP),
+            ("xmlns:a", NS_A),
+            ("xmlns:pic", NS_PIC),
+        ],
+    )?;
+    xml.start("w:body")?;
+
     // Process body children - convert HTML to Word XML
     let body_children = if let Some(body) = find_body(&dom.document) {
         body.children.borrow().clone()
     } else {
         dom.document.children.borrow().clone()
     };
-    
+
+    let mut omml_idx = 0;
+    let mut assets = Assets::new();
     for child in body_children {
-        process_node(&child, &mut doc_xml, &omml_chunks)?;
+        process_node(&child, &mut xml, &omml_chunks, &mut omml_idx, &mut assets)?;
     }
-    
-    doc_xml.push_str("</w:body></w:document>");
-    
-    Ok(doc_xml)
+
+    xml.end("w:body")?;
+    xml.end("w:document")?;
+
+    Ok((xml.into_string()?, assets))
+}
+
+// A comment node is one of these conditional wrappers iff its contents (the text html5ever gives
+// us between `<!--` and `-->`) starts with one of these - matching what `extract_omml_from_html`
+// looked for in the raw HTML, so the Nth such comment the walk passes lines up with
+// `omml_chunks[N]`.
+fn comment_is_omml_wrapper(contents: &str) -> bool {
+    contents.starts_with("[if gte msEquation 12]") || contents.starts_with("[if mso]")
+}
+
+fn next_omml_chunk<'a>(omml_chunks: &'a [String], omml_idx: &mut usize) -> Option<&'a str> {
+    let chunk = omml_chunks.get(*omml_idx).map(|s| s.as_str());
+    *omml_idx += 1;
+    chunk
 }
 
 fn find_body(node: &Handle) -> Option<Handle> {
@@ -151,85 +427,136 @@ fn find_body(node: &Handle) -> Option<Handle> {
     None
 }
 
-fn process_node(node: &Handle, output: &mut String, omml_chunks: &[String]) -> Result<(), String> {
+fn process_node(
+    node: &Handle,
+    xml: &mut XmlOut,
+    omml_chunks: &[String],
+    omml_idx: &mut usize,
+    assets: &mut Assets,
+) -> Result<(), String> {
     match &node.data {
         NodeData::Text { contents } => {
             let text = contents.borrow().to_string();
             if !text.trim().is_empty() {
-                output.push_str("<w:p><w:r><w:t xml:space=\"preserve\">");
-                output.push_str(&escape_xml_text_simple(&text));
-                output.push_str("</w:t></w:r></w:p>");
+                xml.start("w:p")?;
+                xml.start("w:r")?;
+                xml.start_with_attrs("w:t", &[("xml:space", "preserve")])?;
+                xml.text(&text)?;
+                xml.end("w:t")?;
+                xml.end("w:r")?;
+                xml.end("w:p")?;
+            }
+        }
+        NodeData::Comment { contents } => {
+            // A block-level conditional-comment equation (one not nested inside a <p>/<div> run,
+            // e.g. pasted straight into the body): its own paragraph, wrapped in <m:oMathPara>.
+            if comment_is_omml_wrapper(contents) {
+                if let Some(chunk) = next_omml_chunk(omml_chunks, omml_idx) {
+                    xml.start("w:p")?;
+                    xml.start("m:oMathPara")?;
+                    xml.raw(chunk)?;
+                    xml.end("m:oMathPara")?;
+                    xml.end("w:p")?;
+                }
             }
         }
         NodeData::Element { name, .. } => {
             let tag = name.local.to_string().to_ascii_lowercase();
             match tag.as_str() {
                 "p" | "div" => {
-                    output.push_str("<w:p>");
+                    xml.start("w:p")?;
                     for child in node.children.borrow().iter() {
-                        process_run_node(child, output, omml_chunks)?;
+                        process_run_node(child, xml, omml_chunks, omml_idx, assets)?;
                     }
-                    output.push_str("</w:p>");
+                    xml.end("w:p")?;
+                }
+                "table" => {
+                    // thead/tbody/tfoot/tr/td/th are all consumed here rather than getting their
+                    // own process_node arm - process_table walks the whole subtree itself.
+                    process_table(node, xml, omml_chunks, omml_idx, assets)?;
                 }
                 "strong" | "b" => {
-                    output.push_str("<w:r><w:rPr><w:b/></w:rPr>");
+                    xml.start("w:r")?;
+                    xml.start("w:rPr")?;
+                    xml.empty("w:b")?;
+                    xml.end("w:rPr")?;
                     for child in node.children.borrow().iter() {
-                        process_run_node(child, output, omml_chunks)?;
+                        process_run_node(child, xml, omml_chunks, omml_idx, assets)?;
                     }
-                    output.push_str("</w:r>");
+                    xml.end("w:r")?;
                 }
                 "em" | "i" => {
-                    output.push_str("<w:r><w:rPr><w:i/></w:rPr>");
+                    xml.start("w:r")?;
+                    xml.start("w:rPr")?;
+                    xml.empty("w:i")?;
+                    xml.end("w:rPr")?;
                     for child in node.children.borrow().iter() {
-                        process_run_node(child, output, omml_chunks)?;
+                        process_run_node(child, xml, omml_chunks, omml_idx, assets)?;
                     }
-                    output.push_str("</w:r>");
+                    xml.end("w:r")?;
                 }
                 "br" => {
-                    output.push_str("<w:p><w:r><w:br/></w:r></w:p>");
+                    xml.start("w:p")?;
+                    xml.start("w:r")?;
+                    xml.empty("w:br")?;
+                    xml.end("w:r")?;
+                    xml.end("w:p")?;
                 }
                 "ul" | "ol" => {
                     for child in node.children.borrow().iter() {
                         if let NodeData::Element { name, .. } = &child.data {
                             if name.local.to_string().eq_ignore_ascii_case("li") {
-                                output.push_str("<w:p><w:pPr><w:numPr><w:ilvl w:val=\"0\"/><w:numId w:val=\"1\"/></w:numPr></w:pPr>");
+                                xml.start("w:p")?;
+                                xml.start("w:pPr")?;
+                                xml.start("w:numPr")?;
+                                xml.empty_with_attrs("w:ilvl", &[("w:val", "0")])?;
+                                xml.empty_with_attrs("w:numId", &[("w:val", "1")])?;
+                                xml.end("w:numPr")?;
+                                xml.end("w:pPr")?;
                                 for grandchild in child.children.borrow().iter() {
-                                    process_run_node(grandchild, output, omml_chunks)?;
+                                    process_run_node(grandchild, xml, omml_chunks, omml_idx, assets)?;
                                 }
-                                output.push_str("</w:p>");
+                                xml.end("w:p")?;
                             }
                         }
                     }
                 }
                 "a" => {
                     // Extract href attribute
-                    let mut href = String::new();
-                    if let NodeData::Element { attrs, .. } = &node.data {
-                        for attr in attrs.borrow().iter() {
-                            if attr.name.local.to_string().eq_ignore_ascii_case("href") {
-                                href = attr.value.to_string();
-                                break;
-                            }
-                        }
-                    }
-                    if !href.is_empty() {
-                        output.push_str(&format!("<w:hyperlink r:id=\"rId1\"><w:r><w:rPr><w:color w:val=\"1155CC\"/><w:u w:val=\"single\"/></w:rPr><w:t>"));
+                    let href = elem_attr(node, "href").unwrap_or_default();
+                    let rid = if !href.is_empty() { Some(assets.add_hyperlink(&href)) } else { None };
+                    if let Some(rid) = &rid {
+                        xml.start_with_attrs("w:hyperlink", &[("r:id", rid)])?;
+                        xml.start("w:r")?;
+                        xml.start("w:rPr")?;
+                        xml.empty_with_attrs("w:color", &[("w:val", "1155CC")])?;
+                        xml.empty_with_attrs("w:u", &[("w:val", "single")])?;
+                        xml.end("w:rPr")?;
+                        xml.start("w:t")?;
                     } else {
-                        output.push_str("<w:r><w:t>");
+                        xml.start("w:r")?;
+                        xml.start("w:t")?;
                     }
                     for child in node.children.borrow().iter() {
-                        process_run_node(child, output, omml_chunks)?;
+                        process_run_node(child, xml, omml_chunks, omml_idx, assets)?;
                     }
-                    if !href.is_empty() {
-                        output.push_str("</w:t></w:r></w:hyperlink>");
-                    } else {
-                        output.push_str("</w:t></w:r>");
+                    xml.end("w:t")?;
+                    xml.end("w:r")?;
+                    if rid.is_some() {
+                        xml.end("w:hyperlink")?;
+                    }
+                }
+                "img" => {
+                    if let Some((rid, doc_id, cx, cy)) = register_image(node, assets) {
+                        xml.start("w:p")?;
+                        emit_image_run(xml, &rid, doc_id, cx, cy)?;
+                        xml.end("w:p")?;
                     }
                 }
                 _ => {
                     // Default: process children
                     for child in node.children.borrow().iter() {
-                        process_node(child, output, omml_chunks)?;
+                        process_node(child, xml, omml_chunks, omml_idx, assets)?;
                     }
                 }
             }
@@ -239,73 +566,817 @@ fn process_node(node: &Handle, output: &mut String, omml_chunks: &[String]) -> R
     Ok(())
 }
 
-fn process_run_node(node: &Handle, output: &mut String, omml_chunks: &[String]) -> Result<(), String> {
+fn process_run_node(
+    node: &Handle,
+    xml: &mut XmlOut,
+    omml_chunks: &[String],
+    omml_idx: &mut usize,
+    assets: &mut Assets,
+) -> Result<(), String> {
     match &node.data {
         NodeData::Text { contents } => {
             let text = contents.borrow().to_string();
-            output.push_str(&escape_xml_text_simple(&text));
+            xml.text(&text)?;
         }
         NodeData::Element { name, .. } => {
             let tag = name.local.to_string().to_ascii_lowercase();
             match tag.as_str() {
                 "strong" | "b" => {
-                    output.push_str("<w:r><w:rPr><w:b/></w:rPr><w:t>");
+                    xml.start("w:r")?;
+                    xml.start("w:rPr")?;
+                    xml.empty("w:b")?;
+                    xml.end("w:rPr")?;
+                    xml.start("w:t")?;
                     for child in node.children.borrow().iter() {
-                        process_run_node(child, output, omml_chunks)?;
+                        process_run_node(child, xml, omml_chunks, omml_idx, assets)?;
                     }
-                    output.push_str("</w:t></w:r>");
+                    xml.end("w:t")?;
+                    xml.end("w:r")?;
                 }
                 "em" | "i" => {
-                    output.push_str("<w:r><w:rPr><w:i/></w:rPr><w:t>");
+                    xml.start("w:r")?;
+                    xml.start("w:rPr")?;
+                    xml.empty("w:i")?;
+                    xml.end("w:rPr")?;
+                    xml.start("w:t")?;
                     for child in node.children.borrow().iter() {
-                        process_run_node(child, output, omml_chunks)?;
+                        process_run_node(child, xml, omml_chunks, omml_idx, assets)?;
+                    }
+                    xml.end("w:t")?;
+                    xml.end("w:r")?;
+                }
+                "img" => {
+                    if let Some((rid, doc_id, cx, cy)) = register_image(node, assets) {
+                        emit_image_run(xml, &rid, doc_id, cx, cy)?;
                     }
-                    output.push_str("</w:t></w:r>");
                 }
                 _ => {
                     for child in node.children.borrow().iter() {
-                        process_run_node(child, output, omml_chunks)?;
+                        process_run_node(child, xml, omml_chunks, omml_idx, assets)?;
                     }
                 }
             }
         }
-        NodeData::Comment { .. } => {
-            // Skip comments for now - OMML is extracted separately
+        NodeData::Comment { contents } => {
+            // Inline conditional-comment equation (sits among a paragraph's runs): splice the
+            // OMML in raw, the same way plain text is pushed bare in this run context.
+            if comment_is_omml_wrapper(contents) {
+                if let Some(chunk) = next_omml_chunk(omml_chunks, omml_idx) {
+                    xml.raw(chunk)?;
+                }
+            }
         }
         _ => {}
     }
     Ok(())
 }
 
+/// Resolves an `<img>`'s `src`/`width`/`height` into a registered `Assets` entry, returning
+/// `(r:id, docPr id, cx EMU, cy EMU)`. `None` for anything `decode_data_uri_image` can't decode
+/// (a remote URL, an unsupported format) - such images are silently dropped, matching the
+/// pre-existing behavior of falling through the default "process children" branch.
+fn register_image(node: &Handle, assets: &mut Assets) -> Option<(String, usize, i64, i64)> {
+    let src = elem_attr(node, "src")?;
+    let (extension, content_type, bytes) = decode_data_uri_image(&src)?;
+    let width_px = elem_attr(node, "width").and_then(|v| parse_px(&v)).unwrap_or(DEFAULT_IMAGE_PX);
+    let height_px = elem_attr(node, "height").and_then(|v| parse_px(&v)).unwrap_or(DEFAULT_IMAGE_PX);
+    let (rid, doc_id) = assets.add_image(&extension, &content_type, bytes);
+    Some((rid, doc_id, px_to_emu(width_px), px_to_emu(height_px)))
+}
+
+/// Emits `<w:r><w:drawing>...</w:drawing></w:r>` for one registered image: a `wp:inline` anchor
+/// sized `cx`x`cy` EMU, wrapping a `pic:pic` whose `a:blip` points at `rid`.
+fn emit_image_run(xml: &mut XmlOut, rid: &str, doc_id: usize, cx: i64, cy: i64) -> Result<(), String> {
+    let name = format!("Picture {doc_id}");
+    xml.start("w:r")?;
+    xml.start("w:drawing")?;
+    xml.start_with_attrs(
+        "wp:inline",
+        &[("distT", "0"), ("distB", "0"), ("distL", "0"), ("distR", "0")],
+    )?;
+    xml.empty_with_attrs("wp:extent", &[("cx", &cx.to_string()), ("cy", &cy.to_string())])?;
+    xml.empty_with_attrs("wp:docPr", &[("id", &doc_id.to_string()), ("name", &name)])?;
+    xml.start("a:graphic")?;
+    xml.start_with_attrs("a:graphicData", &[("uri", NS_PIC)])?;
+    xml.start("pic:pic")?;
+    xml.start("pic:nvPicPr")?;
+    xml.empty_with_attrs("pic:cNvPr", &[("id", &doc_id.to_string()), ("name", &name)])?;
+    xml.empty("pic:cNvPicPr")?;
+    xml.end("pic:nvPicPr")?;
+    xml.start("pic:blipFill")?;
+    xml.empty_with_attrs("a:blip", &[("r:embed", rid)])?;
+    xml.start("a:stretch")?;
+    xml.empty("a:fillRect")?;
+    xml.end("a:stretch")?;
+    xml.end("pic:blipFill")?;
+    xml.start("pic:spPr")?;
+    xml.start("a:xfrm")?;
+    xml.empty_with_attrs("a:off", &[("x", "0"), ("y", "0")])?;
+    xml.empty_with_attrs("a:ext", &[("cx", &cx.to_string()), ("cy", &cy.to_string())])?;
+    xml.end("a:xfrm")?;
+    xml.start_with_attrs("a:prstGeom", &[("prst", "rect")])?;
+    xml.empty("a:avLst")?;
+    xml.end("a:prstGeom")?;
+    xml.end("pic:spPr")?;
+    xml.end("pic:pic")?;
+    xml.end("a:graphicData")?;
+    xml.end("a:graphic")?;
+    xml.end("wp:inline")?;
+    xml.end("w:drawing")?;
+    xml.end("w:r")?;
+    Ok(())
+}
+
+fn elem_tag(node: &Handle) -> Option<String> {
+    if let NodeData::Element { name, .. } = &node.data {
+        Some(name.local.to_string().to_ascii_lowercase())
+    } else {
+        None
+    }
+}
+
+fn elem_attr(node: &Handle, attr_name: &str) -> Option<String> {
+    if let NodeData::Element { attrs, .. } = &node.data {
+        for attr in attrs.borrow().iter() {
+            if attr.name.local.to_string().eq_ignore_ascii_case(attr_name) {
+                return Some(attr.value.to_string());
+            }
+        }
+    }
+    None
+}
+
+fn cell_span(node: &Handle, attr_name: &str) -> usize {
+    elem_attr(node, attr_name)
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(1)
+}
+
+// Walks a <table> subtree collecting its <tr> rows in document order, descending into
+// thead/tbody/tfoot wrappers (and tolerating a table with no such wrappers at all).
+fn collect_table_rows(table: &Handle) -> Vec<Handle> {
+    let mut rows = Vec::new();
+    for child in table.children.borrow().iter() {
+        match elem_tag(child).as_deref() {
+            Some("tr") => rows.push(child.clone()),
+            Some("thead") | Some("tbody") | Some("tfoot") => {
+                for grandchild in child.children.borrow().iter() {
+                    if elem_tag(grandchild).as_deref() == Some("tr") {
+                        rows.push(grandchild.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    rows
+}
+
+fn collect_row_cells(row: &Handle) -> Vec<Handle> {
+    row.children
+        .borrow()
+        .iter()
+        .filter(|c| matches!(elem_tag(c).as_deref(), Some("td") | Some("th")))
+        .cloned()
+        .collect()
+}
+
+// Renders a <table> as <w:tbl>: a <w:tblGrid> sized to the widest row, one <w:tr> per row, and
+// one <w:tc> per cell - honoring `colspan` via <w:gridSpan> and `rowspan` via <w:vMerge>, with
+// `vmerge_remaining` tracking which columns owe a continuation placeholder on later rows.
+fn process_table(
+    node: &Handle,
+    xml: &mut XmlOut,
+    omml_chunks: &[String],
+    omml_idx: &mut usize,
+    assets: &mut Assets,
+) -> Result<(), String> {
+    let rows = collect_table_rows(node);
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let row_cells: Vec<Vec<Handle>> = rows.iter().map(collect_row_cells).collect();
+    let max_cols = row_cells
+        .iter()
+        .map(|cells| cells.iter().map(|c| cell_span(c, "colspan")).sum::<usize>())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    xml.start("w:tbl")?;
+    xml.start("w:tblPr")?;
+    xml.start("w:tblBorders")?;
+    for edge in ["top", "left", "bottom", "right", "insideH", "insideV"] {
+        xml.empty_with_attrs(
+            &format!("w:{edge}"),
+            &[("w:val", "single"), ("w:sz", "4"), ("w:space", "0"), ("w:color", "auto")],
+        )?;
+    }
+    xml.end("w:tblBorders")?;
+    xml.end("w:tblPr")?;
+    xml.start("w:tblGrid")?;
+    for _ in 0..max_cols {
+        xml.empty("w:gridCol")?;
+    }
+    xml.end("w:tblGrid")?;
+
+    let mut vmerge_remaining: Vec<usize> = vec![0; max_cols];
+    for cells in &row_cells {
+        xml.start("w:tr")?;
+        let mut col = 0usize;
+        let mut cell_iter = cells.iter();
+        let mut next_cell = cell_iter.next();
+        while col < max_cols {
+            if vmerge_remaining[col] > 0 {
+                vmerge_remaining[col] -= 1;
+                xml.start("w:tc")?;
+                xml.start("w:tcPr")?;
+                xml.empty("w:vMerge")?;
+                xml.end("w:tcPr")?;
+                xml.empty("w:p")?;
+                xml.end("w:tc")?;
+                col += 1;
+                continue;
+            }
+            let Some(cell) = next_cell else { break };
+            let colspan = cell_span(cell, "colspan").min(max_cols - col);
+            let rowspan = cell_span(cell, "rowspan");
+
+            xml.start("w:tc")?;
+            xml.start("w:tcPr")?;
+            if colspan > 1 {
+                xml.empty_with_attrs("w:gridSpan", &[("w:val", &colspan.to_string())])?;
+            }
+            if rowspan > 1 {
+                xml.empty_with_attrs("w:vMerge", &[("w:val", "restart")])?;
+                for c in col..col + colspan {
+                    vmerge_remaining[c] = rowspan - 1;
+                }
+            }
+            xml.end("w:tcPr")?;
+            xml.start("w:p")?;
+            for child in cell.children.borrow().iter() {
+                process_run_node(child, xml, omml_chunks, omml_idx, assets)?;
+            }
+            xml.end("w:p")?;
+            xml.end("w:tc")?;
+
+            col += colspan;
+            next_cell = cell_iter.next();
+        }
+        xml.end("w:tr")?;
+    }
+    xml.end("w:tbl")?;
+    Ok(())
+}
+
 pub fn html_with_omml_to_docx(html: &str) -> Result<Vec<u8>, String> {
-    // Generate Word XML document
-    let doc_xml = html_to_word_xml(html)?;
-    
+    // Generate Word XML document, plus whatever <img> parts it collected along the way.
+    let (doc_xml, assets) = html_to_word_xml(html)?;
+
     // Create ZIP archive
     let mut zip_buf = Vec::new();
     {
         let mut zip = ZipWriter::new(Cursor::new(&mut zip_buf));
         let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
-        
+
         // Add [Content_Types].xml
         zip.start_file("[Content_Types].xml", options).map_err(|e| format!("ZIP error: {}", e))?;
-        zip.write_all(generate_content_types_xml().as_bytes()).map_err(|e| format!("Write error: {}", e))?;
-        
+        zip.write_all(generate_content_types_xml(&assets.images).as_bytes()).map_err(|e| format!("Write error: {}", e))?;
+
         // Add _rels/.rels
         zip.start_file("_rels/.rels", options).map_err(|e| format!("ZIP error: {}", e))?;
         zip.write_all(generate_rels_xml().as_bytes()).map_err(|e| format!("Write error: {}", e))?;
-        
+
         // Add word/document.xml
         zip.start_file("word/document.xml", options).map_err(|e| format!("ZIP error: {}", e))?;
         zip.write_all(doc_xml.as_bytes()).map_err(|e| format!("Write error: {}", e))?;
-        
+
         // Add word/_rels/document.xml.rels
         zip.start_file("word/_rels/document.xml.rels", options).map_err(|e| format!("ZIP error: {}", e))?;
-        zip.write_all(generate_document_rels_xml().as_bytes()).map_err(|e| format!("Write error: {}", e))?;
-        
+        zip.write_all(generate_document_rels_xml(&assets).as_bytes()).map_err(|e| format!("Write error: {}", e))?;
+
+        // Add word/media/imageN.ext for each embedded image
+        for img in &assets.images {
+            let part_name = format!("word/media/image{}.{}", img.index, img.extension);
+            zip.start_file(&part_name, options).map_err(|e| format!("ZIP error: {}", e))?;
+            zip.write_all(&img.bytes).map_err(|e| format!("Write error: {}", e))?;
+        }
+
         zip.finish().map_err(|e| format!("ZIP finish error: {}", e))?;
     }
-    
+
     Ok(zip_buf)
 }
 
+// --- docx -> HTML -----------------------------------------------------------------------------
+//
+// The reverse direction: open a `.docx` package, read `word/document.xml` (plus
+// `word/_rels/document.xml.rels` for hyperlink targets and `word/numbering.xml` for list type),
+// and reconstruct the HTML subset `html_with_omml_to_docx` understands. `<w:p>` becomes `<p>`,
+// bold/italic runs become `<strong>`/`<em>`, `<w:hyperlink>` resolves through the rels part back
+// to `<a href>`, consecutive `<w:numPr>` paragraphs regroup into a single `<ul>`/`<ol>`, and
+// `<m:oMath>` is re-wrapped in the `<!--[if gte msEquation 12]>...<![endif]-->` conditional
+// comment `extract_omml_from_html` already knows how to splice back out.
+
+/// One inline run recovered from a paragraph, mirroring the subset of HTML `process_run_node`
+/// understands on the way in.
+enum DocInline {
+    Text { text: String, bold: bool, italic: bool },
+    Break,
+    Math(String),
+    Link { href: Option<String>, inlines: Vec<DocInline> },
+}
+
+/// One paragraph-level unit recovered from `word/body`, before consecutive list items are
+/// regrouped into a single `<ul>`/`<ol>` by `render_blocks`.
+enum DocBlock {
+    Paragraph(Vec<DocInline>),
+    ListItem { num_id: Option<u32>, inlines: Vec<DocInline> },
+    /// A paragraph whose only content was an `<m:oMathPara>` - i.e. the block-equation shape
+    /// `process_node` emits for a top-level conditional-comment math chunk, which isn't itself
+    /// wrapped in a `<p>` on the HTML side.
+    MathBlock(String),
+}
+
+fn read_zip_entry(archive: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Option<String>, String> {
+    match archive.by_name(name) {
+        Ok(mut file) => {
+            let mut buf = String::new();
+            file.read_to_string(&mut buf).map_err(|e| format!("read error: {e}"))?;
+            Ok(Some(buf))
+        }
+        Err(zip::result::ZipError::FileNotFound) => Ok(None),
+        Err(e) => Err(format!("zip error: {e}")),
+    }
+}
+
+fn attr_value(start: &BytesStart, name: &[u8]) -> Option<String> {
+    start
+        .attributes()
+        .flatten()
+        .find(|a| a.key.as_ref() == name)
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.into_owned())
+}
+
+/// Consumes events up to and including the matching `</name>` for a `<name>` start tag already
+/// read (so depth starts at 1), tolerating same-named children along the way.
+fn skip_element(reader: &mut QuickXmlReader<&[u8]>, name: &[u8]) -> Result<(), String> {
+    let mut depth = 1;
+    loop {
+        match reader.read_event().map_err(|e| format!("XML error: {e}"))? {
+            Event::Start(e) if e.name().as_ref() == name => depth += 1,
+            Event::End(e) if e.name().as_ref() == name => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+            Event::Eof => {
+                return Err(format!("unexpected EOF while skipping <{}>", String::from_utf8_lossy(name)))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Skips a `<name>` start tag already read, returning the exact raw XML (open tag through close
+/// tag) it spanned - used to lift an `<m:oMath>` block back out verbatim, the same way
+/// `XmlOut::raw` writes one back out without re-escaping it.
+fn capture_raw_element(
+    reader: &mut QuickXmlReader<&[u8]>,
+    xml: &str,
+    start_pos: usize,
+    name: &[u8],
+) -> Result<String, String> {
+    skip_element(reader, name)?;
+    let end_pos = reader.buffer_position() as usize;
+    Ok(xml[start_pos..end_pos].to_string())
+}
+
+fn read_element_text(reader: &mut QuickXmlReader<&[u8]>, end_name: &[u8]) -> Result<String, String> {
+    let mut text = String::new();
+    loop {
+        match reader.read_event().map_err(|e| format!("XML error: {e}"))? {
+            Event::Text(t) => text.push_str(&t.unescape().map_err(|e| format!("XML error: {e}"))?),
+            Event::End(e) if e.name().as_ref() == end_name => break,
+            Event::Eof => return Err("unexpected EOF reading text".to_string()),
+            _ => {}
+        }
+    }
+    Ok(text)
+}
+
+fn parse_r_pr(reader: &mut QuickXmlReader<&[u8]>) -> Result<(bool, bool), String> {
+    let mut bold = false;
+    let mut italic = false;
+    loop {
+        match reader.read_event().map_err(|e| format!("XML error: {e}"))? {
+            Event::Empty(e) if e.name().as_ref() == b"w:b" => bold = true,
+            Event::Start(e) if e.name().as_ref() == b"w:b" => {
+                bold = true;
+                skip_element(reader, b"w:b")?;
+            }
+            Event::Empty(e) if e.name().as_ref() == b"w:i" => italic = true,
+            Event::Start(e) if e.name().as_ref() == b"w:i" => {
+                italic = true;
+                skip_element(reader, b"w:i")?;
+            }
+            Event::Start(e) => {
+                let name = e.name().as_ref().to_vec();
+                skip_element(reader, &name)?;
+            }
+            Event::End(e) if e.name().as_ref() == b"w:rPr" => break,
+            Event::Eof => return Err("unexpected EOF in <w:rPr>".to_string()),
+            _ => {}
+        }
+    }
+    Ok((bold, italic))
+}
+
+fn parse_run(reader: &mut QuickXmlReader<&[u8]>, xml: &str) -> Result<Vec<DocInline>, String> {
+    let mut bold = false;
+    let mut italic = false;
+    let mut out = Vec::new();
+    loop {
+        let pos_before = reader.buffer_position() as usize;
+        match reader.read_event().map_err(|e| format!("XML error: {e}"))? {
+            Event::Start(e) if e.name().as_ref() == b"w:rPr" => {
+                let (b, i) = parse_r_pr(reader)?;
+                bold = b;
+                italic = i;
+            }
+            Event::Empty(e) if e.name().as_ref() == b"w:rPr" => {}
+            Event::Start(e) if e.name().as_ref() == b"w:t" => {
+                let text = read_element_text(reader, b"w:t")?;
+                out.push(DocInline::Text { text, bold, italic });
+            }
+            Event::Empty(e) if e.name().as_ref() == b"w:t" => {
+                out.push(DocInline::Text { text: String::new(), bold, italic });
+            }
+            Event::Empty(e) if e.name().as_ref() == b"w:br" => out.push(DocInline::Break),
+            Event::Start(e) if e.name().as_ref() == b"m:oMath" => {
+                out.push(DocInline::Math(capture_raw_element(reader, xml, pos_before, b"m:oMath")?));
+            }
+            Event::Start(e) => {
+                let name = e.name().as_ref().to_vec();
+                skip_element(reader, &name)?;
+            }
+            Event::End(e) if e.name().as_ref() == b"w:r" => break,
+            Event::Eof => return Err("unexpected EOF in <w:r>".to_string()),
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn parse_hyperlink(
+    reader: &mut QuickXmlReader<&[u8]>,
+    xml: &str,
+    href: Option<String>,
+) -> Result<DocInline, String> {
+    let mut inlines = Vec::new();
+    loop {
+        match reader.read_event().map_err(|e| format!("XML error: {e}"))? {
+            Event::Start(e) if e.name().as_ref() == b"w:r" => {
+                inlines.extend(parse_run(reader, xml)?);
+            }
+            Event::Start(e) => {
+                let name = e.name().as_ref().to_vec();
+                skip_element(reader, &name)?;
+            }
+            Event::End(e) if e.name().as_ref() == b"w:hyperlink" => break,
+            Event::Eof => return Err("unexpected EOF in <w:hyperlink>".to_string()),
+            _ => {}
+        }
+    }
+    Ok(DocInline::Link { href, inlines })
+}
+
+fn parse_omath_para(reader: &mut QuickXmlReader<&[u8]>, xml: &str) -> Result<String, String> {
+    let mut raw = String::new();
+    loop {
+        let pos_before = reader.buffer_position() as usize;
+        match reader.read_event().map_err(|e| format!("XML error: {e}"))? {
+            Event::Start(e) if e.name().as_ref() == b"m:oMath" => {
+                raw = capture_raw_element(reader, xml, pos_before, b"m:oMath")?;
+            }
+            Event::Start(e) => {
+                let name = e.name().as_ref().to_vec();
+                skip_element(reader, &name)?;
+            }
+            Event::End(e) if e.name().as_ref() == b"m:oMathPara" => break,
+            Event::Eof => return Err("unexpected EOF in <m:oMathPara>".to_string()),
+            _ => {}
+        }
+    }
+    Ok(raw)
+}
+
+fn parse_num_pr(reader: &mut QuickXmlReader<&[u8]>) -> Result<Option<u32>, String> {
+    let mut num_id = None;
+    loop {
+        match reader.read_event().map_err(|e| format!("XML error: {e}"))? {
+            Event::Empty(e) if e.name().as_ref() == b"w:numId" => {
+                num_id = attr_value(&e, b"w:val").and_then(|v| v.parse().ok());
+            }
+            Event::Start(e) => {
+                let name = e.name().as_ref().to_vec();
+                skip_element(reader, &name)?;
+            }
+            Event::End(e) if e.name().as_ref() == b"w:numPr" => break,
+            Event::Eof => return Err("unexpected EOF in <w:numPr>".to_string()),
+            _ => {}
+        }
+    }
+    Ok(num_id)
+}
+
+fn parse_p_pr(reader: &mut QuickXmlReader<&[u8]>) -> Result<Option<u32>, String> {
+    let mut num_id = None;
+    loop {
+        match reader.read_event().map_err(|e| format!("XML error: {e}"))? {
+            Event::Start(e) if e.name().as_ref() == b"w:numPr" => {
+                num_id = parse_num_pr(reader)?;
+            }
+            Event::Start(e) => {
+                let name = e.name().as_ref().to_vec();
+                skip_element(reader, &name)?;
+            }
+            Event::End(e) if e.name().as_ref() == b"w:pPr" => break,
+            Event::Eof => return Err("unexpected EOF in <w:pPr>".to_string()),
+            _ => {}
+        }
+    }
+    Ok(num_id)
+}
+
+fn parse_paragraph(
+    reader: &mut QuickXmlReader<&[u8]>,
+    xml: &str,
+    rels: &HashMap<String, String>,
+) -> Result<DocBlock, String> {
+    let mut inlines = Vec::new();
+    let mut num_id: Option<u32> = None;
+    let mut math_block: Option<String> = None;
+
+    loop {
+        match reader.read_event().map_err(|e| format!("XML error: {e}"))? {
+            Event::Start(e) if e.name().as_ref() == b"w:pPr" => {
+                num_id = parse_p_pr(reader)?;
+            }
+            Event::Empty(e) if e.name().as_ref() == b"w:pPr" => {}
+            Event::Start(e) if e.name().as_ref() == b"w:r" => {
+                inlines.extend(parse_run(reader, xml)?);
+            }
+            Event::Start(e) if e.name().as_ref() == b"w:hyperlink" => {
+                let rid = attr_value(&e, b"r:id");
+                let href = rid.and_then(|id| rels.get(&id).cloned());
+                inlines.push(parse_hyperlink(reader, xml, href)?);
+            }
+            Event::Start(e) if e.name().as_ref() == b"m:oMathPara" => {
+                math_block = Some(parse_omath_para(reader, xml)?);
+            }
+            Event::Start(e) => {
+                let name = e.name().as_ref().to_vec();
+                skip_element(reader, &name)?;
+            }
+            Event::End(e) if e.name().as_ref() == b"w:p" => break,
+            Event::Eof => return Err("unexpected EOF in <w:p>".to_string()),
+            _ => {}
+        }
+    }
+
+    if let Some(raw) = math_block {
+        if inlines.is_empty() {
+            return Ok(DocBlock::MathBlock(raw));
+        }
+        // A block equation alongside other runs in the same paragraph (unusual, but splice it in
+        // rather than dropping it).
+        inlines.push(DocInline::Math(raw));
+    }
+
+    if num_id.is_some() {
+        Ok(DocBlock::ListItem { num_id, inlines })
+    } else {
+        Ok(DocBlock::Paragraph(inlines))
+    }
+}
+
+fn parse_document_body(xml: &str, rels: &HashMap<String, String>) -> Result<Vec<DocBlock>, String> {
+    let mut reader = QuickXmlReader::from_str(xml);
+    let mut blocks = Vec::new();
+    loop {
+        match reader.read_event().map_err(|e| format!("XML error: {e}"))? {
+            Event::Start(e) if e.name().as_ref() == b"w:p" => {
+                blocks.push(parse_paragraph(&mut reader, xml, rels)?);
+            }
+            Event::Start(e) => {
+                let name = e.name().as_ref().to_vec();
+                skip_element(&mut reader, &name)?;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(blocks)
+}
+
+/// Maps `word/_rels/document.xml.rels` relationship `Id`s to their `Target`, the way
+/// `<w:hyperlink r:id>` needs resolved back to an `href`. Missing part (no hyperlinks were ever
+/// registered) yields an empty map.
+fn parse_hyperlink_rels(xml: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    if xml.trim().is_empty() {
+        return map;
+    }
+    let mut reader = QuickXmlReader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.name().as_ref() == b"Relationship" => {
+                if let (Some(id), Some(target)) = (attr_value(&e, b"Id"), attr_value(&e, b"Target")) {
+                    map.insert(id, target);
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    map
+}
+
+/// Reads `word/numbering.xml` (when present) and returns the set of `w:numId`s whose abstract
+/// numbering definition's first level uses a `decimal` format, i.e. an `<ol>` rather than the
+/// default `<ul>` every other `numId` renders as.
+fn parse_numbering_ordered_ids(xml: &str) -> HashSet<u32> {
+    let mut ordered = HashSet::new();
+    if xml.trim().is_empty() {
+        return ordered;
+    }
+
+    let mut abstract_ordered: HashMap<u32, bool> = HashMap::new();
+    let mut num_to_abstract: HashMap<u32, u32> = HashMap::new();
+    let mut current_abstract: Option<u32> = None;
+    let mut current_num: Option<u32> = None;
+    let mut seen_fmt_for_current_abstract = false;
+
+    let mut reader = QuickXmlReader::from_str(xml);
+    loop {
+        let event = match reader.read_event() {
+            Ok(e) => e,
+            Err(_) => break,
+        };
+        match event {
+            Event::Start(e) if e.name().as_ref() == b"w:abstractNum" => {
+                current_abstract = attr_value(&e, b"w:abstractNumId").and_then(|v| v.parse().ok());
+                seen_fmt_for_current_abstract = false;
+            }
+            Event::End(e) if e.name().as_ref() == b"w:abstractNum" => current_abstract = None,
+            Event::Empty(e) | Event::Start(e) if e.name().as_ref() == b"w:numFmt" => {
+                if let Some(id) = current_abstract {
+                    if !seen_fmt_for_current_abstract {
+                        abstract_ordered.insert(id, attr_value(&e, b"w:val").as_deref() == Some("decimal"));
+                        seen_fmt_for_current_abstract = true;
+                    }
+                }
+            }
+            Event::Start(e) if e.name().as_ref() == b"w:num" => {
+                current_num = attr_value(&e, b"w:numId").and_then(|v| v.parse().ok());
+            }
+            Event::End(e) if e.name().as_ref() == b"w:num" => current_num = None,
+            Event::Empty(e) | Event::Start(e) if e.name().as_ref() == b"w:abstractNumId" => {
+                if let (Some(num_id), Some(abs_id)) =
+                    (current_num, attr_value(&e, b"w:val").and_then(|v| v.parse().ok()))
+                {
+                    num_to_abstract.insert(num_id, abs_id);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    for (num_id, abs_id) in &num_to_abstract {
+        if abstract_ordered.get(abs_id).copied().unwrap_or(false) {
+            ordered.insert(*num_id);
+        }
+    }
+    ordered
+}
+
+fn escape_html_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn render_inlines(inlines: &[DocInline], out: &mut String) {
+    for inline in inlines {
+        match inline {
+            DocInline::Text { text, bold, italic } => {
+                let escaped = escape_html_text(text);
+                match (bold, italic) {
+                    (true, true) => out.push_str(&format!("<strong><em>{escaped}</em></strong>")),
+                    (true, false) => out.push_str(&format!("<strong>{escaped}</strong>")),
+                    (false, true) => out.push_str(&format!("<em>{escaped}</em>")),
+                    (false, false) => out.push_str(&escaped),
+                }
+            }
+            DocInline::Break => out.push_str("<br>"),
+            DocInline::Math(raw) => {
+                out.push_str("<!--[if gte msEquation 12]>");
+                out.push_str(raw);
+                out.push_str("<![endif]-->");
+            }
+            DocInline::Link { href, inlines } => {
+                match href {
+                    Some(href) => out.push_str(&format!("<a href=\"{}\">", escape_xml_attr(href))),
+                    None => out.push_str("<a>"),
+                }
+                render_inlines(inlines, out);
+                out.push_str("</a>");
+            }
+        }
+    }
+}
+
+/// Flushes a run of consecutive `<w:numPr>` paragraphs collected in `pending` into a single
+/// `<ul>`/`<ol>` wrapping one `<li>` per item, using the first item's `numId` to decide which
+/// (`ordered_num_ids` empty - no `numbering.xml` in the package, or an unrecognized `numId` -
+/// both fall back to `<ul>`).
+fn flush_list_items(out: &mut String, pending: &mut Vec<Vec<DocInline>>, ordered: bool) {
+    if pending.is_empty() {
+        return;
+    }
+    let tag = if ordered { "ol" } else { "ul" };
+    out.push_str(&format!("<{tag}>"));
+    for inlines in pending.drain(..) {
+        out.push_str("<li>");
+        render_inlines(&inlines, out);
+        out.push_str("</li>");
+    }
+    out.push_str(&format!("</{tag}>"));
+}
+
+fn render_blocks(blocks: Vec<DocBlock>, ordered_num_ids: &HashSet<u32>) -> String {
+    let mut out = String::new();
+    let mut pending: Vec<Vec<DocInline>> = Vec::new();
+    let mut pending_ordered = false;
+
+    for block in blocks {
+        match block {
+            DocBlock::ListItem { num_id, inlines } => {
+                let ordered = num_id.map(|id| ordered_num_ids.contains(&id)).unwrap_or(false);
+                if !pending.is_empty() && ordered != pending_ordered {
+                    flush_list_items(&mut out, &mut pending, pending_ordered);
+                }
+                pending_ordered = ordered;
+                pending.push(inlines);
+            }
+            DocBlock::Paragraph(inlines) => {
+                flush_list_items(&mut out, &mut pending, pending_ordered);
+                out.push_str("<p>");
+                render_inlines(&inlines, &mut out);
+                out.push_str("</p>");
+            }
+            DocBlock::MathBlock(raw) => {
+                flush_list_items(&mut out, &mut pending, pending_ordered);
+                out.push_str("<!--[if gte msEquation 12]>");
+                out.push_str(&raw);
+                out.push_str("<![endif]-->");
+            }
+        }
+    }
+    flush_list_items(&mut out, &mut pending, pending_ordered);
+    out
+}
+
+/// Opens a `.docx` package and reconstructs the HTML `html_with_omml_to_docx` would have produced
+/// it from - `<w:p>`/`<w:r>` structure, bold/italic runs, hyperlinks resolved through the rels
+/// part, `<w:numPr>` lists, and `<m:oMath>` equations re-wrapped in their conditional-comment
+/// envelope. Tables and images aren't reconstructed (the forward direction never reads them back
+/// either); a document with no `word/document.xml` at all is the only hard error.
+pub fn docx_to_html(bytes: &[u8]) -> Result<String, String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("zip error: {e}"))?;
+
+    let document_xml = read_zip_entry(&mut archive, "word/document.xml")?
+        .ok_or_else(|| "missing word/document.xml in docx package".to_string())?;
+    let rels_xml = read_zip_entry(&mut archive, "word/_rels/document.xml.rels")?.unwrap_or_default();
+    let numbering_xml = read_zip_entry(&mut archive, "word/numbering.xml")?.unwrap_or_default();
+
+    let rel_targets = parse_hyperlink_rels(&rels_xml);
+    let ordered_num_ids = parse_numbering_ordered_ids(&numbering_xml);
+
+    let blocks = parse_document_body(&document_xml, &rel_targets)?;
+    Ok(render_blocks(blocks, &ordered_num_ids))
+}
@@ -0,0 +1,132 @@
+// Inline syntax highlighting for language-tagged fenced code blocks pasted into Office documents.
+// Word discards `class` attributes (and has no stylesheet to resolve `language-xxx` against
+// anyway), so rather than emitting CSS classes we resolve each token's color/weight in Rust with
+// syntect and write it back as an inline `style="..."` `<span>` - the same "materialize it inline"
+// approach `office::html_to_office_html` already takes for fonts/borders/etc.
+
+use crate::entities::decode_entities;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Style, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+fn esc_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn style_attr(style: Style) -> String {
+    let fg = style.foreground;
+    let mut decl = format!("color:#{:02x}{:02x}{:02x};", fg.r, fg.g, fg.b);
+    if style.font_style.contains(FontStyle::BOLD) {
+        decl.push_str(" font-weight:bold;");
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        decl.push_str(" font-style:italic;");
+    }
+    decl
+}
+
+/// `SyntaxSet::find_syntax_by_token` wants the bare language name (`rust`), not the
+/// `language-rust` form fenced-code-block classes use.
+fn syntax_for_lang<'a>(set: &'a SyntaxSet, lang: &str) -> Option<&'a SyntaxReference> {
+    let lang = lang.strip_prefix("language-").unwrap_or(lang);
+    set.find_syntax_by_token(lang)
+}
+
+/// Highlights `code` as `lang`, returning each token wrapped in an inline-styled `<span>`; `None`
+/// if syntect doesn't recognize `lang` or errors partway through, so the caller can fall back to
+/// the plain-text rendering it already had.
+pub fn highlight_code(code: &str, lang: &str) -> Option<String> {
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let syntax = syntax_for_lang(&syntax_set, lang)?;
+    let theme_set = ThemeSet::load_defaults();
+    let theme = &theme_set.themes["InspiredGitHub"];
+    let mut highlighter = HighlightLines::new(syntax, theme);
+
+    let mut out = String::with_capacity(code.len() * 2);
+    for line in code.lines() {
+        let ranges = highlighter.highlight_line(line, &syntax_set).ok()?;
+        for (style, text) in ranges {
+            if text.is_empty() {
+                continue;
+            }
+            out.push_str("<span style=\"");
+            out.push_str(&style_attr(style));
+            out.push_str("\">");
+            out.push_str(&esc_text(text));
+            out.push_str("</span>");
+        }
+        out.push('\n');
+    }
+    out.pop(); // the per-line loop above always adds a trailing newline; `<pre>` supplies its own
+    Some(out)
+}
+
+fn find_language_class(attrs: &str) -> Option<&str> {
+    let low = attrs.to_ascii_lowercase();
+    let idx = low.find("class=")?;
+    let after = &attrs[idx + "class=".len()..];
+    let (quote, rest) = if let Some(r) = after.strip_prefix('"') {
+        ('"', r)
+    } else {
+        let r = after.strip_prefix('\'')?;
+        ('\'', r)
+    };
+    let end = rest.find(quote)?;
+    rest[..end]
+        .split_whitespace()
+        .find(|c| c.starts_with("language-"))
+}
+
+/// Scans `html` for `<code class="language-XXX">...</code>` bodies (the form fenced code blocks
+/// from GPT markdown render as) and replaces the body of each one whose language syntect
+/// recognizes with highlighted markup; everything else - unrecognized/missing language, no
+/// `<code>` at all - is left byte-for-byte untouched so `office::html_to_office_html` can still
+/// give it its usual plain monospace styling.
+pub fn highlight_tagged_code_blocks(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(open_rel) = rest.find("<code") {
+        let after_tag_name = &rest[open_rel + "<code".len()..];
+        let starts_tag = matches!(after_tag_name.as_bytes().first(), Some(b' ') | Some(b'>'));
+        if !starts_tag {
+            out.push_str(&rest[..open_rel + "<code".len()]);
+            rest = after_tag_name;
+            continue;
+        }
+        let Some(gt_rel) = after_tag_name.find('>') else {
+            out.push_str(rest);
+            rest = "";
+            break;
+        };
+        let attrs = &after_tag_name[..gt_rel];
+        let open_tag_end = open_rel + "<code".len() + gt_rel + 1;
+        out.push_str(&rest[..open_tag_end]);
+
+        let body_and_rest = &rest[open_tag_end..];
+        let Some(close_rel) = body_and_rest.find("</code>") else {
+            out.push_str(body_and_rest);
+            rest = "";
+            break;
+        };
+        let body = &body_and_rest[..close_rel];
+        let highlighted =
+            find_language_class(attrs).and_then(|lang| highlight_code(&decode_entities(body), lang));
+        match highlighted {
+            Some(html) => out.push_str(&html),
+            None => out.push_str(body),
+        }
+        out.push_str("</code>");
+        rest = &body_and_rest[close_rel + "</code>".len()..];
+    }
+    out.push_str(rest);
+    out
+}
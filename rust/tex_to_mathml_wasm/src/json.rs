@@ -1,42 +1,25 @@
-use crate::pipeline::{PreparedOffice, TexJob};
+use crate::pipeline::{self, PreparedOffice, TexJob};
 
-fn json_escape(s: &str) -> String {
-    let mut out = String::with_capacity(s.len() + 8);
-    for c in s.chars() {
-        match c {
-            '\\' => out.push_str("\\\\"),
-            '"' => out.push_str("\\\""),
-            '\n' => out.push_str("\\n"),
-            '\r' => out.push_str("\\r"),
-            '\t' => out.push_str("\\t"),
-            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
-            _ => out.push(c),
-        }
-    }
-    out
-}
-
-fn job_to_json(j: &TexJob) -> String {
-    format!(
-        "{{\"id\":{},\"latex\":\"{}\",\"display\":{}}}",
-        j.id,
-        json_escape(&j.latex),
-        if j.display { "true" } else { "false" }
-    )
+/// Serializes a `PreparedOffice` via its derived `Serialize` impl rather than hand-escaping each
+/// field - see `pipeline::TexJob`/`PreparedOffice` for the schema. Falls back to an empty prepared
+/// document on the (practically unreachable, since `String`/`Vec`/`usize`/`bool` always succeed)
+/// chance serialization fails, matching the "never panic across the FFI boundary" rule the rest of
+/// this crate follows.
+pub fn prepared_office_to_json(p: &PreparedOffice) -> String {
+    serde_json::to_string(p).unwrap_or_else(|_| "{\"html\":\"\",\"jobs\":[]}".to_string())
 }
 
-pub fn prepared_office_to_json(p: &PreparedOffice) -> String {
-    let mut out = String::new();
-    out.push('{');
-    out.push_str("\"html\":\"");
-    out.push_str(&json_escape(&p.html));
-    out.push_str("\",\"jobs\":[");
-    for (i, j) in p.jobs.iter().enumerate() {
-        if i > 0 {
-            out.push(',');
-        }
-        out.push_str(&job_to_json(j));
-    }
-    out.push_str("]}");
-    out
+/// Parses `jobs_json` (a `Vec<TexJob>`, the same shape `PreparedOffice::jobs` serializes to) and
+/// `results_json` (a `{"results":[{"id":N,"mathml":"...","display":bool,"error":"..."}]}` blob)
+/// before delegating to `pipeline::office_apply_mathml_results` - the entry point `lib.rs`'s
+/// `office_apply_mathml_json` FFI export wraps directly.
+pub fn apply_mathml_results_from_json(
+    html: &str,
+    jobs_json: &str,
+    results_json: &str,
+) -> Result<String, String> {
+    let jobs: Vec<TexJob> =
+        serde_json::from_str(jobs_json).map_err(|e| format!("invalid jobs json: {e}"))?;
+    let results = serde_json::from_str(results_json).map_err(|e| format!("invalid results json: {e}"))?;
+    pipeline::office_apply_mathml_results(html, &jobs, &results)
 }
@@ -0,0 +1,608 @@
+// nom-based tokenizer for LaTeX math delimiters inside plain text, replacing the manual byte
+// scanning that used to live directly in `pipeline::emit_text_with_tex_placeholders`. Unlike that
+// scanner, a math delimiter that's opened but never closed surfaces as a `ParsingError` carrying
+// a byte offset, instead of being silently re-emitted as literal text.
+//
+// Which delimiters are recognized, and how a `$...$` body is told apart from a currency amount,
+// is driven entirely by `PrepareOptions` rather than hard-coded, so different callers (different
+// model outputs wrap currency/math differently) can tune the grammar without forking this file.
+
+use crate::sanitize::ImagePolicy;
+use nom::bytes::complete::tag;
+use nom::error::{ErrorKind, ParseError};
+use nom::IResult;
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsingError<'a> {
+    pub input: &'a str,
+    pub offset: usize,
+    pub reason: Cow<'static, str>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MathToken<'a> {
+    Literal(&'a str),
+    Math {
+        open: &'a str,
+        inner: &'a str,
+        close: &'a str,
+        display: bool,
+    },
+}
+
+/// How a bare (un-escaped) `$...$` body is told apart from a currency amount like `$100`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CurrencyMode {
+    /// Guess from content: digits/commas/periods (or nothing) look like currency, anything else
+    /// is math. The long-standing default.
+    Heuristic,
+    /// Turn the guard off entirely - every un-escaped `$...$` is math, even `$100`. Callers must
+    /// write `\$100` to keep a literal dollar amount out of the math pipeline.
+    Disabled,
+    /// Today this behaves exactly like `Disabled` (an un-escaped `$` always opens math, so a
+    /// literal amount already has to be escaped) - kept as a separate variant so callers can say
+    /// "we require escaping" as their actual intent rather than "we turned the guard off", leaving
+    /// room for a stricter escape-only implementation later without another breaking enum change.
+    RequireEscape,
+}
+
+impl CurrencyMode {
+    fn treats_as_currency(&self, inner: &str) -> bool {
+        match self {
+            CurrencyMode::Heuristic => is_currency_like(inner),
+            CurrencyMode::Disabled | CurrencyMode::RequireEscape => false,
+        }
+    }
+}
+
+/// Configures how `html_to_office_prepared`/`markdown_to_office_prepared` turn pasted content into
+/// office-ready HTML, in the spirit of comrak's `Options` - different model outputs wrap
+/// currency/math in incompatible conventions, and different hosts want pasted images handled
+/// differently, so nothing here is hard-coded. `Default` reproduces this crate's long-standing
+/// behavior: all four built-in delimiter forms enabled, currency guessed heuristically, no house
+/// delimiters, `<img src>` kept as-is.
+#[derive(Clone, Debug)]
+pub struct PrepareOptions {
+    pub inline_dollar: bool,
+    pub display_dollar: bool,
+    pub paren_delims: bool,
+    pub bracket_delims: bool,
+    pub currency_guard: CurrencyMode,
+    /// Extra `(open, close, display)` delimiter pairs, tried after the built-ins, for teams that
+    /// want to register house delimiters like `\begin{math}...\end{math}`.
+    pub extra_delims: Vec<(String, String, bool)>,
+    /// Whether a bare `\begin{NAME}...\end{NAME}` is recognized as display math when `NAME` is one
+    /// of `MATH_ENVIRONMENTS` (`equation`, `align`, `cases`, `pmatrix`, ...), the form GPT emits
+    /// most often for multi-line math instead of `$$...$$`/`\[...\]`.
+    pub environments: bool,
+    /// How `sanitize_for_office_with` treats a pasted `<img src>` - see `ImagePolicy`.
+    pub image_policy: ImagePolicy,
+}
+
+impl Default for PrepareOptions {
+    fn default() -> Self {
+        Self {
+            inline_dollar: true,
+            display_dollar: true,
+            paren_delims: true,
+            bracket_delims: true,
+            image_policy: ImagePolicy::default(),
+            currency_guard: CurrencyMode::Heuristic,
+            extra_delims: Vec::new(),
+            environments: true,
+        }
+    }
+}
+
+/// LaTeX math environments recognized by the `environments` option. Starred variants (`align*`,
+/// ...) suppress equation numbering but are otherwise the same environment, so both forms are
+/// listed explicitly rather than derived.
+const MATH_ENVIRONMENTS: &[&str] = &[
+    "equation", "equation*",
+    "align", "align*",
+    "alignat", "alignat*",
+    "flalign", "flalign*",
+    "gather", "gather*",
+    "multline", "multline*",
+    "cases", "split", "array", "eqnarray", "eqnarray*",
+    "matrix", "pmatrix", "bmatrix", "vmatrix", "Vmatrix", "smallmatrix",
+];
+
+pub(crate) fn is_math_environment(name: &str) -> bool {
+    MATH_ENVIRONMENTS.contains(&name)
+}
+
+/// Byte offset, within `after_open` (the text right after `\begin{NAME}`), of the position right
+/// after the matching `\end{NAME}`. Nested `\begin{NAME}`/`\end{NAME}` pairs of the *same* name are
+/// counted so e.g. a `cases` environment can itself contain another `cases` without the inner
+/// `\end{cases}` closing the outer one; environments of a *different* name (a `cases` nested in an
+/// `align`) aren't tracked, since only a same-named `\end` could ever be mistaken for this one's.
+/// `None` means unterminated.
+pub(crate) fn find_environment_end(after_open: &str, name: &str) -> Option<usize> {
+    let begin_tag = format!("\\begin{{{name}}}");
+    let end_tag = format!("\\end{{{name}}}");
+    let mut depth: i32 = 1;
+    let mut i = 0;
+
+    while let Some(rel) = after_open[i..].find('\\') {
+        let pos = i + rel;
+        if after_open[pos..].starts_with(&begin_tag) {
+            depth += 1;
+            i = pos + begin_tag.len();
+        } else if after_open[pos..].starts_with(&end_tag) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(pos + end_tag.len());
+            }
+            i = pos + end_tag.len();
+        } else {
+            i = pos + 1;
+        }
+    }
+    None
+}
+
+/// Recognizes a bare `\begin{NAME}...\end{NAME}` for a known `NAME` (see `MATH_ENVIRONMENTS`),
+/// capturing the *whole* match - including the `\begin`/`\end` markers themselves - as the math
+/// body, since `tex::tex_to_mathml` needs the environment name to lay out alignment/cases rows.
+fn environment_math(input: &str) -> IResult<&str, MathToken, ScanError> {
+    let (rest, _) = tag("\\begin{")(input)?;
+    let Some(name_end) = rest.find('}') else {
+        return Err(nom::Err::Error(ScanError::from_error_kind(input, ErrorKind::Tag)));
+    };
+    let name = &rest[..name_end];
+    if !is_math_environment(name) {
+        return Err(nom::Err::Error(ScanError::from_error_kind(input, ErrorKind::Verify)));
+    }
+    let after_open = &rest[name_end + 1..];
+    match find_environment_end(after_open, name) {
+        Some(end_rel) => {
+            let whole_len = (input.len() - after_open.len()) + end_rel;
+            let whole = &input[..whole_len];
+            let after = &input[whole_len..];
+            Ok((after, MathToken::Math { open: "", inner: whole, close: "", display: true }))
+        }
+        None => Err(nom::Err::Failure(ScanError::Unterminated { open: "\\begin{", close: "\\end{" })),
+    }
+}
+
+/// nom error type for this grammar: a plain recoverable `Error` lets the caller try the next
+/// alternative, while `Unterminated` is raised as a `nom::Err::Failure` so it propagates straight
+/// out instead of falling back to a later branch.
+#[derive(Debug, PartialEq)]
+enum ScanError<'a> {
+    Recoverable(nom::error::Error<&'a str>),
+    Unterminated { open: &'a str, close: &'a str },
+}
+
+impl<'a> ParseError<&'a str> for ScanError<'a> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        ScanError::Recoverable(nom::error::Error::new(input, kind))
+    }
+
+    fn append(_: &'a str, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+/// True for a `$...$` body that looks like a plain currency amount ("$100", "$1,234.56") rather
+/// than math. An empty (or whitespace-only) body also counts, matching a bare "$ $" typo.
+fn is_currency_like(inner: &str) -> bool {
+    let t = inner.trim();
+    t.is_empty() || t.chars().all(|c| c.is_ascii_digit() || c == ',' || c == '.')
+}
+
+/// Byte offset, within `input`, of a `$` that closes a dollar span opened at its start: skips
+/// escaped `\X` pairs (so a literal `\$` inside the span can never close it) and keeps `{...}`
+/// balanced (so an argument-brace body like `f(\{x\})` can't close the span on a `$` nested
+/// inside braces). `None` means unclosed.
+fn find_balanced_dollar_close(input: &str) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let mut i = 0;
+    let mut depth: i32 = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if i + 1 < bytes.len() => {
+                i += 2;
+                continue;
+            }
+            b'{' => depth += 1,
+            b'}' => depth = (depth - 1).max(0),
+            b'$' if depth == 0 => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Generic `open ... close` span parser, parameterized over the delimiter pair and whether it's
+/// display math, so the built-in `$$...$$`/`\[...\]`/`\(...\)` forms and any `extra_delims` house
+/// delimiter can all share one implementation.
+fn delim_math<'a>(
+    open: &'a str,
+    close: &'a str,
+    display: bool,
+) -> impl Fn(&'a str) -> IResult<&'a str, MathToken<'a>, ScanError<'a>> {
+    move |input: &'a str| {
+        let (rest, _) = tag(open)(input)?;
+        match rest.find(close) {
+            Some(end) => {
+                let inner = &rest[..end];
+                let after = &rest[end + close.len()..];
+                Ok((after, MathToken::Math { open, inner, close, display }))
+            }
+            None => Err(nom::Err::Failure(ScanError::Unterminated { open, close })),
+        }
+    }
+}
+
+/// `\$` never opens math: consume it as a two-byte literal run so later alternatives don't see
+/// it as a dollar at all.
+fn escaped_dollar(input: &str) -> IResult<&str, MathToken, ScanError> {
+    let (rest, matched) = tag("\\$")(input)?;
+    Ok((rest, MathToken::Literal(matched)))
+}
+
+/// `\\` (two literal backslashes) is consumed as one escaped unit up front, so it can never be
+/// misread as the start of a `\(`/`\[`/`\begin{` delimiter - all of which open on a single
+/// unescaped `\` - the way a lone `\` immediately followed by one of those would.
+fn escaped_backslash(input: &str) -> IResult<&str, MathToken, ScanError> {
+    let (rest, matched) = tag("\\\\")(input)?;
+    Ok((rest, MathToken::Literal(matched)))
+}
+
+fn inline_dollar_math<'a>(
+    input: &'a str,
+    mode: &CurrencyMode,
+) -> IResult<&'a str, MathToken<'a>, ScanError<'a>> {
+    let (rest, _) = tag("$")(input)?;
+    if rest.starts_with('$') {
+        // "$$" is display math; let that alternative handle it instead.
+        return Err(nom::Err::Error(ScanError::from_error_kind(input, ErrorKind::Tag)));
+    }
+    match find_balanced_dollar_close(rest) {
+        Some(end) => {
+            let inner = &rest[..end];
+            if mode.treats_as_currency(inner) {
+                Err(nom::Err::Error(ScanError::from_error_kind(input, ErrorKind::Verify)))
+            } else {
+                let after = &rest[end + 1..];
+                Ok((after, MathToken::Math { open: "$", inner, close: "$", display: false }))
+            }
+        }
+        None => {
+            // No closing `$` at all: if what immediately follows looks like a plain currency
+            // amount ("$100 today", no second `$` in sight), this was never meant to open math -
+            // treat the `$` as a literal character instead of reporting an unterminated delimiter.
+            // Anything else opened but never closed still needs to surface as a `ParsingError`.
+            let amount_end = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == ',' || c == '.'))
+                .unwrap_or(rest.len());
+            if mode.treats_as_currency(&rest[..amount_end]) {
+                Err(nom::Err::Error(ScanError::from_error_kind(input, ErrorKind::Verify)))
+            } else {
+                Err(nom::Err::Failure(ScanError::Unterminated { open: "$", close: "$" }))
+            }
+        }
+    }
+}
+
+fn single_char(input: &str) -> IResult<&str, MathToken, ScanError> {
+    match input.chars().next() {
+        Some(ch) => {
+            let len = ch.len_utf8();
+            Ok((&input[len..], MathToken::Literal(&input[..len])))
+        }
+        None => Err(nom::Err::Error(ScanError::from_error_kind(input, ErrorKind::Eof))),
+    }
+}
+
+fn offset_of(input: &str, slice: &str) -> usize {
+    slice.as_ptr() as usize - input.as_ptr() as usize
+}
+
+/// Merges adjacent `Literal` tokens (emitted one at a time by `single_char`/`escaped_dollar`)
+/// back into single slices of `input`, so callers don't pay per-character overhead downstream.
+fn merge_literals<'a>(input: &'a str, tokens: Vec<MathToken<'a>>) -> Vec<MathToken<'a>> {
+    let mut merged: Vec<MathToken<'a>> = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        if let MathToken::Literal(s) = token {
+            if let Some(MathToken::Literal(prev)) = merged.last_mut() {
+                if offset_of(input, prev) + prev.len() == offset_of(input, s) {
+                    let start = offset_of(input, prev);
+                    *prev = &input[start..start + prev.len() + s.len()];
+                    continue;
+                }
+            }
+            merged.push(MathToken::Literal(s));
+        } else {
+            merged.push(token);
+        }
+    }
+    merged
+}
+
+/// Tries each delimiter form enabled by `options`, in a fixed precedence order: escapes first (so
+/// neither `\$` nor `\\` is ever mistaken for the start of a delimiter), then display before
+/// inline (so `$$` isn't mistaken for two empty `$...$` spans), built-ins before `extra_delims`
+/// (the least likely to collide). Falls through to a single literal character when nothing else
+/// matches.
+fn try_next_token<'a>(
+    input: &'a str,
+    options: &'a PrepareOptions,
+) -> IResult<&'a str, MathToken<'a>, ScanError<'a>> {
+    match escaped_backslash(input) {
+        Err(nom::Err::Error(_)) => {}
+        other => return other,
+    }
+    if options.inline_dollar || options.display_dollar {
+        match escaped_dollar(input) {
+            Err(nom::Err::Error(_)) => {}
+            other => return other,
+        }
+    }
+    if options.display_dollar {
+        match delim_math("$$", "$$", true)(input) {
+            Err(nom::Err::Error(_)) => {}
+            other => return other,
+        }
+    }
+    if options.bracket_delims {
+        match delim_math("\\[", "\\]", true)(input) {
+            Err(nom::Err::Error(_)) => {}
+            other => return other,
+        }
+    }
+    if options.paren_delims {
+        match delim_math("\\(", "\\)", false)(input) {
+            Err(nom::Err::Error(_)) => {}
+            other => return other,
+        }
+    }
+    if options.inline_dollar {
+        match inline_dollar_math(input, &options.currency_guard) {
+            Err(nom::Err::Error(_)) => {}
+            other => return other,
+        }
+    }
+    if options.environments {
+        match environment_math(input) {
+            Err(nom::Err::Error(_)) => {}
+            other => return other,
+        }
+    }
+    for (open, close, display) in &options.extra_delims {
+        match delim_math(open, close, *display)(input) {
+            Err(nom::Err::Error(_)) => {}
+            other => return other,
+        }
+    }
+    single_char(input)
+}
+
+/// Tokenizes `input` into a run of literal-text and math spans per `options`. Returns a
+/// `ParsingError` with the byte offset of the failing open delimiter if one is opened but never
+/// closed.
+pub fn scan_math_tokens<'a>(
+    input: &'a str,
+    options: &'a PrepareOptions,
+) -> Result<Vec<MathToken<'a>>, ParsingError<'a>> {
+    let mut tokens = Vec::new();
+    let mut remaining = input;
+
+    while !remaining.is_empty() {
+        match try_next_token(remaining, options) {
+            Ok((rest, token)) => {
+                tokens.push(token);
+                remaining = rest;
+            }
+            Err(nom::Err::Failure(ScanError::Unterminated { open, close })) => {
+                return Err(ParsingError {
+                    input,
+                    offset: offset_of(input, remaining),
+                    reason: Cow::Owned(format!(
+                        "unterminated `{open}` math: no matching `{close}` found"
+                    )),
+                });
+            }
+            Err(_) => unreachable!("single_char always matches on a non-empty input"),
+        }
+    }
+
+    Ok(merge_literals(input, tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `'static` default, so `math_tokens`'s borrow of it can outlive the call - `scan_math_tokens`
+    /// ties `options`'s lifetime to the returned tokens' (a token can borrow from `extra_delims`),
+    /// which an inline `&PrepareOptions::default()` temporary can't satisfy.
+    fn default_options() -> &'static PrepareOptions {
+        static DEFAULT: std::sync::OnceLock<PrepareOptions> = std::sync::OnceLock::new();
+        DEFAULT.get_or_init(PrepareOptions::default)
+    }
+
+    fn math_tokens(input: &str) -> Vec<MathToken<'_>> {
+        math_tokens_with(input, default_options())
+    }
+
+    fn math_tokens_with<'a>(input: &'a str, options: &'a PrepareOptions) -> Vec<MathToken<'a>> {
+        scan_math_tokens(input, options)
+            .unwrap()
+            .into_iter()
+            .filter(|t| matches!(t, MathToken::Math { .. }))
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_display_dollar_math() {
+        let tokens = math_tokens("before $$x^2$$ after");
+        assert_eq!(
+            tokens,
+            vec![MathToken::Math { open: "$$", inner: "x^2", close: "$$", display: true }]
+        );
+    }
+
+    #[test]
+    fn tokenizes_display_bracket_math() {
+        let tokens = math_tokens("\\[x = y\\]");
+        assert_eq!(
+            tokens,
+            vec![MathToken::Math { open: "\\[", inner: "x = y", close: "\\]", display: true }]
+        );
+    }
+
+    #[test]
+    fn tokenizes_inline_paren_math() {
+        let tokens = math_tokens("\\(a+b\\)");
+        assert_eq!(
+            tokens,
+            vec![MathToken::Math { open: "\\(", inner: "a+b", close: "\\)", display: false }]
+        );
+    }
+
+    #[test]
+    fn tokenizes_inline_dollar_math() {
+        let tokens = math_tokens("cost is $x^2 + y^2$ today");
+        assert_eq!(
+            tokens,
+            vec![MathToken::Math { open: "$", inner: "x^2 + y^2", close: "$", display: false }]
+        );
+    }
+
+    #[test]
+    fn treats_currency_dollar_as_literal() {
+        assert!(math_tokens("Price is $100 today").is_empty());
+    }
+
+    #[test]
+    fn escaped_dollar_never_opens_math() {
+        let tokens = scan_math_tokens("\\$5 is not math, but $x$ is", default_options()).unwrap();
+        let math: Vec<_> = tokens.iter().filter(|t| matches!(t, MathToken::Math { .. })).collect();
+        assert_eq!(math.len(), 1);
+    }
+
+    #[test]
+    fn keeps_braces_balanced_when_finding_dollar_close() {
+        let tokens = math_tokens("$f(\\{x\\})$");
+        assert_eq!(
+            tokens,
+            vec![MathToken::Math { open: "$", inner: "f(\\{x\\})", close: "$", display: false }]
+        );
+    }
+
+    #[test]
+    fn unterminated_display_math_reports_offset() {
+        let err = scan_math_tokens("before $$x^2 after", default_options()).unwrap_err();
+        assert_eq!(err.offset, "before ".len());
+    }
+
+    #[test]
+    fn unterminated_bracket_math_reports_offset() {
+        let err = scan_math_tokens("\\[x = y", default_options()).unwrap_err();
+        assert_eq!(err.offset, 0);
+    }
+
+    #[test]
+    fn disabling_paren_delims_leaves_them_as_literal_text() {
+        let options = PrepareOptions { paren_delims: false, ..PrepareOptions::default() };
+        assert!(math_tokens_with("\\(a+b\\)", &options).is_empty());
+    }
+
+    #[test]
+    fn currency_mode_disabled_treats_digits_as_math() {
+        let options = PrepareOptions { currency_guard: CurrencyMode::Disabled, ..PrepareOptions::default() };
+        let tokens = math_tokens_with("Price is $100$ today", &options);
+        assert_eq!(tokens, vec![MathToken::Math { open: "$", inner: "100", close: "$", display: false }]);
+    }
+
+    #[test]
+    fn tokenizes_align_environment_as_display_math() {
+        let tokens = math_tokens("before \\begin{align}x &= y \\\\ y &= z\\end{align} after");
+        assert_eq!(
+            tokens,
+            vec![MathToken::Math {
+                open: "",
+                inner: "\\begin{align}x &= y \\\\ y &= z\\end{align}",
+                close: "",
+                display: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn nested_same_name_environment_balances_depth() {
+        let tokens = math_tokens(
+            "\\begin{cases}\\begin{cases}a\\end{cases} & b\\end{cases}",
+        );
+        assert_eq!(tokens.len(), 1);
+        let MathToken::Math { inner, .. } = &tokens[0] else { panic!("expected Math token") };
+        assert_eq!(*inner, "\\begin{cases}\\begin{cases}a\\end{cases} & b\\end{cases}");
+    }
+
+    #[test]
+    fn unknown_environment_name_is_left_as_literal() {
+        assert!(math_tokens("\\begin{itemize}\\item x\\end{itemize}").is_empty());
+    }
+
+    #[test]
+    fn unterminated_environment_reports_offset() {
+        let err = scan_math_tokens("before \\begin{align}x = y", default_options()).unwrap_err();
+        assert_eq!(err.offset, "before ".len());
+    }
+
+    #[test]
+    fn disabling_environments_leaves_them_as_literal_text() {
+        let options = PrepareOptions { environments: false, ..PrepareOptions::default() };
+        assert!(math_tokens_with("\\begin{align}x = y\\end{align}", &options).is_empty());
+    }
+
+    #[test]
+    fn escaped_backslash_never_opens_a_delimiter() {
+        let tokens = math_tokens("\\\\(not math\\\\) but $x$ is");
+        assert_eq!(
+            tokens,
+            vec![MathToken::Math { open: "$", inner: "x", close: "$", display: false }]
+        );
+    }
+
+    /// Table-driven coverage for the scenarios called out when this scanner was rebuilt as a
+    /// combinator parser: nested braces, escaped dollars, currency amounts, and adjacent spans.
+    #[test]
+    fn table_driven_dollar_scenarios() {
+        let cases: &[(&str, &[MathToken])] = &[
+            ("$a$ and $b$", &[
+                MathToken::Math { open: "$", inner: "a", close: "$", display: false },
+                MathToken::Math { open: "$", inner: "b", close: "$", display: false },
+            ]),
+            ("$\\text{cost is \\$5}$", &[
+                MathToken::Math { open: "$", inner: "\\text{cost is \\$5}", close: "$", display: false },
+            ]),
+            ("Price is $100 today", &[]),
+            ("Price is $1,000.00 today", &[]),
+            ("$f(\\{x\\})$", &[
+                MathToken::Math { open: "$", inner: "f(\\{x\\})", close: "$", display: false },
+            ]),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(math_tokens(input), expected.to_vec(), "input: {input:?}");
+        }
+    }
+
+    #[test]
+    fn extra_delims_are_recognized() {
+        let options = PrepareOptions {
+            extra_delims: vec![("\\begin{math}".to_string(), "\\end{math}".to_string(), false)],
+            ..PrepareOptions::default()
+        };
+        let tokens = math_tokens_with("\\begin{math}a+b\\end{math}", &options);
+        assert_eq!(
+            tokens,
+            vec![MathToken::Math { open: "\\begin{math}", inner: "a+b", close: "\\end{math}", display: false }]
+        );
+    }
+}
@@ -1,12 +1,20 @@
+mod ast;
 mod entities;
 mod ffi;
+mod highlight;
 mod json;
+mod latex_scan;
 mod markdown;
+mod mhtml;
+mod math_alphabets;
 mod normalize;
 mod office;
+mod org;
 mod pipeline;
+mod plain_text;
 mod sanitize;
 mod tex;
+mod typst;
 
 use crate::ffi::{read_utf8, set_error, write_out};
 use html5ever::tendril::TendrilSink;
@@ -15,7 +23,7 @@ use markup5ever_rcdom::{NodeData, RcDom};
 
 #[no_mangle]
 pub extern "C" fn api_version() -> u32 {
-    3
+    12
 }
 
 #[no_mangle]
@@ -58,6 +66,23 @@ pub extern "C" fn html_to_office(ptr: u32, len: u32) -> u32 {
     write_out(&out)
 }
 
+#[no_mangle]
+pub extern "C" fn html_to_office_highlighted(ptr: u32, len: u32) -> u32 {
+    let s = match read_utf8(ptr, len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty input");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "input is not valid UTF-8");
+            return 0;
+        }
+    };
+    let out = office::html_to_office_html_highlighted(s);
+    write_out(&out)
+}
+
 #[no_mangle]
 pub extern "C" fn html_to_markdown(ptr: u32, len: u32) -> u32 {
     let s = match read_utf8(ptr, len) {
@@ -75,6 +100,48 @@ pub extern "C" fn html_to_markdown(ptr: u32, len: u32) -> u32 {
     write_out(&out)
 }
 
+/// Unwraps a `multipart/related` MHTML document (Word's "Save as Web Page", or an email client's
+/// saved message) into a single self-contained HTML string with every referenced resource part
+/// inlined as a `data:` URI - see `mhtml::mhtml_to_html` for the part-parsing/rewriting details.
+#[no_mangle]
+pub extern "C" fn mhtml_to_html(ptr: u32, len: u32) -> u32 {
+    let s = match read_utf8(ptr, len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty input");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "input is not valid UTF-8");
+            return 0;
+        }
+    };
+    match mhtml::mhtml_to_html(s) {
+        Ok(out) => write_out(&out),
+        Err(msg) => {
+            set_error(4, &msg);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn html_to_plain_text(ptr: u32, len: u32) -> u32 {
+    let s = match read_utf8(ptr, len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty input");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "input is not valid UTF-8");
+            return 0;
+        }
+    };
+    let out = plain_text::html_to_plain_text(s);
+    write_out(&out)
+}
+
 #[no_mangle]
 pub extern "C" fn markdown_to_html(ptr: u32, len: u32) -> u32 {
     let s = match read_utf8(ptr, len) {
@@ -92,6 +159,100 @@ pub extern "C" fn markdown_to_html(ptr: u32, len: u32) -> u32 {
     write_out(&out)
 }
 
+/// Same as `markdown_to_html`, but slugs each heading with a stable `id` and expands a `[TOC]`
+/// marker paragraph into a nested table of contents linking to those anchors - see
+/// `markdown::markdown_to_html_with_toc`.
+#[no_mangle]
+pub extern "C" fn markdown_to_html_with_toc(ptr: u32, len: u32) -> u32 {
+    let s = match read_utf8(ptr, len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty input");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "input is not valid UTF-8");
+            return 0;
+        }
+    };
+    let out = markdown::markdown_to_html_with_toc(s);
+    write_out(&out)
+}
+
+/// Dumps the `RcDom` tree `html_to_office_prepared`/`html_to_markdown` parse as a compact JSON node
+/// tree (`document`/`element`/`text`/`comment`, each with `children`) - see
+/// `ast::html_to_ast_json`. For integrators who want to inspect the parse rather than re-parse our
+/// string output.
+#[no_mangle]
+pub extern "C" fn html_to_ast(ptr: u32, len: u32) -> u32 {
+    let s = match read_utf8(ptr, len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty input");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "input is not valid UTF-8");
+            return 0;
+        }
+    };
+    let out = ast::html_to_ast_json(s);
+    write_out(&out)
+}
+
+/// Same as `html_to_ast`, but walks the comrak AST `markdown_to_html`/`markdown_to_office_html`
+/// parse instead - see `ast::markdown_to_ast_json`.
+#[no_mangle]
+pub extern "C" fn markdown_to_ast(ptr: u32, len: u32) -> u32 {
+    let s = match read_utf8(ptr, len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty input");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "input is not valid UTF-8");
+            return 0;
+        }
+    };
+    let out = ast::markdown_to_ast_json(s);
+    write_out(&out)
+}
+
+#[no_mangle]
+pub extern "C" fn markdown_to_office_html(ptr: u32, len: u32) -> u32 {
+    let s = match read_utf8(ptr, len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty input");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "input is not valid UTF-8");
+            return 0;
+        }
+    };
+    let out = pipeline::markdown_to_office_html(s);
+    write_out(&out)
+}
+
+#[no_mangle]
+pub extern "C" fn typst_to_office_html(ptr: u32, len: u32, display: u32) -> u32 {
+    let s = match read_utf8(ptr, len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty input");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "input is not valid UTF-8");
+            return 0;
+        }
+    };
+    let out = pipeline::typst_to_office_with_mathml(s, display != 0);
+    write_out(&out)
+}
+
 #[no_mangle]
 pub extern "C" fn html_to_office_prepared(ptr: u32, len: u32) -> u32 {
     let s = match read_utf8(ptr, len) {
@@ -105,7 +266,7 @@ pub extern "C" fn html_to_office_prepared(ptr: u32, len: u32) -> u32 {
             return 0;
         }
     };
-    let prepared = pipeline::html_to_office_prepared(s);
+    let prepared = pipeline::html_to_office_prepared(s, &pipeline::PrepareOptions::default());
     let out = json::prepared_office_to_json(&prepared);
     write_out(&out)
 }
@@ -123,7 +284,25 @@ pub extern "C" fn markdown_to_office_prepared(ptr: u32, len: u32) -> u32 {
             return 0;
         }
     };
-    let prepared = pipeline::markdown_to_office_prepared(s);
+    let prepared = pipeline::markdown_to_office_prepared(s, &pipeline::PrepareOptions::default());
+    let out = json::prepared_office_to_json(&prepared);
+    write_out(&out)
+}
+
+#[no_mangle]
+pub extern "C" fn org_to_office_prepared(ptr: u32, len: u32) -> u32 {
+    let s = match read_utf8(ptr, len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty input");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "input is not valid UTF-8");
+            return 0;
+        }
+    };
+    let prepared = org::org_to_office_prepared(s, &pipeline::PrepareOptions::default());
     let out = json::prepared_office_to_json(&prepared);
     write_out(&out)
 }
@@ -167,6 +346,63 @@ pub extern "C" fn office_apply_mathml(
     }
 }
 
+/// Id-keyed counterpart to `office_apply_mathml`: `jobs_json` is a serialized `Vec<TexJob>` (the
+/// same shape `*_prepared`'s `jobs` field serializes to) and `results_json` is
+/// `{"results":[{"id":N,"mathml":"...","display":bool,"error":"..."}]}`. Matching by `id` rather
+/// than a positional join lets a host render math asynchronously and out of order, and a per-job
+/// `error` falls back to that job's original LaTeX instead of failing the whole document.
+#[no_mangle]
+pub extern "C" fn office_apply_mathml_json(
+    html_ptr: u32,
+    html_len: u32,
+    jobs_ptr: u32,
+    jobs_len: u32,
+    results_ptr: u32,
+    results_len: u32,
+) -> u32 {
+    let html = match read_utf8(html_ptr, html_len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty html");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "html is not valid UTF-8");
+            return 0;
+        }
+    };
+    let jobs_json = match read_utf8(jobs_ptr, jobs_len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty jobs");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "jobs is not valid UTF-8");
+            return 0;
+        }
+    };
+    let results_json = match read_utf8(results_ptr, results_len) {
+        Ok(s) => s,
+        Err(1) => {
+            set_error(1, "empty results");
+            return 0;
+        }
+        Err(_) => {
+            set_error(2, "results is not valid UTF-8");
+            return 0;
+        }
+    };
+
+    match json::apply_mathml_results_from_json(html, jobs_json, results_json) {
+        Ok(out) => write_out(&out),
+        Err(msg) => {
+            set_error(4, &msg);
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn wrap_html_for_clipboard(
     fragment_ptr: u32,
@@ -31,69 +31,693 @@ fn md_format_link(text: &str, href: &str) -> String {
     }
 }
 
+fn find_tag_end(s: &str, lt: usize) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut i = lt;
+    let mut in_s = false;
+    let mut in_d = false;
+    while i < bytes.len() {
+        let c = bytes[i] as char;
+        match c {
+            '\'' if !in_d => in_s = !in_s,
+            '"' if !in_s => in_d = !in_d,
+            '>' if !in_s && !in_d => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn attr_val(raw: &str, name: &str) -> Option<String> {
+    let low = raw.to_ascii_lowercase();
+    let needle = format!("{name}=");
+    let idx = low.find(&needle)?;
+    let after = &raw[idx + needle.len()..].trim_start();
+    if after.starts_with('"') {
+        let rest = &after[1..];
+        let end = rest.find('"')?;
+        return Some(rest[..end].to_string());
+    }
+    if after.starts_with('\'') {
+        let rest = &after[1..];
+        let end = rest.find('\'')?;
+        return Some(rest[..end].to_string());
+    }
+    None
+}
+
+fn has_bool_attr(raw: &str, name: &str) -> bool {
+    attr_val(raw, name).is_some() || raw.to_ascii_lowercase().split_whitespace().any(|tok| tok == name)
+}
+
+/// Reads a code fence's language out of a `<pre>`/`<code>` tag's own attributes: a `data-lang`
+/// value, or a `language-*`/`lang-*` token in its `class` list (the convention highlight.js,
+/// rustdoc, and most static-site generators mark fenced code with).
+fn code_lang_from_attrs(attrs: &str) -> Option<String> {
+    if let Some(data_lang) = attr_val(attrs, "data-lang") {
+        if !data_lang.is_empty() {
+            return Some(data_lang);
+        }
+    }
+    let class = attr_val(attrs, "class")?;
+    class.split_whitespace().find_map(|tok| {
+        tok.strip_prefix("language-")
+            .or_else(|| tok.strip_prefix("lang-"))
+            .map(|s| s.to_string())
+    })
+}
+
+/// Like [`code_lang_from_attrs`], but for the common `<pre><code class="language-rust">` shape
+/// where the language lives on a `<code>` immediately inside `<pre>` rather than on `<pre>` itself.
+fn leading_code_lang(pre_inner: &str) -> Option<String> {
+    let trimmed = pre_inner.trim_start();
+    if !trimmed.to_ascii_lowercase().starts_with("<code") {
+        return None;
+    }
+    let gt = find_tag_end(trimmed, 0)?;
+    let tag_content = trimmed[1..gt].trim();
+    let name_end = tag_content
+        .find(|c: char| c.is_whitespace() || c == '/')
+        .unwrap_or(tag_content.len());
+    if !tag_content[..name_end].eq_ignore_ascii_case("code") {
+        return None;
+    }
+    code_lang_from_attrs(&tag_content[name_end..])
+}
+
+/// Rewrites a fenced code block's lines for rustdoc's doc-example convention: a line whose first
+/// non-whitespace characters are `##` is the escape for a literal leading `#` and is emitted with
+/// only one `#`; a line that is exactly `#` or starts with `# ` is a "hidden" setup line, which is
+/// either dropped (`hide_hidden_lines`) or kept with that single `# ` prefix stripped (the default),
+/// so a round-tripped doc example reads the way rustdoc authors actually wrote it.
+fn process_rustdoc_lines(text: &str, hide_hidden_lines: bool) -> String {
+    let mut out_lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        if let Some(rest) = trimmed.strip_prefix("##") {
+            out_lines.push(format!("{indent}#{rest}"));
+        } else if trimmed == "#" || trimmed.starts_with("# ") {
+            if hide_hidden_lines {
+                continue;
+            }
+            let rest = trimmed.strip_prefix("# ").unwrap_or("");
+            out_lines.push(format!("{indent}{rest}"));
+        } else {
+            out_lines.push(line.to_string());
+        }
+    }
+    out_lines.join("\n")
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum CellAlign {
+    Left,
+    Center,
+    Right,
+}
+
+impl CellAlign {
+    fn delimiter(self) -> &'static str {
+        match self {
+            CellAlign::Left => ":---",
+            CellAlign::Center => ":---:",
+            CellAlign::Right => "---:",
+        }
+    }
+}
+
+fn cell_align(attrs: &str) -> Option<CellAlign> {
+    if let Some(a) = attr_val(attrs, "align") {
+        return match a.to_ascii_lowercase().as_str() {
+            "left" => Some(CellAlign::Left),
+            "center" => Some(CellAlign::Center),
+            "right" => Some(CellAlign::Right),
+            _ => None,
+        };
+    }
+    let style = attr_val(attrs, "style")?;
+    let low = style.to_ascii_lowercase().replace(' ', "");
+    if low.contains("text-align:center") {
+        Some(CellAlign::Center)
+    } else if low.contains("text-align:right") {
+        Some(CellAlign::Right)
+    } else if low.contains("text-align:left") {
+        Some(CellAlign::Left)
+    } else {
+        None
+    }
+}
+
+/// Finds the `</tag>` that closes the element opened at `content_start` (the index right after
+/// the opening tag's `>`), accounting for same-tag nesting. Returns `(content_end, after_close)`.
+fn find_matching_close(input: &str, tag: &str, content_start: usize) -> Option<(usize, usize)> {
+    let low = input.to_ascii_lowercase();
+    let open_needle = format!("<{}", tag.to_ascii_lowercase());
+    let close_needle = format!("</{}>", tag.to_ascii_lowercase());
+    let mut depth = 1usize;
+    let mut i = content_start;
+    while i < input.len() {
+        let next_open = low[i..].find(&open_needle).map(|p| i + p);
+        let next_close = low[i..].find(&close_needle).map(|p| i + p);
+        match (next_open, next_close) {
+            (Some(o), Some(c)) if o < c => {
+                depth += 1;
+                i = o + open_needle.len();
+            }
+            (_, Some(c)) => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((c, c + close_needle.len()));
+                }
+                i = c + close_needle.len();
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+fn extract_balanced(input: &str, content_start: usize, tag: &str) -> Option<(String, usize)> {
+    let (content_end, after_close) = find_matching_close(input, tag, content_start)?;
+    Some((input[content_start..content_end].to_string(), after_close))
+}
+
+type TableRow = Vec<(String, Option<CellAlign>)>;
+
+/// Walks a `<table>`'s inner HTML for `<tr>` rows (transparently skipping `thead`/`tbody`/
+/// `tfoot`/`colgroup` wrappers) and each row's `<th>`/`<td>` cells.
+fn parse_table_rows(body: &str) -> Vec<TableRow> {
+    let mut rows = Vec::new();
+    let low = body.to_ascii_lowercase();
+    let mut i = 0usize;
+    while let Some(rel) = low[i..].find("<tr") {
+        let tr_start = i + rel;
+        let Some(gt) = find_tag_end(body, tr_start) else {
+            break;
+        };
+        let Some((tr_body, after_tr)) = extract_balanced(body, gt + 1, "tr") else {
+            break;
+        };
+        let mut cells: TableRow = Vec::new();
+        let cell_low = tr_body.to_ascii_lowercase();
+        let mut j = 0usize;
+        loop {
+            let next_th = cell_low[j..].find("<th").map(|p| j + p);
+            let next_td = cell_low[j..].find("<td").map(|p| j + p);
+            let (cell_start, tag_name) = match (next_th, next_td) {
+                (Some(th), Some(td)) if th < td => (th, "th"),
+                (Some(th), None) => (th, "th"),
+                (_, Some(td)) => (td, "td"),
+                _ => break,
+            };
+            let Some(cgt) = find_tag_end(&tr_body, cell_start) else {
+                break;
+            };
+            let attrs_raw = &tr_body[cell_start + 1 + tag_name.len()..cgt];
+            let align = cell_align(attrs_raw);
+            let Some((content, after_cell)) = extract_balanced(&tr_body, cgt + 1, tag_name) else {
+                break;
+            };
+            cells.push((content, align));
+            j = after_cell;
+        }
+        rows.push(cells);
+        i = after_tr;
+    }
+    rows
+}
+
+fn cell_to_markdown(html_content: &str) -> String {
+    let md = html_to_markdown_text(html_content);
+    md.split_whitespace().collect::<Vec<_>>().join(" ").replace('|', "\\|")
+}
+
+/// Renders a `<table>`'s inner HTML as a GFM pipe table, inferring each column's delimiter-row
+/// colons from the header row's `align`/`text-align` (falling back to the first row if there is
+/// no recognizable header).
+fn table_html_to_markdown(table_inner: &str) -> Option<String> {
+    let rows = parse_table_rows(table_inner);
+    if rows.is_empty() {
+        return None;
+    }
+    let (header, body) = rows.split_first().unwrap();
+    let col_count = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    if col_count == 0 {
+        return None;
+    }
+
+    let mut md = String::new();
+    md.push('|');
+    for c in 0..col_count {
+        let text = header.get(c).map(|(t, _)| cell_to_markdown(t)).unwrap_or_default();
+        md.push(' ');
+        md.push_str(&text);
+        md.push_str(" |");
+    }
+    md.push('\n');
+    md.push('|');
+    for c in 0..col_count {
+        let align = header.get(c).and_then(|(_, a)| *a);
+        md.push(' ');
+        md.push_str(align.map(CellAlign::delimiter).unwrap_or("---"));
+        md.push_str(" |");
+    }
+    md.push('\n');
+    for row in body {
+        md.push('|');
+        for c in 0..col_count {
+            let text = row.get(c).map(|(t, _)| cell_to_markdown(t)).unwrap_or_default();
+            md.push(' ');
+            md.push_str(&text);
+            md.push_str(" |");
+        }
+        md.push('\n');
+    }
+    Some(md)
+}
+
 pub fn markdown_to_html_string(md: &str) -> String {
     let mut opts = Options::empty();
     opts.insert(Options::ENABLE_TABLES);
     opts.insert(Options::ENABLE_STRIKETHROUGH);
     opts.insert(Options::ENABLE_TASKLISTS);
     opts.insert(Options::ENABLE_FOOTNOTES);
+    opts.insert(Options::ENABLE_HEADING_ATTRIBUTES);
     let parser = Parser::new_ext(md, opts);
     let mut out = String::new();
     html::push_html(&mut out, parser);
     out
 }
 
-pub fn html_to_markdown_text(input: &str) -> String {
-    // Conservative HTML -> Markdown converter, tuned for our clipboard payloads/examples.
-    // Determinism goals:
-    // - Preserve fenced code blocks and inline code
-    // - Preserve links
-    // - Preserve LaTeX from data-math attributes when present
-    fn find_tag_end(s: &str, lt: usize) -> Option<usize> {
-        let bytes = s.as_bytes();
-        let mut i = lt;
-        let mut in_s = false;
-        let mut in_d = false;
-        while i < bytes.len() {
-            let c = bytes[i] as char;
-            match c {
-                '\'' if !in_d => in_s = !in_s,
-                '"' if !in_s => in_d = !in_d,
-                '>' if !in_s && !in_d => return Some(i),
-                _ => {}
+/// Lowercases `text`, collapses each run of non-alphanumeric characters into a single hyphen, and
+/// trims a leading/trailing hyphen, producing a GitHub-style heading-anchor slug.
+fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    let mut last_was_hyphen = true; // swallows any leading run instead of emitting a leading '-'
+    for c in text.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            slug.push(c);
+            last_was_hyphen = false;
+        } else if !last_was_hyphen {
+            slug.push('-');
+            last_was_hyphen = true;
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+fn strip_tags(html_fragment: &str) -> String {
+    let mut out = String::with_capacity(html_fragment.len());
+    let mut i = 0;
+    let bytes = html_fragment.as_bytes();
+    while i < bytes.len() {
+        if bytes[i] == b'<' {
+            match find_tag_end(html_fragment, i) {
+                Some(end) => i = end + 1,
+                None => break,
             }
-            i += 1;
+        } else {
+            let ch = html_fragment[i..].chars().next().unwrap();
+            out.push(ch);
+            i += ch.len_utf8();
         }
-        None
     }
+    out
+}
 
-    let mut out = String::with_capacity(input.len() / 2);
-    let mut i: usize = 0;
-    let b = input.as_bytes();
+fn html_escape_text(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+struct HeadingEntry {
+    level: u8,
+    id: String,
+    text: String,
+}
 
-    let mut pre_depth: u32 = 0;
-    let mut code_depth: u32 = 0;
-    let mut list_stack: Vec<(bool, u32)> = Vec::new(); // (ordered, next_index)
-    let mut link_stack: Vec<Option<String>> = Vec::new();
-    let mut skip_depth: u32 = 0; // used for "math span" content skipping
-    let mut link_text_stack: Vec<String> = Vec::new();
-
-    fn attr_val(raw: &str, name: &str) -> Option<String> {
-        let low = raw.to_ascii_lowercase();
-        let needle = format!("{name}=");
-        let idx = low.find(&needle)?;
-        let after = &raw[idx + needle.len()..].trim_start();
-        if after.starts_with('"') {
-            let rest = &after[1..];
-            let end = rest.find('"')?;
-            return Some(rest[..end].to_string());
-        }
-        if after.starts_with('\'') {
-            let rest = &after[1..];
-            let end = rest.find('\'')?;
-            return Some(rest[..end].to_string());
+/// Walks the rendered HTML for `<h1>`..`<h6>` elements, gives each one a stable `id` slug (derived
+/// from its text content, deduplicated with a `-1`, `-2`, ... suffix on collision), and returns the
+/// rewritten HTML alongside the ordered list of headings for `build_toc`.
+fn add_heading_ids(html_doc: &str) -> (String, Vec<HeadingEntry>) {
+    let mut out = String::with_capacity(html_doc.len());
+    let mut headings = Vec::new();
+    let mut seen: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
+    let mut i = 0;
+    while i < html_doc.len() {
+        let level = (1..=6u8).find(|lvl| html_doc[i..].starts_with(&format!("<h{lvl}>")));
+        if let Some(level) = level {
+            let open_tag = format!("<h{level}>");
+            let content_start = i + open_tag.len();
+            let close_tag = format!("</h{level}>");
+            if let Some(rel_end) = html_doc[content_start..].find(&close_tag) {
+                let inner = &html_doc[content_start..content_start + rel_end];
+                let text = decode_entities(&strip_tags(inner));
+                let base_slug = {
+                    let s = slugify(&text);
+                    if s.is_empty() { "section".to_string() } else { s }
+                };
+                let count = seen.entry(base_slug.clone()).or_insert(0);
+                let id = if *count == 0 { base_slug } else { format!("{base_slug}-{count}") };
+                *count += 1;
+                out.push_str(&format!("<h{level} id=\"{id}\">{inner}</h{level}>"));
+                headings.push(HeadingEntry { level, id, text });
+                i = content_start + rel_end + close_tag.len();
+                continue;
+            }
         }
+        let ch = html_doc[i..].chars().next().unwrap();
+        out.push(ch);
+        i += ch.len_utf8();
+    }
+    (out, headings)
+}
+
+/// Builds a nested `<ul>`/`<li>` table of contents linking to each heading's anchor, tracking the
+/// current heading level to open a deeper `<ul>` on a level increase and close back out on a
+/// decrease.
+fn build_toc(headings: &[HeadingEntry]) -> String {
+    let Some(first) = headings.first() else {
+        return String::new();
+    };
+    let mut out = String::from("<ul>\n");
+    let mut level_stack: Vec<u8> = vec![first.level];
+    out.push_str(&format!("<li><a href=\"#{}\">{}</a>", first.id, html_escape_text(&first.text)));
+
+    for h in &headings[1..] {
+        let current = *level_stack.last().unwrap();
+        if h.level > current {
+            out.push_str("\n<ul>\n");
+            level_stack.push(h.level);
+        } else {
+            out.push_str("</li>\n");
+            while level_stack.len() > 1 && *level_stack.last().unwrap() > h.level {
+                level_stack.pop();
+                out.push_str("</ul></li>\n");
+            }
+            *level_stack.last_mut().unwrap() = h.level;
+        }
+        out.push_str(&format!("<li><a href=\"#{}\">{}</a>", h.id, html_escape_text(&h.text)));
+    }
+
+    out.push_str("</li>\n");
+    for _ in 1..level_stack.len() {
+        out.push_str("</ul></li>\n");
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+/// Same as [`markdown_to_html_string`], but also gives every heading a stable `id` slug and expands
+/// a `[TOC]` marker paragraph into a nested table of contents linking to those anchors. Useful for
+/// long documents pasted into the Office/clipboard pipeline, where an in-document nav aid matters
+/// more than it does for a one-off snippet.
+pub fn markdown_to_html_with_toc(md: &str) -> String {
+    let html_doc = markdown_to_html_string(md);
+    let (with_ids, headings) = add_heading_ids(&html_doc);
+    if headings.is_empty() {
+        return with_ids;
+    }
+    let toc = build_toc(&headings);
+    for marker in ["<p>[TOC]</p>\n", "<p>[TOC]</p>"] {
+        if with_ids.contains(marker) {
+            return with_ids.replacen(marker, &toc, 1);
+        }
+    }
+    with_ids
+}
+
+/// One node in the HTML document tree [`parse_html_nodes`] builds before rendering. Deliberately a
+/// small, purpose-built tree (not the full `html5ever`/`RcDom` one `ast::html_to_ast_json` walks) -
+/// just the handful of shapes Markdown export needs, modeled on comrak's own AST.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Paragraph(Vec<Node>),
+    Heading { level: u8, id: Option<String>, classes: Vec<String>, children: Vec<Node> },
+    /// `items` holds the list's `Item` children directly; anything else found between `<li>`s
+    /// (stray whitespace text) is kept for fidelity but ignored by the renderer.
+    List { ordered: bool, items: Vec<Node> },
+    Item { checked: Option<bool>, children: Vec<Node> },
+    CodeBlock { lang: Option<String>, text: String },
+    /// Pre-rendered GFM pipe table markdown, produced by [`table_html_to_markdown`]. Kept as a
+    /// leaf (rather than its own row/cell node shapes) since the table converter already handles
+    /// per-cell inline conversion and alignment on its own.
+    Table { markdown: String },
+    Link { href: Option<String>, children: Vec<Node> },
+    Strong(Vec<Node>),
+    Emphasis(Vec<Node>),
+    Strikethrough(Vec<Node>),
+    Math { display: bool, tex: String },
+    /// A footnote reference (`[^label]`), recognized from the superscript link pulldown_cmark
+    /// renders a footnote marker as.
+    FootnoteRef(String),
+    /// One `[^label]: ...` footnote definition, recognized from a `<li>` inside the trailing
+    /// footnotes section a document with footnote markers gets rendered with.
+    FootnoteDef { label: String, children: Vec<Node> },
+    LineBreak,
+    Text(String),
+}
+
+fn children_of(node: &Node) -> &[Node] {
+    match node {
+        Node::Paragraph(c) | Node::Strong(c) | Node::Emphasis(c) | Node::Strikethrough(c) => c,
+        Node::Heading { children, .. }
+        | Node::Link { children, .. }
+        | Node::Item { children, .. } => children,
+        Node::List { items, .. } => items,
+        Node::FootnoteDef { children, .. } => children,
+        Node::CodeBlock { .. }
+        | Node::Table { .. }
+        | Node::Math { .. }
+        | Node::FootnoteRef(_)
+        | Node::LineBreak
+        | Node::Text(_) => &[],
+    }
+}
+
+/// Visits every node of a tree produced by [`parse_html_nodes`], depth-first pre-order. Exposed so
+/// a caller can traverse or post-process the parsed tree (e.g. collecting stats, rewriting links)
+/// before handing it to [`render_markdown`], without needing its own copy of the tree-walking logic.
+pub trait NodeVisitor {
+    fn visit(&mut self, node: &Node);
+}
+
+pub fn walk(nodes: &[Node], visitor: &mut impl NodeVisitor) {
+    for node in nodes {
+        visitor.visit(node);
+        walk(children_of(node), visitor);
+    }
+}
+
+/// Gathers the literal text of `nodes` and their descendants - the way comrak's own `collect_text`
+/// walks a subtree joining `Text`/code literals - turning line breaks into spaces. Used wherever
+/// Markdown needs a plain string rather than nested inline markup (link text, heading text).
+pub fn collect_text(nodes: &[Node]) -> String {
+    let mut out = String::new();
+    collect_text_into(nodes, &mut out);
+    out
+}
+
+fn collect_text_into(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(s) => out.push_str(s),
+            Node::CodeBlock { text, .. } => out.push_str(text),
+            Node::Math { tex, .. } => out.push_str(tex),
+            Node::LineBreak => out.push(' '),
+            Node::Table { markdown } => out.push_str(markdown),
+            Node::Paragraph(c) | Node::Strong(c) | Node::Emphasis(c) | Node::Strikethrough(c) => {
+                collect_text_into(c, out)
+            }
+            Node::Heading { children, .. }
+            | Node::Link { children, .. }
+            | Node::Item { children, .. } => collect_text_into(children, out),
+            Node::List { items, .. } => collect_text_into(items, out),
+            Node::FootnoteRef(label) => {
+                out.push_str("[^");
+                out.push_str(label);
+                out.push(']');
+            }
+            Node::FootnoteDef { children, .. } => collect_text_into(children, out),
+        }
+    }
+}
+
+/// Reads the TeX out of a `<annotation encoding="application/x-tex">` inside a parsed `<math>`
+/// element's inner HTML, the way MathJax/KaTeX embed the source alongside their rendered markup.
+fn extract_tex_annotation(math_inner: &str) -> Option<String> {
+    let inner_low = math_inner.to_ascii_lowercase();
+    let a_i = inner_low.find("<annotation")?;
+    let ann = &math_inner[a_i..];
+    let ann_low = ann.to_ascii_lowercase();
+    if !(ann_low.contains("encoding=\"application/x-tex\"") || ann_low.contains("encoding='application/x-tex'"))
+    {
+        return None;
+    }
+    let gt2 = ann.find('>')?;
+    let end_ann = ann_low.find("</annotation>")?;
+    let tex = decode_entities(&ann[gt2 + 1..end_ann]).trim().to_string();
+    if tex.is_empty() {
+        None
+    } else {
+        Some(tex)
+    }
+}
+
+/// What an still-open tag on [`parse_html_nodes`]'s stack will become once its matching close tag
+/// (or end of input) is reached. `Passthrough` covers any tag we don't give special meaning to
+/// (`span`, `u`, `mark`, ...): its children are spliced straight into the parent rather than being
+/// wrapped in a node of their own, so dropping an unrecognized tag can't also drop its content.
+/// `Sup` is the same, except a `<sup>` wrapping nothing but a footnote-anchor link becomes a
+/// [`Node::FootnoteRef`] instead of splicing.
+enum OpenKind {
+    Paragraph,
+    Heading { level: u8, id: Option<String>, classes: Vec<String> },
+    List { ordered: bool },
+    Item { checked: Option<bool> },
+    Link { href: Option<String> },
+    Strong,
+    Emphasis,
+    Strikethrough,
+    Sup,
+    Passthrough,
+}
+
+struct OpenTag {
+    tag: String,
+    kind: OpenKind,
+    children: Vec<Node>,
+}
+
+/// What closing a frame produces: either a wrapping [`Node`], or its children spliced straight
+/// into the parent unwrapped (for `Passthrough` tags, and a `Sup` that isn't a footnote ref).
+enum FrameOutput {
+    Node(Node),
+    Splice(Vec<Node>),
+}
+
+/// Reads a footnote label out of a `#fn...`/`#footnote...` URL fragment (an `<a href>` target or a
+/// bare `<li id>`, the latter passed in with a leading `#` added). Strips the `fn`/`footnote`
+/// prefix pulldown_cmark's footnote rendering adds, plus any `-`/`:`/`_` separator after it.
+fn footnote_label_from_fragment(fragment: &str) -> Option<String> {
+    let frag = fragment.strip_prefix('#')?;
+    let lower = frag.to_ascii_lowercase();
+    let prefix_len = if lower.starts_with("footnote") {
+        "footnote".len()
+    } else if lower.starts_with("fn") {
+        "fn".len()
+    } else {
+        return None;
+    };
+    let label = frag[prefix_len..].trim_start_matches(['-', ':', '_']);
+    if label.is_empty() {
         None
+    } else {
+        Some(label.to_string())
+    }
+}
+
+fn finish_frame(frame: OpenTag) -> FrameOutput {
+    match frame.kind {
+        OpenKind::Paragraph => FrameOutput::Node(Node::Paragraph(frame.children)),
+        OpenKind::Heading { level, id, classes } => {
+            FrameOutput::Node(Node::Heading { level, id, classes, children: frame.children })
+        }
+        OpenKind::List { ordered } => FrameOutput::Node(Node::List { ordered, items: frame.children }),
+        OpenKind::Item { checked } => FrameOutput::Node(Node::Item { checked, children: frame.children }),
+        OpenKind::Link { href } => FrameOutput::Node(Node::Link { href, children: frame.children }),
+        OpenKind::Strong => FrameOutput::Node(Node::Strong(frame.children)),
+        OpenKind::Emphasis => FrameOutput::Node(Node::Emphasis(frame.children)),
+        OpenKind::Strikethrough => FrameOutput::Node(Node::Strikethrough(frame.children)),
+        OpenKind::Sup => {
+            if let [Node::Link { href: Some(href), .. }] = frame.children.as_slice() {
+                if let Some(label) = footnote_label_from_fragment(href) {
+                    return FrameOutput::Node(Node::FootnoteRef(label));
+                }
+            }
+            FrameOutput::Splice(frame.children)
+        }
+        OpenKind::Passthrough => FrameOutput::Splice(frame.children),
+    }
+}
+
+fn close_frame(frame: OpenTag, stack: &mut Vec<OpenTag>, root: &mut Vec<Node>) {
+    match finish_frame(frame) {
+        FrameOutput::Node(node) => match stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => root.push(node),
+        },
+        FrameOutput::Splice(children) => match stack.last_mut() {
+            Some(parent) => parent.children.extend(children),
+            None => root.extend(children),
+        },
+    }
+}
+
+/// Removes a footnote-definition back-reference link (`<a ...>\u{21a9}</a>`, pulldown_cmark's
+/// `\u{21a9}` return arrow pointing back at the reference) from a parsed `<li>`'s children, at
+/// whatever depth it landed - it's normally the last inline child of the last paragraph.
+fn strip_footnote_backrefs(nodes: &mut Vec<Node>) {
+    for node in nodes.iter_mut() {
+        match node {
+            Node::Paragraph(c) | Node::Strong(c) | Node::Emphasis(c) | Node::Strikethrough(c) => {
+                strip_footnote_backrefs(c)
+            }
+            Node::Heading { children, .. }
+            | Node::Link { children, .. }
+            | Node::Item { children, .. }
+            | Node::FootnoteDef { children, .. } => strip_footnote_backrefs(children),
+            Node::List { items, .. } => strip_footnote_backrefs(items),
+            _ => {}
+        }
+    }
+    nodes.retain(|n| !matches!(n, Node::Link { children, .. } if collect_text(children).trim() == "\u{21A9}"));
+}
+
+/// Walks a footnotes section's inner HTML for `<li id="...">` entries (transparently skipping any
+/// `<ol>`/`<ul>` wrapper, the way [`parse_table_rows`] skips `thead`/`tbody`), turning each into a
+/// [`Node::FootnoteDef`] with its generated back-reference link stripped out.
+fn parse_footnote_defs(body: &str, hide_rustdoc_hidden_lines: bool) -> Vec<Node> {
+    let mut defs = Vec::new();
+    let low = body.to_ascii_lowercase();
+    let mut i = 0usize;
+    while let Some(rel) = low[i..].find("<li") {
+        let li_start = i + rel;
+        let Some(gt) = find_tag_end(body, li_start) else { break };
+        let raw = &body[li_start + 1..gt];
+        let name_end = raw.find(|c: char| c.is_whitespace() || c == '/').unwrap_or(raw.len());
+        let rest = &raw[name_end..];
+        let Some((inner, after_li)) = extract_balanced(body, gt + 1, "li") else { break };
+        if let Some(label) = attr_val(rest, "id").and_then(|id| footnote_label_from_fragment(&format!("#{id}"))) {
+            let mut children = parse_html_nodes(&inner, hide_rustdoc_hidden_lines);
+            strip_footnote_backrefs(&mut children);
+            defs.push(Node::FootnoteDef { label, children });
+        }
+        i = after_li;
+    }
+    defs
+}
+
+/// Parses a conservative subset of HTML - tuned for our clipboard payloads/examples - into a tree
+/// of [`Node`]s, pushing a frame on an open tag and popping it on the matching close tag so a
+/// mismatched or unclosed tag just gets flushed at the next close (or at end of input) instead of
+/// corrupting everything after it.
+pub fn parse_html_nodes(input: &str, hide_rustdoc_hidden_lines: bool) -> Vec<Node> {
+    let mut root: Vec<Node> = Vec::new();
+    let mut stack: Vec<OpenTag> = Vec::new();
+    let mut skip_depth: u32 = 0; // subtree content we intentionally discard (rendered math, mjx wrappers)
+    let b = input.as_bytes();
+    let mut i: usize = 0;
+
+    macro_rules! push_child {
+        ($node:expr) => {{
+            let node = $node;
+            match stack.last_mut() {
+                Some(top) => top.children.push(node),
+                None => root.push(node),
+            }
+        }};
     }
 
     while i < b.len() {
@@ -101,22 +725,14 @@ pub fn html_to_markdown_text(input: &str) -> String {
             Some(p) => p,
             None => {
                 if skip_depth == 0 {
-                    if let Some(buf) = link_text_stack.last_mut() {
-                        buf.push_str(&input[i..]);
-                    } else {
-                        out.push_str(&input[i..]);
-                    }
+                    push_child!(Node::Text(input[i..].to_string()));
                 }
                 break;
             }
         };
         let lt = i + lt_rel;
-        if skip_depth == 0 {
-            if let Some(buf) = link_text_stack.last_mut() {
-                buf.push_str(&input[i..lt]);
-            } else {
-                out.push_str(&input[i..lt]);
-            }
+        if skip_depth == 0 && lt > i {
+            push_child!(Node::Text(input[i..lt].to_string()));
         }
         i = lt;
 
@@ -129,7 +745,7 @@ pub fn html_to_markdown_text(input: &str) -> String {
 
         let Some(gt) = find_tag_end(input, i) else {
             if skip_depth == 0 {
-                out.push('<');
+                push_child!(Node::Text("<".to_string()));
             }
             i += 1;
             continue;
@@ -149,7 +765,7 @@ pub fn html_to_markdown_text(input: &str) -> String {
         let lower = name.to_ascii_lowercase();
         let self_close = raw_trim.ends_with('/');
 
-        // Generic subtree skipping: once enabled, we keep a simple depth counter until the matching end tag.
+        // Generic subtree skipping: once enabled, keep a depth counter until the matching end tag.
         if skip_depth > 0 {
             if is_end {
                 skip_depth = skip_depth.saturating_sub(1);
@@ -163,41 +779,12 @@ pub fn html_to_markdown_text(input: &str) -> String {
         // MathML blocks: prefer TeX from <annotation encoding="application/x-tex">.
         if !is_end && lower == "math" {
             let math_open = &input[i..=gt];
-            let display_block = math_open.to_ascii_lowercase().contains("display=\"block\"");
+            let display = math_open.to_ascii_lowercase().contains("display=\"block\"");
             if let Some(close_rel) = input[gt + 1..].to_ascii_lowercase().find("</math>") {
                 let close_i = gt + 1 + close_rel;
                 let inner = &input[gt + 1..close_i];
-                let inner_low = inner.to_ascii_lowercase();
-                if let Some(a_i) = inner_low.find("<annotation") {
-                    let ann = &inner[a_i..];
-                    let ann_low = ann.to_ascii_lowercase();
-                    if ann_low.contains("encoding=\"application/x-tex\"")
-                        || ann_low.contains("encoding='application/x-tex'")
-                    {
-                        if let Some(gt2) = ann.find('>') {
-                            if let Some(end_ann) = ann_low.find("</annotation>") {
-                                let tex_raw = &ann[gt2 + 1..end_ann];
-                                let tex = decode_entities(tex_raw).trim().to_string();
-                                if !tex.is_empty() {
-                                    if display_block {
-                                        if !out.ends_with("\n\n") {
-                                            out.push('\n');
-                                            out.push('\n');
-                                        }
-                                        out.push_str("$$");
-                                        out.push_str(&tex);
-                                        out.push_str("$$");
-                                        out.push('\n');
-                                        out.push('\n');
-                                    } else {
-                                        out.push('$');
-                                        out.push_str(&tex);
-                                        out.push('$');
-                                    }
-                                }
-                            }
-                        }
-                    }
+                if let Some(tex) = extract_tex_annotation(inner) {
+                    push_child!(Node::Math { display, tex });
                 }
                 i = close_i + "</math>".len();
                 continue;
@@ -211,168 +798,464 @@ pub fn html_to_markdown_text(input: &str) -> String {
             continue;
         }
 
-        match (is_end, lower.as_str()) {
-            (false, "br") => out.push('\n'),
-            (false, "p") | (false, "div") => {
-                if !out.ends_with("\n\n") {
-                    if !out.ends_with('\n') {
-                        out.push('\n');
+        // Footnotes section: consume the whole subtree and turn each <li> into a definition,
+        // placed at the same position the section occupies (the end of the document, the way
+        // pulldown_cmark's own footnote rendering appends it).
+        if !is_end && lower == "section" {
+            let is_footnotes = attr_val(rest, "class")
+                .is_some_and(|c| c.split_whitespace().any(|cls| cls.eq_ignore_ascii_case("footnotes")));
+            if is_footnotes {
+                if let Some((inner, after_close)) = extract_balanced(input, gt + 1, "section") {
+                    for def in parse_footnote_defs(&inner, hide_rustdoc_hidden_lines) {
+                        push_child!(def);
                     }
-                    out.push('\n');
+                    i = after_close;
+                    continue;
                 }
             }
-            (true, "p") | (true, "div") => {
-                if !out.ends_with("\n\n") {
-                    out.push('\n');
-                    out.push('\n');
+        }
+
+        // Tables: consume the whole subtree at once and regenerate it as a GFM pipe table.
+        if !is_end && lower == "table" {
+            if let Some((inner, after_close)) = extract_balanced(input, gt + 1, "table") {
+                if let Some(table_md) = table_html_to_markdown(&inner) {
+                    push_child!(Node::Table { markdown: table_md });
                 }
+                i = after_close;
+                continue;
             }
-            (false, "h1")
-            | (false, "h2")
-            | (false, "h3")
-            | (false, "h4")
-            | (false, "h5")
-            | (false, "h6") => {
-                let level = lower[1..2].parse::<usize>().unwrap_or(1);
-                if !out.ends_with("\n\n") {
-                    if !out.ends_with('\n') {
-                        out.push('\n');
-                    }
-                    out.push('\n');
-                }
-                out.push_str(&"#".repeat(level));
-                out.push(' ');
+        }
+
+        // Code blocks: arbitrary nested markup inside <pre> isn't useful as a tree, so flatten its
+        // text content directly into one leaf, reading the fence language off <pre> itself or the
+        // <code> immediately inside it, and applying rustdoc's hidden/escaped-line convention.
+        if !is_end && lower == "pre" {
+            if let Some((inner, after_close)) = extract_balanced(input, gt + 1, "pre") {
+                let lang = code_lang_from_attrs(rest).or_else(|| leading_code_lang(&inner));
+                let raw_text = decode_entities(&strip_tags(&inner));
+                let text = process_rustdoc_lines(&raw_text, hide_rustdoc_hidden_lines);
+                push_child!(Node::CodeBlock { lang, text });
+                i = after_close;
+                continue;
             }
-            (true, "h1")
-            | (true, "h2")
-            | (true, "h3")
-            | (true, "h4")
-            | (true, "h5")
-            | (true, "h6") => {
-                if !out.ends_with("\n\n") {
-                    out.push('\n');
-                    out.push('\n');
+        }
+
+        if !is_end && lower == "br" {
+            push_child!(Node::LineBreak);
+            i = gt + 1;
+            continue;
+        }
+
+        if !is_end && lower == "input" {
+            // Only the checkbox that opens an <li> (nothing rendered in it yet) marks a task item.
+            if let Some(OpenTag { kind: OpenKind::Item { checked }, children, .. }) = stack.last_mut() {
+                if children.is_empty()
+                    && attr_val(rest, "type").is_some_and(|t| t.eq_ignore_ascii_case("checkbox"))
+                {
+                    *checked = Some(has_bool_attr(rest, "checked"));
                 }
             }
-            (false, "strong") | (false, "b") => out.push_str("**"),
-            (true, "strong") | (true, "b") => out.push_str("**"),
-            (false, "em") | (false, "i") => out.push('*'),
-            (true, "em") | (true, "i") => out.push('*'),
-            (false, "ul") => list_stack.push((false, 1)),
-            (true, "ul") => {
-                list_stack.pop();
-                if !out.ends_with('\n') {
-                    out.push('\n');
+            i = gt + 1;
+            continue;
+        }
+
+        if !is_end && lower == "span" {
+            if let Some(tex) = attr_val(rest, "data-math").map(|v| decode_entities(&v)) {
+                let class = attr_val(rest, "class").unwrap_or_default();
+                let display = class.to_ascii_lowercase().contains("math-block");
+                push_child!(Node::Math { display, tex });
+                if !self_close {
+                    skip_depth = 1; // discard the rendered subtree inside this math span
                 }
+                i = gt + 1;
+                continue;
             }
-            (false, "ol") => list_stack.push((true, 1)),
-            (true, "ol") => {
-                list_stack.pop();
-                if !out.ends_with('\n') {
-                    out.push('\n');
+            // A plain span (no data-math) falls through to generic passthrough handling below.
+        }
+
+        if is_end {
+            if let Some(pos) = stack.iter().rposition(|f| f.tag == lower) {
+                while stack.len() > pos {
+                    let frame = stack.pop().unwrap();
+                    close_frame(frame, &mut stack, &mut root);
                 }
             }
-            (false, "li") => {
-                if !out.ends_with('\n') {
-                    out.push('\n');
-                }
-                let indent = "  ".repeat(list_stack.len().saturating_sub(1));
-                out.push_str(&indent);
-                if let Some((ordered, n)) = list_stack.last_mut() {
-                    if *ordered {
-                        out.push_str(&format!("{n}. "));
-                        *n += 1;
-                    } else {
-                        out.push_str("- ");
-                    }
-                } else {
-                    out.push_str("- ");
-                }
+            // An end tag with no matching open frame is ignored rather than treated as an error.
+            i = gt + 1;
+            continue;
+        }
+
+        if self_close {
+            i = gt + 1;
+            continue;
+        }
+
+        let kind = match lower.as_str() {
+            "p" | "div" => OpenKind::Paragraph,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = lower[1..2].parse().unwrap_or(1);
+                let id = attr_val(rest, "id");
+                let classes = attr_val(rest, "class")
+                    .map(|c| c.split_whitespace().map(String::from).collect())
+                    .unwrap_or_default();
+                OpenKind::Heading { level, id, classes }
             }
-            (true, "li") => {
-                if !out.ends_with('\n') {
-                    out.push('\n');
-                }
+            "ul" => OpenKind::List { ordered: false },
+            "ol" => OpenKind::List { ordered: true },
+            "li" => OpenKind::Item { checked: None },
+            "a" => {
+                let href = attr_val(rest, "href")
+                    .map(|v| decode_entities(&v))
+                    .and_then(|v| sanitize_href(&v));
+                OpenKind::Link { href }
             }
-            (false, "pre") => {
-                if !out.ends_with('\n') {
-                    out.push('\n');
-                }
-                out.push_str("```");
-                out.push('\n');
-                pre_depth += 1;
+            "strong" | "b" => OpenKind::Strong,
+            "em" | "i" => OpenKind::Emphasis,
+            "del" | "s" | "strike" => OpenKind::Strikethrough,
+            "sup" => OpenKind::Sup,
+            _ => OpenKind::Passthrough,
+        };
+        stack.push(OpenTag { tag: lower, kind, children: Vec::new() });
+        i = gt + 1;
+    }
+
+    // Unclosed tags at end of input: flush them innermost-first so a partial tree still renders.
+    while let Some(frame) = stack.pop() {
+        close_frame(frame, &mut stack, &mut root);
+    }
+
+    root
+}
+
+/// A node that should sit on its own block, separated from its siblings by a blank line - decided
+/// purely from the node's own kind, never by peeking at the string rendered so far.
+fn is_block(node: &Node) -> bool {
+    matches!(
+        node,
+        Node::Paragraph(_)
+            | Node::Heading { .. }
+            | Node::List { .. }
+            | Node::CodeBlock { .. }
+            | Node::Table { .. }
+            | Node::Math { display: true, .. }
+            | Node::FootnoteDef { .. }
+    )
+}
+
+fn render_nodes(nodes: &[Node], indent: &str) -> String {
+    let mut out = String::new();
+    let mut prev_was_block = false;
+    for node in nodes {
+        if matches!(node, Node::Text(t) if t.trim().is_empty()) {
+            continue;
+        }
+        let block = is_block(node);
+        if block && prev_was_block {
+            out.push_str("\n\n");
+        }
+        render_node(node, indent, &mut out);
+        prev_was_block = block;
+    }
+    out
+}
+
+fn render_node(node: &Node, indent: &str, out: &mut String) {
+    match node {
+        Node::Text(s) => out.push_str(s),
+        Node::LineBreak => out.push('\n'),
+        Node::Strong(children) => {
+            out.push_str("**");
+            out.push_str(&render_nodes(children, indent));
+            out.push_str("**");
+        }
+        Node::Emphasis(children) => {
+            out.push('*');
+            out.push_str(&render_nodes(children, indent));
+            out.push('*');
+        }
+        Node::Strikethrough(children) => {
+            out.push_str("~~");
+            out.push_str(&render_nodes(children, indent));
+            out.push_str("~~");
+        }
+        Node::Link { href, children } => {
+            let text = collect_text(children);
+            let text = text.trim();
+            match href {
+                Some(href) => out.push_str(&md_format_link(text, href)),
+                None => out.push_str(text),
             }
-            (true, "pre") => {
-                if pre_depth > 0 {
-                    pre_depth -= 1;
-                }
-                if !out.ends_with('\n') {
-                    out.push('\n');
-                }
-                out.push_str("```");
-                out.push('\n');
+        }
+        Node::Math { display, tex } => {
+            if *display {
+                out.push_str("$$");
+                out.push_str(tex);
+                out.push_str("$$");
+            } else {
+                out.push('$');
+                out.push_str(tex);
+                out.push('$');
             }
-            (false, "code") => {
-                if pre_depth == 0 {
-                    out.push('`');
-                }
-                code_depth += 1;
+        }
+        Node::CodeBlock { lang, text } => {
+            out.push_str("```");
+            if let Some(lang) = lang {
+                out.push_str(lang);
             }
-            (true, "code") => {
-                if code_depth > 0 {
-                    code_depth -= 1;
-                }
-                if pre_depth == 0 {
-                    out.push('`');
+            out.push('\n');
+            out.push_str(text.trim_matches('\n'));
+            out.push('\n');
+            out.push_str("```");
+        }
+        Node::Table { markdown } => out.push_str(markdown.trim_end_matches('\n')),
+        Node::Paragraph(children) => out.push_str(render_nodes(children, indent).trim()),
+        Node::Heading { level, id, classes, children } => {
+            out.push_str(&"#".repeat((*level) as usize));
+            out.push(' ');
+            out.push_str(collect_text(children).trim());
+            if id.is_some() || !classes.is_empty() {
+                let mut attrs: Vec<String> = Vec::new();
+                if let Some(id) = id {
+                    attrs.push(format!("#{id}"));
                 }
+                attrs.extend(classes.iter().map(|c| format!(".{c}")));
+                out.push_str(" {");
+                out.push_str(&attrs.join(" "));
+                out.push('}');
             }
-            (false, "a") => {
-                let href = attr_val(rest, "href")
-                    .map(|v| decode_entities(&v))
-                    .and_then(|v| sanitize_href(&v));
-                link_stack.push(href);
-                link_text_stack.push(String::new());
-            }
-            (true, "a") => {
-                let href = link_stack.pop().flatten();
-                let text_raw = link_text_stack.pop().unwrap_or_default();
-                let text = decode_entities(&text_raw);
-                if let Some(href) = href {
-                    out.push_str(&md_format_link(text.trim(), &href));
+        }
+        Node::List { ordered, items } => {
+            let mut n = 1u32;
+            let mut first = true;
+            for item in items {
+                let Node::Item { checked, children } = item else {
+                    continue;
+                };
+                if !first {
+                    out.push('\n');
+                }
+                first = false;
+                out.push_str(indent);
+                if *ordered {
+                    out.push_str(&format!("{n}. "));
+                    n += 1;
                 } else {
-                    out.push_str(text.trim());
+                    out.push_str("- ");
                 }
-            }
-            (false, "span") => {
-                let dm = attr_val(rest, "data-math").map(|v| decode_entities(&v));
-                if let Some(tex) = dm {
-                    let class = attr_val(rest, "class").unwrap_or_default();
-                    let display = class.to_ascii_lowercase().contains("math-block");
-                    if display {
-                        if !out.ends_with("\n\n") {
-                            out.push('\n');
-                            out.push('\n');
-                        }
-                        out.push_str("$$");
-                        out.push_str(&tex);
-                        out.push_str("$$");
-                        out.push('\n');
-                        out.push('\n');
-                    } else {
-                        out.push('$');
-                        out.push_str(&tex);
-                        out.push('$');
-                    }
-                    // Skip the rendered subtree inside this math span.
-                    skip_depth = 1;
+                if let Some(checked) = checked {
+                    out.push_str(if *checked { "[x] " } else { "[ ] " });
                 }
+                let child_indent = format!("{indent}  ");
+                out.push_str(render_nodes(children, &child_indent).trim_start());
             }
-            _ => {}
         }
+        Node::Item { checked, children } => {
+            // Only reached for a bare `<li>` outside any `<ul>`/`<ol>` (`List` renders its own
+            // items directly above) - still emit a bullet so the content isn't silently dropped.
+            out.push_str("- ");
+            if let Some(checked) = checked {
+                out.push_str(if *checked { "[x] " } else { "[ ] " });
+            }
+            out.push_str(render_nodes(children, indent).trim());
+        }
+        Node::FootnoteRef(label) => {
+            out.push_str("[^");
+            out.push_str(label);
+            out.push(']');
+        }
+        Node::FootnoteDef { label, children } => {
+            out.push_str("[^");
+            out.push_str(label);
+            out.push_str("]: ");
+            out.push_str(render_nodes(children, indent).trim());
+        }
+    }
+}
 
-        // This keeps escaping/link formatting deterministic.
-        i = gt + 1;
+/// Renders a tree from [`parse_html_nodes`] (optionally walked/post-processed via [`walk`] first)
+/// back to Markdown.
+pub fn render_markdown(nodes: &[Node]) -> String {
+    render_nodes(nodes, "").trim().to_string()
+}
+
+/// Same as [`html_to_markdown_text`], but lets a caller drop rustdoc-style hidden `# ` setup lines
+/// from fenced code blocks instead of keeping them with their prefix stripped.
+pub fn html_to_markdown_text_with_options(input: &str, hide_rustdoc_hidden_lines: bool) -> String {
+    // Conservative HTML -> Markdown converter, tuned for our clipboard payloads/examples.
+    // Determinism goals:
+    // - Preserve fenced code blocks and inline code
+    // - Preserve links
+    // - Preserve LaTeX from data-math attributes when present
+    let nodes = parse_html_nodes(input, hide_rustdoc_hidden_lines);
+    let rendered = render_markdown(&nodes);
+    decode_entities(rendered.replace("\r\n", "\n").as_str())
+}
+
+pub fn html_to_markdown_text(input: &str) -> String {
+    html_to_markdown_text_with_options(input, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_pipe_table_with_per_column_alignment() {
+        let html = "<table><thead><tr><th align=\"left\">Name</th><th align=\"center\">Qty</th></tr></thead><tbody><tr><td>Pen</td><td>3</td></tr></tbody></table>";
+        let md = html_to_markdown_text(html);
+        assert_eq!(md, "| Name | Qty |\n| :--- | :---: |\n| Pen | 3 |");
+    }
+
+    #[test]
+    fn table_round_trips_through_markdown_to_html() {
+        let md = "| Name | Qty |\n| :--- | :---: |\n| Pen | 3 |\n";
+        let html = markdown_to_html_string(md);
+        assert!(html.contains("<table>"));
+        assert!(html.contains("align=\"left\""));
+        assert!(html.contains("align=\"center\""));
+    }
+
+    #[test]
+    fn strikethrough_maps_to_tilde_tilde() {
+        assert_eq!(html_to_markdown_text("<p><del>gone</del></p>"), "~~gone~~");
+        assert_eq!(markdown_to_html_string("~~gone~~"), "<p><del>gone</del></p>\n");
+    }
+
+    #[test]
+    fn task_list_items_round_trip_checked_state() {
+        let html = "<ul><li><input disabled=\"\" type=\"checkbox\"/> todo</li><li><input disabled=\"\" type=\"checkbox\" checked=\"\"/> done</li></ul>";
+        let md = html_to_markdown_text(html);
+        assert!(md.contains("- [ ] todo"));
+        assert!(md.contains("- [x] done"));
+    }
+
+    #[test]
+    fn passthrough_tag_keeps_its_children() {
+        // <span>/<u>/<mark>/... aren't given special meaning (`OpenKind::Passthrough`), so their
+        // wrapper is dropped on close - but their children must still make it into the output
+        // rather than being dropped along with it.
+        let html = "<p>before <span>middle <b>bold</b></span> after</p>";
+        let md = html_to_markdown_text(html);
+        assert_eq!(md, "before middle **bold** after");
+    }
+
+    #[test]
+    fn table_cells_convert_inline_content_recursively_and_escape_pipes() {
+        let html = "<table><tr><th>Name</th><th>Note</th></tr><tr><td><b>Pen</b></td><td>a | b</td></tr></table>";
+        let md = html_to_markdown_text(html);
+        assert_eq!(md, "| Name | Note |\n| --- | --- |\n| **Pen** | a \\| b |");
+    }
+
+    #[test]
+    fn code_fence_keeps_language_from_code_class() {
+        let html = "<pre><code class=\"language-rust\">fn main() {}</code></pre>";
+        let md = html_to_markdown_text(html);
+        assert_eq!(md, "```rust\nfn main() {}\n```");
+    }
+
+    #[test]
+    fn code_fence_reads_language_from_pre_data_lang() {
+        let html = "<pre data-lang=\"python\"><code>print(1)</code></pre>";
+        let md = html_to_markdown_text(html);
+        assert_eq!(md, "```python\nprint(1)\n```");
+    }
+
+    #[test]
+    fn rustdoc_hidden_lines_are_kept_with_prefix_stripped_by_default() {
+        let html = "<pre><code class=\"language-rust\"># fn main() {\nlet x = 1;\n# }</code></pre>";
+        let md = html_to_markdown_text(html);
+        assert_eq!(md, "```rust\nfn main() {\nlet x = 1;\n}\n```");
+    }
+
+    #[test]
+    fn rustdoc_hidden_lines_can_be_dropped() {
+        let html = "<pre><code class=\"language-rust\"># fn main() {\nlet x = 1;\n# }</code></pre>";
+        let md = html_to_markdown_text_with_options(html, true);
+        assert_eq!(md, "```rust\nlet x = 1;\n```");
+    }
+
+    #[test]
+    fn rustdoc_double_hash_escape_keeps_a_literal_hash() {
+        let html = "<pre><code class=\"language-rust\">## comment, not hidden</code></pre>";
+        let md = html_to_markdown_text(html);
+        assert_eq!(md, "```rust\n# comment, not hidden\n```");
+    }
+
+    #[test]
+    fn heading_anchor_attributes_round_trip_through_markdown() {
+        let md = "## Title {#my-id .note}\n";
+        let html = markdown_to_html_string(md);
+        assert!(html.contains("<h2 id=\"my-id\" class=\"note\">"));
+        let back = html_to_markdown_text(&html);
+        assert_eq!(back, "## Title {#my-id .note}");
+    }
+
+    #[test]
+    fn heading_without_attributes_renders_without_trailing_block() {
+        let md = html_to_markdown_text("<h1>Plain</h1>");
+        assert_eq!(md, "# Plain");
+    }
+
+    #[test]
+    fn plain_list_items_are_unaffected_by_checkbox_handling() {
+        let html = "<ul><li>One</li><li>Two</li></ul>";
+        let md = html_to_markdown_text(html);
+        assert!(md.contains("- One"));
+        assert!(md.contains("- Two"));
+    }
+
+    #[test]
+    fn headings_get_unique_slugged_ids() {
+        let md = "# Intro\n\n## Intro\n\n## Getting Started!\n";
+        let html = markdown_to_html_with_toc(md);
+        assert!(html.contains("<h1 id=\"intro\">Intro</h1>"));
+        assert!(html.contains("<h2 id=\"intro-1\">Intro</h2>"));
+        assert!(html.contains("<h2 id=\"getting-started\">Getting Started!</h2>"));
+    }
+
+    #[test]
+    fn toc_marker_expands_into_nested_links() {
+        let md = "[TOC]\n\n# Top\n\n## Sub One\n\n## Sub Two\n";
+        let html = markdown_to_html_with_toc(md);
+        assert!(!html.contains("[TOC]"));
+        assert!(html.contains("<a href=\"#top\">Top</a>"));
+        assert!(html.contains("<a href=\"#sub-one\">Sub One</a>"));
+        assert!(html.contains("<a href=\"#sub-two\">Sub Two</a>"));
+        let top_idx = html.find("<a href=\"#top\"").unwrap();
+        let sub_idx = html.find("<a href=\"#sub-one\"").unwrap();
+        assert!(top_idx < sub_idx);
+    }
+
+    #[test]
+    fn document_without_toc_marker_still_gets_heading_ids_but_no_toc_list() {
+        let md = "# Solo Heading\n\nBody text.\n";
+        let html = markdown_to_html_with_toc(md);
+        assert!(html.contains("<h1 id=\"solo-heading\">Solo Heading</h1>"));
+        assert!(!html.contains("<ul>"));
     }
 
-    decode_entities(out.trim().replace("\r\n", "\n").as_str())
+    #[test]
+    fn footnote_reference_and_trailing_definition_round_trip_to_markdown() {
+        let html = "<p>Claim<sup class=\"footnote-reference\"><a href=\"#fn-note\">1</a></sup>.</p>\
+                    <section class=\"footnotes\"><ol><li id=\"fn-note\">\
+                    <p>Supporting detail. <a href=\"#fnref-note\" class=\"footnote-backref\">\u{21a9}</a></p>\
+                    </li></ol></section>";
+        let md = html_to_markdown_text(html);
+        assert_eq!(md, "Claim[^note].\n\n[^note]: Supporting detail.");
+    }
+
+    #[test]
+    fn footnote_definition_drops_the_back_reference_link_entirely() {
+        let html = "<section class=\"footnotes\"><ol><li id=\"footnote1\">\
+                    <p>Text <a href=\"#fnref1\">\u{21a9}</a></p></li></ol></section>";
+        let md = html_to_markdown_text(html);
+        assert_eq!(md, "[^1]: Text");
+    }
+
+    #[test]
+    fn a_plain_superscript_without_a_footnote_link_is_kept_as_inline_content() {
+        let html = "<p>x<sup>2</sup></p>";
+        let md = html_to_markdown_text(html);
+        assert_eq!(md, "x2");
+    }
 }
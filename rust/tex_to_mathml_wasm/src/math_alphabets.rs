@@ -0,0 +1,182 @@
+// Rewrites \mathbb{X}/\mathcal{X}/\mathfrak{X}/\mathbf{X}/\mathscr{X} into the corresponding
+// Unicode Mathematical Alphanumeric Symbols before latex2mathml ever sees them. latex2mathml
+// doesn't implement these style commands - a bare \mathcal{L} comes back as a
+// [PARSE ERROR: ...] <mtext>, losing the intended glyph - but it's perfectly happy with the plain
+// Unicode character, so turning "\mathcal{L}" into "ℒ" ahead of time keeps the intended symbol.
+
+/// `\mathbb` maps onto the double-struck block (U+1D538 upper / U+1D552 lower / U+1D7D8 digits),
+/// except the seven letters Unicode already assigned a dedicated Letterlike Symbols code point to,
+/// long before the astral math-alphabet blocks existed.
+fn mathbb(c: char) -> Option<char> {
+    let hole = match c {
+        'C' => Some('\u{2102}'),
+        'H' => Some('\u{210D}'),
+        'N' => Some('\u{2115}'),
+        'P' => Some('\u{2119}'),
+        'Q' => Some('\u{211A}'),
+        'R' => Some('\u{211D}'),
+        'Z' => Some('\u{2124}'),
+        _ => None,
+    };
+    if let Some(hole) = hole {
+        return Some(hole);
+    }
+    match c {
+        'A'..='Z' => char::from_u32(0x1D538 + (c as u32 - 'A' as u32)),
+        'a'..='z' => char::from_u32(0x1D552 + (c as u32 - 'a' as u32)),
+        '0'..='9' => char::from_u32(0x1D7D8 + (c as u32 - '0' as u32)),
+        _ => None,
+    }
+}
+
+/// `\mathcal`/`\mathscr` share the one Unicode "script" block (U+1D49C upper / U+1D4B6 lower) -
+/// Unicode never drew a calligraphic-vs-script distinction, so both commands resolve here. Eight
+/// uppercase letters again fall back to pre-existing Letterlike Symbols.
+fn mathcal(c: char) -> Option<char> {
+    let hole = match c {
+        'B' => Some('\u{212C}'),
+        'E' => Some('\u{2130}'),
+        'F' => Some('\u{2131}'),
+        'H' => Some('\u{210B}'),
+        'I' => Some('\u{2110}'),
+        'L' => Some('\u{2112}'),
+        'M' => Some('\u{2133}'),
+        'R' => Some('\u{211B}'),
+        _ => None,
+    };
+    if let Some(hole) = hole {
+        return Some(hole);
+    }
+    match c {
+        'A'..='Z' => char::from_u32(0x1D49C + (c as u32 - 'A' as u32)),
+        'a'..='z' => char::from_u32(0x1D4B6 + (c as u32 - 'a' as u32)),
+        _ => None,
+    }
+}
+
+/// `\mathfrak` maps onto the Fraktur block (U+1D504 upper / U+1D51E lower); five uppercase letters
+/// again have pre-existing Letterlike Symbols instead. Fraktur has no dedicated digit block, so
+/// digits are left unmapped.
+fn mathfrak(c: char) -> Option<char> {
+    let hole = match c {
+        'C' => Some('\u{212D}'),
+        'H' => Some('\u{210C}'),
+        'I' => Some('\u{2111}'),
+        'R' => Some('\u{211C}'),
+        'Z' => Some('\u{2128}'),
+        _ => None,
+    };
+    if let Some(hole) = hole {
+        return Some(hole);
+    }
+    match c {
+        'A'..='Z' => char::from_u32(0x1D504 + (c as u32 - 'A' as u32)),
+        'a'..='z' => char::from_u32(0x1D51E + (c as u32 - 'a' as u32)),
+        _ => None,
+    }
+}
+
+/// `\mathbf` maps onto the bold serif block (U+1D400 upper / U+1D41A lower / U+1D7CE digits); it
+/// has no Letterlike Symbols exceptions.
+fn mathbf(c: char) -> Option<char> {
+    match c {
+        'A'..='Z' => char::from_u32(0x1D400 + (c as u32 - 'A' as u32)),
+        'a'..='z' => char::from_u32(0x1D41A + (c as u32 - 'a' as u32)),
+        '0'..='9' => char::from_u32(0x1D7CE + (c as u32 - '0' as u32)),
+        _ => None,
+    }
+}
+
+fn mapper_for(command: &str) -> Option<fn(char) -> Option<char>> {
+    match command {
+        "mathbb" => Some(mathbb),
+        "mathcal" | "mathscr" => Some(mathcal),
+        "mathfrak" => Some(mathfrak),
+        "mathbf" => Some(mathbf),
+        _ => None,
+    }
+}
+
+/// Rewrites every `\mathbb{...}`/`\mathcal{...}`/`\mathfrak{...}`/`\mathbf{...}`/`\mathscr{...}`
+/// run in `latex` into the matching Unicode Mathematical Alphanumeric Symbols. A character the
+/// target alphabet has no mapping for (punctuation, a nested command, ...) is copied through
+/// unchanged rather than dropped. Braces aren't balanced beyond the first `}` - these commands are
+/// only ever seen wrapping a handful of plain identifiers or digits in practice, not nested groups.
+pub fn rewrite_math_alphabets(latex: &str) -> String {
+    let mut out = String::with_capacity(latex.len());
+    let mut rest = latex;
+
+    while let Some(backslash_rel) = rest.find('\\') {
+        out.push_str(&rest[..backslash_rel]);
+        let after_backslash = &rest[backslash_rel + 1..];
+        let name_end = after_backslash
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(after_backslash.len());
+        let command = &after_backslash[..name_end];
+
+        let Some(mapper) = mapper_for(command) else {
+            out.push('\\');
+            rest = after_backslash;
+            continue;
+        };
+
+        let after_command = &after_backslash[name_end..];
+        let Some(brace_rest) = after_command.strip_prefix('{') else {
+            out.push('\\');
+            out.push_str(command);
+            rest = after_command;
+            continue;
+        };
+        let Some(close_rel) = brace_rest.find('}') else {
+            out.push('\\');
+            out.push_str(command);
+            out.push('{');
+            rest = brace_rest;
+            continue;
+        };
+
+        let body = &brace_rest[..close_rel];
+        for ch in body.chars() {
+            out.push(mapper(ch).unwrap_or(ch));
+        }
+        rest = &brace_rest[close_rel + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mathbb_maps_plain_letters_and_digits() {
+        assert_eq!(rewrite_math_alphabets("\\mathbb{A}"), "\u{1D538}");
+        assert_eq!(rewrite_math_alphabets("\\mathbb{9}"), "\u{1D7E1}");
+    }
+
+    #[test]
+    fn mathbb_redirects_letterlike_holes() {
+        assert_eq!(rewrite_math_alphabets("\\mathbb{R}"), "\u{211D}");
+        assert_eq!(rewrite_math_alphabets("x \\in \\mathbb{R}"), "x \\in \u{211D}");
+    }
+
+    #[test]
+    fn mathcal_and_mathscr_share_the_script_block() {
+        assert_eq!(rewrite_math_alphabets("\\mathcal{L}"), "\u{2112}");
+        assert_eq!(rewrite_math_alphabets("\\mathscr{L}"), "\u{2112}");
+        assert_eq!(rewrite_math_alphabets("\\mathcal{A}"), "\u{1D49C}");
+    }
+
+    #[test]
+    fn mathfrak_and_mathbf_map_without_touching_unrelated_commands() {
+        assert_eq!(rewrite_math_alphabets("\\mathfrak{Z}"), "\u{2128}");
+        assert_eq!(rewrite_math_alphabets("\\mathbf{1}"), "\u{1D7CF}");
+        assert_eq!(rewrite_math_alphabets("\\alpha + \\mathbf{v}"), "\\alpha + \u{1D42F}");
+    }
+
+    #[test]
+    fn unmapped_characters_pass_through_unchanged() {
+        assert_eq!(rewrite_math_alphabets("\\mathbb{A+1}"), "\u{1D538}+\u{1D7D9}");
+    }
+}
@@ -0,0 +1,293 @@
+// MHTML ingestion: Word's "Save as Web Page" and many email clients emit a MIME
+// `multipart/related` envelope wrapping one `text/html` part plus image/resource parts the HTML
+// references via `cid:` URLs or their original `Content-Location`. This module unwraps that
+// envelope back into a single self-contained HTML string (every referenced part inlined as a
+// `data:` URI) suitable for the existing `html_to_office_prepared` pipeline. The MIME part header
+// parsing below mirrors the minimal approach `docx.rs` takes to OOXML - hand-rolled, tuned to the
+// shapes real producers (Word, Outlook, browsers) emit rather than the full RFC 2045 grammar.
+
+use std::collections::HashMap;
+
+// Mirrors the hand-rolled codec in `docx.rs` (itself mirroring `translation_wasm/src/utils.rs`) -
+// there's no shared crate for it and no `base64` dependency in this one.
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_decode(s: &str) -> Result<Vec<u8>, String> {
+    let clean: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if clean.len() % 4 != 0 || clean.is_empty() {
+        return Err("invalid base64 length".to_string());
+    }
+    let value_of = |b: u8| -> Result<u8, String> {
+        if b == b'=' {
+            Ok(0)
+        } else {
+            BASE64_ALPHABET
+                .iter()
+                .position(|&c| c == b)
+                .map(|p| p as u8)
+                .ok_or_else(|| format!("invalid base64 byte: {b}"))
+        }
+    };
+    let mut out = Vec::with_capacity(clean.len() / 4 * 3);
+    for chunk in clean.chunks(4) {
+        let b0 = value_of(chunk[0])?;
+        let b1 = value_of(chunk[1])?;
+        let b2 = value_of(chunk[2])?;
+        let b3 = value_of(chunk[3])?;
+        out.push((b0 << 2) | (b1 >> 4));
+        if chunk[2] != b'=' {
+            out.push((b1 << 4) | (b2 >> 2));
+        }
+        if chunk[3] != b'=' {
+            out.push((b2 << 6) | b3);
+        }
+    }
+    Ok(out)
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = *chunk.get(1).unwrap_or(&0);
+        let b2 = *chunk.get(2).unwrap_or(&0);
+        out.push(BASE64_ALPHABET[(b0 >> 2) as usize] as char);
+        out.push(BASE64_ALPHABET[(((b0 & 0x03) << 4) | (b1 >> 4)) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            BASE64_ALPHABET[(((b1 & 0x0f) << 2) | (b2 >> 6)) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 { BASE64_ALPHABET[(b2 & 0x3f) as usize] as char } else { '=' });
+    }
+    out
+}
+
+/// Decodes a `quoted-printable` body: `=XX` hex escapes become the literal byte, and a trailing
+/// `=` before a line break (a "soft" break) is dropped along with the break itself.
+fn decode_quoted_printable(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'=' {
+            out.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        if bytes[i + 1..].starts_with(b"\r\n") {
+            i += 3;
+        } else if bytes.get(i + 1) == Some(&b'\n') {
+            i += 2;
+        } else if i + 2 < bytes.len() {
+            match u8::from_str_radix(&s[i + 1..i + 3], 16) {
+                Ok(byte) => {
+                    out.push(byte);
+                    i += 3;
+                }
+                Err(_) => {
+                    out.push(b'=');
+                    i += 1;
+                }
+            }
+        } else {
+            out.push(b'=');
+            i += 1;
+        }
+    }
+    out
+}
+
+fn decode_body(body: &str, transfer_encoding: &str) -> Vec<u8> {
+    match transfer_encoding {
+        "quoted-printable" => decode_quoted_printable(body),
+        "base64" => base64_decode(body).unwrap_or_default(),
+        // 7bit/8bit/binary, or no Content-Transfer-Encoding header at all: the bytes are literal.
+        _ => body.as_bytes().to_vec(),
+    }
+}
+
+/// Splits a header block from the body that follows its first blank line, tolerating both CRLF
+/// and bare-LF line endings (real-world MHTML producers are inconsistent about this).
+fn split_headers_body(s: &str) -> (&str, &str) {
+    if let Some(idx) = s.find("\r\n\r\n") {
+        (&s[..idx], &s[idx + 4..])
+    } else if let Some(idx) = s.find("\n\n") {
+        (&s[..idx], &s[idx + 2..])
+    } else {
+        (s, "")
+    }
+}
+
+/// Parses a header block into a lowercased-name map, unfolding continuation lines (ones starting
+/// with a space or tab, per RFC 2045) onto the header they continue.
+fn parse_headers(raw: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+    for line in raw.lines() {
+        if (line.starts_with(' ') || line.starts_with('\t')) && current.is_some() {
+            let (_, val) = current.as_mut().unwrap();
+            val.push(' ');
+            val.push_str(line.trim());
+            continue;
+        }
+        if let Some((k, v)) = current.take() {
+            map.insert(k, v);
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            current = Some((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    if let Some((k, v)) = current {
+        map.insert(k, v);
+    }
+    map
+}
+
+/// Pulls the `boundary` parameter out of a top-level `Content-Type: multipart/related; boundary=...`
+/// header value, handling both the quoted and bare forms.
+fn extract_boundary(content_type: &str) -> Option<String> {
+    let low = content_type.to_ascii_lowercase();
+    let idx = low.find("boundary=")?;
+    let after = content_type[idx + "boundary=".len()..].trim_start();
+    if let Some(rest) = after.strip_prefix('"') {
+        let end = rest.find('"')?;
+        Some(rest[..end].to_string())
+    } else {
+        let end = after.find([';', ' ', '\t', '\r', '\n']).unwrap_or(after.len());
+        Some(after[..end].to_string())
+    }
+}
+
+struct MimePart {
+    headers: HashMap<String, String>,
+    bytes: Vec<u8>,
+}
+
+impl MimePart {
+    fn content_type(&self) -> String {
+        self.headers.get("content-type").cloned().unwrap_or_else(|| "application/octet-stream".to_string())
+    }
+
+    /// The MIME type without any `; charset=...`/`; name=...` parameters, as a `data:` URI needs.
+    fn mime(&self) -> String {
+        self.content_type().split(';').next().unwrap_or("application/octet-stream").trim().to_string()
+    }
+}
+
+/// Parses an MHTML (`multipart/related`) document into its constituent parts, decoding each body
+/// per its own `Content-Transfer-Encoding`.
+fn parse_parts(input: &str) -> Result<Vec<MimePart>, String> {
+    let (top_headers_raw, rest) = split_headers_body(input);
+    let top_headers = parse_headers(top_headers_raw);
+    let content_type = top_headers.get("content-type").cloned().unwrap_or_default();
+    let boundary = extract_boundary(&content_type).ok_or("missing multipart boundary")?;
+    let marker = format!("--{boundary}");
+
+    let mut parts = Vec::new();
+    for chunk in rest.split(&marker) {
+        let trimmed = chunk.trim_start_matches(['\r', '\n']);
+        // The preamble before the first boundary, and the `--boundary--` closing marker (whose
+        // remainder starts with "--"), aren't parts.
+        if trimmed.trim().is_empty() || trimmed.starts_with("--") {
+            continue;
+        }
+        let (headers_raw, body_raw) = split_headers_body(trimmed);
+        let headers = parse_headers(headers_raw);
+        let encoding = headers
+            .get("content-transfer-encoding")
+            .map(|v| v.to_ascii_lowercase())
+            .unwrap_or_default();
+        let bytes = decode_body(body_raw, &encoding);
+        parts.push(MimePart { headers, bytes });
+    }
+    Ok(parts)
+}
+
+/// Parses an MHTML `multipart/related` document and returns a single self-contained HTML string
+/// with every `cid:`/`Content-Location` reference the `text/html` part makes into another part
+/// rewritten as a `data:<mime>;base64,...` URI.
+pub fn mhtml_to_html(input: &str) -> Result<String, String> {
+    let parts = parse_parts(input)?;
+    let html_idx = parts
+        .iter()
+        .position(|p| p.mime().eq_ignore_ascii_case("text/html"))
+        .ok_or("no text/html part found in MHTML document")?;
+
+    let mut html = String::from_utf8_lossy(&parts[html_idx].bytes).into_owned();
+
+    for (i, part) in parts.iter().enumerate() {
+        if i == html_idx {
+            continue;
+        }
+        let data_uri = format!("data:{};base64,{}", part.mime(), base64_encode(&part.bytes));
+        if let Some(cid) = part.headers.get("content-id") {
+            let clean = cid.trim().trim_start_matches('<').trim_end_matches('>');
+            if !clean.is_empty() {
+                html = html.replace(&format!("cid:{clean}"), &data_uri);
+            }
+        }
+        if let Some(loc) = part.headers.get("content-location") {
+            let loc = loc.trim();
+            if !loc.is_empty() {
+                html = html.replace(loc, &data_uri);
+            }
+        }
+    }
+
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inlines_a_cid_referenced_image_as_a_data_uri() {
+        let mhtml = "Content-Type: multipart/related; boundary=\"BOUND\"\r\n\r\n\
+--BOUND\r\n\
+Content-Type: text/html; charset=\"utf-8\"\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+<html><body><img src=3D\"cid:image001\"></body></html>\r\n\
+--BOUND\r\n\
+Content-Type: image/png\r\n\
+Content-Transfer-Encoding: base64\r\n\
+Content-ID: <image001>\r\n\
+\r\n\
+AAAA\r\n\
+--BOUND--\r\n";
+        let html = mhtml_to_html(mhtml).unwrap();
+        assert!(html.contains("src=\"data:image/png;base64,AAAA\""));
+    }
+
+    #[test]
+    fn inlines_a_content_location_referenced_image() {
+        let mhtml = "Content-Type: multipart/related; boundary=BOUND\r\n\r\n\
+--BOUND\r\n\
+Content-Type: text/html\r\n\
+\r\n\
+<html><body><img src=\"file:///C:/doc/image1.png\"></body></html>\r\n\
+--BOUND\r\n\
+Content-Type: image/png\r\n\
+Content-Location: file:///C:/doc/image1.png\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+QUJD\r\n\
+--BOUND--\r\n";
+        let html = mhtml_to_html(mhtml).unwrap();
+        assert!(html.contains("data:image/png;base64,QUJD"));
+    }
+
+    #[test]
+    fn errors_when_no_html_part_is_present() {
+        let mhtml = "Content-Type: multipart/related; boundary=BOUND\r\n\r\n\
+--BOUND\r\n\
+Content-Type: image/png\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+QUJD\r\n\
+--BOUND--\r\n";
+        assert!(mhtml_to_html(mhtml).is_err());
+    }
+}
@@ -0,0 +1,419 @@
+// Mirrors the hand-rolled codec in `docx.rs`/`mhtml.rs` (itself mirroring
+// `translation_wasm/src/utils.rs`) - there's no shared crate for it and no `base64` dependency in
+// this one.
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let clean: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if clean.is_empty() || clean.len() % 4 != 0 {
+        return None;
+    }
+    let value_of = |b: u8| -> Option<u8> {
+        if b == b'=' {
+            Some(0)
+        } else {
+            BASE64_ALPHABET.iter().position(|&c| c == b).map(|p| p as u8)
+        }
+    };
+    let mut out = Vec::with_capacity(clean.len() / 4 * 3);
+    for chunk in clean.chunks(4) {
+        let b0 = value_of(chunk[0])?;
+        let b1 = value_of(chunk[1])?;
+        let b2 = value_of(chunk[2])?;
+        let b3 = value_of(chunk[3])?;
+        out.push((b0 << 2) | (b1 >> 4));
+        if chunk[2] != b'=' {
+            out.push((b1 << 4) | (b2 >> 2));
+        }
+        if chunk[3] != b'=' {
+            out.push((b2 << 6) | b3);
+        }
+    }
+    Some(out)
+}
+
+/// RFC 2047 "Q" encoding: like quoted-printable, but `_` stands in for a space (since the literal
+/// space is not allowed inside an encoded word).
+fn decode_q(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'_' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b'=' if i + 2 < bytes.len() => match u8::from_str_radix(&s[i + 1..i + 3], 16) {
+                Ok(b) => {
+                    out.push(b);
+                    i += 3;
+                }
+                Err(_) => {
+                    out.push(b'=');
+                    i += 1;
+                }
+            },
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Windows-1252 agrees with ISO-8859-1 everywhere except 0x80-0x9F, where it places punctuation
+/// (smart quotes, em dash, ellipsis, ...) that ISO-8859-1 leaves as C1 control codes. The handful
+/// of code points Windows never assigned in that range fall back to the raw byte, matching the
+/// lenient behavior most mail/browser decoders use rather than rendering U+FFFD.
+fn windows_1252_char(b: u8) -> char {
+    match b {
+        0x80 => '\u{20AC}',
+        0x82 => '\u{201A}',
+        0x83 => '\u{0192}',
+        0x84 => '\u{201E}',
+        0x85 => '\u{2026}',
+        0x86 => '\u{2020}',
+        0x87 => '\u{2021}',
+        0x88 => '\u{02C6}',
+        0x89 => '\u{2030}',
+        0x8A => '\u{0160}',
+        0x8B => '\u{2039}',
+        0x8C => '\u{0152}',
+        0x8E => '\u{017D}',
+        0x91 => '\u{2018}',
+        0x92 => '\u{2019}',
+        0x93 => '\u{201C}',
+        0x94 => '\u{201D}',
+        0x95 => '\u{2022}',
+        0x96 => '\u{2013}',
+        0x97 => '\u{2014}',
+        0x98 => '\u{02DC}',
+        0x99 => '\u{2122}',
+        0x9A => '\u{0161}',
+        0x9B => '\u{203A}',
+        0x9C => '\u{0153}',
+        0x9E => '\u{017E}',
+        0x9F => '\u{0178}',
+        _ => b as char,
+    }
+}
+
+fn transcode_to_utf8(charset: &str, bytes: &[u8]) -> String {
+    match charset.to_ascii_lowercase().as_str() {
+        "windows-1252" | "cp1252" | "x-cp1252" => bytes.iter().map(|&b| windows_1252_char(b)).collect(),
+        "iso-8859-1" | "iso8859-1" | "latin1" => bytes.iter().map(|&b| b as char).collect(),
+        // UTF-8 and anything unrecognized: treat the bytes as UTF-8, tolerating invalid sequences
+        // rather than failing the whole document over one bad encoded word.
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Attempts to decode one RFC 2047 encoded word (`=?charset?encoding?text?=`) starting exactly at
+/// `input[start..]`. Returns the decoded text and the byte offset right after the closing `?=`.
+fn try_decode_encoded_word(input: &str, start: usize) -> Option<(String, usize)> {
+    let s = &input[start..];
+    let rest = s.strip_prefix("=?")?;
+    let charset_end = rest.find('?')?;
+    let charset = &rest[..charset_end];
+    if charset.is_empty() || charset.contains(char::is_whitespace) {
+        return None;
+    }
+    let after_charset = &rest[charset_end + 1..];
+    let mut enc_chars = after_charset.char_indices();
+    let (_, enc_char) = enc_chars.next()?;
+    let encoding = enc_char.to_ascii_uppercase();
+    if encoding != 'Q' && encoding != 'B' {
+        return None;
+    }
+    let after_enc = &after_charset[enc_char.len_utf8()..];
+    let after_enc = after_enc.strip_prefix('?')?;
+    let term_rel = after_enc.find("?=")?;
+    let text = &after_enc[..term_rel];
+
+    let decoded_bytes = match encoding {
+        'Q' => decode_q(text),
+        'B' => base64_decode(text)?,
+        _ => unreachable!(),
+    };
+    let decoded = transcode_to_utf8(charset, &decoded_bytes);
+
+    let consumed = 2 + charset_end + 1 + enc_char.len_utf8() + 1 + term_rel + 2;
+    Some((decoded, start + consumed))
+}
+
+/// Decodes RFC 2047 "encoded words" (`=?charset?encoding?text?=`) that show up in attribute
+/// values and text pasted from mail-derived sources (MHTML, Outlook) - `encoding` is `Q`
+/// (quoted-printable-like, with `_` for space) or `B` (base64), and the decoded bytes are
+/// transcoded from `charset` (at minimum UTF-8, ISO-8859-1, and Windows-1252) to UTF-8. Per RFC
+/// 2047 section 6.2, adjacent encoded words separated only by whitespace are concatenated with
+/// that whitespace dropped, so this also swallows whitespace that sits between two matches.
+pub fn decode_encoded_words(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+    let mut last_was_encoded_word = false;
+    while i < input.len() {
+        if let Some((decoded, next)) = try_decode_encoded_word(input, i) {
+            out.push_str(&decoded);
+            i = next;
+            last_was_encoded_word = true;
+            continue;
+        }
+        let ch = input[i..].chars().next().unwrap();
+        if last_was_encoded_word && ch.is_whitespace() {
+            let ws_end = input[i..]
+                .find(|c: char| !c.is_whitespace())
+                .map(|p| i + p)
+                .unwrap_or(input.len());
+            if try_decode_encoded_word(input, ws_end).is_some() {
+                i = ws_end;
+                continue;
+            }
+        }
+        out.push(ch);
+        last_was_encoded_word = false;
+        i += ch.len_utf8();
+    }
+    out
+}
+
+/// Single-character Unicode-to-LaTeX substitutions, covering symbols that commonly appear in math
+/// pasted from a rendered source (a PDF, a web page, another editor's output) rather than typed as
+/// LaTeX source. Greek uppercase is limited to the eleven letters with a dedicated LaTeX macro -
+/// Alpha, Beta, Epsilon, Zeta, Eta, Iota, Kappa, Mu, Nu, Omicron, Rho, Tau and Chi are visually
+/// identical to Latin letters and plain LaTeX defines no `\Alpha` etc. for them, so they pass
+/// through unmapped rather than expanding to a command that doesn't exist.
+fn unicode_math_symbol(c: char) -> Option<&'static str> {
+    match c {
+        // Carried over from the old hardcoded replacements.
+        '\u{2016}' => Some("||"), // ‖, double vertical bar (norm)
+        '\u{E020}' => Some("\\neq"), // PUA glyph sometimes used for "not equals"
+        '\u{2297}' => Some("\\otimes"), // ⊗
+        '\u{03F5}' => Some("\\epsilon"), // ϵ, lunate epsilon symbol
+        '\u{03D5}' => Some("\\phi"), // ϕ, straight phi symbol
+        '\u{2192}' => Some("\\to"), // →
+        '\u{2260}' => Some("\\neq"), // ≠
+        '\u{27E8}' => Some("\\langle"), // ⟨
+        '\u{27E9}' => Some("\\rangle"), // ⟩
+
+        // Greek lowercase.
+        '\u{03B1}' => Some("\\alpha"),
+        '\u{03B2}' => Some("\\beta"),
+        '\u{03B3}' => Some("\\gamma"),
+        '\u{03B4}' => Some("\\delta"),
+        '\u{03B5}' => Some("\\varepsilon"), // ε, rounded epsilon (distinct from ϵ above)
+        '\u{03B6}' => Some("\\zeta"),
+        '\u{03B7}' => Some("\\eta"),
+        '\u{03B8}' => Some("\\theta"),
+        '\u{03B9}' => Some("\\iota"),
+        '\u{03BA}' => Some("\\kappa"),
+        '\u{03BB}' => Some("\\lambda"),
+        '\u{03BC}' => Some("\\mu"),
+        '\u{03BD}' => Some("\\nu"),
+        '\u{03BE}' => Some("\\xi"),
+        '\u{03C0}' => Some("\\pi"),
+        '\u{03C1}' => Some("\\rho"),
+        '\u{03C2}' => Some("\\varsigma"), // ς, final-position sigma
+        '\u{03C3}' => Some("\\sigma"),
+        '\u{03C4}' => Some("\\tau"),
+        '\u{03C5}' => Some("\\upsilon"),
+        '\u{03C6}' => Some("\\varphi"), // φ, rounded phi (distinct from ϕ above)
+        '\u{03C7}' => Some("\\chi"),
+        '\u{03C8}' => Some("\\psi"),
+        '\u{03C9}' => Some("\\omega"),
+
+        // Greek uppercase with a distinct LaTeX macro.
+        '\u{0393}' => Some("\\Gamma"),
+        '\u{0394}' => Some("\\Delta"),
+        '\u{0398}' => Some("\\Theta"),
+        '\u{039B}' => Some("\\Lambda"),
+        '\u{039E}' => Some("\\Xi"),
+        '\u{03A0}' => Some("\\Pi"),
+        '\u{03A3}' => Some("\\Sigma"),
+        '\u{03A5}' => Some("\\Upsilon"),
+        '\u{03A6}' => Some("\\Phi"),
+        '\u{03A8}' => Some("\\Psi"),
+        '\u{03A9}' => Some("\\Omega"),
+
+        // Operators and relations.
+        '\u{00B1}' => Some("\\pm"),
+        '\u{00D7}' => Some("\\times"),
+        '\u{00B7}' => Some("\\cdot"),
+        '\u{2264}' => Some("\\leq"),
+        '\u{2265}' => Some("\\geq"),
+        '\u{2248}' => Some("\\approx"),
+        '\u{2208}' => Some("\\in"),
+        '\u{2209}' => Some("\\notin"),
+        '\u{2200}' => Some("\\forall"),
+        '\u{2203}' => Some("\\exists"),
+
+        // Arrows.
+        '\u{21A6}' => Some("\\mapsto"),
+        '\u{21D2}' => Some("\\Rightarrow"),
+        '\u{21D4}' => Some("\\iff"),
+
+        // Big operators.
+        '\u{2211}' => Some("\\sum"),
+        '\u{220F}' => Some("\\prod"),
+        '\u{222B}' => Some("\\int"),
+        '\u{2207}' => Some("\\nabla"),
+        '\u{2202}' => Some("\\partial"),
+
+        // Blackboard-bold letters - Unicode assigned these their own Letterlike Symbols code
+        // points long before the astral math-alphabet blocks existed, same story as `mathbb` in
+        // math_alphabets.rs.
+        '\u{211D}' => Some("\\mathbb{R}"),
+        '\u{2102}' => Some("\\mathbb{C}"),
+        '\u{2124}' => Some("\\mathbb{Z}"),
+        '\u{2115}' => Some("\\mathbb{N}"),
+        '\u{211A}' => Some("\\mathbb{Q}"),
+
+        _ => None,
+    }
+}
+
+/// Returns `0..=9` for a Unicode superscript digit (`⁰`-`⁹`), which Unicode didn't lay out
+/// contiguously - `¹`, `²` and `³` live in Latin-1 Supplement, the rest in General Punctuation.
+fn superscript_digit(c: char) -> Option<u8> {
+    match c {
+        '\u{2070}' => Some(0),
+        '\u{00B9}' => Some(1),
+        '\u{00B2}' => Some(2),
+        '\u{00B3}' => Some(3),
+        '\u{2074}'..='\u{2079}' => Some(4 + (c as u32 - 0x2074) as u8),
+        _ => None,
+    }
+}
+
+/// Returns `0..=9` for a Unicode subscript digit (`₀`-`₉`); unlike superscripts these are one
+/// contiguous block.
+fn subscript_digit(c: char) -> Option<u8> {
+    match c {
+        '\u{2080}'..='\u{2089}' => Some((c as u32 - 0x2080) as u8),
+        _ => None,
+    }
+}
+
+/// Coalesces runs of Unicode super/subscript digits into a single grouped LaTeX exponent/index
+/// (`x²³` -> `x^{23}`, not `x^{2}^{3}`) rather than converting each character in isolation.
+fn group_super_subscripts(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::with_capacity(s.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let (prefix, digit_at) = if superscript_digit(chars[i]).is_some() {
+            ("^{", superscript_digit as fn(char) -> Option<u8>)
+        } else if subscript_digit(chars[i]).is_some() {
+            ("_{", subscript_digit as fn(char) -> Option<u8>)
+        } else {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        };
+        let mut run = String::new();
+        while let Some(digit) = chars.get(i).and_then(|&c| digit_at(c)) {
+            run.push((b'0' + digit) as char);
+            i += 1;
+        }
+        out.push_str(prefix);
+        out.push_str(&run);
+        out.push('}');
+    }
+    out
+}
+
+pub fn normalize_latex(latex: &str) -> String {
+    let mut s = String::from(latex);
+    // Remove zero-width characters that often sneak into copied math identifiers.
+    s.retain(|c| {
+        !matches!(
+            c,
+            '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}'
+        )
+    });
+    let mut mapped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match unicode_math_symbol(c) {
+            Some(command) => mapped.push_str(command),
+            None => mapped.push(c),
+        }
+    }
+    group_super_subscripts(&mapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_a_q_encoded_word_with_underscore_spaces() {
+        assert_eq!(decode_encoded_words("=?UTF-8?Q?Caf=C3=A9_menu?="), "Café menu");
+    }
+
+    #[test]
+    fn decodes_a_b_encoded_word() {
+        assert_eq!(decode_encoded_words("=?UTF-8?B?SGVsbG8=?="), "Hello");
+    }
+
+    #[test]
+    fn concatenates_adjacent_encoded_words_dropping_the_whitespace_between_them() {
+        let input = "=?UTF-8?Q?Hello=2C_?= =?UTF-8?Q?World!?=";
+        assert_eq!(decode_encoded_words(input), "Hello, World!");
+    }
+
+    #[test]
+    fn leaves_unrelated_whitespace_and_plain_text_untouched() {
+        assert_eq!(decode_encoded_words("plain text =?UTF-8?Q?word?= more text"), "plain text word more text");
+        assert_eq!(decode_encoded_words("no encoded words here"), "no encoded words here");
+    }
+
+    #[test]
+    fn transcodes_windows_1252_smart_quotes() {
+        assert_eq!(decode_encoded_words("=?windows-1252?Q?It=92s?="), "It\u{2019}s");
+    }
+
+    #[test]
+    fn zero_width_characters_are_stripped_before_the_symbol_table_applies() {
+        assert_eq!(normalize_latex("\u{200B}\u{03B1}\u{FEFF}"), "\\alpha");
+    }
+
+    #[test]
+    fn maps_greek_letters_including_existing_variant_forms() {
+        assert_eq!(normalize_latex("\u{03B1}\u{0393}"), "\\alpha\\Gamma");
+        assert_eq!(normalize_latex("\u{03F5}\u{03D5}"), "\\epsilon\\phi");
+        assert_eq!(normalize_latex("\u{03B5}\u{03C6}"), "\\varepsilon\\varphi");
+    }
+
+    #[test]
+    fn uppercase_greek_letters_identical_to_latin_pass_through_unmapped() {
+        assert_eq!(normalize_latex("\u{0391}"), "\u{0391}"); // Α (Alpha)
+    }
+
+    #[test]
+    fn maps_operators_relations_and_arrows() {
+        assert_eq!(
+            normalize_latex("a \u{00B1} b \u{2264} c \u{2208} d \u{21A6} e"),
+            "a \\pm b \\leq c \\in d \\mapsto e"
+        );
+    }
+
+    #[test]
+    fn maps_big_operators_and_blackboard_bold_letters() {
+        assert_eq!(normalize_latex("\u{2211}\u{222B}\u{211D}\u{2124}"), "\\sum\\int\\mathbb{R}\\mathbb{Z}");
+    }
+
+    #[test]
+    fn groups_consecutive_superscript_and_subscript_digits() {
+        assert_eq!(normalize_latex("x\u{00B2}\u{00B3}"), "x^{23}");
+        assert_eq!(normalize_latex("a\u{2081}\u{2082}"), "a_{12}");
+    }
+
+    #[test]
+    fn normalize_latex_is_idempotent() {
+        let input = "x\u{00B2}\u{00B3} + \u{03B1} \u{2264} \u{211D} \u{200B}";
+        let once = normalize_latex(input);
+        let twice = normalize_latex(&once);
+        assert_eq!(once, twice);
+    }
+}
@@ -1,3 +1,12 @@
+/// Opt-in sibling to `html_to_office_html` that also syntax-highlights any `<pre><code
+/// class="language-XXX">`-tagged block before the usual Office styling pass runs - kept as a
+/// separate entry point (rather than a bool parameter) since loading syntect's bundled syntaxes
+/// and theme isn't free, and most callers don't want to pay for it on every conversion.
+pub fn html_to_office_html_highlighted(input: &str) -> String {
+    let highlighted = crate::highlight::highlight_tagged_code_blocks(input);
+    html_to_office_html(&highlighted)
+}
+
 pub fn html_to_office_html(input: &str) -> String {
     // "Transition table" (HTML -> Office-friendly HTML):
     // - Normalize semantic tags to stable equivalents
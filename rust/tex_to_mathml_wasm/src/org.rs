@@ -0,0 +1,195 @@
+// Org-mode input front end. Modeled on the element categories an Org parser like `orgize` would
+// expose (headline, block, table, paragraph) but walked by hand line-by-line directly against
+// this crate's existing placeholder machinery, since headline/table/block structure and math
+// extraction need to be interleaved as we go. Math extraction itself is NOT reimplemented here:
+// it delegates to `pipeline::emit_text_with_tex_placeholders`, the same routine the sanitized-HTML
+// front end uses, so `$...$`, `$$...$$`, `\(...\)` and `\[...\]` behave identically everywhere.
+
+use crate::office::html_to_office_html;
+use crate::pipeline::{emit_text_with_tex_placeholders, PrepareOptions, PreparedOffice, TexJob};
+
+fn esc_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum BlockKind {
+    Src,
+    Example,
+}
+
+/// Lines of a `#+BEGIN_SRC`/`#+BEGIN_EXAMPLE` header take an optional language/args tail
+/// (`#+BEGIN_SRC rust`), which we don't otherwise use but still need to recognize and skip.
+fn block_open(line: &str) -> Option<BlockKind> {
+    let lower = line.trim_start().to_ascii_lowercase();
+    if lower.starts_with("#+begin_src") {
+        Some(BlockKind::Src)
+    } else if lower.starts_with("#+begin_example") {
+        Some(BlockKind::Example)
+    } else {
+        None
+    }
+}
+
+fn block_close(line: &str, kind: BlockKind) -> bool {
+    let lower = line.trim().to_ascii_lowercase();
+    match kind {
+        BlockKind::Src => lower == "#+end_src",
+        BlockKind::Example => lower == "#+end_example",
+    }
+}
+
+/// `* Headline` / `** Sub-headline` etc; returns the star count (clamped to the 6 levels HTML
+/// headings support) and the headline text.
+fn headline(line: &str) -> Option<(u8, &str)> {
+    let stars = line.chars().take_while(|&c| c == '*').count();
+    if stars == 0 {
+        return None;
+    }
+    let rest = &line[stars..];
+    if !rest.starts_with(' ') {
+        return None;
+    }
+    Some((stars.min(6) as u8, rest.trim()))
+}
+
+/// `| a | b | c |`-style row. A separator row (`|---+---|`) is reported as `None` cells so the
+/// caller can use it to distinguish a header row from the body without rendering it.
+fn table_row(line: &str) -> Option<Option<Vec<&str>>> {
+    let t = line.trim();
+    if !t.starts_with('|') {
+        return None;
+    }
+    let inner = t.trim_start_matches('|').trim_end_matches('|');
+    if inner.chars().all(|c| c == '-' || c == '+' || c == ':') {
+        return Some(None);
+    }
+    Some(Some(inner.split('|').map(|cell| cell.trim()).collect()))
+}
+
+fn flush_paragraph(html: &mut String, buf: &mut String, jobs: &mut Vec<TexJob>, options: &PrepareOptions) {
+    if buf.is_empty() {
+        return;
+    }
+    html.push_str("<p>");
+    emit_text_with_tex_placeholders(html, buf, jobs, options);
+    html.push_str("</p>");
+    buf.clear();
+}
+
+fn flush_table(
+    html: &mut String,
+    rows: &mut Vec<Option<Vec<&str>>>,
+    jobs: &mut Vec<TexJob>,
+    options: &PrepareOptions,
+) {
+    if rows.is_empty() {
+        return;
+    }
+    html.push_str("<table>");
+    // Org convention: a header row is followed by a `|---+---|` separator. Render the first row
+    // as `<th>` only when such a separator is actually present among the buffered rows.
+    let has_header = rows.iter().any(|r| r.is_none());
+    for (i, row) in rows.drain(..).enumerate() {
+        let cells = match row {
+            Some(cells) => cells,
+            None => continue,
+        };
+        let is_header = has_header && i == 0;
+        html.push_str("<tr>");
+        let tag = if is_header { "th" } else { "td" };
+        for cell in cells {
+            html.push('<');
+            html.push_str(tag);
+            html.push('>');
+            emit_text_with_tex_placeholders(html, cell, jobs, options);
+            html.push_str("</");
+            html.push_str(tag);
+            html.push('>');
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</table>");
+}
+
+/// Converts `org` to the Office-ready HTML + `TexJob` list per `options`, reusing
+/// `office::html_to_office_html` and `pipeline::office_apply_mathml` for the rest of the backend
+/// unchanged.
+pub fn org_to_office_prepared(org: &str, options: &PrepareOptions) -> PreparedOffice {
+    let mut jobs: Vec<TexJob> = Vec::new();
+    let mut html = String::new();
+    let mut paragraph = String::new();
+    let mut table_rows: Vec<Option<Vec<&str>>> = Vec::new();
+    let mut block: Option<BlockKind> = None;
+
+    for line in org.lines() {
+        if let Some(kind) = block {
+            if block_close(line, kind) {
+                html.push_str("</code></pre>");
+                block = None;
+            } else {
+                html.push_str(&esc_text(line));
+                html.push('\n');
+            }
+            continue;
+        }
+
+        if let Some(kind) = block_open(line) {
+            flush_paragraph(&mut html, &mut paragraph, &mut jobs, options);
+            flush_table(&mut html, &mut table_rows, &mut jobs, options);
+            html.push_str("<pre><code>");
+            block = Some(kind);
+            continue;
+        }
+
+        if let Some(row) = table_row(line) {
+            flush_paragraph(&mut html, &mut paragraph, &mut jobs, options);
+            table_rows.push(row);
+            continue;
+        }
+        flush_table(&mut html, &mut table_rows, &mut jobs, options);
+
+        if let Some((level, text)) = headline(line) {
+            flush_paragraph(&mut html, &mut paragraph, &mut jobs, options);
+            html.push_str(&format!("<h{level}>"));
+            emit_text_with_tex_placeholders(&mut html, text, &mut jobs, options);
+            html.push_str(&format!("</h{level}>"));
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            flush_paragraph(&mut html, &mut paragraph, &mut jobs, options);
+            continue;
+        }
+
+        // Org lines starting with "#+" that aren't a recognized block are file-level keywords
+        // (`#+TITLE:`, `#+AUTHOR:`, ...); these carry no renderable body text, so drop them.
+        if line.trim_start().starts_with("#+") {
+            continue;
+        }
+
+        if !paragraph.is_empty() {
+            paragraph.push(' ');
+        }
+        paragraph.push_str(line.trim());
+    }
+
+    if block.is_some() {
+        // Unterminated block: close it out rather than losing its captured content.
+        html.push_str("</code></pre>");
+    }
+    flush_paragraph(&mut html, &mut paragraph, &mut jobs, options);
+    flush_table(&mut html, &mut table_rows, &mut jobs, options);
+
+    let html = html_to_office_html(&html);
+    PreparedOffice { html, jobs }
+}
@@ -1,25 +1,118 @@
 use crate::entities::decode_entities;
-use crate::markdown::markdown_to_html_string;
-use crate::normalize::normalize_latex;
+use crate::latex_scan::{self, MathToken};
+pub use crate::latex_scan::{CurrencyMode, PrepareOptions};
+use crate::normalize::{decode_encoded_words, normalize_latex};
 use crate::office::html_to_office_html;
-use crate::sanitize::sanitize_for_office;
-use html5ever::parse_document;
+use crate::sanitize::sanitize_for_office_with;
+pub use crate::sanitize::ImagePolicy;
+pub use crate::typst::MathDialect;
+use crate::typst::typst_math_to_latex;
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{format_html, parse_document, Arena, Options as ComrakOptions};
+use html5ever::parse_document as parse_html_document;
 use html5ever::tendril::TendrilSink;
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
 
-#[derive(Clone, Debug)]
+/// Which delimiter form a `TexJob` was extracted from, so a caller debugging a dropped or
+/// misrendered formula can tell at a glance which scanner produced it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TexJobKind {
+    /// `$...$`
+    InlineDollar,
+    /// `$$...$$`
+    DisplayDollar,
+    /// `\[...\]`
+    Bracket,
+    /// `\(...\)`
+    Paren,
+    /// A `<TAG data-math="...">` element.
+    DataMath,
+    /// A bare `\begin{NAME}...\end{NAME}` math environment.
+    Environment,
+}
+
+impl TexJobKind {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            TexJobKind::InlineDollar => "inline-dollar",
+            TexJobKind::DisplayDollar => "display-dollar",
+            TexJobKind::Bracket => "bracket",
+            TexJobKind::Paren => "paren",
+            TexJobKind::DataMath => "data-math",
+            TexJobKind::Environment => "environment",
+        }
+    }
+}
+
+/// Classifies a delimiter pair into the `TexJobKind` a caller would expect, bucketing any
+/// `extra_delims` house delimiter (which isn't one of the four built-in forms) by its `display`
+/// flag since that's the closest built-in analog.
+fn classify_delim(open: &str, close: &str, display: bool) -> TexJobKind {
+    match (open, close) {
+        ("$$", "$$") => TexJobKind::DisplayDollar,
+        ("$", "$") => TexJobKind::InlineDollar,
+        ("\\[", "\\]") => TexJobKind::Bracket,
+        ("\\(", "\\)") => TexJobKind::Paren,
+        ("", "") => TexJobKind::Environment,
+        _ if display => TexJobKind::Bracket,
+        _ => TexJobKind::Paren,
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TexJob {
     pub id: usize,
     pub latex: String,
     pub display: bool,
+    /// Which dialect `latex` is actually written in. `MathDialect::Typst` jobs are lowered to
+    /// LaTeX via `typst_math_to_latex` before `tex::tex_to_mathml` ever sees them - see
+    /// `render_mathml_or_error`.
+    pub dialect: MathDialect,
+    /// Which delimiter form this job was extracted from.
+    pub kind: TexJobKind,
+    /// Byte offset range, within the source text the extracting scanner was given (an HTML text
+    /// node's literal, a Markdown text node's literal, or the raw input HTML for `DataMath`), that
+    /// the original delimited span covered. Spans are local to that immediate chunk rather than
+    /// the whole document: the comrak-driven dollar-math path in particular only has the math
+    /// node's own `literal` to work with, since comrak's AST doesn't retain the original `$.../$$`
+    /// delimiters once parsed, so its span is `0..literal.len()` rather than a true document
+    /// offset.
+    pub start: usize,
+    pub end: usize,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PreparedOffice {
     pub html: String,
     pub jobs: Vec<TexJob>,
 }
 
+/// One entry of an `office_apply_mathml_json` result set: the host's rendering of `TexJob::id`'s
+/// MathML, computed asynchronously (and possibly out of order) so jobs are matched back to their
+/// placeholder by `id` rather than by position. `error`, when set, means the host's renderer
+/// failed that job specifically - `office_apply_mathml_results` falls back to the job's original
+/// LaTeX for just that placeholder rather than failing the whole document.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MathmlResult {
+    pub id: usize,
+    #[serde(default)]
+    pub mathml: String,
+    #[serde(default)]
+    pub display: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Top-level shape of the JSON blob `office_apply_mathml_json` accepts: `{"results":[...]}`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MathmlResults {
+    pub results: Vec<MathmlResult>,
+}
+
 fn placeholder_for(id: usize, display: bool) -> String {
     let marker = format!("<!--COF_TEX_{id}-->");
     if display {
@@ -31,7 +124,7 @@ fn placeholder_for(id: usize, display: bool) -> String {
 }
 
 fn parse_to_dom(input: &str) -> RcDom {
-    parse_document(RcDom::default(), Default::default()).one(input)
+    parse_html_document(RcDom::default(), Default::default()).one(input)
 }
 
 fn find_body_children(dom: &RcDom) -> Vec<Handle> {
@@ -89,146 +182,89 @@ fn is_void(tag: &str) -> bool {
     )
 }
 
-fn is_currency_like_inline_dollar(inner: &str) -> bool {
-    let t = inner.trim();
-    if t.is_empty() {
-        return true;
-    }
-    // Heuristic: "$100" style currency should not be interpreted as math.
-    t.chars()
-        .all(|c| c.is_ascii_digit() || c == ',' || c == '.')
+/// Byte offset of `slice` within `base`, assuming `slice` is actually a sub-slice of `base` (true
+/// for every `&str` this is called with here, all of which come from splitting `base` itself).
+fn offset_of(base: &str, slice: &str) -> usize {
+    slice.as_ptr() as usize - base.as_ptr() as usize
 }
 
-fn emit_text_with_tex_placeholders(out: &mut String, text: &str, jobs: &mut Vec<TexJob>) {
-    let bytes = text.as_bytes();
-    let mut i: usize = 0;
-    let mut last: usize = 0;
-
-    while i < bytes.len() {
-        if text[i..].starts_with("$$") {
-            if let Some(end_rel) = text[i + 2..].find("$$") {
-                let end = i + 2 + end_rel;
-                out.push_str(&esc_text(&text[last..i]));
-                let inner = &text[i + 2..end];
-                let latex = normalize_latex(&decode_entities(inner.trim()));
-                if !latex.trim().is_empty() {
-                    let id = jobs.len();
-                    jobs.push(TexJob {
-                        id,
-                        latex,
-                        display: true,
-                    });
-                    out.push_str(&placeholder_for(id, true));
-                } else {
-                    out.push_str(&esc_text(&text[i..end + 2]));
-                }
-                i = end + 2;
-                last = i;
-                continue;
-            }
-        }
-
-        if text[i..].starts_with("\\[") {
-            if let Some(end_rel) = text[i + 2..].find("\\]") {
-                let end = i + 2 + end_rel;
-                out.push_str(&esc_text(&text[last..i]));
-                let inner = &text[i + 2..end];
-                let latex = normalize_latex(&decode_entities(inner.trim()));
-                if !latex.trim().is_empty() {
-                    let id = jobs.len();
-                    jobs.push(TexJob {
-                        id,
-                        latex,
-                        display: true,
-                    });
-                    out.push_str(&placeholder_for(id, true));
-                } else {
-                    out.push_str(&esc_text(&text[i..end + 2]));
-                }
-                i = end + 2;
-                last = i;
-                continue;
+/// Scans `text` for math delimiters and appends escaped literal text / `COF_TEX` placeholders to
+/// `out`, recording a `TexJob` for each span found. Shared by every front end that needs to run
+/// math extraction over plain prose (currently the sanitized-HTML and Org-mode paths).
+pub(crate) fn emit_text_with_tex_placeholders(
+    out: &mut String,
+    text: &str,
+    jobs: &mut Vec<TexJob>,
+    options: &PrepareOptions,
+) {
+    let tokens = match latex_scan::scan_math_tokens(text, options) {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            // An open delimiter with no matching close: this runs over arbitrary user HTML, where
+            // a stray "$$"/"\[" is plausible prose rather than a bug worth surfacing further up.
+            // Everything before the failing delimiter already scanned cleanly - re-run just that
+            // prefix (guaranteed to succeed) instead of discarding it, and only fall back to
+            // verbatim text for the unterminated span onward.
+            if err.offset > 0 {
+                emit_text_with_tex_placeholders(out, &text[..err.offset], jobs, options);
             }
+            out.push_str(&esc_text(&text[err.offset..]));
+            return;
         }
+    };
 
-        if text[i..].starts_with("\\(") {
-            if let Some(end_rel) = text[i + 2..].find("\\)") {
-                let end = i + 2 + end_rel;
-                out.push_str(&esc_text(&text[last..i]));
-                let inner = &text[i + 2..end];
+    for token in tokens {
+        match token {
+            MathToken::Literal(s) => out.push_str(&esc_text(s)),
+            MathToken::Math { open, inner, close, display } => {
                 let latex = normalize_latex(&decode_entities(inner.trim()));
-                if !latex.trim().is_empty() {
-                    let id = jobs.len();
-                    jobs.push(TexJob {
-                        id,
-                        latex,
-                        display: false,
-                    });
-                    out.push_str(&placeholder_for(id, false));
-                } else {
-                    out.push_str(&esc_text(&text[i..end + 2]));
-                }
-                i = end + 2;
-                last = i;
-                continue;
-            }
-        }
-
-        if bytes[i] == b'$' && !text[i..].starts_with("$$") {
-            let escaped = i > 0 && bytes[i - 1] == b'\\';
-            if !escaped {
-                // Find the next non-escaped "$".
-                let mut j = i + 1;
-                while j < bytes.len() {
-                    if bytes[j] == b'$' && !(j > 0 && bytes[j - 1] == b'\\') {
-                        break;
-                    }
-                    let ch = text[j..].chars().next().unwrap_or('\0');
-                    j += ch.len_utf8().max(1);
-                }
-                if j < bytes.len() && bytes[j] == b'$' {
-                    let inner = &text[i + 1..j];
-                    if !is_currency_like_inline_dollar(inner) {
-                        out.push_str(&esc_text(&text[last..i]));
-                        let latex = normalize_latex(&decode_entities(inner.trim()));
-                        if !latex.trim().is_empty() {
-                            let id = jobs.len();
-                            jobs.push(TexJob {
-                                id,
-                                latex,
-                                display: false,
-                            });
-                            out.push_str(&placeholder_for(id, false));
-                            i = j + 1;
-                            last = i;
-                            continue;
-                        }
-                    }
+                if latex.trim().is_empty() {
+                    out.push_str(&esc_text(open));
+                    out.push_str(&esc_text(inner));
+                    out.push_str(&esc_text(close));
+                    continue;
                 }
+                let id = jobs.len();
+                let start = if open.is_empty() { offset_of(text, inner) } else { offset_of(text, open) };
+                let end = if close.is_empty() { start + inner.len() } else { offset_of(text, close) + close.len() };
+                jobs.push(TexJob {
+                    id,
+                    latex,
+                    display,
+                    dialect: MathDialect::Latex,
+                    kind: classify_delim(open, close, display),
+                    start,
+                    end,
+                });
+                out.push_str(&placeholder_for(id, display));
             }
         }
-
-        let ch = text[i..].chars().next().unwrap_or('\0');
-        i += ch.len_utf8().max(1);
     }
-
-    out.push_str(&esc_text(&text[last..]));
 }
 
-fn inject_text_math_placeholders_in_sanitized_html(input_html: &str) -> (String, Vec<TexJob>) {
+fn inject_text_math_placeholders_in_sanitized_html(
+    input_html: &str,
+    options: &PrepareOptions,
+) -> (String, Vec<TexJob>) {
     let dom = parse_to_dom(input_html);
     let children = find_body_children(&dom);
     let mut out = String::with_capacity(input_html.len() + 256);
     let mut jobs: Vec<TexJob> = Vec::new();
 
-    fn walk(node: &Handle, out: &mut String, jobs: &mut Vec<TexJob>, in_code: bool) {
+    fn walk(
+        node: &Handle,
+        out: &mut String,
+        jobs: &mut Vec<TexJob>,
+        in_code: bool,
+        options: &PrepareOptions,
+    ) {
         match &node.data {
             NodeData::Text { contents } => {
                 let t = contents.borrow().to_string();
                 if in_code {
                     out.push_str(&esc_text(&t));
                 } else {
-                    emit_text_with_tex_placeholders(out, &t, jobs);
+                    emit_text_with_tex_placeholders(out, &t, jobs, options);
                 }
             }
             NodeData::Comment { contents } => {
@@ -260,7 +296,7 @@ fn inject_text_math_placeholders_in_sanitized_html(input_html: &str) -> (String,
 
                 out.push('>');
                 for c in node.children.borrow().iter() {
-                    walk(c, out, jobs, now_in_code);
+                    walk(c, out, jobs, now_in_code, options);
                 }
                 out.push_str("</");
                 out.push_str(&tag);
@@ -268,7 +304,7 @@ fn inject_text_math_placeholders_in_sanitized_html(input_html: &str) -> (String,
             }
             NodeData::Document => {
                 for c in node.children.borrow().iter() {
-                    walk(c, out, jobs, in_code);
+                    walk(c, out, jobs, in_code, options);
                 }
             }
             NodeData::Doctype { .. } | NodeData::ProcessingInstruction { .. } => {}
@@ -276,7 +312,7 @@ fn inject_text_math_placeholders_in_sanitized_html(input_html: &str) -> (String,
     }
 
     for c in children {
-        walk(&c, &mut out, &mut jobs, false);
+        walk(&c, &mut out, &mut jobs, false, options);
     }
 
     (out, jobs)
@@ -441,9 +477,17 @@ fn replace_data_math_blocks_with_placeholders(input: &str) -> (String, Vec<TexJo
             let id = jobs.len();
             let display = is_block_math(tag_name, raw_trim);
             let latex = normalize_latex(tex_raw.trim());
-            jobs.push(TexJob { id, latex, display });
-
             let end = find_matching_end_tag(input, gt + 1, tag_name).unwrap_or(gt + 1);
+            jobs.push(TexJob {
+                id,
+                latex,
+                display,
+                dialect: MathDialect::Latex,
+                kind: TexJobKind::DataMath,
+                start: lt,
+                end,
+            });
+
             out.push_str(&placeholder_for(id, display));
             i = end;
             continue;
@@ -457,110 +501,312 @@ fn replace_data_math_blocks_with_placeholders(input: &str) -> (String, Vec<TexJo
     (out, jobs)
 }
 
-pub fn html_to_office_prepared(input_html: &str) -> PreparedOffice {
-    let (without_tex, jobs) = replace_data_math_blocks_with_placeholders(input_html);
-    let sanitized = sanitize_for_office(&without_tex);
-    let (with_text_math, mut more_jobs) = inject_text_math_placeholders_in_sanitized_html(&sanitized);
+pub fn html_to_office_prepared(input_html: &str, options: &PrepareOptions) -> PreparedOffice {
+    let decoded_words = decode_encoded_words(input_html);
+    let (without_tex, jobs) = replace_data_math_blocks_with_placeholders(&decoded_words);
+    let sanitized = sanitize_for_office_with(&without_tex, options.image_policy);
+    let (with_text_math, mut more_jobs) =
+        inject_text_math_placeholders_in_sanitized_html(&sanitized, options);
     let mut jobs = jobs;
     jobs.append(&mut more_jobs);
     let html = html_to_office_html(&with_text_math);
     PreparedOffice { html, jobs }
 }
 
-fn inject_markdown_math_placeholders(md: &str) -> (String, Vec<TexJob>) {
-    let mut out = String::with_capacity(md.len());
-    let mut jobs: Vec<TexJob> = Vec::new();
+fn comrak_options(options: &PrepareOptions) -> ComrakOptions {
+    let mut opts = ComrakOptions::default();
+    opts.extension.table = true;
+    opts.extension.strikethrough = true;
+    opts.extension.tasklist = true;
+    opts.extension.footnotes = true;
+    // comrak's dollar-math extension has no inline-vs-display or currency-guard knobs of its own,
+    // so the most these options can do for it is turn it on/off as a whole; `replace_math_nodes`
+    // below re-checks `inline_dollar`/`display_dollar` per node to cover the case where only one
+    // of the two is wanted.
+    let dollar_math = options.inline_dollar || options.display_dollar;
+    opts.extension.math_dollars = dollar_math;
+    opts.extension.math_code = dollar_math;
+    opts
+}
 
-    let bytes = md.as_bytes();
-    let mut i: usize = 0;
-    let mut in_fence = false;
-    let mut in_inline_code = false;
+/// Scans a `Text` node's literal for the bracket-style math forms comrak's dollar/code-span math
+/// extension doesn't recognize (`\[...\]`, `\(...\)`, a bare `\begin{NAME}...\end{NAME}` math
+/// environment, and any `extra_delims`), returning escaped text with placeholders spliced in only
+/// if at least one was found; `None` means the node should be left untouched. Delimiter forms
+/// `options` disables are left as literal text.
+fn inject_bracket_math_in_text(
+    text: &str,
+    jobs: &mut Vec<TexJob>,
+    options: &PrepareOptions,
+) -> Option<String> {
+    let mut delims: Vec<(&str, &str, bool)> = Vec::new();
+    if options.bracket_delims {
+        delims.push(("\\[", "\\]", true));
+    }
+    if options.paren_delims {
+        delims.push(("\\(", "\\)", false));
+    }
+    for (open, close, display) in &options.extra_delims {
+        delims.push((open.as_str(), close.as_str(), *display));
+    }
+    if delims.is_empty() && !options.environments {
+        return None;
+    }
 
-    while i < bytes.len() {
-        if md[i..].starts_with("```") {
-            in_fence = !in_fence;
-            out.push_str("```");
-            i += 3;
-            continue;
+    let mut found = false;
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut i = 0;
+
+    while i < text.len() {
+        let mut matched = false;
+
+        if options.environments {
+            if let Some(rest) = text[i..].strip_prefix("\\begin{") {
+                if let Some(name_end) = rest.find('}') {
+                    let name = &rest[..name_end];
+                    if latex_scan::is_math_environment(name) {
+                        let after_open = &rest[name_end + 1..];
+                        if let Some(end_rel) = latex_scan::find_environment_end(after_open, name) {
+                            let whole_len = "\\begin{".len() + name_end + 1 + end_rel;
+                            let whole = &text[i..i + whole_len];
+                            out.push_str(&esc_text(&text[last..i]));
+                            let latex = normalize_latex(&decode_entities(whole.trim()));
+                            let id = jobs.len();
+                            jobs.push(TexJob {
+                                id,
+                                latex,
+                                display: true,
+                                dialect: MathDialect::Latex,
+                                kind: TexJobKind::Environment,
+                                start: i,
+                                end: i + whole_len,
+                            });
+                            out.push_str(&placeholder_for(id, true));
+                            i += whole_len;
+                            last = i;
+                            found = true;
+                            matched = true;
+                        }
+                    }
+                }
+            }
         }
-        if in_fence {
-            out.push(bytes[i] as char);
-            i += 1;
+
+        if matched {
             continue;
         }
 
-        if bytes[i] == b'`' {
-            in_inline_code = !in_inline_code;
-            out.push('`');
-            i += 1;
-            continue;
+        for (open, close, display) in &delims {
+            if let Some(rest) = text[i..].strip_prefix(*open) {
+                if let Some(end_rel) = rest.find(*close) {
+                    let end = i + open.len() + end_rel;
+                    out.push_str(&esc_text(&text[last..i]));
+                    let latex = normalize_latex(&decode_entities(rest[..end_rel].trim()));
+                    let id = jobs.len();
+                    jobs.push(TexJob {
+                        id,
+                        latex,
+                        display: *display,
+                        dialect: MathDialect::Latex,
+                        kind: classify_delim(open, close, *display),
+                        start: i,
+                        end: end + close.len(),
+                    });
+                    out.push_str(&placeholder_for(id, *display));
+                    i = end + close.len();
+                    last = i;
+                    found = true;
+                    matched = true;
+                    break;
+                }
+            }
         }
-        if in_inline_code {
-            out.push(bytes[i] as char);
-            i += 1;
+        if matched {
             continue;
         }
+        let ch = text[i..].chars().next().unwrap_or('\0');
+        i += ch.len_utf8().max(1);
+    }
 
-        if md[i..].starts_with("$$") {
-            if let Some(end_rel) = md[i + 2..].find("$$") {
-                let inner = &md[i + 2..i + 2 + end_rel];
-                let latex = normalize_latex(inner.trim());
-                let id = jobs.len();
-                jobs.push(TexJob {
-                    id,
-                    latex,
-                    display: true,
-                });
-                out.push_str(&placeholder_for(id, true));
-                i = i + 2 + end_rel + 2;
-                continue;
-            }
-        }
-        if md[i..].starts_with("\\[") {
-            if let Some(end_rel) = md[i + 2..].find("\\]") {
-                let inner = &md[i + 2..i + 2 + end_rel];
-                let latex = normalize_latex(inner.trim());
-                let id = jobs.len();
-                jobs.push(TexJob {
-                    id,
-                    latex,
-                    display: true,
-                });
-                out.push_str(&placeholder_for(id, true));
-                i = i + 2 + end_rel + 2;
-                continue;
+    if !found {
+        return None;
+    }
+    out.push_str(&esc_text(&text[last..]));
+    Some(out)
+}
+
+/// Walks the comrak AST in document order, turning each math node into a `TexJob` plus an
+/// `HtmlInline` placeholder. `Math` nodes come from comrak's own dollar/code-span math extension;
+/// `\[...\]`/`\(...\)` forms are caught by scanning `Text` literals instead, since the extension
+/// doesn't cover them. Matching only ever happens against `NodeValue::Text`/`NodeValue::Math`, so
+/// a `Code`/`CodeBlock`/`HtmlBlock` node's literal (which comrak never represents as a `Text`
+/// child) is never mistaken for prose, unlike the old backtick-counting scanner.
+fn replace_math_nodes<'a>(node: &'a AstNode<'a>, jobs: &mut Vec<TexJob>, options: &PrepareOptions) {
+    let replacement = {
+        let ast = node.data.borrow();
+        match &ast.value {
+            NodeValue::Math(math) => {
+                let enabled = if math.display_math { options.display_dollar } else { options.inline_dollar };
+                if !enabled {
+                    // comrak can only turn its dollar-math extension on/off as a whole (see
+                    // `comrak_options`), so a node can show up here even when this particular form
+                    // (inline vs display) was asked to be disabled - put the original delimiters
+                    // back rather than placeholdering it.
+                    let delim = if math.display_math { "$$" } else { "$" };
+                    Some(NodeValue::Text(format!("{delim}{}{delim}", math.literal)))
+                } else {
+                    let id = jobs.len();
+                    let literal_len = math.literal.len();
+                    let latex = normalize_latex(&decode_entities(math.literal.trim()));
+                    let kind = if math.display_math { TexJobKind::DisplayDollar } else { TexJobKind::InlineDollar };
+                    // Best-effort span: comrak's dollar-math extension doesn't retain the
+                    // original `$.../$$` delimiters on the node, only `literal`, so this is
+                    // relative to the literal itself rather than a true document offset (see the
+                    // `start`/`end` doc comment on `TexJob`).
+                    jobs.push(TexJob {
+                        id,
+                        latex,
+                        display: math.display_math,
+                        dialect: MathDialect::Latex,
+                        kind,
+                        start: 0,
+                        end: literal_len,
+                    });
+                    Some(NodeValue::HtmlInline(placeholder_for(id, math.display_math)))
+                }
             }
-        }
-        if md[i..].starts_with("\\(") {
-            if let Some(end_rel) = md[i + 2..].find("\\)") {
-                let inner = &md[i + 2..i + 2 + end_rel];
-                let latex = normalize_latex(inner.trim());
-                let id = jobs.len();
-                jobs.push(TexJob {
-                    id,
-                    latex,
-                    display: false,
-                });
-                out.push_str(&placeholder_for(id, false));
-                i = i + 2 + end_rel + 2;
-                continue;
+            NodeValue::Text(text) => {
+                inject_bracket_math_in_text(text, jobs, options).map(NodeValue::HtmlInline)
             }
+            _ => None,
         }
+    };
 
-        out.push(bytes[i] as char);
-        i += 1;
+    if let Some(value) = replacement {
+        node.data.borrow_mut().value = value;
+        return;
     }
 
-    (out, jobs)
+    for child in node.children() {
+        replace_math_nodes(child, jobs, options);
+    }
 }
 
-pub fn markdown_to_office_prepared(md: &str) -> PreparedOffice {
-    let (md_with_placeholders, jobs) = inject_markdown_math_placeholders(md);
-    let html = markdown_to_html_string(&md_with_placeholders);
+pub fn markdown_to_office_prepared(md: &str, options: &PrepareOptions) -> PreparedOffice {
+    let arena = Arena::new();
+    let comrak_opts = comrak_options(options);
+    let root = parse_document(&arena, md, &comrak_opts);
+
+    let mut jobs: Vec<TexJob> = Vec::new();
+    replace_math_nodes(root, &mut jobs, options);
+
+    let mut rendered = Vec::new();
+    format_html(root, &comrak_opts, &mut rendered).unwrap_or_default();
+    let html = String::from_utf8(rendered).unwrap_or_default();
+
     let html = html_to_office_html(&html);
     PreparedOffice { html, jobs }
 }
 
+/// One-shot CommonMark (GPT-style `$...$`/`$$...$$`/`\(...\)`/`\[...\]` math included) to
+/// Word-pasteable HTML, for callers that just want a string in and a string out rather than the
+/// prepare/apply split `markdown_to_office_prepared` + `office_apply_with` give async renderers.
+/// Math that fails to convert is inlined as a `cof-math-error` span rather than failing the whole
+/// document, matching `MathMlRenderer`'s own fallback.
+pub fn markdown_to_office_html(md: &str) -> String {
+    let prepared = markdown_to_office_prepared(md, &PrepareOptions::default());
+    office_apply_with(&prepared.html, &prepared.jobs, &MathMlRenderer).unwrap_or(prepared.html)
+}
+
+/// Per-job math rendering, so a caller can swap in OMML, an SVG/PNG fallback image, or an
+/// alt-text-annotated span instead of MathML without forking the crate. Takes the whole `TexJob`
+/// (not just its LaTeX) so a renderer can also key off `id` or `display` if it wants to. Split
+/// into `render_inline`/`render_display` rather than a single method plus a `display: bool`
+/// parameter, since most custom renderers (e.g. an SVG fallback) only want to special-case one of
+/// the two and can let a default method cover the other - see `MathMlRenderer` below.
+pub trait MathRenderer {
+    fn render_inline(&self, job: &TexJob) -> String;
+    fn render_display(&self, job: &TexJob) -> String;
+}
+
+/// Default renderer: computes MathML for each job via `tex::tex_to_mathml`, the same conversion
+/// `office_apply_mathml` has always relied on a caller to pre-join - `office_apply_with` calls it
+/// per job instead, so there's no longer a brittle requirement that the caller join per-job
+/// MathML strings in exactly id order.
+pub struct MathMlRenderer;
+
+impl MathRenderer for MathMlRenderer {
+    fn render_inline(&self, job: &TexJob) -> String {
+        render_mathml_or_error(job, false)
+    }
+
+    fn render_display(&self, job: &TexJob) -> String {
+        render_mathml_or_error(job, true)
+    }
+}
+
+fn render_mathml_or_error(job: &TexJob, display: bool) -> String {
+    let latex = match job.dialect {
+        MathDialect::Latex => Cow::Borrowed(job.latex.as_str()),
+        MathDialect::Typst => Cow::Owned(typst_math_to_latex(&job.latex)),
+    };
+    match crate::tex::tex_to_mathml(&latex, display) {
+        Ok(mathml) => mathml,
+        Err(msg) => format!("<span class=\"cof-math-error\">{}</span>", esc_text(&msg)),
+    }
+}
+
+/// One-shot Typst-math to Office MathML, mirroring `markdown_to_office_html`'s
+/// prepare-then-apply-with-the-default-renderer shape but for a single bare math expression
+/// rather than a whole document: `input` is transpiled via `typst_math_to_latex` and rendered
+/// through the same `tex::tex_to_mathml` backend everything else uses. Falls back to the
+/// `cof-math-error` span (from `render_mathml_or_error`) rather than failing outright, matching
+/// every other renderer in this module.
+pub fn typst_to_office_with_mathml(input: &str, display: bool) -> String {
+    let job = TexJob {
+        id: 0,
+        latex: input.to_string(),
+        display,
+        dialect: MathDialect::Typst,
+        kind: if display { TexJobKind::DisplayDollar } else { TexJobKind::InlineDollar },
+        start: 0,
+        end: input.len(),
+    };
+    let placeholder = placeholder_for(0, display);
+    office_apply_with(&placeholder, &[job], &MathMlRenderer).unwrap_or(placeholder)
+}
+
+/// Walks `html`'s `COF_TEX` placeholders and substitutes each with `renderer`'s output for that
+/// job, keyed by the job's own `display` flag rather than requiring the caller to pre-join a
+/// single `\u{001F}`-separated MathML string in placeholder-id order (see `office_apply_mathml`
+/// for that older, MathML-only entry point, still kept for existing callers).
+pub fn office_apply_with<R: MathRenderer>(
+    html: &str,
+    jobs: &[TexJob],
+    renderer: &R,
+) -> Result<String, String> {
+    if !html.contains("<!--COF_TEX_") {
+        return Ok(html.to_string());
+    }
+
+    let mut out = html.to_string();
+    for job in jobs {
+        let marker = format!("<!--COF_TEX_{}-->", job.id);
+        if !out.contains(&marker) {
+            return Err(format!("missing placeholder for job {}", job.id));
+        }
+        let rendered = if job.display {
+            renderer.render_display(job)
+        } else {
+            renderer.render_inline(job)
+        };
+        out = out.replace(&marker, &rendered);
+    }
+
+    Ok(out)
+}
+
 pub fn office_apply_mathml(html: &str, joined_mathml: &str) -> Result<String, String> {
     let sep = '\u{001F}';
     let parts: Vec<&str> = if joined_mathml.is_empty() {
@@ -585,14 +831,91 @@ pub fn office_apply_mathml(html: &str, joined_mathml: &str) -> Result<String, St
     Ok(out)
 }
 
+/// Renders `job`'s own LaTeX back into the placeholder, escaped and flagged with the same
+/// `cof-math-error` class `render_mathml_or_error` uses for a hard rendering failure - the
+/// difference is this is a per-job fallback keyed by `id`, not a whole-document failure, so the
+/// rest of the document's math still comes through normally.
+fn original_latex_fallback(job: &TexJob, error: Option<&str>) -> String {
+    let body = esc_text(&job.latex);
+    match error {
+        Some(msg) => format!(
+            "<span class=\"cof-math-error\" title=\"{}\">{}</span>",
+            esc_attr(msg),
+            body
+        ),
+        None => format!("<span class=\"cof-math-error\">{}</span>", body),
+    }
+}
+
+/// Id-keyed counterpart to `office_apply_mathml`: `results` carries each job's rendered MathML
+/// tagged with its own `id`, so a host that renders math asynchronously (and may finish jobs out
+/// of order, or drop one) can still match every result back to the right placeholder. A job with
+/// no matching result, or one carrying an `error`, falls back to its original LaTeX via
+/// `original_latex_fallback` instead of failing the whole document.
+pub fn office_apply_mathml_results(
+    html: &str,
+    jobs: &[TexJob],
+    results: &MathmlResults,
+) -> Result<String, String> {
+    if !html.contains("<!--COF_TEX_") {
+        return Ok(html.to_string());
+    }
+
+    let by_id: HashMap<usize, &MathmlResult> =
+        results.results.iter().map(|r| (r.id, r)).collect();
+
+    let mut out = html.to_string();
+    for job in jobs {
+        let marker = format!("<!--COF_TEX_{}-->", job.id);
+        if !out.contains(&marker) {
+            return Err(format!("missing placeholder for job {}", job.id));
+        }
+        let rendered = match by_id.get(&job.id) {
+            Some(r) if r.error.is_none() => r.mathml.clone(),
+            Some(r) => original_latex_fallback(job, r.error.as_deref()),
+            None => original_latex_fallback(job, None),
+        };
+        out = out.replace(&marker, &rendered);
+    }
+
+    Ok(out)
+}
+
+/// Renders a `PreparedOffice`'s jobs as a compact, S-expression-style debug dump (one line per
+/// job: id, kind, source span, display flag, and normalized LaTeX) - meant for a human debugging
+/// why a formula was dropped or misclassified, not for machine consumption (see `json` for that).
+pub fn describe_jobs(prepared: &PreparedOffice) -> String {
+    let mut out = String::new();
+    for job in &prepared.jobs {
+        out.push_str(&format!(
+            "(job {} :kind {} :span {}..{} :display {} :latex {:?})\n",
+            job.id,
+            job.kind.as_str(),
+            job.start,
+            job.end,
+            job.display,
+            job.latex,
+        ));
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn html_prepared(html: &str) -> PreparedOffice {
+        html_to_office_prepared(html, &PrepareOptions::default())
+    }
+
+    fn md_prepared(md: &str) -> PreparedOffice {
+        markdown_to_office_prepared(md, &PrepareOptions::default())
+    }
+
     #[test]
     fn replaces_data_math_block_with_placeholder_and_job() {
         let html = r#"<div><div class="math-block" data-math="\\text{Logit}_{ij} = 1+1"></div><p>ok</p></div>"#;
-        let prepared = html_to_office_prepared(html);
+        let prepared = html_prepared(html);
         assert!(prepared.html.contains("<!--COF_TEX_0-->"));
         assert_eq!(prepared.jobs.len(), 1);
         assert!(prepared.jobs[0].latex.contains("\\text{Logit}_{ij}"));
@@ -602,7 +925,7 @@ mod tests {
     #[test]
     fn replaces_inline_dollar_math_with_placeholder_and_job() {
         let html = r#"<p>Price is $100 and math is $x^2 + y^2 = z^2$ ok</p>"#;
-        let prepared = html_to_office_prepared(html);
+        let prepared = html_prepared(html);
         assert!(prepared.html.contains("Price is $100 and math is"));
         assert!(prepared.html.contains("<!--COF_TEX_0-->"));
         assert_eq!(prepared.jobs.len(), 1);
@@ -613,7 +936,7 @@ mod tests {
     #[test]
     fn replaces_display_bracket_math_with_placeholder_and_job() {
         let html = r#"<p>Display: \[x = \frac{-b}{2a}\]</p>"#;
-        let prepared = html_to_office_prepared(html);
+        let prepared = html_prepared(html);
         assert!(prepared.html.contains("Display:"));
         assert!(prepared.html.contains("<!--COF_TEX_0-->"));
         assert_eq!(prepared.jobs.len(), 1);
@@ -621,6 +944,151 @@ mod tests {
         assert!(prepared.jobs[0].latex.contains("\\frac"));
     }
 
+    #[test]
+    fn markdown_inline_dollar_math_becomes_job() {
+        let prepared = md_prepared("Price is $100 and math is $x^2 + y^2 = z^2$ ok");
+        assert!(prepared.html.contains("<!--COF_TEX_0-->"));
+        assert_eq!(prepared.jobs.len(), 1);
+        assert!(!prepared.jobs[0].display);
+        assert!(prepared.jobs[0].latex.contains("x^2"));
+    }
+
+    #[test]
+    fn markdown_display_dollar_math_becomes_job() {
+        let prepared = md_prepared("$$x = \\frac{-b}{2a}$$");
+        assert_eq!(prepared.jobs.len(), 1);
+        assert!(prepared.jobs[0].display);
+        assert!(prepared.jobs[0].latex.contains("\\frac"));
+    }
+
+    #[test]
+    fn markdown_bracket_math_becomes_job() {
+        let prepared = md_prepared("Display: \\[x = \\frac{-b}{2a}\\]");
+        assert_eq!(prepared.jobs.len(), 1);
+        assert!(prepared.jobs[0].display);
+    }
+
+    #[test]
+    fn markdown_align_environment_becomes_display_job() {
+        let prepared = md_prepared("\\begin{align}x &= y \\\\ y &= z\\end{align}");
+        assert_eq!(prepared.jobs.len(), 1);
+        assert!(prepared.jobs[0].display);
+        assert!(prepared.jobs[0].latex.starts_with("\\begin{align}"));
+        assert!(prepared.jobs[0].latex.ends_with("\\end{align}"));
+    }
+
+    #[test]
+    fn html_nested_cases_environment_balances_depth() {
+        let prepared = html_prepared(
+            "<p>\\begin{cases}\\begin{cases}a\\end{cases} & b\\end{cases}</p>",
+        );
+        assert_eq!(prepared.jobs.len(), 1);
+        assert!(prepared.jobs[0].latex.contains("\\begin{cases}\\begin{cases}a\\end{cases}"));
+    }
+
+    #[test]
+    fn markdown_unknown_environment_name_is_left_as_text() {
+        let prepared = md_prepared("\\begin{itemize}\\item x\\end{itemize}");
+        assert!(prepared.jobs.is_empty());
+    }
+
+    #[test]
+    fn markdown_dollar_inside_fenced_code_is_not_math() {
+        let prepared = md_prepared("```\nlet price = \"$100\";\n```");
+        assert!(prepared.jobs.is_empty());
+        assert!(prepared.html.contains("$100"));
+    }
+
+    #[test]
+    fn markdown_dollar_inside_inline_code_is_not_math() {
+        let prepared = md_prepared("Use `$x$` literally, then $y^2$ for real math.");
+        assert_eq!(prepared.jobs.len(), 1);
+        assert!(prepared.jobs[0].latex.contains('y'));
+        assert!(prepared.html.contains("<code>$x$</code>"));
+    }
+
+    #[test]
+    fn markdown_math_survives_non_ascii_text() {
+        // A byte-indexed scanner that reconstructs chars via `bytes[i] as char` would corrupt any
+        // multi-byte UTF-8 around the math; comrak's AST walk operates on whole `Text` literals,
+        // so this round-trips intact.
+        let prepared = md_prepared("Café price is €100, and math is $x^2 = é$ après tout");
+        assert_eq!(prepared.jobs.len(), 1);
+        assert!(prepared.html.contains("Café"));
+        assert!(prepared.html.contains("après"));
+    }
+
+    #[test]
+    fn markdown_dollar_inside_tilde_fence_is_not_math() {
+        let prepared = md_prepared("~~~\nlet price = \"$100\";\n~~~");
+        assert!(prepared.jobs.is_empty());
+        assert!(prepared.html.contains("$100"));
+    }
+
+    #[test]
+    fn markdown_dollar_inside_four_backtick_fence_is_not_math() {
+        let prepared = md_prepared("````\nprice = \"$100\"; `embedded backticks` too\n````");
+        assert!(prepared.jobs.is_empty());
+        assert!(prepared.html.contains("$100"));
+    }
+
+    #[test]
+    fn markdown_dollar_inside_double_backtick_inline_code_is_not_math() {
+        let prepared = md_prepared("Use `` `$x$` `` literally, then $y^2$ for real math.");
+        assert_eq!(prepared.jobs.len(), 1);
+        assert!(prepared.jobs[0].latex.contains('y'));
+        assert!(prepared.html.contains("<code>`$x$`</code>"));
+    }
+
+    #[test]
+    fn markdown_table_renders_as_html_table() {
+        let prepared = md_prepared("| a | b |\n| - | - |\n| 1 | 2 |\n");
+        assert!(prepared.html.contains("<table>"));
+    }
+
+    #[test]
+    fn markdown_to_office_html_splices_mathml_inline() {
+        let out = markdown_to_office_html("Euler's identity is $e^{i\\pi} + 1 = 0$ and **bold** text.");
+        assert!(!out.contains("COF_TEX"));
+        assert!(out.contains("<b"));
+        assert!(out.contains("<math"));
+    }
+
+    #[test]
+    fn disabling_paren_delims_leaves_html_escaped_verbatim() {
+        let options = PrepareOptions { paren_delims: false, ..PrepareOptions::default() };
+        let prepared = html_to_office_prepared(r#"<p>\(a+b\)</p>"#, &options);
+        assert!(prepared.jobs.is_empty());
+        assert!(prepared.html.contains("\\(a+b\\)"));
+    }
+
+    #[test]
+    fn currency_mode_disabled_turns_digit_only_dollars_into_math() {
+        let options = PrepareOptions { currency_guard: CurrencyMode::Disabled, ..PrepareOptions::default() };
+        let prepared = html_to_office_prepared(r#"<p>Price is $100$ today</p>"#, &options);
+        assert_eq!(prepared.jobs.len(), 1);
+        assert_eq!(prepared.jobs[0].latex, "100");
+    }
+
+    #[test]
+    fn extra_delims_register_a_house_delimiter() {
+        let options = PrepareOptions {
+            extra_delims: vec![("\\begin{math}".to_string(), "\\end{math}".to_string(), false)],
+            ..PrepareOptions::default()
+        };
+        let prepared = html_to_office_prepared(r#"<p>\begin{math}a+b\end{math}</p>"#, &options);
+        assert_eq!(prepared.jobs.len(), 1);
+        assert!(prepared.jobs[0].latex.contains("a+b"));
+    }
+
+    #[test]
+    fn markdown_disabling_inline_dollar_leaves_single_dollar_math_as_text() {
+        let options = PrepareOptions { inline_dollar: false, ..PrepareOptions::default() };
+        let prepared = markdown_to_office_prepared("cost is $x^2$ today", &options);
+        assert!(prepared.jobs.is_empty());
+        assert!(prepared.html.contains("$x^2$"));
+    }
+
     #[test]
     fn apply_mathml_replaces_markers() {
         let html = r#"<div><!--COF_TEX_0--></div><span><!--COF_TEX_1--></span>"#;
@@ -634,4 +1102,153 @@ mod tests {
         assert!(out.contains("<mi>a</mi>"));
         assert!(out.contains("<mi>b</mi>"));
     }
+
+    struct TaggingRenderer;
+
+    impl MathRenderer for TaggingRenderer {
+        fn render_inline(&self, job: &TexJob) -> String {
+            format!("[inline:{}]", job.latex)
+        }
+
+        fn render_display(&self, job: &TexJob) -> String {
+            format!("[display:{}]", job.latex)
+        }
+    }
+
+    #[test]
+    fn apply_with_dispatches_on_job_display_flag() {
+        let html = r#"<p><!--COF_TEX_0--></p><div><!--COF_TEX_1--></div>"#;
+        let jobs = vec![
+            TexJob { id: 0, latex: "x".to_string(), display: false, dialect: MathDialect::Latex, kind: TexJobKind::InlineDollar, start: 0, end: 1 },
+            TexJob { id: 1, latex: "y".to_string(), display: true, dialect: MathDialect::Latex, kind: TexJobKind::DisplayDollar, start: 0, end: 1 },
+        ];
+        let out = office_apply_with(html, &jobs, &TaggingRenderer).unwrap();
+        assert!(out.contains("[inline:x]"));
+        assert!(out.contains("[display:y]"));
+    }
+
+    #[test]
+    fn apply_with_errors_on_missing_placeholder() {
+        let jobs = vec![TexJob { id: 0, latex: "x".to_string(), display: false, dialect: MathDialect::Latex, kind: TexJobKind::InlineDollar, start: 0, end: 1 }];
+        let err = office_apply_with("<p>no markers here</p>", &jobs, &TaggingRenderer).unwrap_err();
+        assert!(err.contains('0'));
+    }
+
+    #[test]
+    fn apply_mathml_results_matches_by_id_out_of_order() {
+        let html = r#"<p><!--COF_TEX_0--></p><div><!--COF_TEX_1--></div>"#;
+        let jobs = vec![
+            TexJob { id: 0, latex: "x".to_string(), display: false, dialect: MathDialect::Latex, kind: TexJobKind::InlineDollar, start: 0, end: 1 },
+            TexJob { id: 1, latex: "y".to_string(), display: true, dialect: MathDialect::Latex, kind: TexJobKind::DisplayDollar, start: 0, end: 1 },
+        ];
+        let results = MathmlResults {
+            results: vec![
+                MathmlResult { id: 1, mathml: "<math><mi>y</mi></math>".to_string(), display: true, error: None },
+                MathmlResult { id: 0, mathml: "<math><mi>x</mi></math>".to_string(), display: false, error: None },
+            ],
+        };
+        let out = office_apply_mathml_results(html, &jobs, &results).unwrap();
+        assert!(out.contains("<mi>x</mi>"));
+        assert!(out.contains("<mi>y</mi>"));
+    }
+
+    #[test]
+    fn apply_mathml_results_falls_back_to_original_latex_on_error() {
+        let html = r#"<p><!--COF_TEX_0--></p>"#;
+        let jobs = vec![TexJob { id: 0, latex: "\\frac{a}{b}".to_string(), display: false, dialect: MathDialect::Latex, kind: TexJobKind::InlineDollar, start: 0, end: 1 }];
+        let results = MathmlResults {
+            results: vec![MathmlResult { id: 0, mathml: String::new(), display: false, error: Some("renderer timed out".to_string()) }],
+        };
+        let out = office_apply_mathml_results(html, &jobs, &results).unwrap();
+        assert!(out.contains("cof-math-error"));
+        assert!(out.contains("\\frac{a}{b}"));
+    }
+
+    #[test]
+    fn apply_mathml_results_falls_back_when_a_job_has_no_result() {
+        let html = r#"<p><!--COF_TEX_0--></p>"#;
+        let jobs = vec![TexJob { id: 0, latex: "x".to_string(), display: false, dialect: MathDialect::Latex, kind: TexJobKind::InlineDollar, start: 0, end: 1 }];
+        let results = MathmlResults { results: vec![] };
+        let out = office_apply_mathml_results(html, &jobs, &results).unwrap();
+        assert!(out.contains("cof-math-error"));
+        assert!(out.contains('x'));
+    }
+
+    #[test]
+    fn typst_job_is_transpiled_before_rendering() {
+        let out = typst_to_office_with_mathml("sqrt(x)", false);
+        assert!(!out.contains("cof-math-error"), "{out}");
+        assert!(out.contains("<math"));
+    }
+
+    #[test]
+    fn typst_job_renders_as_display_math_when_requested() {
+        let out = typst_to_office_with_mathml("frac(a, b)", true);
+        assert!(out.contains("cof-math-block"));
+    }
+
+    #[test]
+    fn inline_dollar_job_has_inline_dollar_kind_and_span() {
+        let prepared = html_prepared("$x$");
+        assert_eq!(prepared.jobs.len(), 1);
+        let job = &prepared.jobs[0];
+        assert_eq!(job.kind, TexJobKind::InlineDollar);
+        assert_eq!((job.start, job.end), (0, 3));
+    }
+
+    #[test]
+    fn display_dollar_job_has_display_dollar_kind_and_span() {
+        let prepared = html_prepared("$$x$$");
+        assert_eq!(prepared.jobs.len(), 1);
+        let job = &prepared.jobs[0];
+        assert_eq!(job.kind, TexJobKind::DisplayDollar);
+        assert_eq!((job.start, job.end), (0, 5));
+    }
+
+    #[test]
+    fn bracket_job_has_bracket_kind_and_span() {
+        let prepared = html_prepared("\\[x\\]");
+        assert_eq!(prepared.jobs.len(), 1);
+        let job = &prepared.jobs[0];
+        assert_eq!(job.kind, TexJobKind::Bracket);
+        assert_eq!((job.start, job.end), (0, 5));
+    }
+
+    #[test]
+    fn paren_job_has_paren_kind_and_span() {
+        let prepared = html_prepared("\\(x\\)");
+        assert_eq!(prepared.jobs.len(), 1);
+        let job = &prepared.jobs[0];
+        assert_eq!(job.kind, TexJobKind::Paren);
+        assert_eq!((job.start, job.end), (0, 5));
+    }
+
+    #[test]
+    fn environment_job_has_environment_kind_and_span() {
+        let prepared = md_prepared("\\begin{align}x\\end{align}");
+        assert_eq!(prepared.jobs.len(), 1);
+        let job = &prepared.jobs[0];
+        assert_eq!(job.kind, TexJobKind::Environment);
+        assert_eq!((job.start, job.end), (0, "\\begin{align}x\\end{align}".len()));
+    }
+
+    #[test]
+    fn data_math_job_has_data_math_kind_and_span() {
+        let html = r#"<div data-math="x"></div>"#;
+        let prepared = html_prepared(html);
+        assert_eq!(prepared.jobs.len(), 1);
+        let job = &prepared.jobs[0];
+        assert_eq!(job.kind, TexJobKind::DataMath);
+        assert_eq!((job.start, job.end), (0, html.len()));
+    }
+
+    #[test]
+    fn describe_jobs_renders_one_sexp_line_per_job() {
+        let prepared = html_prepared("$x$");
+        let dump = describe_jobs(&prepared);
+        assert_eq!(
+            dump,
+            "(job 0 :kind inline-dollar :span 0..3 :display false :latex \"x\")\n"
+        );
+    }
 }
@@ -0,0 +1,162 @@
+// Plain-text clipboard flavor: callers that write `text/html` to the clipboard usually want a
+// `text/plain` fallback alongside it, for paste targets that can't render HTML.
+
+use crate::sanitize::{sanitize_to_nodes, OutNode};
+
+fn ensure_blank_line(out: &mut String) {
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+}
+
+/// Recovers the LaTeX a `<math>` node was rendered from, the way a browser's copy of
+/// KaTeX/MathJax output embeds it: an `<annotation encoding="application/x-tex">` child holding
+/// the original source next to the MathML it was compiled into.
+fn find_annotation_tex(nodes: &[OutNode]) -> Option<String> {
+    for n in nodes {
+        if let OutNode::Element { tag, attrs, children } = n {
+            if tag == "annotation"
+                && attrs
+                    .iter()
+                    .any(|(k, v)| k == "encoding" && v.eq_ignore_ascii_case("application/x-tex"))
+            {
+                let text = collect_text(children);
+                let trimmed = text.trim();
+                if !trimmed.is_empty() {
+                    return Some(trimmed.to_string());
+                }
+            }
+            if let Some(found) = find_annotation_tex(children) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+fn collect_text(nodes: &[OutNode]) -> String {
+    let mut out = String::new();
+    for n in nodes {
+        match n {
+            OutNode::Text(t) => out.push_str(t),
+            OutNode::Comment(_) => {}
+            OutNode::Element { children, .. } => out.push_str(&collect_text(children)),
+        }
+    }
+    out
+}
+
+fn render(nodes: &[OutNode], out: &mut String, list_stack: &mut Vec<(bool, u32)>) {
+    for n in nodes {
+        match n {
+            OutNode::Text(t) => out.push_str(t),
+            OutNode::Comment(_) => {}
+            OutNode::Element { tag, children, .. } => match tag.as_str() {
+                "br" => out.push(' '),
+                "p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "blockquote" => {
+                    ensure_blank_line(out);
+                    render(children, out, list_stack);
+                    ensure_blank_line(out);
+                }
+                "ul" => {
+                    list_stack.push((false, 1));
+                    render(children, out, list_stack);
+                    list_stack.pop();
+                }
+                "ol" => {
+                    list_stack.push((true, 1));
+                    render(children, out, list_stack);
+                    list_stack.pop();
+                }
+                "li" => {
+                    ensure_blank_line(out);
+                    match list_stack.last_mut() {
+                        Some((true, n)) => {
+                            out.push_str(&format!("{n}. "));
+                            *n += 1;
+                        }
+                        _ => out.push_str("- "),
+                    }
+                    render(children, out, list_stack);
+                    ensure_blank_line(out);
+                }
+                "math" => {
+                    out.push(' ');
+                    match find_annotation_tex(children) {
+                        Some(tex) => out.push_str(&tex),
+                        None => render(children, out, list_stack),
+                    }
+                    out.push(' ');
+                }
+                _ => {
+                    out.push(' ');
+                    render(children, out, list_stack);
+                    out.push(' ');
+                }
+            },
+        }
+    }
+}
+
+/// Collapses the whitespace `render` leaves behind (plain spaces at inline boundaries, `\n\n` at
+/// block boundaries) into single spaces within a line and single blank lines between blocks,
+/// trimming leading/trailing blank lines.
+fn normalize(raw: &str) -> String {
+    let mut lines: Vec<String> = raw
+        .split('\n')
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+
+    let mut out_lines: Vec<String> = Vec::with_capacity(lines.len());
+    for line in lines.drain(..) {
+        if line.is_empty() && out_lines.last().map(String::is_empty).unwrap_or(true) {
+            continue;
+        }
+        out_lines.push(line);
+    }
+    while out_lines.last().map(String::is_empty).unwrap_or(false) {
+        out_lines.pop();
+    }
+    out_lines.join("\n")
+}
+
+pub fn html_to_plain_text(input: &str) -> String {
+    let nodes = sanitize_to_nodes(input);
+    let mut out = String::new();
+    let mut list_stack = Vec::new();
+    render(&nodes, &mut out, &mut list_stack);
+    normalize(&out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_paragraphs_with_a_blank_line() {
+        let html = "<p>First</p><p>Second</p>";
+        assert_eq!(html_to_plain_text(html), "First\n\nSecond");
+    }
+
+    #[test]
+    fn renders_list_items_with_prefixes() {
+        let html = "<ul><li>One</li><li>Two</li></ul><ol><li>First</li><li>Second</li></ol>";
+        let out = html_to_plain_text(html);
+        assert!(out.contains("- One"));
+        assert!(out.contains("- Two"));
+        assert!(out.contains("1. First"));
+        assert!(out.contains("2. Second"));
+    }
+
+    #[test]
+    fn prefers_tex_annotation_over_mathml_text() {
+        let html = r#"<p>See <math><mi>x</mi><annotation encoding="application/x-tex">x^2</annotation></math> here</p>"#;
+        assert_eq!(html_to_plain_text(html), "See x^2 here");
+    }
+
+    #[test]
+    fn inserts_space_between_adjacent_inline_elements() {
+        let html = "<table><tr><td>A</td><td>B</td></tr></table>";
+        assert_eq!(html_to_plain_text(html), "A B");
+    }
+}
@@ -41,6 +41,48 @@ fn sanitize_href(href: &str) -> Option<String> {
     Some(h.to_string())
 }
 
+/// How `<img src>` is handled while sanitizing pasted HTML, in the spirit of `CurrencyMode`:
+/// hosts pasting newsletter/email HTML often want remote images neither fetched (tracking pixels)
+/// nor left broken (Office has no network access), so the choice is a caller-selected policy
+/// rather than hard-coded. `Default` (`Keep`) reproduces this crate's long-standing behavior of
+/// passing `src` through unconditionally.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ImagePolicy {
+    /// Pass `src` through unconditionally, including remote URLs and `data:` URIs.
+    #[default]
+    Keep,
+    /// Drop `<img>` entirely, content and all.
+    Strip,
+    /// Keep the `<img>` but move its address to `data-src` so nothing loads - the host can
+    /// re-hydrate `src` itself once/if it decides to fetch the image.
+    Neutralize,
+    /// Keep only `src` values that are already an inline `data:image/...` URI; otherwise behaves
+    /// like `Strip` would, since a dangling remote reference is no more useful than no image.
+    DataUriOnly,
+}
+
+/// Applies `policy` to an `<img>`'s `src`, returning the attribute name/value pair to emit (`src`
+/// for `Keep`/`DataUriOnly`, `data-src` for `Neutralize`) or `None` if the image should have no
+/// address at all - analogous to `sanitize_href`, but the policy can also rename the attribute.
+fn sanitize_img_src(src: &str, policy: ImagePolicy) -> Option<(&'static str, String)> {
+    let s = src.trim();
+    if s.is_empty() {
+        return None;
+    }
+    match policy {
+        ImagePolicy::Keep => Some(("src", s.to_string())),
+        ImagePolicy::Strip => None,
+        ImagePolicy::Neutralize => Some(("data-src", s.to_string())),
+        ImagePolicy::DataUriOnly => {
+            if s.to_ascii_lowercase().starts_with("data:image/") {
+                Some(("src", s.to_string()))
+            } else {
+                None
+            }
+        }
+    }
+}
+
 fn is_keep_tag(lower: &str) -> bool {
     matches!(
         lower,
@@ -63,6 +105,9 @@ fn is_keep_tag(lower: &str) -> bool {
             | "ol"
             | "li"
             | "table"
+            | "caption"
+            | "colgroup"
+            | "col"
             | "thead"
             | "tbody"
             | "tr"
@@ -88,7 +133,7 @@ fn is_drop_content_tag(lower: &str) -> bool {
 }
 
 #[derive(Clone, Debug)]
-enum OutNode {
+pub(crate) enum OutNode {
     Element {
         tag: String,
         attrs: Vec<(String, String)>,
@@ -98,7 +143,7 @@ enum OutNode {
     Comment(String),
 }
 
-fn keep_attrs(tag: &str, attrs: &HashMap<String, String>) -> Vec<(String, String)> {
+fn keep_attrs(tag: &str, attrs: &HashMap<String, String>, image_policy: ImagePolicy) -> Vec<(String, String)> {
     let t = tag.to_ascii_lowercase();
     let mut out: Vec<(String, String)> = Vec::new();
     match t.as_str() {
@@ -111,7 +156,10 @@ fn keep_attrs(tag: &str, attrs: &HashMap<String, String>) -> Vec<(String, String
             }
         }
         "img" => {
-            for k in ["src", "alt", "title", "width", "height"] {
+            if let Some((k, v)) = attrs.get("src").and_then(|v| sanitize_img_src(v, image_policy)) {
+                out.push((k.to_string(), v));
+            }
+            for k in ["alt", "title", "width", "height"] {
                 if let Some(v) = attrs.get(k) {
                     out.push((k.to_string(), v.to_string()));
                 }
@@ -123,6 +171,19 @@ fn keep_attrs(tag: &str, attrs: &HashMap<String, String>) -> Vec<(String, String
                     out.push((k.to_string(), v.to_string()));
                 }
             }
+            if let Some(align) = resolve_align(attrs) {
+                out.push(("align".to_string(), align));
+            }
+            if let Some(v) = attrs.get("valign") {
+                out.push(("valign".to_string(), v.trim().to_ascii_lowercase()));
+            }
+        }
+        "col" => {
+            for k in ["span", "width"] {
+                if let Some(v) = attrs.get(k) {
+                    out.push((k.to_string(), v.to_string()));
+                }
+            }
         }
         "math" => {
             if let Some(v) = attrs.get("xmlns") {
@@ -137,6 +198,195 @@ fn keep_attrs(tag: &str, attrs: &HashMap<String, String>) -> Vec<(String, String
     out
 }
 
+fn is_bold_font_weight(val: &str) -> bool {
+    matches!(val, "bold" | "bolder" | "600" | "700" | "800" | "900")
+}
+
+/// Reads the `style` declarations a rich-text editor (Google Docs, a browser's "copy" of
+/// rendered HTML, ...) uses in place of semantic tags and maps each one this crate understands to
+/// the tag it's equivalent to, in declaration order - mirroring how a mail parser decomposes a
+/// structured parameter string into typed values rather than keeping it opaque. Declarations this
+/// crate doesn't have a tag for (color, font-size, ...) are silently ignored; callers wrap the
+/// element's sanitized content in these tags, outermost first.
+fn style_wrapper_tags(style: &str) -> Vec<&'static str> {
+    let mut tags = Vec::new();
+    for decl in style.split(';') {
+        let mut parts = decl.splitn(2, ':');
+        let prop = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let val = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        if prop.is_empty() || val.is_empty() {
+            continue;
+        }
+        match prop.as_str() {
+            "font-weight" if is_bold_font_weight(&val) => tags.push("b"),
+            "font-style" if val == "italic" || val == "oblique" => tags.push("i"),
+            "text-decoration" | "text-decoration-line" => {
+                if val.contains("underline") {
+                    tags.push("u");
+                }
+                if val.contains("line-through") {
+                    tags.push("s");
+                }
+            }
+            "vertical-align" if val == "sub" => tags.push("sub"),
+            "vertical-align" if val == "super" => tags.push("sup"),
+            _ => {}
+        }
+    }
+    tags
+}
+
+/// Wraps `nodes` in one `OutNode::Element` per tag `style_wrapper_tags` recovered from `attrs`'
+/// `style` declaration, nesting them in declaration order (the first declaration ends up
+/// outermost) so the sanitized result round-trips through `serialize_node` like any other element.
+fn wrap_with_style(nodes: Vec<OutNode>, attrs: &HashMap<String, String>) -> Vec<OutNode> {
+    let Some(style) = attrs.get("style") else {
+        return nodes;
+    };
+    style_wrapper_tags(style).into_iter().rev().fold(nodes, |children, tag| {
+        vec![OutNode::Element { tag: tag.to_string(), attrs: Vec::new(), children }]
+    })
+}
+
+/// A cell's own alignment: a `text-align` declaration in its inline `style` (checked first, since
+/// it's the more specific/modern way rich editors express it), falling back to a plain `align`
+/// attribute. `None` when the cell specifies neither, so a caller can fall back further to its
+/// column's `<colgroup>` alignment.
+fn resolve_align(attrs: &HashMap<String, String>) -> Option<String> {
+    if let Some(style) = attrs.get("style") {
+        for decl in style.split(';') {
+            let mut parts = decl.splitn(2, ':');
+            let prop = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+            let val = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+            if prop == "text-align" && !val.is_empty() {
+                return Some(val);
+            }
+        }
+    }
+    attrs
+        .get("align")
+        .map(|v| v.trim().to_ascii_lowercase())
+        .filter(|v| !v.is_empty())
+}
+
+fn cell_colspan(attrs: &HashMap<String, String>) -> usize {
+    attrs
+        .get("colspan")
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(1)
+}
+
+/// One alignment per column, read off a `<table>`'s `<colgroup>` (`None` for a column no `<col>`
+/// covers, or a table with no `colgroup` at all) - the same per-column metadata tracking the
+/// org-mode table parser keeps, so a `<td>`/`<th>` with no alignment of its own can still inherit
+/// its column's.
+fn colgroup_aligns(table: &Handle) -> Vec<Option<String>> {
+    for child in node_children(table) {
+        if elem_tag_lower(&child).as_deref() == Some("colgroup") {
+            let mut aligns = Vec::new();
+            for col in node_children(&child) {
+                if elem_tag_lower(&col).as_deref() == Some("col") {
+                    let attrs = attrs_map(&col);
+                    let align = resolve_align(&attrs);
+                    let span = attrs
+                        .get("span")
+                        .and_then(|v| v.trim().parse::<usize>().ok())
+                        .filter(|n| *n > 0)
+                        .unwrap_or(1);
+                    for _ in 0..span {
+                        aligns.push(align.clone());
+                    }
+                }
+            }
+            return aligns;
+        }
+    }
+    Vec::new()
+}
+
+fn sanitize_cell(cell: &Handle, tag_lower: &str, col_align: Option<&String>, in_li: bool, image_policy: ImagePolicy) -> OutNode {
+    let attrs = attrs_map(cell);
+    let mut kept = keep_attrs(tag_lower, &attrs, image_policy);
+    if !kept.iter().any(|(k, _)| k == "align") {
+        if let Some(align) = col_align {
+            kept.push(("align".to_string(), align.clone()));
+        }
+    }
+    OutNode::Element {
+        tag: tag_lower.to_string(),
+        attrs: kept,
+        children: sanitize_children(&node_children(cell), false, in_li, image_policy),
+    }
+}
+
+fn sanitize_row(row: &Handle, col_aligns: &[Option<String>], in_li: bool, image_policy: ImagePolicy) -> OutNode {
+    let mut children = Vec::new();
+    let mut col = 0usize;
+    for cell in node_children(row) {
+        let tag_lower = match elem_tag_lower(&cell) {
+            Some(t) if t == "td" || t == "th" => t,
+            _ => continue,
+        };
+        let span = cell_colspan(&attrs_map(&cell));
+        children.push(sanitize_cell(&cell, &tag_lower, col_aligns.get(col).and_then(|a| a.as_ref()), in_li, image_policy));
+        col += span;
+    }
+    OutNode::Element { tag: "tr".to_string(), attrs: Vec::new(), children }
+}
+
+fn sanitize_rows_container(container: &Handle, col_aligns: &[Option<String>], in_li: bool, image_policy: ImagePolicy) -> Vec<OutNode> {
+    node_children(container)
+        .iter()
+        .filter(|c| elem_tag_lower(c).as_deref() == Some("tr"))
+        .map(|tr| sanitize_row(tr, col_aligns, in_li, image_policy))
+        .collect()
+}
+
+/// Walks a `<table>` directly (rather than through the generic `sanitize_node` recursion) so
+/// `colgroup_aligns` can be computed once up front and threaded down to every `<td>`/`<th>`,
+/// alongside keeping `<caption>`/`<colgroup>`/`<col>` that the generic keep-tag path would
+/// otherwise have no special handling for.
+fn sanitize_table(table: &Handle, in_li: bool, image_policy: ImagePolicy) -> OutNode {
+    let col_aligns = colgroup_aligns(table);
+    let mut children = Vec::new();
+    for child in node_children(table) {
+        match elem_tag_lower(&child).as_deref() {
+            Some("caption") => children.push(OutNode::Element {
+                tag: "caption".to_string(),
+                attrs: Vec::new(),
+                children: sanitize_children(&node_children(&child), false, in_li, image_policy),
+            }),
+            Some("colgroup") => {
+                let cols = node_children(&child)
+                    .iter()
+                    .filter(|c| elem_tag_lower(c).as_deref() == Some("col"))
+                    .map(|col| OutNode::Element {
+                        tag: "col".to_string(),
+                        attrs: keep_attrs("col", &attrs_map(col), image_policy),
+                        children: Vec::new(),
+                    })
+                    .collect();
+                children.push(OutNode::Element {
+                    tag: "colgroup".to_string(),
+                    attrs: keep_attrs("colgroup", &attrs_map(&child), image_policy),
+                    children: cols,
+                });
+            }
+            Some(tag @ ("thead" | "tbody" | "tfoot")) => {
+                children.push(OutNode::Element {
+                    tag: tag.to_string(),
+                    attrs: Vec::new(),
+                    children: sanitize_rows_container(&child, &col_aligns, in_li, image_policy),
+                });
+            }
+            Some("tr") => children.push(sanitize_row(&child, &col_aligns, in_li, image_policy)),
+            _ => {}
+        }
+    }
+    OutNode::Element { tag: "table".to_string(), attrs: Vec::new(), children }
+}
+
 fn find_first_math(node: &Handle) -> Option<Handle> {
     if let Some(tag) = elem_tag_lower(node) {
         if tag == "math" {
@@ -151,10 +401,10 @@ fn find_first_math(node: &Handle) -> Option<Handle> {
     None
 }
 
-fn sanitize_children(children: &[Handle], in_math: bool, in_li: bool) -> Vec<OutNode> {
+fn sanitize_children(children: &[Handle], in_math: bool, in_li: bool, image_policy: ImagePolicy) -> Vec<OutNode> {
     let mut out: Vec<OutNode> = Vec::new();
     for c in children {
-        out.extend(sanitize_node(c, in_math, in_li));
+        out.extend(sanitize_node(c, in_math, in_li, image_policy));
     }
     out
 }
@@ -182,11 +432,11 @@ fn element_local_name(node: &Handle) -> Option<String> {
     }
 }
 
-fn sanitize_node(node: &Handle, in_math: bool, in_li: bool) -> Vec<OutNode> {
+fn sanitize_node(node: &Handle, in_math: bool, in_li: bool, image_policy: ImagePolicy) -> Vec<OutNode> {
     match &node.data {
         NodeData::Text { contents } => vec![OutNode::Text(contents.borrow().to_string())],
         NodeData::Comment { contents } => vec![OutNode::Comment(contents.to_string())],
-        NodeData::Document => sanitize_children(&node_children(node), in_math, in_li),
+        NodeData::Document => sanitize_children(&node_children(node), in_math, in_li, image_policy),
         NodeData::Doctype { .. } | NodeData::ProcessingInstruction { .. } => Vec::new(),
         NodeData::Element { .. } => {
             let tag_lower = elem_tag_lower(node).unwrap_or_default();
@@ -199,7 +449,7 @@ fn sanitize_node(node: &Handle, in_math: bool, in_li: bool) -> Vec<OutNode> {
                 return vec![OutNode::Element {
                     tag: element_local_name(node).unwrap_or_default(),
                     attrs,
-                    children: sanitize_children(&node_children(node), true, in_li),
+                    children: sanitize_children(&node_children(node), true, in_li, image_policy),
                 }];
             }
 
@@ -207,41 +457,53 @@ fn sanitize_node(node: &Handle, in_math: bool, in_li: bool) -> Vec<OutNode> {
                 return Vec::new();
             }
 
+            if tag_lower == "img" && image_policy == ImagePolicy::Strip {
+                return Vec::new();
+            }
+
             let attrs = attrs_map(node);
 
             if tag_lower == "span" && has_katex_class(&attrs) {
                 if let Some(m) = find_first_math(node) {
-                    return sanitize_node(&m, false, in_li);
+                    return sanitize_node(&m, false, in_li, image_policy);
                 }
             }
 
             if tag_lower == "math" {
                 return vec![OutNode::Element {
                     tag: "math".to_string(),
-                    attrs: keep_attrs("math", &attrs),
-                    children: sanitize_children(&node_children(node), true, in_li),
+                    attrs: keep_attrs("math", &attrs, image_policy),
+                    children: sanitize_children(&node_children(node), true, in_li, image_policy),
                 }];
             }
 
             let now_in_li = in_li || tag_lower == "li";
+
+            if tag_lower == "table" {
+                return wrap_with_style(vec![sanitize_table(node, now_in_li, image_policy)], &attrs);
+            }
+
             if now_in_li && (tag_lower == "p" || tag_lower == "div") {
-                let mut kids = sanitize_children(&node_children(node), false, true);
+                let mut kids = sanitize_children(&node_children(node), false, true, image_policy);
                 if !ends_with_space(&kids) {
                     kids.push(OutNode::Text(" ".to_string()));
                 }
-                return kids;
+                return wrap_with_style(kids, &attrs);
             }
 
             if !is_keep_tag(&tag_lower) {
-                return sanitize_children(&node_children(node), false, now_in_li);
+                return wrap_with_style(sanitize_children(&node_children(node), false, now_in_li, image_policy), &attrs);
             }
 
             let tag = element_local_name(node).unwrap_or_default();
-            vec![OutNode::Element {
-                tag: tag.clone(),
-                attrs: keep_attrs(&tag, &attrs),
-                children: sanitize_children(&node_children(node), false, now_in_li),
-            }]
+            wrap_with_style(
+                vec![OutNode::Element {
+                    tag: tag.clone(),
+                    attrs: keep_attrs(&tag, &attrs, image_policy),
+                    children: sanitize_children(&node_children(node), false, now_in_li, image_policy),
+                }],
+                &attrs,
+            )
         }
     }
 }
@@ -276,7 +538,7 @@ fn esc_attr(s: &str) -> String {
 fn is_void(tag: &str) -> bool {
     matches!(
         tag.to_ascii_lowercase().as_str(),
-        "br" | "hr" | "img" | "meta" | "link" | "input"
+        "br" | "hr" | "img" | "meta" | "link" | "input" | "col"
     )
 }
 
@@ -342,12 +604,27 @@ fn find_body_children(dom: &RcDom) -> Option<Vec<Handle>> {
 }
 
 pub fn sanitize_for_office(input: &str) -> String {
+    sanitize_for_office_with(input, ImagePolicy::default())
+}
+
+/// Like `sanitize_for_office`, but lets the caller pick how `<img src>` is handled instead of
+/// always keeping it - see `ImagePolicy`.
+pub fn sanitize_for_office_with(input: &str, image_policy: ImagePolicy) -> String {
     let dom = parse_to_dom(input);
     let children = find_body_children(&dom).unwrap_or_else(|| dom.document.children.borrow().clone());
-    let sanitized = sanitize_children(&children, false, false);
+    let sanitized = sanitize_children(&children, false, false, image_policy);
     serialize_nodes(&sanitized)
 }
 
+/// Parses and sanitizes `input` the same way `sanitize_for_office` does, but returns the
+/// intermediate `OutNode` tree instead of serializing it back to HTML - for callers (like
+/// `plain_text`) that want to walk the simplified node model rather than re-parse its HTML output.
+pub(crate) fn sanitize_to_nodes(input: &str) -> Vec<OutNode> {
+    let dom = parse_to_dom(input);
+    let children = find_body_children(&dom).unwrap_or_else(|| dom.document.children.borrow().clone());
+    sanitize_children(&children, false, false, ImagePolicy::default())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -377,4 +654,75 @@ mod tests {
         assert!(out.contains("Dual Phasers"));
         assert!(out.contains("So phases missing"));
     }
+
+    #[test]
+    fn converts_inline_style_formatting_to_semantic_tags() {
+        let html = r#"<p><span style="font-weight:bold">Bold</span> <span style="font-style: italic">Italic</span> <span style="text-decoration: underline">Under</span></p>"#;
+        let out = sanitize_for_office(html);
+        assert!(out.contains("<b>Bold</b>"));
+        assert!(out.contains("<i>Italic</i>"));
+        assert!(out.contains("<u>Under</u>"));
+    }
+
+    #[test]
+    fn nests_multiple_style_declarations_in_order() {
+        let html = r#"<span style="font-weight: 700; font-style: italic">Strong</span>"#;
+        let out = sanitize_for_office(html);
+        assert!(out.contains("<b><i>Strong</i></b>"));
+    }
+
+    #[test]
+    fn keeps_caption_and_colgroup() {
+        let html = r#"<table><caption>Totals</caption><colgroup><col span="2" /></colgroup><tr><td>1</td><td>2</td></tr></table>"#;
+        let out = sanitize_for_office(html);
+        assert!(out.contains("<caption>Totals</caption>"));
+        assert!(out.contains(r#"<col span="2""#));
+    }
+
+    #[test]
+    fn propagates_colgroup_alignment_to_cells_without_their_own() {
+        let html = r#"<table><colgroup><col align="center" /><col align="right" /></colgroup><tr><td>A</td><td align="left">B</td></tr></table>"#;
+        let out = sanitize_for_office(html);
+        assert!(out.contains(r#"<td align="center">A</td>"#));
+        assert!(out.contains(r#"<td align="left">B</td>"#));
+    }
+
+    #[test]
+    fn cell_style_text_align_wins_over_align_attribute() {
+        let html = r#"<table><tr><td align="left" style="text-align: right">A</td></tr></table>"#;
+        let out = sanitize_for_office(html);
+        assert!(out.contains(r#"<td align="right">A</td>"#));
+    }
+
+    #[test]
+    fn keep_image_policy_passes_src_through_unchanged() {
+        let html = r#"<p><img src="https://example.com/a.png" alt="A"></p>"#;
+        let out = sanitize_for_office_with(html, ImagePolicy::Keep);
+        assert!(out.contains(r#"src="https://example.com/a.png""#));
+    }
+
+    #[test]
+    fn strip_image_policy_removes_the_element_entirely() {
+        let html = r#"<p>before<img src="https://example.com/a.png">after</p>"#;
+        let out = sanitize_for_office_with(html, ImagePolicy::Strip);
+        assert!(!out.to_ascii_lowercase().contains("<img"));
+        assert!(out.contains("beforeafter"));
+    }
+
+    #[test]
+    fn neutralize_image_policy_moves_src_to_data_src() {
+        let html = r#"<img src="https://example.com/a.png" alt="A">"#;
+        let out = sanitize_for_office_with(html, ImagePolicy::Neutralize);
+        assert!(!out.contains(r#"src="https://example.com/a.png""#));
+        assert!(out.contains(r#"data-src="https://example.com/a.png""#));
+        assert!(out.contains(r#"alt="A""#));
+    }
+
+    #[test]
+    fn data_uri_only_policy_keeps_inline_images_but_drops_remote_ones() {
+        let html = r#"<img id="remote" src="https://example.com/a.png"><img id="inline" src="data:image/png;base64,Zm9v">"#;
+        let out = sanitize_for_office_with(html, ImagePolicy::DataUriOnly);
+        assert!(out.contains(r#"src="data:image/png;base64,Zm9v""#));
+        assert!(!out.contains("example.com"));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::math_alphabets::rewrite_math_alphabets;
 use latex2mathml::{latex_to_mathml, DisplayStyle};
 
 pub fn tex_to_mathml(latex: &str, display: bool) -> Result<String, String> {
@@ -7,7 +8,12 @@ pub fn tex_to_mathml(latex: &str, display: bool) -> Result<String, String> {
         DisplayStyle::Inline
     };
 
-    match latex_to_mathml(latex, style) {
+    // latex2mathml doesn't implement \mathbb/\mathcal/\mathfrak/\mathbf/\mathscr - rewrite them to
+    // the Unicode glyphs they're standing in for first, so it sees plain identifiers it can parse
+    // instead of rejecting the whole command with a [PARSE ERROR: ...].
+    let latex = rewrite_math_alphabets(latex);
+
+    match latex_to_mathml(&latex, style) {
         Ok(mathml) => {
             if mathml.contains("[PARSE ERROR:") {
                 return Err("parse error: unsupported LaTeX command or token".to_string());
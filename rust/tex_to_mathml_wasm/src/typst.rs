@@ -0,0 +1,312 @@
+// Transpiles a (small, common-case) dialect of Typst math syntax down to LaTeX, so GPT output that
+// comes out Typst-flavored (`sum_(i=1)^n`, `frac(a, b)`, `sqrt(x)`, `arrow`, `NN`, ...) can still
+// ride `tex::tex_to_mathml` instead of needing a second MathML backend.
+
+/// Which math dialect a `TexJob::latex` is written in. `Default` is `Latex`, matching every
+/// caller that predates this module; `Typst` routes the job through `typst_math_to_latex` before
+/// `tex::tex_to_mathml` ever sees it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MathDialect {
+    #[default]
+    Latex,
+    Typst,
+}
+
+/// Known Typst math identifiers mapped to the LaTeX command/Unicode they stand in for. Anything
+/// not in this table falls back to the generic rules in `parse_atom` (bare-through for a single
+/// letter or digits, `\mathrm{...}` for a longer unknown name).
+const SYMBOL_TABLE: &[(&str, &str)] = &[
+    ("sum", "\\sum"),
+    ("prod", "\\prod"),
+    ("integral", "\\int"),
+    ("int", "\\int"),
+    ("infinity", "\\infty"),
+    ("infty", "\\infty"),
+    ("arrow", "\\to"),
+    ("in", "\\in"),
+    ("notin", "\\notin"),
+    ("subset", "\\subset"),
+    ("supset", "\\supset"),
+    ("union", "\\cup"),
+    ("inter", "\\cap"),
+    ("NN", "\\mathbb{N}"),
+    ("RR", "\\mathbb{R}"),
+    ("ZZ", "\\mathbb{Z}"),
+    ("QQ", "\\mathbb{Q}"),
+    ("CC", "\\mathbb{C}"),
+    ("alpha", "\\alpha"),
+    ("beta", "\\beta"),
+    ("gamma", "\\gamma"),
+    ("delta", "\\delta"),
+    ("epsilon", "\\epsilon"),
+    ("theta", "\\theta"),
+    ("lambda", "\\lambda"),
+    ("mu", "\\mu"),
+    ("pi", "\\pi"),
+    ("sigma", "\\sigma"),
+    ("phi", "\\phi"),
+    ("omega", "\\omega"),
+    ("times", "\\times"),
+    ("dot", "\\cdot"),
+    ("cdot", "\\cdot"),
+    ("leq", "\\leq"),
+    ("geq", "\\geq"),
+    ("neq", "\\neq"),
+    ("approx", "\\approx"),
+    ("equiv", "\\equiv"),
+    ("forall", "\\forall"),
+    ("exists", "\\exists"),
+    ("nabla", "\\nabla"),
+    ("partial", "\\partial"),
+    ("emptyset", "\\emptyset"),
+    ("star", "\\star"),
+    ("lim", "\\lim"),
+];
+
+fn lookup_symbol(name: &str) -> Option<&'static str> {
+    SYMBOL_TABLE
+        .iter()
+        .find(|(k, _)| *k == name)
+        .map(|(_, v)| *v)
+}
+
+// Identifiers never include `_`/`^`: those are the subscript/superscript operators.
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+}
+
+/// Byte offset of the `)` matching the `(` at the start of `s`, accounting for nesting. `None`
+/// means unbalanced (the caller then treats the `(` as a literal character instead of failing the
+/// whole transpile - this runs over arbitrary GPT output, not a strict grammar).
+fn find_matching_paren(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut depth: i32 = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits `s` (the contents between a function call's outer parens) on top-level commas, leaving
+/// commas inside nested parens alone - so `frac(a, b)` sees two args but `sqrt(frac(a, b))` sees
+/// one.
+fn split_top_level_args(s: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut depth: i32 = 0;
+    let mut start = 0;
+    let bytes = s.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            b',' if depth == 0 => {
+                args.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(s[start..].trim());
+    args
+}
+
+/// Parses one space-delimited Typst atom (an identifier, a `name(args)` call, a bare
+/// parenthesized group, or a number), including any trailing `_sub`/`^sup` postfixes, and returns
+/// the LaTeX it lowers to plus the unconsumed remainder of `s`.
+fn parse_atom(s: &str) -> (String, &str) {
+    let (mut latex, mut rest) = parse_head(s);
+
+    loop {
+        let Some(c) = rest.chars().next() else { break };
+        if c != '_' && c != '^' {
+            break;
+        }
+        let op = c;
+        let after_op = &rest[1..];
+        let (body, remainder) = match after_op.chars().next() {
+            Some('(') => {
+                let Some(close_rel) = find_matching_paren(after_op) else {
+                    break;
+                };
+                let inner = &after_op[1..close_rel];
+                (format!("{{{}}}", transpile(inner)), &after_op[close_rel + 1..])
+            }
+            Some(_) => {
+                let (token, remainder) = parse_single_token(after_op);
+                (token, remainder)
+            }
+            None => break,
+        };
+        latex.push(op);
+        latex.push_str(&body);
+        rest = remainder;
+    }
+
+    (latex, rest)
+}
+
+/// A single bare token used as a subscript/superscript body, e.g. the `n` in `^n` or the `i` in
+/// `_i` - kept unbraced per Typst's own convention that single tokens stay bare.
+fn parse_single_token(s: &str) -> (String, &str) {
+    let end = s
+        .find(|c: char| !is_ident_char(c) && c != '-' && c != '+' && c != '.')
+        .unwrap_or(s.len());
+    let end = end.max(if s.is_empty() { 0 } else { 1 });
+    let token = &s[..end];
+    (render_identifier(token), &s[end..])
+}
+
+fn render_identifier(name: &str) -> String {
+    if name.is_empty() {
+        return String::new();
+    }
+    if name.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return name.to_string();
+    }
+    if let Some(mapped) = lookup_symbol(name) {
+        return mapped.to_string();
+    }
+    if name.chars().count() >= 2 {
+        format!("\\mathrm{{{name}}}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Parses the "head" of an atom: a bare parenthesized group, or a name that's either a known
+/// function call (`frac(...)`/`sqrt(...)`) or a plain identifier/number.
+fn parse_head(s: &str) -> (String, &str) {
+    if s.starts_with('(') {
+        match find_matching_paren(s) {
+            Some(close) => {
+                let inner = &s[1..close];
+                return (format!("({})", transpile(inner)), &s[close + 1..]);
+            }
+            None => {
+                // Unbalanced: treat the lone `(` as a literal character rather than failing.
+                return ("(".to_string(), &s[1..]);
+            }
+        }
+    }
+
+    let name_end = s.find(|c: char| !is_ident_char(c)).unwrap_or(s.len());
+    if name_end == 0 {
+        // Not an identifier start (a digit run, punctuation, etc). Consume one char as-is.
+        let ch_len = s.chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+        return (s[..ch_len].to_string(), &s[ch_len..]);
+    }
+
+    let name = &s[..name_end];
+    let rest = &s[name_end..];
+
+    if rest.starts_with('(') {
+        if let Some(close_rel) = find_matching_paren(rest) {
+            let args_src = &rest[1..close_rel];
+            let args = split_top_level_args(args_src);
+            let after = &rest[close_rel + 1..];
+            let rendered = match (name, args.as_slice()) {
+                ("frac", [a, b]) => format!("\\frac{{{}}}{{{}}}", transpile(a), transpile(b)),
+                ("sqrt", [a]) => format!("\\sqrt{{{}}}", transpile(a)),
+                _ => {
+                    let joined = args
+                        .iter()
+                        .map(|a| transpile(a))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("{}({})", render_identifier(name), joined)
+                }
+            };
+            return (rendered, after);
+        }
+    }
+
+    (render_identifier(name), rest)
+}
+
+/// Tokenizes `s` into atoms and lowers each to LaTeX, re-inserting a space between two atoms only
+/// where `s` itself had one - so `x y` stays two space-separated atoms, but a bare operator like
+/// the `=` in a `sum_(i=1)^n` subscript (which splits into the atoms `i`, `=`, `1` since `=` isn't
+/// an identifier char) doesn't grow spurious whitespace that was never there.
+fn transpile(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    let mut first = true;
+    loop {
+        let trimmed = rest.trim_start();
+        if trimmed.is_empty() {
+            break;
+        }
+        if !first && trimmed.len() != rest.len() {
+            out.push(' ');
+        }
+        let (latex, remainder) = parse_atom(trimmed);
+        out.push_str(&latex);
+        rest = remainder;
+        first = false;
+    }
+    out
+}
+
+/// Entry point: transpiles a Typst math expression to LaTeX, for `TexJob`s tagged
+/// `MathDialect::Typst` (see `pipeline::render_mathml_or_error`).
+pub fn typst_math_to_latex(input: &str) -> String {
+    transpile(input.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_known_symbols() {
+        assert_eq!(typst_math_to_latex("arrow"), "\\to");
+        assert_eq!(typst_math_to_latex("in"), "\\in");
+        assert_eq!(typst_math_to_latex("NN"), "\\mathbb{N}");
+        assert_eq!(typst_math_to_latex("RR"), "\\mathbb{R}");
+    }
+
+    #[test]
+    fn rewrites_frac_call() {
+        assert_eq!(typst_math_to_latex("frac(a, b)"), "\\frac{a}{b}");
+    }
+
+    #[test]
+    fn rewrites_sqrt_call() {
+        assert_eq!(typst_math_to_latex("sqrt(x)"), "\\sqrt{x}");
+    }
+
+    #[test]
+    fn translates_sum_with_parenthesized_sub_and_bare_sup() {
+        assert_eq!(typst_math_to_latex("sum_(i=1)^n"), "\\sum_{i=1}^n");
+    }
+
+    #[test]
+    fn single_token_superscript_stays_bare() {
+        assert_eq!(typst_math_to_latex("x^2"), "x^2");
+    }
+
+    #[test]
+    fn space_separated_atoms_are_preserved() {
+        assert_eq!(typst_math_to_latex("x y z"), "x y z");
+    }
+
+    #[test]
+    fn long_unknown_identifier_becomes_mathrm() {
+        assert_eq!(typst_math_to_latex("cost"), "\\mathrm{cost}");
+    }
+
+    #[test]
+    fn nested_function_call_transpiles_args_recursively() {
+        assert_eq!(typst_math_to_latex("sqrt(frac(a, b))"), "\\sqrt{\\frac{a}{b}}");
+    }
+}
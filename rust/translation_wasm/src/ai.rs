@@ -3,6 +3,36 @@
 use crate::service::{TranslationInfo, TranslationService};
 use serde_json::json;
 
+/// Builds the LLM prompt body for a batch of `requests`, wrapping each one's `original_text` in
+/// the `<a i=N>…</a>` index marker `GoogleService`'s HTML endpoint already uses, so a model that
+/// merges, reorders, or drops lines can still be mapped back to the N inputs it was given.
+pub(crate) fn build_marked_prompt(target_lang: &str, requests: &[TranslationInfo]) -> String {
+    let marked_text = requests
+        .iter()
+        .enumerate()
+        .map(|(i, r)| format!("<a i={}>{}</a>", i, r.original_text))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "Translate the following text to {}. Preserve formatting, code blocks, and formulas. Only translate the text content, not code or formulas. Each segment is wrapped in an <a i=N> marker; keep those exact markers around the translated text for each segment and do not merge, reorder, or drop them.\n\nText to translate:\n{}",
+        target_lang, marked_text
+    )
+}
+
+/// Recovers one segment per input from `content`, an LLM reply to a prompt built by
+/// `build_marked_prompt`, using the `<a i=N>` markers it was asked to preserve. Falls back to
+/// splitting `content` back on `\n` (the join `get_request_body` used before markers existed) when
+/// the model drops the markers, so a malformed reply still yields a best-effort result.
+pub(crate) fn split_marked_response(content: &str) -> Vec<(String, Option<String>)> {
+    let marked = crate::html::parse_google_html_response(content).unwrap_or_default();
+    if !marked.is_empty() {
+        marked.into_iter().map(|s| (s, None)).collect()
+    } else {
+        content.split('\n').map(|s| (s.to_string(), None)).collect()
+    }
+}
+
 pub struct ChatGPTService {
     api_key: String,
 }
@@ -32,7 +62,7 @@ impl TranslationService for ChatGPTService {
             if let Some(choice) = choices.get(0) {
                 if let Some(message) = choice.get("message") {
                     if let Some(content) = message.get("content").and_then(|v| v.as_str()) {
-                        return Ok(vec![(content.to_string(), None)]);
+                        return Ok(split_marked_response(content));
                     }
                 }
             }
@@ -60,19 +90,16 @@ impl TranslationService for ChatGPTService {
         target_lang: &str,
         requests: &[TranslationInfo],
     ) -> Option<String> {
-        let text = requests.iter().map(|r| r.original_text.clone()).collect::<Vec<_>>().join("\n");
-        let prompt = format!(
-            "Translate the following text to {}. Preserve formatting, code blocks, and formulas. Only translate the text content, not code or formulas.\n\nText to translate:\n{}",
-            target_lang, text
-        );
-        
+        let prompt = build_marked_prompt(target_lang, requests);
+
         Some(json!({
             "model": "gpt-3.5-turbo",
             "messages": [{
                 "role": "user",
                 "content": prompt
             }],
-            "temperature": 0.3
+            "temperature": 0.3,
+            "stream": true
         }).to_string())
     }
 
@@ -90,6 +117,36 @@ impl TranslationService for ChatGPTService {
     fn get_method(&self) -> &str {
         "POST"
     }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn parse_stream_chunk(&self, buf: &mut String) -> Vec<String> {
+        use serde_json::Value;
+
+        let mut out = Vec::new();
+        while let Some(boundary) = buf.find("\n\n") {
+            let event: String = buf.drain(..boundary + 2).collect();
+            for line in event.lines() {
+                let Some(data) = line.strip_prefix("data: ") else { continue };
+                if data == "[DONE]" {
+                    continue;
+                }
+                let Ok(json) = serde_json::from_str::<Value>(data) else { continue };
+                if let Some(content) = json
+                    .get("choices")
+                    .and_then(|c| c.get(0))
+                    .and_then(|c| c.get("delta"))
+                    .and_then(|d| d.get("content"))
+                    .and_then(|v| v.as_str())
+                {
+                    out.push(content.to_string());
+                }
+            }
+        }
+        out
+    }
 }
 
 pub struct GeminiService {
@@ -127,7 +184,7 @@ impl TranslationService for GeminiService {
                     if let Some(parts) = content.get("parts").and_then(|v| v.as_array()) {
                         if let Some(part) = parts.get(0) {
                             if let Some(text) = part.get("text").and_then(|v| v.as_str()) {
-                                return Ok(vec![(text.to_string(), None)]);
+                                return Ok(split_marked_response(text));
                             }
                         }
                     }
@@ -148,7 +205,7 @@ impl TranslationService for GeminiService {
         _target_lang: &str,
         _requests: &[TranslationInfo],
     ) -> String {
-        format!("?key={}", crate::utils::url_encode(&self.api_key))
+        format!("&key={}", crate::utils::url_encode_query(&self.api_key))
     }
 
     fn get_request_body(
@@ -157,12 +214,8 @@ impl TranslationService for GeminiService {
         target_lang: &str,
         requests: &[TranslationInfo],
     ) -> Option<String> {
-        let text = requests.iter().map(|r| r.original_text.clone()).collect::<Vec<_>>().join("\n");
-        let prompt = format!(
-            "Translate the following text to {}. Preserve formatting, code blocks, and formulas. Only translate the text content, not code or formulas.\n\nText to translate:\n{}",
-            target_lang, text
-        );
-        
+        let prompt = build_marked_prompt(target_lang, requests);
+
         Some(json!({
             "contents": [{
                 "parts": [{
@@ -177,12 +230,41 @@ impl TranslationService for GeminiService {
     }
 
     fn get_base_url(&self) -> String {
-        format!("https://generativelanguage.googleapis.com/v1/models/{}:generateContent", self.model)
+        format!("https://generativelanguage.googleapis.com/v1/models/{}:streamGenerateContent?alt=sse", self.model)
     }
 
     fn get_method(&self) -> &str {
         "POST"
     }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn parse_stream_chunk(&self, buf: &mut String) -> Vec<String> {
+        use serde_json::Value;
+
+        let mut out = Vec::new();
+        while let Some(boundary) = buf.find("\n\n") {
+            let event: String = buf.drain(..boundary + 2).collect();
+            for line in event.lines() {
+                let Some(data) = line.strip_prefix("data: ") else { continue };
+                let Ok(json) = serde_json::from_str::<Value>(data) else { continue };
+                if let Some(text) = json
+                    .get("candidates")
+                    .and_then(|c| c.get(0))
+                    .and_then(|c| c.get("content"))
+                    .and_then(|c| c.get("parts"))
+                    .and_then(|p| p.get(0))
+                    .and_then(|p| p.get("text"))
+                    .and_then(|v| v.as_str())
+                {
+                    out.push(text.to_string());
+                }
+            }
+        }
+        out
+    }
 }
 
 pub struct PollinationsService {
@@ -213,18 +295,18 @@ impl TranslationService for PollinationsService {
         match serde_json::from_str::<serde_json::Value>(response) {
             Ok(json) => {
                 if let Some(text) = json.get("text").and_then(|v| v.as_str()) {
-                    Ok(vec![(text.to_string(), None)])
+                    Ok(split_marked_response(text))
                 } else if let Some(result) = json.get("result").and_then(|v| v.as_str()) {
-                    Ok(vec![(result.to_string(), None)])
+                    Ok(split_marked_response(result))
                 } else if let Some(content) = json.get("content").and_then(|v| v.as_str()) {
-                    Ok(vec![(content.to_string(), None)])
+                    Ok(split_marked_response(content))
                 } else if let Some(response) = json.get("response").and_then(|v| v.as_str()) {
-                    Ok(vec![(response.to_string(), None)])
+                    Ok(split_marked_response(response))
                 } else {
-                    Ok(vec![(response.trim().to_string(), None)])
+                    Ok(split_marked_response(response.trim()))
                 }
             }
-            Err(_) => Ok(vec![(response.trim().to_string(), None)]),
+            Err(_) => Ok(split_marked_response(response.trim())),
         }
     }
 
@@ -238,16 +320,12 @@ impl TranslationService for PollinationsService {
         target_lang: &str,
         requests: &[TranslationInfo],
     ) -> String {
-        let text = requests.iter().map(|r| r.original_text.clone()).collect::<Vec<_>>().join("\n");
-        let prompt = format!(
-            "Translate the following text to {}. Preserve formatting, code blocks, and formulas. Only translate the text content, not code or formulas.\n\nText to translate:\n{}",
-            target_lang, text
-        );
-        
+        let prompt = build_marked_prompt(target_lang, requests);
+
         if self.custom_endpoint.is_some() {
-            format!("?prompt={}", crate::utils::url_encode(&prompt))
+            format!("?prompt={}", crate::utils::url_encode_query(&prompt))
         } else {
-            format!("/{}", crate::utils::url_encode(&prompt))
+            format!("/{}", crate::utils::url_encode_path(&prompt))
         }
     }
 
@@ -2,10 +2,24 @@
 // Ports TWP's auth helper classes
 // Note: Actual HTTP calls will be handled via JavaScript bridge
 
+/// How long a found token is trusted before `should_update` asks for a new one anyway, even if it
+/// hasn't hit its own `expires_at`.
+const FOUND_TTL_MS: u64 = 20 * 60_000;
+/// How long to wait before retrying after the endpoint reported no auth was available.
+const NOT_FOUND_TTL_MS: u64 = 5 * 60_000;
+/// How long to wait before the very first retry, before we know whether "not found" is
+/// persistent or just a slow first fetch.
+const FIRST_ATTEMPT_TTL_MS: u64 = 60_000;
+
 pub struct GoogleHelper {
     last_request_time: Option<u64>,
     translate_auth: Option<String>,
     auth_not_found: bool,
+    /// Epoch millis after which `translate_auth` is treated as stale; `get_auth` stops returning
+    /// it past this point even though it's still cached, so a caller knows to fetch a fresh one.
+    expires_at: Option<u64>,
+    max_retries: u32,
+    base_delay_ms: u64,
 }
 
 impl GoogleHelper {
@@ -14,28 +28,76 @@ impl GoogleHelper {
             last_request_time: None,
             translate_auth: None,
             auth_not_found: false,
+            expires_at: None,
+            max_retries: crate::retry::MAX_ATTEMPTS,
+            base_delay_ms: crate::retry::DEFAULT_BASE_DELAY_MS,
         }
     }
 
-    pub fn get_auth(&self) -> Option<String> {
+    /// Returns the cached token, unless it's expired as of `now_millis`.
+    pub fn get_auth(&self, now_millis: u64) -> Option<String> {
+        if self.is_expired(now_millis) {
+            return None;
+        }
         self.translate_auth.clone()
     }
 
-    pub fn set_auth(&mut self, auth: String) {
+    pub fn is_expired(&self, now_millis: u64) -> bool {
+        self.expires_at.map(|exp| now_millis >= exp).unwrap_or(false)
+    }
+
+    /// Caches `auth`, good for `ttl_ms` from `now_millis`.
+    pub fn set_auth(&mut self, auth: String, now_millis: u64, ttl_ms: u64) {
         self.translate_auth = Some(auth);
         self.auth_not_found = false;
+        self.last_request_time = Some(now_millis);
+        self.expires_at = Some(now_millis.saturating_add(ttl_ms));
     }
 
-    pub fn set_auth_not_found(&mut self) {
+    pub fn set_auth_not_found(&mut self, now_millis: u64) {
         // No hardcoded fallback - API keys should be provided by the user
         // This method marks that auth was not found, but does not set a fallback key
         self.auth_not_found = true;
+        self.translate_auth = None;
+        self.expires_at = None;
+        self.last_request_time = Some(now_millis);
+    }
+
+    /// Throws away the cached token so the next `get_auth` call returns `None`; used when the
+    /// endpoint rejects it with a `401` so the caller knows to fetch a fresh one.
+    pub fn invalidate(&mut self) {
+        self.translate_auth = None;
+        self.expires_at = None;
+    }
+
+    pub fn should_update(&self, now_millis: u64) -> bool {
+        let Some(last) = self.last_request_time else {
+            return true;
+        };
+        let elapsed = now_millis.saturating_sub(last);
+        if self.translate_auth.is_some() {
+            self.is_expired(now_millis) || elapsed >= FOUND_TTL_MS
+        } else if self.auth_not_found {
+            elapsed >= NOT_FOUND_TTL_MS
+        } else {
+            elapsed >= FIRST_ATTEMPT_TTL_MS
+        }
     }
 
-    pub fn should_update(&self) -> bool {
-        // Check if we need to update (cache for 20 minutes if found, 5 minutes if not found, 1 minute if first time)
-        // Actual time checking will be done in JavaScript
-        self.last_request_time.is_none() || self.translate_auth.is_none()
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    pub fn base_delay_ms(&self) -> u64 {
+        self.base_delay_ms
+    }
+
+    pub fn set_max_retries(&mut self, max_retries: u32) {
+        self.max_retries = max_retries;
+    }
+
+    pub fn set_base_delay_ms(&mut self, base_delay_ms: u64) {
+        self.base_delay_ms = base_delay_ms;
     }
 }
 
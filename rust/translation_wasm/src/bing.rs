@@ -1,7 +1,7 @@
 // Bing/Microsoft Translator service implementation
 
 use crate::escape::escape_html;
-use crate::service::{TranslationInfo, TranslationService};
+use crate::service::{SentenceBoundary, TranslationInfo, TranslationService};
 use crate::utils::url_encode;
 use serde_json::json;
 
@@ -33,6 +33,16 @@ impl BingService {
 }
 
 
+/// Reads a `sentLen.srcSentLen`/`transSentLen` array (each element the character count of one
+/// sentence, in order) out of the raw JSON `Value`.
+fn lengths_from_value(value: &serde_json::Value) -> Option<Vec<usize>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|v| v.as_u64().map(|n| n as usize))
+        .collect()
+}
+
 fn map_language_code(lang: &str) -> String {
     // Language code mappings from TWP
     match lang {
@@ -100,6 +110,31 @@ impl TranslationService for BingService {
         Ok(results)
     }
 
+    fn parse_sentence_boundaries(&self, response: &str) -> Option<Vec<SentenceBoundary>> {
+        use serde_json::Value;
+
+        let json: Value = serde_json::from_str(response).ok()?;
+        let translations = json.as_array()?.first()?.get("translations")?.as_array()?;
+        let sent_len = translations.first()?.get("sentLen")?;
+        let src_lengths = lengths_from_value(sent_len.get("srcSentLen")?)?;
+        let trans_lengths = lengths_from_value(sent_len.get("transSentLen")?)?;
+        if src_lengths.len() != trans_lengths.len() {
+            return None;
+        }
+
+        let mut boundaries = Vec::with_capacity(src_lengths.len());
+        let mut src_pos = 0usize;
+        let mut trans_pos = 0usize;
+        for (src_len, trans_len) in src_lengths.into_iter().zip(trans_lengths) {
+            let source = (src_pos, src_pos + src_len);
+            let translated = (trans_pos, trans_pos + trans_len);
+            boundaries.push(SentenceBoundary { source, translated });
+            src_pos += src_len;
+            trans_pos += trans_len;
+        }
+        Some(boundaries)
+    }
+
     fn transform_response(&self, result: &str, dont_sort: bool) -> Vec<String> {
         // Parse HTML response with <b{id}> tags
         use html5ever::parse_document;
@@ -180,8 +215,11 @@ impl TranslationService for BingService {
     }
 
     fn get_extra_headers(&self) -> Vec<(String, String)> {
-        let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
-        
+        let mut headers = vec![
+            ("Content-Type".to_string(), "application/json".to_string()),
+            ("Accept-Encoding".to_string(), self.accept_encoding().to_string()),
+        ];
+
         if let Some(ref api_key) = self.api_key {
             headers.push(("Ocp-Apim-Subscription-Key".to_string(), api_key.clone()));
             if let Some(ref region) = self.region {
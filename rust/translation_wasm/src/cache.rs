@@ -2,6 +2,7 @@
 // Note: IndexedDB operations will be handled via JavaScript bridge due to complexity
 // This module provides the interface and data structures
 
+use crate::sha256::hex_digest;
 use serde::{Deserialize, Serialize};
 
 const DB_NAME: &str = "translation_cache";
@@ -11,12 +12,82 @@ const STORE_NAME: &str = "translations";
 pub struct CacheEntry {
     pub translated_text: String,
     pub detected_language: String,
+    /// Epoch millis when this entry was written, supplied by the JS `now()` bridge.
+    pub stored_at: u64,
+    /// `Cache-Control: max-age` in seconds, if the upstream response carried one.
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+    /// `ETag` response header, used for conditional revalidation instead of a full re-fetch.
+    #[serde(default)]
+    pub etag: Option<String>,
 }
 
+/// Content-addressed cache key: a SHA-256 digest over `service`, `source_lang`, `target_lang` and
+/// the full `text`, so two different texts of the same length in the same language pair no longer
+/// collide the way a `text.len()`-based key did. Fields are joined with a `0x1f` unit separator
+/// (not `:`, which legitimately appears in e.g. `source_lang` BCP-47 tags) so `("a", "b:c")` and
+/// `("a:b", "c")` can never hash to the same bytes.
 pub fn get_cache_key(service: &str, source_lang: &str, target_lang: &str, text: &str) -> String {
-    // Create a cache key from service, languages, and text
-    // For production, use proper hashing (e.g., SHA-256)
-    format!("{}:{}:{}:{}", service, source_lang, target_lang, text.len())
+    let mut input = Vec::with_capacity(
+        service.len() + source_lang.len() + target_lang.len() + text.len() + 3,
+    );
+    input.extend_from_slice(service.as_bytes());
+    input.push(0x1f);
+    input.extend_from_slice(source_lang.as_bytes());
+    input.push(0x1f);
+    input.extend_from_slice(target_lang.as_bytes());
+    input.push(0x1f);
+    input.extend_from_slice(text.as_bytes());
+    hex_digest(&input)
+}
+
+/// Parsed `Cache-Control` directives relevant to translation responses.
+#[derive(Clone, Debug, Default)]
+pub struct CacheControl {
+    pub no_store: bool,
+    pub max_age_secs: Option<u64>,
+}
+
+/// Tokenizes a `Cache-Control` header value on commas, splitting `key=value` pairs and
+/// ignoring directives we don't understand (e.g. `private`, `must-revalidate`).
+pub fn parse_cache_control(header: &str) -> CacheControl {
+    let mut cc = CacheControl::default();
+    for directive in header.split(',') {
+        let directive = directive.trim();
+        if directive.is_empty() {
+            continue;
+        }
+        let (key, value) = match directive.split_once('=') {
+            Some((k, v)) => (k.trim(), Some(v.trim().trim_matches('"'))),
+            None => (directive, None),
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "no-store" => cc.no_store = true,
+            "max-age" => {
+                if let Some(v) = value.and_then(|v| v.parse::<u64>().ok()) {
+                    cc.max_age_secs = Some(v);
+                }
+            }
+            _ => {}
+        }
+    }
+    cc
+}
+
+/// `Age` response header (seconds already spent in an upstream cache); folded into the
+/// effective max-age so we don't treat an already-stale-at-origin response as fresh.
+pub fn parse_age_secs(header: &str) -> Option<u64> {
+    header.trim().parse::<u64>().ok()
+}
+
+/// An entry is fresh if `now - stored_at <= max_age`. Entries with no `max_age_secs` (the
+/// upstream gave no freshness hint) are treated as always fresh, matching the old behavior.
+pub fn is_fresh(entry: &CacheEntry, now_millis: u64) -> bool {
+    let Some(max_age) = entry.max_age_secs else {
+        return true;
+    };
+    let age_secs = now_millis.saturating_sub(entry.stored_at) / 1000;
+    age_secs <= max_age
 }
 
 // Cache operations will be implemented via JavaScript bridge
@@ -25,3 +96,39 @@ pub fn get_cache_key(service: &str, source_lang: &str, target_lang: &str, text:
 // - JavaScript handles IndexedDB operations
 // - Results passed back to Rust
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_key_does_not_collide_on_equal_length_text() {
+        let a = get_cache_key("google", "en", "es", "cat dog bird");
+        let b = get_cache_key("google", "en", "es", "red fox lynx");
+        assert_ne!(a, b);
+        assert_eq!(a, get_cache_key("google", "en", "es", "cat dog bird"));
+    }
+
+    #[test]
+    fn parses_max_age_and_no_store() {
+        let cc = parse_cache_control("max-age=600, must-revalidate");
+        assert_eq!(cc.max_age_secs, Some(600));
+        assert!(!cc.no_store);
+
+        let cc = parse_cache_control("no-store");
+        assert!(cc.no_store);
+        assert_eq!(cc.max_age_secs, None);
+    }
+
+    #[test]
+    fn freshness_respects_max_age() {
+        let entry = CacheEntry {
+            translated_text: "hola".to_string(),
+            detected_language: "en".to_string(),
+            stored_at: 1_000_000,
+            max_age_secs: Some(60),
+            etag: None,
+        };
+        assert!(is_fresh(&entry, 1_000_000 + 30_000));
+        assert!(!is_fresh(&entry, 1_000_000 + 90_000));
+    }
+}
@@ -0,0 +1,66 @@
+// Best-effort charset-aware decoding of HTTP response bodies. The free Google endpoint (and
+// mirrors of it) can reply in whatever codepage the request implied instead of UTF-8, which
+// `parse_response`'s plain string handling would otherwise turn into mojibake.
+
+use encoding_rs::Encoding;
+
+/// Decodes `bytes` into a `String`, preferring the charset declared in `content_type`'s
+/// `charset=` parameter, then falling back to a UTF-8 validity check, and finally to
+/// Windows-1252 for servers that send neither a charset nor valid UTF-8.
+pub fn decode_body(bytes: &[u8], content_type: Option<&str>) -> String {
+    if let Some(encoding) = content_type.and_then(charset_from_content_type) {
+        return encoding.decode(bytes).0.into_owned();
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => encoding_rs::WINDOWS_1252.decode(bytes).0.into_owned(),
+    }
+}
+
+fn charset_from_content_type(content_type: &str) -> Option<&'static Encoding> {
+    let charset = content_type
+        .split(';')
+        .skip(1)
+        .find_map(|param| param.trim().strip_prefix("charset="))?
+        .trim_matches('"');
+    Encoding::for_label(charset.as_bytes())
+}
+
+/// Recovers the original response bytes from a "raw" bridge string: one JS built straight from
+/// the response `ArrayBuffer` via a single `String.fromCharCode` per byte, never interpreting any
+/// charset itself, so each `char` here is guaranteed to hold a single byte value.
+pub fn bytes_from_raw_text(raw: &str) -> Vec<u8> {
+    raw.chars().map(|c| c as u32 as u8).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_declared_charset() {
+        let (bytes, _, _) = encoding_rs::WINDOWS_1252.encode("café");
+        let decoded = decode_body(&bytes, Some("text/plain; charset=windows-1252"));
+        assert_eq!(decoded, "café");
+    }
+
+    #[test]
+    fn falls_back_to_utf8_when_valid_and_undeclared() {
+        let decoded = decode_body("héllo".as_bytes(), None);
+        assert_eq!(decoded, "héllo");
+    }
+
+    #[test]
+    fn falls_back_to_windows_1252_for_invalid_utf8() {
+        let (bytes, _, _) = encoding_rs::WINDOWS_1252.encode("café");
+        let decoded = decode_body(&bytes, None);
+        assert_eq!(decoded, "café");
+    }
+
+    #[test]
+    fn round_trips_raw_text_through_bytes() {
+        let bytes = vec![0xE9u8, 0x20, 0x61];
+        let raw: String = bytes.iter().map(|&b| b as char).collect();
+        assert_eq!(bytes_from_raw_text(&raw), bytes);
+    }
+}
@@ -0,0 +1,93 @@
+// Response body decompression: `decode_body` turns raw, possibly `Content-Encoding`-compressed
+// bytes back into plain bytes, mirroring `charset::decode_body`'s job for character sets. Sits
+// upstream of that module in `lib.rs`'s `AwaitingHttp` handling - decompress first, then decode
+// the charset of the now-plain bytes.
+
+use std::io::Read;
+
+/// Decompresses `bytes` according to `content_encoding` (an HTTP `Content-Encoding` header
+/// value, e.g. `"gzip"`, `"br"`, `"identity"`, or empty). An empty or unrecognized tag - and a
+/// payload that fails to decompress under the tag it claims - is treated as identity, so a host
+/// or proxy that strips/mangles the header doesn't break existing uncompressed flows.
+pub fn decode_body(bytes: &[u8], content_encoding: &str) -> Vec<u8> {
+    let decoded = match content_encoding.trim().to_ascii_lowercase().as_str() {
+        "gzip" | "x-gzip" => decode_gzip(bytes),
+        "deflate" => decode_deflate(bytes),
+        "br" => decode_brotli(bytes),
+        "zstd" => decode_zstd(bytes),
+        _ => None,
+    };
+    decoded.unwrap_or_else(|| bytes.to_vec())
+}
+
+fn decode_gzip(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    flate2::read::GzDecoder::new(bytes).read_to_end(&mut out).ok()?;
+    Some(out)
+}
+
+fn decode_deflate(bytes: &[u8]) -> Option<Vec<u8>> {
+    // Some servers send raw (non-zlib-wrapped) deflate despite the "deflate" tag; try the
+    // standards-compliant zlib wrapper first and fall back to raw deflate.
+    let mut out = Vec::new();
+    if flate2::read::ZlibDecoder::new(bytes).read_to_end(&mut out).is_ok() && !out.is_empty() {
+        return Some(out);
+    }
+    out.clear();
+    flate2::read::DeflateDecoder::new(bytes).read_to_end(&mut out).ok()?;
+    Some(out)
+}
+
+fn decode_brotli(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    brotli::Decompressor::new(bytes, 4096).read_to_end(&mut out).ok()?;
+    Some(out)
+}
+
+fn decode_zstd(bytes: &[u8]) -> Option<Vec<u8>> {
+    zstd::stream::decode_all(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_gzip() {
+        use std::io::Write;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"hello gzip").unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert_eq!(decode_body(&compressed, "gzip"), b"hello gzip");
+    }
+
+    #[test]
+    fn round_trips_zlib_deflate() {
+        use std::io::Write;
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"hello deflate").unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert_eq!(decode_body(&compressed, "deflate"), b"hello deflate");
+    }
+
+    #[test]
+    fn round_trips_zstd() {
+        let compressed = zstd::stream::encode_all(&b"hello zstd"[..], 0).unwrap();
+        assert_eq!(decode_body(&compressed, "zstd"), b"hello zstd");
+    }
+
+    #[test]
+    fn falls_back_to_identity_for_empty_tag() {
+        assert_eq!(decode_body(b"plain text", ""), b"plain text");
+    }
+
+    #[test]
+    fn falls_back_to_identity_for_unknown_tag() {
+        assert_eq!(decode_body(b"plain text", "compress"), b"plain text");
+    }
+
+    #[test]
+    fn falls_back_to_identity_when_tagged_bytes_dont_decompress() {
+        assert_eq!(decode_body(b"not actually gzip", "gzip"), b"not actually gzip");
+    }
+}
@@ -1,23 +1,88 @@
-// Custom API service implementation
+// Custom API service implementation: lets users point the crate at a self-hosted or niche
+// translation endpoint by describing its request/response shape as JSON instead of hand-writing
+// a TranslationService impl.
 
 use crate::service::{TranslationInfo, TranslationService};
 use serde::{Deserialize, Serialize};
 
+fn default_config_version() -> u32 {
+    1
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CustomApiConfig {
-    pub endpoint: String,
+    /// Config shape version. Starts at `1` (also the default for configs saved before this field
+    /// existed) so a future breaking change to this struct has somewhere to branch on instead of
+    /// guessing from which fields are present.
+    #[serde(default = "default_config_version")]
+    pub version: u32,
+    /// Renamed from `endpoint`; the alias keeps configs saved under the old name loading.
+    #[serde(alias = "endpoint")]
+    pub base_url: String,
     pub method: String,
-    pub headers: std::collections::HashMap<String, String>,
+    /// Renamed from `headers`; the alias keeps configs saved under the old name loading.
+    #[serde(alias = "headers", default)]
+    pub extra_headers: std::collections::HashMap<String, String>,
     pub payload_format: PayloadFormat,
+    /// Substituted for `{api_key}` in the request template, if the provider needs one.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Dotted path locating the translated text in the response, e.g.
+    /// `data.translations[].translatedText` (`[]` maps over an array instead of indexing into an
+    /// object). `None` falls back to guessing common field names (`text`, `translatedText`,
+    /// `result`), matching the behavior before this field existed.
+    #[serde(default)]
+    pub response_path: Option<String>,
+    /// Whether `response_path` resolves to one newline-joined string per batch rather than a
+    /// per-request value of its own. `transform_request` joins the whole batch into a single
+    /// `\n`-separated blob before sending it, so a `response_path` that lands on a single string
+    /// (not an array) normally comes back as one translation covering the entire batch; setting
+    /// this re-splits that string on `\n` so it lines up with `requests` the way an array result
+    /// already does. Has no effect when `response_path` resolves to an array.
+    #[serde(default)]
+    pub batch_mode: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PayloadFormat {
+    /// A request-body template. Supports `{texts}` (a JSON array of the source segments),
+    /// `{source}`, `{target}`, and `{api_key}`, plus the older `{{text}}` (segments newline-joined)
+    /// and `{{lang}}` placeholders for configs saved before those were added.
     pub template: Option<String>,
     #[serde(flatten)]
     pub extra: std::collections::HashMap<String, serde_json::Value>,
 }
 
+/// Walks a dotted JSON path like `data.translations[].translatedText`, collecting every string
+/// value it reaches. A segment ending in `[]` maps over that array instead of indexing into an
+/// object, so a single path can reach into a batch response.
+fn extract_by_path(root: &serde_json::Value, path: &str) -> Vec<String> {
+    let mut current = vec![root.clone()];
+    for segment in path.split('.') {
+        let mut next = Vec::new();
+        if let Some(field) = segment.strip_suffix("[]") {
+            for value in &current {
+                let target = if field.is_empty() {
+                    Some(value)
+                } else {
+                    value.get(field)
+                };
+                if let Some(arr) = target.and_then(|v| v.as_array()) {
+                    next.extend(arr.iter().cloned());
+                }
+            }
+        } else {
+            for value in &current {
+                if let Some(v) = value.get(segment) {
+                    next.push(v.clone());
+                }
+            }
+        }
+        current = next;
+    }
+    current.into_iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect()
+}
+
 pub struct CustomService {
     config: CustomApiConfig,
 }
@@ -39,10 +104,20 @@ impl TranslationService for CustomService {
 
     fn parse_response(&self, response: &str) -> Result<Vec<(String, Option<String>)>, String> {
         use serde_json::Value;
-        
+
         // Try to parse as JSON
         match serde_json::from_str::<Value>(response) {
             Ok(json) => {
+                if let Some(path) = self.config.response_path.as_deref().filter(|p| !p.is_empty()) {
+                    let extracted = extract_by_path(&json, path);
+                    match extracted.as_slice() {
+                        [] => {}
+                        [single] if self.config.batch_mode => {
+                            return Ok(single.split('\n').map(|s| (s.to_string(), None)).collect());
+                        }
+                        _ => return Ok(extracted.into_iter().map(|t| (t, None)).collect()),
+                    }
+                }
                 // Try common response field names
                 if let Some(text) = json.get("text").and_then(|v| v.as_str()) {
                     Ok(vec![(text.to_string(), None)])
@@ -77,20 +152,26 @@ impl TranslationService for CustomService {
 
     fn get_request_body(
         &self,
-        _source_lang: &str,
+        source_lang: &str,
         target_lang: &str,
         requests: &[TranslationInfo],
     ) -> Option<String> {
         let text = requests.iter().map(|r| r.original_text.clone()).collect::<Vec<_>>().join("\n");
-        
+
         if self.config.method == "GET" {
             return None;
         }
 
         if let Some(ref template) = self.config.payload_format.template {
-            // Use template with {{text}} and {{lang}} placeholders
+            let texts: Vec<String> = requests.iter().map(|r| r.original_text.clone()).collect();
+            let texts_json = serde_json::to_string(&texts).unwrap_or_default();
             Some(
                 template
+                    .replace("{texts}", &texts_json)
+                    .replace("{source}", source_lang)
+                    .replace("{target}", target_lang)
+                    .replace("{api_key}", self.config.api_key.as_deref().unwrap_or(""))
+                    // Older templates written against the pre-placeholder config.
                     .replace("{{text}}", &text)
                     .replace("{{lang}}", target_lang)
             )
@@ -99,29 +180,32 @@ impl TranslationService for CustomService {
             let mut payload = serde_json::Map::new();
             payload.insert("text".to_string(), serde_json::Value::String(text));
             payload.insert("targetLang".to_string(), serde_json::Value::String(target_lang.to_string()));
-            
+            if let Some(ref api_key) = self.config.api_key {
+                payload.insert("apiKey".to_string(), serde_json::Value::String(api_key.clone()));
+            }
+
             // Add extra fields from config
             for (k, v) in &self.config.payload_format.extra {
                 payload.insert(k.clone(), v.clone());
             }
-            
+
             Some(serde_json::to_string(&payload).unwrap_or_default())
         }
     }
 
     fn get_extra_headers(&self) -> Vec<(String, String)> {
         let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
-        
+
         // Add custom headers from config
-        for (k, v) in &self.config.headers {
+        for (k, v) in &self.config.extra_headers {
             headers.push((k.clone(), v.clone()));
         }
-        
+
         headers
     }
 
     fn get_base_url(&self) -> String {
-        self.config.endpoint.clone()
+        self.config.base_url.clone()
     }
 
     fn get_method(&self) -> &str {
@@ -2,17 +2,48 @@
 
 use crate::service::{TranslationInfo, TranslationService};
 
+/// DeepL's `formality` parameter - a closed set of values, so this is an enum rather than a raw
+/// string the way `source_lang`/`target_lang` are (those are open-ended language tags).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Formality {
+    Default,
+    More,
+    Less,
+    PreferMore,
+    PreferLess,
+}
+
+impl Formality {
+    fn as_param(&self) -> &'static str {
+        match self {
+            Formality::Default => "default",
+            Formality::More => "more",
+            Formality::Less => "less",
+            Formality::PreferMore => "prefer_more",
+            Formality::PreferLess => "prefer_less",
+        }
+    }
+}
+
 pub struct DeepLService {
     api_key: Option<String>,
     use_free_api: bool,
+    formality: Option<Formality>,
+    glossary_id: Option<String>,
 }
 
 impl DeepLService {
-    pub fn new(api_key: Option<String>) -> Self {
+    pub fn new(
+        api_key: Option<String>,
+        formality: Option<Formality>,
+        glossary_id: Option<String>,
+    ) -> Self {
         let use_free = api_key.is_some();
         Self {
             api_key,
             use_free_api: use_free,
+            formality,
+            glossary_id,
         }
     }
 }
@@ -81,17 +112,30 @@ impl TranslationService for DeepLService {
     ) -> Option<String> {
         let text = requests.iter().map(|r| r.original_text.clone()).collect::<Vec<_>>().join("\n");
         let target = map_language_code(target_lang);
-        
-        // Form-encoded body
-        let mut params = format!("text={}&target_lang={}", 
+
+        // `tag_handling=html` lets DeepL translate `extract_fragment_by_comment_tokens` output
+        // (already well-formed HTML) in place instead of this crate stripping and re-stitching
+        // markup around the call. `ignore_tags=math` keeps the MathML `tex_to_mathml` produced
+        // untouched, and `non_splitting_tags=span` stops a sentence boundary from landing inside
+        // a cof-math-inline/cof-math-block wrapper span.
+        let mut params = format!(
+            "text={}&target_lang={}&tag_handling=html&ignore_tags=math&non_splitting_tags=span",
             crate::utils::url_encode(&text),
             crate::utils::url_encode(&target)
         );
-        
+
         if source_lang != "auto" {
             params.push_str(&format!("&source_lang={}", crate::utils::url_encode(source_lang)));
         }
-        
+
+        if let Some(ref formality) = self.formality {
+            params.push_str(&format!("&formality={}", formality.as_param()));
+        }
+
+        if let Some(ref glossary_id) = self.glossary_id {
+            params.push_str(&format!("&glossary_id={}", crate::utils::url_encode(glossary_id)));
+        }
+
         Some(params)
     }
 
@@ -122,7 +166,7 @@ impl TranslationService for DeepLService {
 
 impl Default for DeepLService {
     fn default() -> Self {
-        Self::new(None)
+        Self::new(None, None, None)
     }
 }
 
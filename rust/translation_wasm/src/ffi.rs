@@ -0,0 +1,185 @@
+// Shared FFI plumbing: linear-memory allocation, last-error/last-result slots, and UTF-8 helpers.
+// Mirrors the tex_to_mathml_wasm crate's ffi module so both WASM surfaces speak the same ABI.
+//
+// Result/error state lives in a handle-indexed slab (`CALL_STATES`) rather than bare `static mut`
+// globals, so a host that pipelines multiple translate calls - or just happens to have two in
+// flight - doesn't have one call's output or error clobber another's. `ctx_new`/`ctx_free` manage
+// a handle's lifetime; handle `0` always exists and is never freed, so every pre-existing export
+// (`last_len`, `write_out`, ...) keeps working unmodified as a thin wrapper over it.
+
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+
+const DEFAULT_HANDLE: u32 = 0;
+
+#[derive(Default)]
+struct CallState {
+    len: u32,
+    err_ptr: u32,
+    err_len: u32,
+    err_code: u32,
+}
+
+lazy_static! {
+    /// Slot `DEFAULT_HANDLE` always holds `Some`; later slots are `None` once freed so their index
+    /// can't be mistaken for a live handle, but are never removed (that would shift every handle
+    /// after them).
+    static ref CALL_STATES: Mutex<Vec<Option<CallState>>> = Mutex::new(vec![Some(CallState::default())]);
+}
+
+fn with_state<T>(handle: u32, default: T, f: impl FnOnce(&CallState) -> T) -> T {
+    let states = CALL_STATES.lock().unwrap();
+    match states.get(handle as usize).and_then(|slot| slot.as_ref()) {
+        Some(state) => f(state),
+        None => default,
+    }
+}
+
+fn with_state_mut(handle: u32, f: impl FnOnce(&mut CallState)) {
+    let mut states = CALL_STATES.lock().unwrap();
+    if let Some(Some(state)) = states.get_mut(handle as usize) {
+        f(state);
+    }
+}
+
+/// Allocates a fresh handle for a new call context, returning its index. Never reuses `0`.
+#[no_mangle]
+pub extern "C" fn ctx_new() -> u32 {
+    let mut states = CALL_STATES.lock().unwrap();
+    states.push(Some(CallState::default()));
+    (states.len() - 1) as u32
+}
+
+/// Reclaims `handle`'s slot. A no-op for `0` (the default handle always exists) and for an
+/// already-freed or out-of-range handle.
+#[no_mangle]
+pub extern "C" fn ctx_free(handle: u32) {
+    if handle == DEFAULT_HANDLE {
+        return;
+    }
+    let mut states = CALL_STATES.lock().unwrap();
+    if let Some(slot) = states.get_mut(handle as usize) {
+        *slot = None;
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn last_len_for(handle: u32) -> u32 {
+    with_state(handle, 0, |s| s.len)
+}
+
+#[no_mangle]
+pub extern "C" fn last_err_ptr_for(handle: u32) -> u32 {
+    with_state(handle, 0, |s| s.err_ptr)
+}
+
+#[no_mangle]
+pub extern "C" fn last_err_len_for(handle: u32) -> u32 {
+    with_state(handle, 0, |s| s.err_len)
+}
+
+#[no_mangle]
+pub extern "C" fn last_err_code_for(handle: u32) -> u32 {
+    with_state(handle, 0, |s| s.err_code)
+}
+
+#[no_mangle]
+pub extern "C" fn clear_last_error_for(handle: u32) {
+    with_state_mut(handle, |s| {
+        s.err_ptr = 0;
+        s.err_len = 0;
+        s.err_code = 0;
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn last_len() -> u32 {
+    last_len_for(DEFAULT_HANDLE)
+}
+
+#[no_mangle]
+pub extern "C" fn last_err_ptr() -> u32 {
+    last_err_ptr_for(DEFAULT_HANDLE)
+}
+
+#[no_mangle]
+pub extern "C" fn last_err_len() -> u32 {
+    last_err_len_for(DEFAULT_HANDLE)
+}
+
+#[no_mangle]
+pub extern "C" fn last_err_code() -> u32 {
+    last_err_code_for(DEFAULT_HANDLE)
+}
+
+#[no_mangle]
+pub extern "C" fn clear_last_error() {
+    clear_last_error_for(DEFAULT_HANDLE)
+}
+
+#[no_mangle]
+pub extern "C" fn alloc(size: u32) -> u32 {
+    let mut buf = Vec::<u8>::with_capacity(size as usize);
+    let ptr = buf.as_mut_ptr() as u32;
+    std::mem::forget(buf);
+    ptr
+}
+
+#[no_mangle]
+pub extern "C" fn dealloc(ptr: u32, size: u32) {
+    if ptr == 0 || size == 0 {
+        return;
+    }
+    unsafe {
+        let _ = Vec::<u8>::from_raw_parts(ptr as *mut u8, size as usize, size as usize);
+    }
+}
+
+pub fn set_error_for(handle: u32, code: u32, message: &str) {
+    let bytes = message.as_bytes();
+    let mut out = Vec::<u8>::with_capacity(bytes.len());
+    out.extend_from_slice(bytes);
+    let err_len = out.len() as u32;
+    let err_ptr = out.as_mut_ptr() as u32;
+    std::mem::forget(out);
+    with_state_mut(handle, |s| {
+        s.err_code = code;
+        s.len = 0;
+        s.err_len = err_len;
+        s.err_ptr = err_ptr;
+    });
+}
+
+pub fn set_error(code: u32, message: &str) {
+    set_error_for(DEFAULT_HANDLE, code, message)
+}
+
+pub fn read_utf8(ptr: u32, len: u32) -> Result<&'static str, u32> {
+    if ptr == 0 || len == 0 {
+        return Err(1);
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(ptr as *const u8, len as usize) };
+    std::str::from_utf8(bytes).map_err(|_| 2)
+}
+
+pub fn write_out_for(handle: u32, text: &str) -> u32 {
+    with_state_mut(handle, |s| {
+        s.err_code = 0;
+        s.err_ptr = 0;
+        s.err_len = 0;
+    });
+    let bytes = text.as_bytes();
+    let mut out = Vec::<u8>::with_capacity(bytes.len());
+    out.extend_from_slice(bytes);
+    let len = out.len() as u32;
+    let out_ptr = out.as_mut_ptr() as u32;
+    std::mem::forget(out);
+    with_state_mut(handle, |s| {
+        s.len = len;
+    });
+    out_ptr
+}
+
+pub fn write_out(text: &str) -> u32 {
+    write_out_for(DEFAULT_HANDLE, text)
+}
@@ -0,0 +1,163 @@
+// Config-driven generic LLM provider: lets a user describe any chat/completion-style LLM
+// endpoint as data instead of a bespoke `TranslationService` impl, the same idea as
+// `custom::CustomService` but shaped for LLM APIs - a prompt body template plus a JSON-pointer
+// response path instead of a dotted field path.
+
+use crate::service::{TranslationInfo, TranslationService};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+fn default_config_version() -> u32 {
+    1
+}
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenericLLMConfig {
+    #[serde(default = "default_config_version")]
+    pub version: u32,
+    pub name: String,
+    pub endpoint: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// The request body. `{prompt}`, `{target_lang}`, and `{text}` are substituted (JSON-escaped)
+    /// before the result is sent as-is, so this must already be valid JSON once substituted.
+    pub body_template: String,
+    /// An RFC 6901 JSON pointer (e.g. `/choices/0/message/content`) locating the translated text
+    /// in the response body.
+    pub response_pointer: String,
+}
+
+impl GenericLLMConfig {
+    /// Configs equivalent to the dedicated `ai::ChatGPTService`/`ai::GeminiService` presets, so a
+    /// saved `chatgpt`/`gemini` config keeps working when routed through this generic
+    /// implementation instead of the bespoke struct.
+    pub fn preset(name: &str, api_key: &str) -> Option<Self> {
+        match name {
+            "chatgpt" => Some(Self {
+                version: 1,
+                name: "chatgpt".to_string(),
+                endpoint: "https://api.openai.com/v1/chat/completions".to_string(),
+                method: "POST".to_string(),
+                headers: HashMap::from([
+                    ("Content-Type".to_string(), "application/json".to_string()),
+                    ("Authorization".to_string(), format!("Bearer {}", api_key)),
+                ]),
+                body_template: r#"{"model":"gpt-3.5-turbo","messages":[{"role":"user","content":"{prompt}"}],"temperature":0.3}"#.to_string(),
+                response_pointer: "/choices/0/message/content".to_string(),
+            }),
+            "gemini" => Some(Self {
+                version: 1,
+                name: "gemini".to_string(),
+                endpoint: "https://generativelanguage.googleapis.com/v1/models/gemini-1.5-flash:generateContent".to_string(),
+                method: "POST".to_string(),
+                headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+                body_template: r#"{"contents":[{"parts":[{"text":"{prompt}"}]}]}"#.to_string(),
+                response_pointer: "/candidates/0/content/parts/0/text".to_string(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+pub struct GenericLLMService {
+    config: GenericLLMConfig,
+}
+
+impl GenericLLMService {
+    pub fn new(config: GenericLLMConfig) -> Self {
+        Self { config }
+    }
+}
+
+/// Escapes `s` for interpolation into a JSON string literal inside `body_template` (the template
+/// isn't parsed and re-substituted through `serde_json::Value`, so callers must not leave stray
+/// quotes/control characters in `{prompt}`/`{text}`).
+fn escape_json_string(s: &str) -> String {
+    let quoted = serde_json::to_string(s).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+impl TranslationService for GenericLLMService {
+    fn service_name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn transform_request(&self, source_array: &[String]) -> String {
+        source_array.join("\n")
+    }
+
+    fn parse_response(&self, response: &str) -> Result<Vec<(String, Option<String>)>, String> {
+        let json: serde_json::Value =
+            serde_json::from_str(response).map_err(|e| format!("JSON parse error: {}", e))?;
+        let text = json
+            .pointer(&self.config.response_pointer)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                format!(
+                    "response pointer {} did not resolve to a string",
+                    self.config.response_pointer
+                )
+            })?;
+        Ok(crate::ai::split_marked_response(text))
+    }
+
+    fn transform_response(&self, result: &str, _dont_sort: bool) -> Vec<String> {
+        vec![result.to_string()]
+    }
+
+    fn get_extra_parameters(
+        &self,
+        _source_lang: &str,
+        _target_lang: &str,
+        _requests: &[TranslationInfo],
+    ) -> String {
+        String::new()
+    }
+
+    fn get_request_body(
+        &self,
+        _source_lang: &str,
+        target_lang: &str,
+        requests: &[TranslationInfo],
+    ) -> Option<String> {
+        if self.config.method == "GET" {
+            return None;
+        }
+        let prompt = crate::ai::build_marked_prompt(target_lang, requests);
+        let text = requests
+            .iter()
+            .map(|r| r.original_text.clone())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Some(
+            self.config
+                .body_template
+                .replace("{prompt}", &escape_json_string(&prompt))
+                .replace("{target_lang}", target_lang)
+                .replace("{text}", &escape_json_string(&text)),
+        )
+    }
+
+    fn get_extra_headers(&self) -> Vec<(String, String)> {
+        self.config
+            .headers
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    fn get_base_url(&self) -> String {
+        self.config.endpoint.clone()
+    }
+
+    fn get_method(&self) -> &str {
+        &self.config.method
+    }
+}
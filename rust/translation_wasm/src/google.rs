@@ -7,10 +7,15 @@ use crate::service::{TranslationInfo, TranslationService};
 use crate::utils::url_encode;
 use serde_json::json;
 
+/// The free endpoint's own URL has to fit Google's ~2000-character limit alongside whatever path
+/// the translated text shares it with, so this leaves headroom for the base URL and other params.
+pub const DEFAULT_MAX_QUERY_BYTES: usize = 1500;
+
 pub struct GoogleService {
     api_key: Option<String>,
     use_html_endpoint: bool,
     auth_helper: crate::auth::GoogleHelper,
+    max_query_bytes: usize,
 }
 
 impl GoogleService {
@@ -19,6 +24,7 @@ impl GoogleService {
             api_key: None,
             use_html_endpoint: true, // Use HTML endpoint by default (TWP approach)
             auth_helper: crate::auth::GoogleHelper::new(),
+            max_query_bytes: DEFAULT_MAX_QUERY_BYTES,
         }
     }
 
@@ -29,6 +35,43 @@ impl GoogleService {
     pub fn use_free_endpoint(&mut self) {
         self.use_html_endpoint = false;
     }
+
+    pub fn max_query_bytes(&self) -> usize {
+        self.max_query_bytes
+    }
+
+    pub fn set_max_query_bytes(&mut self, max_query_bytes: usize) {
+        self.max_query_bytes = max_query_bytes;
+    }
+}
+
+/// Splits `segments`'s indices into ordered groups whose combined `<a i=N>`-tagged, url-encoded
+/// text stays within `max_query_bytes`, so a large multi-segment batch can be sent to the free
+/// GET endpoint as several requests instead of one URL that blows past its length limit. A single
+/// segment that alone exceeds the budget still gets its own (oversized) group rather than being
+/// silently dropped or split mid-segment, since a text unit can't be divided without losing
+/// translation context. Since each group is a contiguous, in-order slice of `segments`, sending
+/// them in order and concatenating their results reconstructs the original order with no need to
+/// track indices past this point.
+pub fn chunk_segment_indices(segments: &[String], max_query_bytes: usize) -> Vec<Vec<usize>> {
+    let mut chunks: Vec<Vec<usize>> = Vec::new();
+    let mut current: Vec<usize> = Vec::new();
+    let mut current_bytes = 0usize;
+
+    for (i, seg) in segments.iter().enumerate() {
+        let tagged = format!("<a i={}>{}</a>", current.len(), escape_html(seg));
+        let encoded_len = url_encode(&tagged).len();
+        if !current.is_empty() && current_bytes + encoded_len > max_query_bytes {
+            chunks.push(std::mem::take(&mut current));
+            current_bytes = 0;
+        }
+        current.push(i);
+        current_bytes += encoded_len;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
 }
 
 impl TranslationService for GoogleService {
@@ -72,11 +115,21 @@ impl TranslationService for GoogleService {
 
     fn get_extra_parameters(
         &self,
-        _source_lang: &str,
-        _target_lang: &str,
-        _requests: &[TranslationInfo],
+        source_lang: &str,
+        target_lang: &str,
+        requests: &[TranslationInfo],
     ) -> String {
-        String::new()
+        // HTML endpoint and paid API both send the text as a request body instead.
+        if self.use_html_endpoint || self.api_key.is_some() {
+            return String::new();
+        }
+        let text = requests.first().map(|r| r.original_text.as_str()).unwrap_or("");
+        format!(
+            "?client=gtx&sl={}&tl={}&dt=t&q={}",
+            url_encode(source_lang),
+            url_encode(target_lang),
+            url_encode(text)
+        )
     }
 
     fn get_request_body(
@@ -115,8 +168,9 @@ impl TranslationService for GoogleService {
                 "Content-Type".to_string(),
                 "application/application/json+protobuf".to_string(),
             ));
-            // Try to get auth token from helper
-            if let Some(auth) = self.auth_helper.get_auth() {
+            // Try to get auth token from helper, if it hasn't expired.
+            let now_millis = unsafe { crate::now() };
+            if let Some(auth) = self.auth_helper.get_auth(now_millis) {
                 headers.push(("Authorization".to_string(), format!("Bearer {}", auth)));
             }
         } else if self.api_key.is_some() {
@@ -145,6 +199,79 @@ impl TranslationService for GoogleService {
             "GET"
         }
     }
+
+    fn retry_policy(&self) -> (u32, u64) {
+        (self.auth_helper.max_retries(), self.auth_helper.base_delay_ms())
+    }
+
+    fn invalidate_auth(&mut self) {
+        self.auth_helper.invalidate();
+    }
+
+    fn needs_auth_refresh(&self, now_millis: u64) -> bool {
+        self.use_html_endpoint && self.auth_helper.should_update(now_millis)
+    }
+
+    fn set_fetched_auth(&mut self, auth: Option<String>, now_millis: u64, ttl_ms: u64) {
+        match auth {
+            Some(auth) => self.auth_helper.set_auth(auth, now_millis, ttl_ms),
+            None => self.auth_helper.set_auth_not_found(now_millis),
+        }
+    }
+
+    fn max_query_bytes(&self) -> Option<usize> {
+        if self.use_html_endpoint || self.api_key.is_some() {
+            None
+        } else {
+            Some(self.max_query_bytes)
+        }
+    }
+}
+
+impl GoogleService {
+    /// URL for a standalone language-detection request for `text`: the v2 `/detect` endpoint
+    /// when an API key is configured, otherwise the free `translate_a/single` endpoint with
+    /// `sl=auto` (the same trick TWP uses to get a detection without a real translation target).
+    pub fn detect_request_url(&self, text: &str) -> String {
+        if self.api_key.is_some() {
+            "https://translation.googleapis.com/language/translate/v2/detect".to_string()
+        } else {
+            format!(
+                "https://translate.googleapis.com/translate_a/single?client=gtx&sl=auto&tl=auto&dt=t&q={}",
+                url_encode(text)
+            )
+        }
+    }
+
+    /// Parses a response from `detect_request_url` into ranked `(language_code, confidence)`
+    /// pairs. The v2 endpoint reports real confidences; the free endpoint only ever reports its
+    /// single best guess, so that guess is given a confidence of `1.0`.
+    pub fn parse_detect_response(&self, response: &str) -> Result<Vec<(String, f32)>, String> {
+        if self.api_key.is_some() {
+            let json: serde_json::Value =
+                serde_json::from_str(response).map_err(|e| format!("JSON parse error: {}", e))?;
+            let detections = json
+                .get("data")
+                .and_then(|d| d.get("detections"))
+                .and_then(|d| d.as_array())
+                .and_then(|d| d.first())
+                .and_then(|d| d.as_array())
+                .ok_or("Missing detections in response")?;
+            let results = detections
+                .iter()
+                .filter_map(|d| {
+                    let lang = d.get("language")?.as_str()?.to_string();
+                    let confidence = d.get("confidence").and_then(|c| c.as_f64()).unwrap_or(0.0) as f32;
+                    Some((lang, confidence))
+                })
+                .collect();
+            Ok(results)
+        } else {
+            let (_translations, detected) = parse_google_json_response(response)?;
+            let lang = detected.ok_or("No detected language in response")?;
+            Ok(vec![(lang, 1.0)])
+        }
+    }
 }
 
 impl Default for GoogleService {
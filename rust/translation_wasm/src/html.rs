@@ -6,6 +6,24 @@ use html5ever::parse_document;
 use html5ever::tendril::TendrilSink;
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
 
+// Find body or use document root
+fn find_body(dom: &RcDom) -> Option<Handle> {
+    fn find_elem(node: &Handle, name: &str) -> Option<Handle> {
+        if let NodeData::Element { name: q, .. } = &node.data {
+            if q.local.to_string().eq_ignore_ascii_case(name) {
+                return Some(node.clone());
+            }
+        }
+        for c in node.children.borrow().iter() {
+            if let Some(x) = find_elem(c, name) {
+                return Some(x);
+            }
+        }
+        None
+    }
+    find_elem(&dom.document, "body")
+}
+
 pub fn wrap_html_for_translation(html: &str) -> String {
     // For Google's HTML translation endpoint, wrap text in <pre> with <a i={index}> markers
     let dom = parse_document(RcDom::default(), Default::default()).one(html);
@@ -26,7 +44,7 @@ pub fn wrap_html_for_translation(html: &str) -> String {
                 let tag = name.local.to_string();
                 out.push('<');
                 out.push_str(&tag);
-                
+
                 // Preserve attributes
                 for attr in attrs.borrow().iter() {
                     out.push(' ');
@@ -35,13 +53,13 @@ pub fn wrap_html_for_translation(html: &str) -> String {
                     out.push_str(&escape_html(&attr.value.to_string()));
                     out.push('"');
                 }
-                
+
                 out.push('>');
-                
+
                 for child in node.children.borrow().iter() {
                     walk(child, out, index);
                 }
-                
+
                 out.push_str("</");
                 out.push_str(&tag);
                 out.push('>');
@@ -54,30 +72,100 @@ pub fn wrap_html_for_translation(html: &str) -> String {
         }
     }
 
-    // Find body or use document root
-    fn find_body(dom: &RcDom) -> Option<Handle> {
-        fn find_elem(node: &Handle, name: &str) -> Option<Handle> {
-            if let NodeData::Element { name: q, .. } = &node.data {
-                if q.local.to_string().eq_ignore_ascii_case(name) {
-                    return Some(node.clone());
+    if let Some(body) = find_body(&dom) {
+        out.push_str("<pre>");
+        walk(&body, &mut out, &mut index);
+        out.push_str("</pre>");
+    }
+
+    out
+}
+
+/// Private-use-area delimiter wrapping the segment index of a translatable text run in the
+/// template produced by `extract_translatable_segments`; chosen because it can't appear in
+/// parsed HTML text content.
+const SEGMENT_MARK: char = '\u{E000}';
+
+/// Splits `html` into a template that keeps the original tag/attribute/comment structure intact,
+/// with each translatable text run replaced by a `SEGMENT_MARK`-delimited placeholder, plus the
+/// ordered list of those runs. `<script>`/`<style>` contents and whitespace-only text nodes are
+/// copied through untouched rather than offered up for translation, so attributes and code don't
+/// get mangled by round-tripping through a translation engine.
+pub fn extract_translatable_segments(html: &str) -> (String, Vec<String>) {
+    let dom = parse_document(RcDom::default(), Default::default()).one(html);
+    let mut out = String::new();
+    let mut segments: Vec<String> = Vec::new();
+
+    fn walk(node: &Handle, out: &mut String, segments: &mut Vec<String>) {
+        match &node.data {
+            NodeData::Text { contents } => {
+                let text = contents.borrow().to_string();
+                if text.trim().is_empty() {
+                    out.push_str(&escape_html(&text));
+                } else {
+                    out.push(SEGMENT_MARK);
+                    out.push_str(&segments.len().to_string());
+                    out.push(SEGMENT_MARK);
+                    segments.push(text);
                 }
             }
-            for c in node.children.borrow().iter() {
-                if let Some(x) = find_elem(c, name) {
-                    return Some(x);
+            NodeData::Element { name, attrs, .. } => {
+                let tag = name.local.to_string();
+                let raw_text_element = tag.eq_ignore_ascii_case("script") || tag.eq_ignore_ascii_case("style");
+                out.push('<');
+                out.push_str(&tag);
+                for attr in attrs.borrow().iter() {
+                    out.push(' ');
+                    out.push_str(&attr.name.local.to_string());
+                    out.push_str("=\"");
+                    out.push_str(&escape_html(&attr.value.to_string()));
+                    out.push('"');
+                }
+                out.push('>');
+                if raw_text_element {
+                    for child in node.children.borrow().iter() {
+                        if let NodeData::Text { contents } = &child.data {
+                            out.push_str(&contents.borrow());
+                        }
+                    }
+                } else {
+                    for child in node.children.borrow().iter() {
+                        walk(child, out, segments);
+                    }
+                }
+                out.push_str("</");
+                out.push_str(&tag);
+                out.push('>');
+            }
+            NodeData::Comment { contents } => {
+                out.push_str("<!--");
+                out.push_str(contents);
+                out.push_str("-->");
+            }
+            _ => {
+                for child in node.children.borrow().iter() {
+                    walk(child, out, segments);
                 }
             }
-            None
         }
-        find_elem(&dom.document, "body")
     }
 
-    if let Some(body) = find_body(&dom) {
-        out.push_str("<pre>");
-        walk(&body, &mut out, &mut index);
-        out.push_str("</pre>");
+    let root = find_body(&dom).unwrap_or_else(|| dom.document.clone());
+    for child in root.children.borrow().iter() {
+        walk(child, &mut out, &mut segments);
     }
 
+    (out, segments)
+}
+
+/// Splices `translations` (same order as the segments `extract_translatable_segments` returned)
+/// back into its template, escaping each one for safe inclusion as HTML text.
+pub fn splice_translated_segments(template: &str, translations: &[String]) -> String {
+    let mut out = template.to_string();
+    for (i, text) in translations.iter().enumerate() {
+        let placeholder = format!("{SEGMENT_MARK}{i}{SEGMENT_MARK}");
+        out = out.replacen(&placeholder, &escape_html(text), 1);
+    }
     out
 }
 
@@ -158,5 +246,26 @@ mod tests {
         assert!(wrapped.contains("<pre>"));
         assert!(wrapped.contains("<a i="));
     }
+
+    #[test]
+    fn extract_preserves_tags_and_skips_script() {
+        let html = r#"<p>Hello <b>world</b></p><script>var x = 1;</script>"#;
+        let (template, segments) = extract_translatable_segments(html);
+        assert_eq!(segments, vec!["Hello ".to_string(), "world".to_string()]);
+        assert!(template.contains("<b>"));
+        assert!(template.contains("var x = 1;"));
+        assert!(!template.contains("Hello"));
+    }
+
+    #[test]
+    fn splice_round_trips_through_extract() {
+        let html = "<p>Hello <b>world</b></p>";
+        let (template, segments) = extract_translatable_segments(html);
+        let translated: Vec<String> = segments.iter().map(|s| s.to_uppercase()).collect();
+        let spliced = splice_translated_segments(&template, &translated);
+        assert!(spliced.contains("HELLO"));
+        assert!(spliced.contains("WORLD"));
+        assert!(spliced.contains("<b>"));
+    }
 }
 
@@ -2,6 +2,13 @@ mod ffi;
 mod utils;
 mod escape;
 mod cache;
+mod sha256;
+mod retry;
+mod charset;
+mod compression;
+mod placeholder;
+#[cfg(feature = "mock-host")]
+mod mock_host;
 mod html;
 mod service;
 mod auth;
@@ -12,25 +19,132 @@ mod deepl;
 mod libre;
 mod ai;
 mod custom;
+mod rsa;
+mod vertex;
+mod generic_llm;
+mod nllb;
 
-use ffi::{read_utf8, set_error, write_out};
+use ffi::{read_utf8, set_error, set_error_for, write_out, write_out_for};
 use std::collections::HashMap;
 use std::sync::Mutex;
 use lazy_static::lazy_static;
-use std::sync::atomic::{AtomicU32, Ordering};
 
 // Global service registry
 lazy_static! {
     static ref SERVICE_REGISTRY: Mutex<HashMap<String, Box<dyn service::TranslationService + Send + Sync>>> = Mutex::new(HashMap::new());
 }
 
-// JavaScript bridge functions (imported from JS)
+// JavaScript bridge functions (imported from JS).
+// None of these are polled for a result anymore: JS resumes the pending request by
+// calling back into `translate_resume` once its async work (cache lookup or fetch) settles.
+//
+// Under `--features mock-host`, native stand-ins from the `mock_host` module are used instead
+// so the whole service registry can be covered by `cargo test` without a JS host.
+#[cfg(not(feature = "mock-host"))]
 extern "C" {
     fn http_request(url_ptr: u32, url_len: u32, method_ptr: u32, method_len: u32, headers_ptr: u32, headers_len: u32, body_ptr: u32, body_len: u32, callback_id: u32);
-    fn http_request_get_result(callback_id: u32, result_len_ptr: *mut u32) -> u32;
     fn cache_get(key_ptr: u32, key_len: u32, callback_id: u32);
-    fn cache_get_result(callback_id: u32, result_len_ptr: *mut u32) -> u32;
     fn cache_set(key_ptr: u32, key_len: u32, value_ptr: u32, value_len: u32) -> u32;
+    // Epoch milliseconds, supplied by JS since WASM has no clock of its own.
+    fn now() -> u64;
+    // Asks JS to `setTimeout` for `delay_ms` and then call `translate_retry_fire(callback_id)`,
+    // since WASM can't sleep on its own.
+    fn schedule_retry(callback_id: u32, delay_ms: u32);
+}
+
+#[cfg(feature = "mock-host")]
+use mock_host::{cache_get, cache_set, http_request, now, schedule_retry};
+
+// Stage a pending translation has reached; determines how `translate_resume` interprets the
+// bytes JS hands back for a given `callback_id`.
+enum PendingStage {
+    AwaitingCache,
+    AwaitingHttp,
+}
+
+struct PendingTranslation {
+    service_name: String,
+    source_lang: String,
+    target_lang: String,
+    cache_key: String,
+    url: String,
+    method: String,
+    headers_json: String,
+    body: Option<String>,
+    /// Placeholder maps for the exact segments just sent in `body` (index-aligned with them),
+    /// so `transform_response`'s output can have each segment's sentinels restored once the
+    /// response lands. Unlike `segments` below, this always matches what's actually in flight -
+    /// the first chunk of a chunked batch, or a single segment during per-segment fallback -
+    /// since it's rebuilt by `plan_request` on every call, the same way `url`/`body` are.
+    placeholder_maps: Vec<placeholder::PlaceholderMap>,
+    stage: PendingStage,
+    /// Set when the HTTP request is a conditional revalidation (`If-None-Match`) of a stale
+    /// cache entry; a `304` response refreshes `stored_at` and serves this entry as-is.
+    revalidating: Option<cache::CacheEntry>,
+    /// Number of retries already spent on a retryable HTTP failure (408/429/5xx, a network error,
+    /// or a 401 from stale credentials); capped at the current service's `retry_policy`.
+    attempt: u32,
+    /// Set for `translate_html`: the HTML template and extracted segments needed to splice the
+    /// response back into place instead of just writing it out.
+    html_batch: Option<HtmlBatch>,
+    /// The text segments sent to `service_name` in the current (or most recent) request; reused
+    /// unchanged when a fallback-chain failure moves this attempt on to the next service.
+    segments: Vec<String>,
+    /// Services still to try, in order, if `service_name` doesn't pan out.
+    chain: Vec<String>,
+    /// Index of `service_name` within `chain`.
+    chain_index: usize,
+    /// `"<service>: <message>"` for every chain entry that's already failed, so an eventual hard
+    /// failure can report a combined diagnostic instead of just the last provider's error.
+    chain_errors: Vec<String>,
+}
+
+/// State for an in-flight HTML translation: the template/segments from
+/// `html::extract_translatable_segments`, plus progress through an optional per-segment fallback
+/// used when the service's response arity doesn't match the request.
+struct HtmlBatch {
+    template: String,
+    segments: Vec<String>,
+    dont_sort: bool,
+    /// `Some(i)` once a segment-count mismatch forced translating one segment at a time, where
+    /// `i` is the index currently in flight.
+    fallback_index: Option<usize>,
+    /// Segment translations collected so far, in original order.
+    collected: Vec<String>,
+    /// For a service with a `max_query_bytes` limit (the free Google GET endpoint): ordered
+    /// groups of original segment indices kept under that budget (see
+    /// `google::chunk_segment_indices`), with `chunk_pos` tracking which group is currently in
+    /// flight. `None` means the whole batch fit in a single request.
+    chunks: Option<Vec<Vec<usize>>>,
+    /// Index into `chunks` of the request currently in flight.
+    chunk_pos: usize,
+}
+
+// Keyed by the `ffi::ctx_new` handle minted for the call, so the handle doubles as the
+// correlation id JS already has to track to call `translate_resume`/`translate_retry_fire` -
+// that same id is what `write_out_for`/`set_error_for` write a call's result or error under, so
+// overlapping translate calls never clobber each other's state.
+lazy_static! {
+    static ref PENDING_TRANSLATIONS: Mutex<HashMap<u32, PendingTranslation>> = Mutex::new(HashMap::new());
+}
+
+lazy_static! {
+    /// In-process translation memory, keyed by the same `cache::get_cache_key` string as the
+    /// JS/IndexedDB-backed cache. Checked before even asking JS for a cache lookup, so translating
+    /// the same segment twice in one WASM instance's lifetime (e.g. re-exporting a document that
+    /// reuses a lot of text) never costs an async round trip, let alone a network request.
+    static ref TRANSLATION_MEMORY: Mutex<HashMap<String, cache::CacheEntry>> = Mutex::new(HashMap::new());
+}
+
+fn alloc_copy(bytes: &[u8]) -> (u32, u32) {
+    if bytes.is_empty() {
+        return (0, 0);
+    }
+    let ptr = ffi::alloc(bytes.len() as u32);
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr as *mut u8, bytes.len());
+    }
+    (ptr, bytes.len() as u32)
 }
 
 // Initialize services
@@ -47,7 +161,7 @@ fn init_services() {
         registry.insert("yandex".to_string(), Box::new(yandex::YandexService::new()));
     }
     if !registry.contains_key("deepl") {
-        registry.insert("deepl".to_string(), Box::new(deepl::DeepLService::new(None)));
+        registry.insert("deepl".to_string(), Box::new(deepl::DeepLService::new(None, None, None)));
     }
     if !registry.contains_key("libre") {
         registry.insert("libre".to_string(), Box::new(libre::LibreService::new("".to_string(), None)));
@@ -63,32 +177,83 @@ fn init_services() {
     }
     if !registry.contains_key("custom") {
         let custom_config = custom::CustomApiConfig {
-            endpoint: "".to_string(),
+            version: 1,
+            base_url: "".to_string(),
             method: "POST".to_string(),
-            headers: HashMap::new(),
+            extra_headers: HashMap::new(),
             payload_format: custom::PayloadFormat {
                 template: None,
                 extra: HashMap::new(),
             },
+            api_key: None,
+            response_path: None,
+            batch_mode: false,
         };
         registry.insert("custom".to_string(), Box::new(custom::CustomService::new(custom_config)));
     }
+    if !registry.contains_key("generic_llm") {
+        let generic_config = generic_llm::GenericLLMConfig {
+            version: 1,
+            name: "generic_llm".to_string(),
+            endpoint: "".to_string(),
+            method: "POST".to_string(),
+            headers: HashMap::new(),
+            body_template: "".to_string(),
+            response_pointer: "".to_string(),
+        };
+        registry.insert(
+            "generic_llm".to_string(),
+            Box::new(generic_llm::GenericLLMService::new(generic_config)),
+        );
+    }
+    if !registry.contains_key("nllb") {
+        registry.insert("nllb".to_string(), Box::new(nllb::LocalNllbService::new()));
+    }
+    if !registry.contains_key("vertex") {
+        registry.insert(
+            "vertex".to_string(),
+            Box::new(vertex::VertexAIService::new(
+                None,
+                "".to_string(),
+                "us-central1".to_string(),
+                "gemini-1.5-flash-002".to_string(),
+            )),
+        );
+    }
 }
 
 // Service configuration storage
 struct ServiceConfig {
     api_keys: HashMap<String, String>,
     custom_services: HashMap<String, custom::CustomApiConfig>,
+    generic_llm_services: HashMap<String, generic_llm::GenericLLMConfig>,
+    /// Default fallback chain applied by `resolve_service_chain` when the caller passes a single
+    /// service name (no commas) and this has been set via `set_fallback_chain`.
+    fallback_chain: Option<Vec<String>>,
 }
 
 static SERVICE_CONFIG: Mutex<Option<ServiceConfig>> = Mutex::new(None);
 
+/// Most recently detected source language across all completed translations, regardless of
+/// which provider produced it. Consulted when a fallback chain advances past its first entry
+/// with `source_lang == "auto"`, so later providers get a concrete language instead of having to
+/// re-detect it themselves.
+static LAST_DETECTED_LANGUAGE: Mutex<Option<String>> = Mutex::new(None);
+
+/// JSON-encoded `Vec<service::SentenceBoundary>` from the most recently completed translation
+/// that had any (currently only Bing's `includeSentenceLength` response reports them), for a host
+/// to pull via `get_sentence_boundaries` after a `translate_resume` call returns. `None` once a
+/// service without this data completes, so stale boundaries from an earlier call aren't reused.
+static LAST_SENTENCE_BOUNDARIES: Mutex<Option<String>> = Mutex::new(None);
+
 fn init_config() {
     let mut config = SERVICE_CONFIG.lock().unwrap();
     if config.is_none() {
         *config = Some(ServiceConfig {
             api_keys: HashMap::new(),
             custom_services: HashMap::new(),
+            generic_llm_services: HashMap::new(),
+            fallback_chain: None,
         });
     }
 }
@@ -123,6 +288,52 @@ pub extern "C" fn set_api_key(
     }
 }
 
+/// Whether `service_name` has no usable cached auth token right now and a host should fetch one
+/// (e.g. Google's HTML endpoint needs a bearer token scraped from a page, the way JS-side TWP
+/// does it) and hand it back via `set_service_auth`. `0`/`1` for false/true; `0` for an unknown
+/// service name too, since there's nothing for a host to refresh.
+#[no_mangle]
+pub extern "C" fn service_needs_auth_refresh(service_ptr: u32, service_len: u32) -> u32 {
+    init_services();
+    let Ok(service_name) = read_utf8(service_ptr, service_len) else {
+        return 0;
+    };
+    let now_millis = unsafe { now() };
+    let registry = SERVICE_REGISTRY.lock().unwrap();
+    match registry.get(service_name) {
+        Some(service) if service.needs_auth_refresh(now_millis) => 1,
+        _ => 0,
+    }
+}
+
+/// Delivers the result of a host-driven auth fetch for `service_name`, started after
+/// `service_needs_auth_refresh` returned true: the fetched token good for `ttl_ms` from now, or
+/// no token (`auth_ptr`/`auth_len` both `0`) if the host couldn't find one. A no-op for a service
+/// with no auth state to manage (see `TranslationService::set_fetched_auth`).
+#[no_mangle]
+pub extern "C" fn set_service_auth(
+    service_ptr: u32,
+    service_len: u32,
+    auth_ptr: u32,
+    auth_len: u32,
+    ttl_ms: u64,
+) {
+    init_services();
+    let Ok(service_name) = read_utf8(service_ptr, service_len) else {
+        return;
+    };
+    let auth = if auth_ptr == 0 || auth_len == 0 {
+        None
+    } else {
+        read_utf8(auth_ptr, auth_len).ok().map(|s| s.to_string())
+    };
+    let now_millis = unsafe { now() };
+    let mut registry = SERVICE_REGISTRY.lock().unwrap();
+    if let Some(service) = registry.get_mut(service_name) {
+        service.set_fetched_auth(auth, now_millis, ttl_ms);
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn set_custom_service(config_ptr: u32, config_len: u32) {
     init_config();
@@ -144,6 +355,76 @@ pub extern "C" fn set_custom_service(config_ptr: u32, config_len: u32) {
     }
 }
 
+#[no_mangle]
+pub extern "C" fn set_generic_llm_service(config_ptr: u32, config_len: u32) {
+    init_config();
+    let config_str = match read_utf8(config_ptr, config_len) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    match serde_json::from_str::<generic_llm::GenericLLMConfig>(config_str) {
+        Ok(llm_config) => {
+            let mut cfg = SERVICE_CONFIG.lock().unwrap();
+            if let Some(ref mut c) = *cfg {
+                let service_name = llm_config.name.clone();
+                c.generic_llm_services.insert(service_name, llm_config);
+            }
+        }
+        Err(_) => {}
+    }
+}
+
+/// Sets the default service fallback chain (e.g. `"deepl,google,libre"`) used whenever a
+/// translation is requested with a single, comma-free service name; `translate_begin`/
+/// `translate_html` still accept an explicit comma-separated list per call, which takes
+/// precedence over this default. Pass an empty string to clear it.
+#[no_mangle]
+pub extern "C" fn set_fallback_chain(service_list_ptr: u32, service_list_len: u32) {
+    init_config();
+    let service_list = match read_utf8(service_list_ptr, service_list_len) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    let mut config = SERVICE_CONFIG.lock().unwrap();
+    if let Some(ref mut cfg) = *config {
+        cfg.fallback_chain = parse_service_list(service_list);
+    }
+}
+
+/// Splits a comma-separated service list into trimmed, non-empty names; `None` if that leaves
+/// nothing to try.
+fn parse_service_list(service_list: &str) -> Option<Vec<String>> {
+    let chain: Vec<String> = service_list
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if chain.is_empty() {
+        None
+    } else {
+        Some(chain)
+    }
+}
+
+/// Resolves the service fallback chain for a `translate_begin`/`translate_html` call: an
+/// explicit comma-separated `service_name` wins; otherwise falls back to whatever
+/// `set_fallback_chain` configured; otherwise it's just the one service.
+fn resolve_service_chain(service_name: &str) -> Vec<String> {
+    if let Some(chain) = parse_service_list(service_name) {
+        if chain.len() > 1 {
+            return chain;
+        }
+    }
+    let configured = SERVICE_CONFIG
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|cfg| cfg.fallback_chain.clone());
+    configured.unwrap_or_else(|| vec![service_name.to_string()])
+}
+
 #[no_mangle]
 pub extern "C" fn clear_translation_cache() {
     // Cache clearing will be handled via JavaScript bridge
@@ -156,11 +437,152 @@ pub extern "C" fn remove_translations_with_error() {
     manager.remove_translations_with_error();
 }
 
-// Translation functions - these prepare requests and parse responses
-// JavaScript handles the async HTTP part
+// Translation functions - these prepare requests and parse responses.
+// JavaScript drives the async cache/HTTP steps and resumes us via `translate_resume`;
+// nothing here blocks or polls.
+
+/// Everything needed to kick off an HTTP request for a batch of segments, shared between a
+/// plain single-text translation and an HTML batch (or its per-segment fallback).
+struct HttpPlan {
+    cache_key: String,
+    url: String,
+    method: String,
+    headers_json: String,
+    body: Option<String>,
+    /// One `PlaceholderMap` per entry in the `segments` this plan was built from, in the same
+    /// order - see `PendingTranslation::placeholder_maps`.
+    placeholder_maps: Vec<placeholder::PlaceholderMap>,
+}
+
+fn plan_request(
+    service: &dyn service::TranslationService,
+    service_name: &str,
+    source_lang: &str,
+    target_lang: &str,
+    segments: &[String],
+) -> HttpPlan {
+    let (protected_segments, placeholder_maps): (Vec<String>, Vec<placeholder::PlaceholderMap>) =
+        segments.iter().map(|s| placeholder::protect(s)).unzip();
+    let transformed_text = service.transform_request(&protected_segments);
+    let requests = vec![service::TranslationInfo {
+        original_text: transformed_text.clone(),
+        translated_text: None,
+        detected_language: None,
+        status: service::TranslationStatus::Translating,
+        sentence_boundaries: None,
+    }];
 
+    let extra_params = service.get_extra_parameters(source_lang, target_lang, &requests);
+    let request_body = service.get_request_body(source_lang, target_lang, &requests);
+    let extra_headers = service.get_extra_headers();
+    let base_url = service.get_base_url();
+    let method = service.get_method().to_string();
+
+    let url = if extra_params.is_empty() {
+        base_url
+    } else {
+        format!("{}{}", base_url, extra_params)
+    };
+
+    HttpPlan {
+        cache_key: cache::get_cache_key(service_name, source_lang, target_lang, &transformed_text),
+        url,
+        method,
+        headers_json: serde_json::to_string(&extra_headers).unwrap_or_else(|_| "[]".to_string()),
+        body: request_body,
+        placeholder_maps,
+    }
+}
+
+/// Finds the earliest index at or after `start` in `chain` that names a registered service,
+/// recording `"<name>: unknown service"` in `chain_errors` for every name skipped along the way.
+/// `None` once the whole chain has been exhausted.
+fn next_chain_index(
+    registry: &HashMap<String, Box<dyn service::TranslationService + Send + Sync>>,
+    chain: &[String],
+    start: usize,
+    chain_errors: &mut Vec<String>,
+) -> Option<usize> {
+    for idx in start..chain.len() {
+        if registry.contains_key(chain[idx].as_str()) {
+            return Some(idx);
+        }
+        chain_errors.push(format!("{}: unknown service", chain[idx]));
+    }
+    None
+}
+
+/// The source language to use for the chain entry at `chain_index`: `source_lang` as given for
+/// the first entry, or the last globally detected language in place of `"auto"` for later ones,
+/// so a provider that can't auto-detect isn't stuck guessing.
+fn effective_source_lang(source_lang: &str, chain_index: usize) -> String {
+    if chain_index > 0 && source_lang == "auto" {
+        if let Some(lang) = LAST_DETECTED_LANGUAGE.lock().unwrap().clone() {
+            return lang;
+        }
+    }
+    source_lang.to_string()
+}
+
+fn remember_detected_language(detected_lang: &Option<String>) {
+    if let Some(lang) = detected_lang {
+        if !lang.is_empty() && lang != "unknown" {
+            *LAST_DETECTED_LANGUAGE.lock().unwrap() = Some(lang.clone());
+        }
+    }
+}
+
+fn remember_sentence_boundaries(boundaries: &Option<Vec<service::SentenceBoundary>>) {
+    *LAST_SENTENCE_BOUNDARIES.lock().unwrap() = boundaries
+        .as_ref()
+        .and_then(|b| serde_json::to_string(b).ok());
+}
+
+/// Moves `pending` on to the next service in its fallback chain after `pending.service_name`
+/// failed with `failure_message`. Starts the new service's HTTP request and returns the updated
+/// `pending` for the caller to re-insert under `callback_id`, or `None` once the whole chain is
+/// exhausted (in which case a combined diagnostic has already been passed to `set_error`).
+fn advance_chain(mut pending: PendingTranslation, callback_id: u32, failure_message: &str) -> Option<PendingTranslation> {
+    pending.chain_errors.push(format!("{}: {}", pending.service_name, failure_message));
+
+    let registry = SERVICE_REGISTRY.lock().unwrap();
+    let Some(next_index) = next_chain_index(&registry, &pending.chain, pending.chain_index + 1, &mut pending.chain_errors) else {
+        set_error_for(callback_id, 1, &format!("All services failed: {}", pending.chain_errors.join("; ")));
+        return None;
+    };
+    let next_service_name = pending.chain[next_index].clone();
+    let service = registry.get(&next_service_name).unwrap();
+    let source_lang = effective_source_lang(&pending.source_lang, next_index);
+    let plan = plan_request(service.as_ref(), &next_service_name, &source_lang, &pending.target_lang, &pending.segments);
+    drop(registry);
+
+    pending.service_name = next_service_name;
+    pending.chain_index = next_index;
+    pending.cache_key = plan.cache_key;
+    pending.url = plan.url;
+    pending.method = plan.method;
+    pending.headers_json = plan.headers_json;
+    pending.body = plan.body;
+    pending.stage = PendingStage::AwaitingHttp;
+    pending.revalidating = None;
+    pending.attempt = 0;
+    if let Some(batch) = pending.html_batch.as_mut() {
+        batch.fallback_index = None;
+        batch.collected = Vec::new();
+    }
+    start_http_request(&pending, callback_id);
+    Some(pending)
+}
+
+/// Starts a translation and returns the `ffi::ctx_new` handle minted for this call - a host with
+/// several translations in flight at once passes that same value to
+/// `last_len_for`/`last_err_ptr_for`/etc. (and to `translate_resume`/`translate_retry_fire`, which
+/// reuse it as their `callback_id`) to read this call's result without racing any other call's.
+/// A service that resolves synchronously (e.g. a loaded local model) has already called
+/// `write_out_for(handle, ...)` by the time this returns; otherwise the result lands there once
+/// `translate_resume` completes.
 #[no_mangle]
-pub extern "C" fn translate_text(
+pub extern "C" fn translate_begin(
     service_ptr: u32,
     service_len: u32,
     source_lang_ptr: u32,
@@ -172,266 +594,568 @@ pub extern "C" fn translate_text(
 ) -> u32 {
     init_services();
     init_config();
-    
+
+    // Minted up front, before anything can fail, so every exit path - including a parameter
+    // validation error - reports through this call's own handle rather than the shared default
+    // one. The handle doubles as the callback_id JS already threads through
+    // translate_resume/translate_retry_fire, so overlapping translate_begin calls never share
+    // result/error state.
+    let callback_id = ffi::ctx_new();
+
     let service_name = match read_utf8(service_ptr, service_len) {
         Ok(s) => s,
         Err(_) => {
-            set_error(1, "Invalid service parameter");
-            return 0;
+            set_error_for(callback_id, 1, "Invalid service parameter");
+            return callback_id;
         }
     };
-    
+
     let source_lang = match read_utf8(source_lang_ptr, source_lang_len) {
         Ok(s) => s,
         Err(_) => {
-            set_error(1, "Invalid source_lang parameter");
-            return 0;
+            set_error_for(callback_id, 1, "Invalid source_lang parameter");
+            return callback_id;
         }
     };
-    
+
     let target_lang = match read_utf8(target_lang_ptr, target_lang_len) {
         Ok(s) => s,
         Err(_) => {
-            set_error(1, "Invalid target_lang parameter");
-            return 0;
+            set_error_for(callback_id, 1, "Invalid target_lang parameter");
+            return callback_id;
         }
     };
-    
+
     let text = match read_utf8(text_ptr, text_len) {
         Ok(s) => s,
         Err(_) => {
-            set_error(1, "Invalid text parameter");
-            return 0;
+            set_error_for(callback_id, 1, "Invalid text parameter");
+            return callback_id;
         }
     };
-    
-    // Get service from registry
+
+    let chain = resolve_service_chain(service_name);
+    let mut chain_errors = Vec::new();
     let registry = SERVICE_REGISTRY.lock().unwrap();
-    let service = match registry.get(service_name) {
-        Some(s) => s,
-        None => {
-            set_error(1, &format!("Unknown service: {}", service_name));
-            return 0;
-        }
+    let Some(chain_index) = next_chain_index(&registry, &chain, 0, &mut chain_errors) else {
+        set_error_for(callback_id, 1, &format!("All services failed: {}", chain_errors.join("; ")));
+        return callback_id;
     };
-    
-    // Prepare request using service
-    let source_array = vec![text.to_string()];
-    let transformed = service.transform_request(&source_array);
-    let transformed_text = transformed.clone();
-    let requests = vec![service::TranslationInfo {
-        original_text: transformed,
-        translated_text: None,
-        detected_language: None,
-        status: service::TranslationStatus::Translating,
-    }];
-    
-    let extra_params = service.get_extra_parameters(source_lang, target_lang, &requests);
-    let request_body = service.get_request_body(source_lang, target_lang, &requests);
-    let extra_headers = service.get_extra_headers();
-    let base_url = service.get_base_url();
-    let method = service.get_method();
-    
-    // Build full URL
-    let url = if extra_params.is_empty() {
-        base_url
-    } else {
-        format!("{}{}", base_url, extra_params)
-    };
-    
-    // Check cache first
-    let cache_key = cache::get_cache_key(service_name, source_lang, target_lang, &transformed_text);
-    let cache_key_bytes = cache_key.as_bytes();
-    let cache_key_ptr = ffi::alloc(cache_key_bytes.len() as u32);
-    unsafe {
-        std::ptr::copy_nonoverlapping(cache_key_bytes.as_ptr(), cache_key_ptr as *mut u8, cache_key_bytes.len());
-    }
-    
-    use std::sync::atomic::{AtomicU32, Ordering};
-    static CACHE_CALLBACK_ID: AtomicU32 = AtomicU32::new(1);
-    let cache_callback_id = CACHE_CALLBACK_ID.fetch_add(1, Ordering::Relaxed);
-    
-    unsafe {
-        cache_get(cache_key_ptr, cache_key_bytes.len() as u32, cache_callback_id);
-    }
-    ffi::dealloc(cache_key_ptr, cache_key_bytes.len() as u32);
-    
-    // Poll for cache result
-    let mut cache_result_len = 0u32;
-    let mut cache_attempts = 0;
-    let cache_result_ptr = loop {
-        unsafe {
-            let ptr = cache_get_result(cache_callback_id, &mut cache_result_len);
-            if ptr != 0 {
-                break ptr;
-            }
-        }
-        cache_attempts += 1;
-        if cache_attempts > 100 {
-            break 0; // Timeout, proceed with HTTP request
-        }
-        // Simple busy-wait (in WASM, we can't use threads, so this is acceptable)
-        // JavaScript will complete the async operation quickly
-        unsafe {
-            // Use a simple loop to wait (WASM doesn't have sleep)
-            for _ in 0..1000 {
-                std::hint::spin_loop();
+    let current_service = &chain[chain_index];
+    let current_source_lang = effective_source_lang(source_lang, chain_index);
+    let service = registry.get(current_service).unwrap();
+
+    let segments = vec![text.to_string()];
+
+    // A service that can translate in-process (e.g. `nllb::LocalNllbService` with a model
+    // loaded) bypasses the cache/HTTP bridge entirely - there's no round trip to await, so the
+    // result is ready before this function returns rather than via `translate_resume`.
+    let local_requests: Vec<service::TranslationInfo> = segments
+        .iter()
+        .map(|s| service::TranslationInfo {
+            original_text: s.clone(),
+            translated_text: None,
+            detected_language: None,
+            status: service::TranslationStatus::Translating,
+            sentence_boundaries: None,
+        })
+        .collect();
+    if let Some(results) = service.run_local(&current_source_lang, target_lang, &local_requests) {
+        drop(registry);
+        return match results.first() {
+            Some(result) => write_out_for(callback_id, result),
+            None => {
+                set_error_for(callback_id, 1, "local translation produced no result");
+                callback_id
             }
-        }
-    };
-    
-    // If cache hit, return cached result
-    if cache_result_ptr != 0 {
-        let cache_result_bytes = unsafe {
-            std::slice::from_raw_parts(cache_result_ptr as *const u8, cache_result_len as usize)
         };
-        if let Ok(cache_result_str) = String::from_utf8(cache_result_bytes.to_vec()) {
-            if let Ok(cache_entry) = serde_json::from_str::<cache::CacheEntry>(&cache_result_str) {
-                ffi::dealloc(cache_result_ptr, cache_result_len);
-                return write_out(&cache_entry.translated_text);
-            }
-        }
-        ffi::dealloc(cache_result_ptr, cache_result_len);
     }
-    
-    // No cache hit, make HTTP request via bridge
-    let url_bytes = url.as_bytes();
-    let method_bytes = method.as_bytes();
-    let headers_json = serde_json::to_string(&extra_headers).unwrap_or_else(|_| "[]".to_string());
-    let headers_bytes = headers_json.as_bytes();
-    let body_bytes = request_body.as_ref().map(|s| s.as_bytes()).unwrap_or(&[]);
-    
-    // Allocate memory for HTTP request
-    let url_ptr = ffi::alloc(url_bytes.len() as u32);
-    let method_ptr = ffi::alloc(method_bytes.len() as u32);
-    let headers_ptr = ffi::alloc(headers_bytes.len() as u32);
-    let body_ptr = if request_body.is_some() { ffi::alloc(body_bytes.len() as u32) } else { 0 };
-    
+
+    let plan = plan_request(service.as_ref(), current_service, &current_source_lang, target_lang, &segments);
+    drop(registry);
+
+    PENDING_TRANSLATIONS.lock().unwrap().insert(
+        callback_id,
+        PendingTranslation {
+            service_name: current_service.clone(),
+            source_lang: source_lang.to_string(),
+            target_lang: target_lang.to_string(),
+            cache_key: plan.cache_key.clone(),
+            url: plan.url,
+            method: plan.method,
+            headers_json: plan.headers_json,
+            body: plan.body,
+            placeholder_maps: plan.placeholder_maps,
+            stage: PendingStage::AwaitingCache,
+            revalidating: None,
+            attempt: 0,
+            html_batch: None,
+            segments,
+            chain,
+            chain_index,
+            chain_errors,
+        },
+    );
+
+    let (key_ptr, key_len) = alloc_copy(plan.cache_key.as_bytes());
     unsafe {
-        std::ptr::copy_nonoverlapping(url_bytes.as_ptr(), url_ptr as *mut u8, url_bytes.len());
-        std::ptr::copy_nonoverlapping(method_bytes.as_ptr(), method_ptr as *mut u8, method_bytes.len());
-        std::ptr::copy_nonoverlapping(headers_bytes.as_ptr(), headers_ptr as *mut u8, headers_bytes.len());
-        if request_body.is_some() {
-            std::ptr::copy_nonoverlapping(body_bytes.as_ptr(), body_ptr as *mut u8, body_bytes.len());
-        }
+        cache_get(key_ptr, key_len, callback_id);
     }
-    
-    // Generate callback ID for HTTP request
-    static HTTP_CALLBACK_ID: AtomicU32 = AtomicU32::new(1);
-    let http_callback_id = HTTP_CALLBACK_ID.fetch_add(1, Ordering::Relaxed);
-    
-    // Call HTTP bridge
+    ffi::dealloc(key_ptr, key_len);
+
+    callback_id
+}
+
+fn start_http_request(pending: &PendingTranslation, callback_id: u32) {
+    let (url_ptr, url_len) = alloc_copy(pending.url.as_bytes());
+    let (method_ptr, method_len) = alloc_copy(pending.method.as_bytes());
+    let (headers_ptr, headers_len) = alloc_copy(pending.headers_json.as_bytes());
+    let body_bytes = pending.body.as_deref().unwrap_or("").as_bytes();
+    let (body_ptr, body_len) = alloc_copy(body_bytes);
+
     unsafe {
         http_request(
-            url_ptr, url_bytes.len() as u32,
-            method_ptr, method_bytes.len() as u32,
-            headers_ptr, headers_bytes.len() as u32,
-            body_ptr, body_bytes.len() as u32,
-            http_callback_id,
+            url_ptr, url_len, method_ptr, method_len, headers_ptr, headers_len, body_ptr, body_len,
+            callback_id,
         );
     }
-    
-    // Clean up request memory
-    ffi::dealloc(url_ptr, url_bytes.len() as u32);
-    ffi::dealloc(method_ptr, method_bytes.len() as u32);
-    ffi::dealloc(headers_ptr, headers_bytes.len() as u32);
-    if request_body.is_some() {
-        ffi::dealloc(body_ptr, body_bytes.len() as u32);
+
+    ffi::dealloc(url_ptr, url_len);
+    ffi::dealloc(method_ptr, method_len);
+    ffi::dealloc(headers_ptr, headers_len);
+    ffi::dealloc(body_ptr, body_len);
+}
+
+/// Called by JS after a `schedule_retry` timeout elapses; re-issues the HTTP request for a
+/// pending translation that's still awaiting its retry.
+#[no_mangle]
+pub extern "C" fn translate_retry_fire(callback_id: u32) {
+    let registry = PENDING_TRANSLATIONS.lock().unwrap();
+    if let Some(pending) = registry.get(&callback_id) {
+        start_http_request(pending, callback_id);
     }
-    
-    // Poll for HTTP result
-    let mut http_result_len = 0u32;
-    let mut http_attempts = 0;
-    let http_result_ptr = loop {
-        unsafe {
-            let ptr = http_request_get_result(http_callback_id, &mut http_result_len);
-            if ptr != 0 {
-                break ptr;
+}
+
+/// Called by JS once the cache lookup (first call) or HTTP fetch (second call) for
+/// `callback_id` has settled. Returns 0 while another async step is still pending (the
+/// registration for `callback_id` stays alive) or the `write_out` pointer once resolved.
+#[no_mangle]
+pub extern "C" fn translate_resume(callback_id: u32, response_ptr: u32, response_len: u32) -> u32 {
+    let mut registry = PENDING_TRANSLATIONS.lock().unwrap();
+    let Some(pending) = registry.get_mut(&callback_id) else {
+        set_error_for(callback_id, 1, "Unknown callback_id");
+        return 0;
+    };
+
+    match pending.stage {
+        PendingStage::AwaitingCache => {
+            // Check in-process translation memory first: a hit here means we've already seen
+            // this exact (service, languages, text) in this WASM instance, so there's no need to
+            // wait on JS's cache_get response at all. Subject to the same HTML-batch guard as the
+            // JS-cache check below.
+            if pending.html_batch.is_none() {
+                if let Some(entry) = TRANSLATION_MEMORY.lock().unwrap().get(&pending.cache_key).cloned() {
+                    let now_millis = unsafe { now() };
+                    if cache::is_fresh(&entry, now_millis) {
+                        registry.remove(&callback_id);
+                        return write_out_for(callback_id, &entry.translated_text);
+                    }
+                }
             }
-        }
-        http_attempts += 1;
-        if http_attempts > 1000 {
-            set_error(1, "HTTP request timeout");
-            return 0;
-        }
-        // Simple busy-wait (WASM doesn't have threads)
-        unsafe {
-            for _ in 0..1000 {
-                std::hint::spin_loop();
+            // An HTML batch never writes a cache entry under its own key (see the success path
+            // below), so a hit here can only be a plain single-text entry that happens to share
+            // the key; serving it directly would skip the splice and hand back raw provider
+            // output instead of HTML. Treat it as a miss and fetch fresh instead.
+            if response_len > 0 && pending.html_batch.is_none() {
+                if let Ok(raw) = read_utf8(response_ptr, response_len) {
+                    if let Ok(entry) = serde_json::from_str::<cache::CacheEntry>(raw) {
+                        let now_millis = unsafe { now() };
+                        if cache::is_fresh(&entry, now_millis) {
+                            registry.remove(&callback_id);
+                            return write_out_for(callback_id, &entry.translated_text);
+                        }
+                        if let Some(ref etag) = entry.etag {
+                            // Stale but revalidatable: re-fetch with `If-None-Match` and serve
+                            // the cached text as-is on a `304`.
+                            let mut headers: Vec<(String, String)> =
+                                serde_json::from_str(&pending.headers_json).unwrap_or_default();
+                            headers.push(("If-None-Match".to_string(), etag.clone()));
+                            pending.headers_json = serde_json::to_string(&headers).unwrap_or_else(|_| "[]".to_string());
+                            pending.revalidating = Some(entry);
+                            pending.stage = PendingStage::AwaitingHttp;
+                            start_http_request(pending, callback_id);
+                            return 0;
+                        }
+                    }
+                }
             }
+            // Cache miss (or stale with no etag to revalidate against): fetch fresh.
+            pending.stage = PendingStage::AwaitingHttp;
+            start_http_request(pending, callback_id);
+            0
         }
-    };
-    
-    // Read HTTP response
-    let http_result_bytes = unsafe {
-        std::slice::from_raw_parts(http_result_ptr as *const u8, http_result_len as usize)
-    };
-    let http_result_str = match String::from_utf8(http_result_bytes.to_vec()) {
-        Ok(s) => s,
-        Err(_) => {
-            ffi::dealloc(http_result_ptr, http_result_len);
-            set_error(1, "Invalid UTF-8 in HTTP response");
-            return 0;
-        }
-    };
-    ffi::dealloc(http_result_ptr, http_result_len);
-    
-    let http_result: serde_json::Value = match serde_json::from_str(&http_result_str) {
-        Ok(v) => v,
-        Err(e) => {
-            set_error(1, &format!("Failed to parse HTTP response: {}", e));
-            return 0;
-        }
-    };
-    
-    // Check if HTTP request was successful
-    if !http_result.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
-        let error_msg = http_result.get("statusText").and_then(|v| v.as_str()).unwrap_or("HTTP request failed");
-        set_error(1, error_msg);
-        return 0;
-    }
-    
-    let response_text = http_result.get("text").and_then(|v| v.as_str()).unwrap_or("");
-    
-    // Parse response using service
-    match service.parse_response(response_text) {
-        Ok(results) => {
-            if let Some((translated, detected_lang)) = results.first() {
-                let transformed = service.transform_response(translated, false);
-                if let Some(result) = transformed.first() {
-                    // Store in cache
-                    let cache_entry = cache::CacheEntry {
-                        translated_text: result.clone(),
-                        detected_language: detected_lang.clone().unwrap_or_else(|| "unknown".to_string()),
-                    };
-                    let cache_entry_json = serde_json::to_string(&cache_entry).unwrap_or_else(|_| "{}".to_string());
-                    let cache_entry_bytes = cache_entry_json.as_bytes();
-                    let cache_key_ptr2 = ffi::alloc(cache_key_bytes.len() as u32);
-                    let cache_entry_ptr = ffi::alloc(cache_entry_bytes.len() as u32);
-                    unsafe {
-                        std::ptr::copy_nonoverlapping(cache_key_bytes.as_ptr(), cache_key_ptr2 as *mut u8, cache_key_bytes.len());
-                        std::ptr::copy_nonoverlapping(cache_entry_bytes.as_ptr(), cache_entry_ptr as *mut u8, cache_entry_bytes.len());
+        PendingStage::AwaitingHttp => {
+            let raw = match read_utf8(response_ptr, response_len) {
+                Ok(s) => s,
+                Err(_) => {
+                    registry.remove(&callback_id);
+                    set_error_for(callback_id, 1, "Invalid UTF-8 in HTTP response");
+                    return 0;
+                }
+            };
+            let http_result: serde_json::Value = match serde_json::from_str(raw) {
+                Ok(v) => v,
+                Err(e) => {
+                    registry.remove(&callback_id);
+                    set_error_for(callback_id, 1, &format!("Failed to parse HTTP response: {}", e));
+                    return 0;
+                }
+            };
+            let status = http_result.get("status").and_then(|v| v.as_u64()).unwrap_or(0);
+            let ok = http_result.get("ok").and_then(|v| v.as_bool()).unwrap_or(false);
+            let response_headers = http_result.get("headers").cloned();
+            let header_value = |name: &str| -> Option<String> {
+                response_headers
+                    .as_ref()
+                    .and_then(|h| h.get(name).or_else(|| h.get(name.to_ascii_lowercase())))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            };
+
+            if let Some(mut stale) = pending.revalidating.clone() {
+                if status == 304 {
+                    stale.stored_at = unsafe { now() };
+                    if let Ok(entry_json) = serde_json::to_string(&stale) {
+                        let (key_ptr, key_len) = alloc_copy(pending.cache_key.as_bytes());
+                        let (entry_ptr, entry_len) = alloc_copy(entry_json.as_bytes());
+                        unsafe {
+                            cache_set(key_ptr, key_len, entry_ptr, entry_len);
+                        }
+                        ffi::dealloc(key_ptr, key_len);
+                        ffi::dealloc(entry_ptr, entry_len);
+                    }
+                    TRANSLATION_MEMORY.lock().unwrap().insert(pending.cache_key.clone(), stale.clone());
+                    registry.remove(&callback_id);
+                    return write_out_for(callback_id, &stale.translated_text);
+                }
+            }
+
+            if !ok {
+                // A status of 0 means the bridge couldn't even complete the request (DNS/TCP/CORS
+                // failure), which is just as retryable as a 5xx from the server itself. A 401
+                // means the service's cached credentials are stale; invalidate_auth gives it a
+                // chance to discard them before the retry picks up a fresh one.
+                let retryable = status == 0 || status == 401 || retry::is_retryable_status(status);
+                let (max_retries, base_delay_ms) = {
+                    let reg = SERVICE_REGISTRY.lock().unwrap();
+                    reg.get(pending.service_name.as_str())
+                        .map(|s| s.retry_policy())
+                        .unwrap_or((retry::MAX_ATTEMPTS, retry::DEFAULT_BASE_DELAY_MS))
+                };
+                if retryable && pending.attempt < max_retries {
+                    if status == 401 {
+                        let mut reg = SERVICE_REGISTRY.lock().unwrap();
+                        if let Some(service) = reg.get_mut(pending.service_name.as_str()) {
+                            service.invalidate_auth();
+                        }
                     }
+                    let now_millis = unsafe { now() };
+                    let delay_ms = header_value("Retry-After")
+                        .and_then(|v| retry::parse_retry_after(&v, now_millis))
+                        .unwrap_or_else(|| retry::backoff_delay_ms_with_base(pending.attempt, now_millis, base_delay_ms));
+                    pending.attempt += 1;
                     unsafe {
-                        cache_set(cache_key_ptr2, cache_key_bytes.len() as u32, cache_entry_ptr, cache_entry_bytes.len() as u32);
+                        schedule_retry(callback_id, delay_ms as u32);
                     }
-                    ffi::dealloc(cache_key_ptr2, cache_key_bytes.len() as u32);
-                    ffi::dealloc(cache_entry_ptr, cache_entry_bytes.len() as u32);
-                    
-                    write_out(result)
+                    return 0;
+                }
+                let status_text = http_result
+                    .get("statusText")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("HTTP request failed");
+                let msg = if retryable {
+                    // Retryable but the attempt budget ran out - say so, rather than reporting
+                    // the last failure as if no retry had been attempted at all.
+                    format!(
+                        "{} (status {}) after {} attempt{}",
+                        status_text,
+                        status,
+                        pending.attempt + 1,
+                        if pending.attempt == 0 { "" } else { "s" }
+                    )
                 } else {
-                    set_error(1, "No translation result");
+                    status_text.to_string()
+                };
+                let pending = registry.remove(&callback_id).unwrap();
+                if let Some(next_pending) = advance_chain(pending, callback_id, &msg) {
+                    registry.insert(callback_id, next_pending);
+                }
+                return 0;
+            }
+            let mut pending = registry.remove(&callback_id).unwrap();
+
+            let reg = SERVICE_REGISTRY.lock().unwrap();
+            let Some(service) = reg.get(pending.service_name.as_str()) else {
+                let msg = format!("unknown service: {}", pending.service_name);
+                drop(reg);
+                if let Some(next_pending) = advance_chain(pending, callback_id, &msg) {
+                    registry.insert(callback_id, next_pending);
+                }
+                return 0;
+            };
+
+            // A host new enough to send raw bytes (rather than a pre-decoded string) lets the
+            // service itself pick the right charset instead of assuming UTF-8.
+            let decoded_body;
+            let response_text: &str = match http_result.get("rawText").and_then(|v| v.as_str()) {
+                Some(raw_text) => {
+                    let bytes = charset::bytes_from_raw_text(raw_text);
+                    let content_encoding = header_value("Content-Encoding").unwrap_or_default();
+                    let bytes = compression::decode_body(&bytes, &content_encoding);
+                    decoded_body = service.decode_body(&bytes, header_value("Content-Type").as_deref());
+                    &decoded_body
+                }
+                None => http_result.get("text").and_then(|v| v.as_str()).unwrap_or(""),
+            };
+
+            let sentence_boundaries = service.parse_sentence_boundaries(response_text);
+            match service.parse_response(response_text) {
+                Ok(results) => {
+                    let Some((translated, detected_lang)) = results.first() else {
+                        drop(reg);
+                        if let Some(next_pending) = advance_chain(pending, callback_id, "empty translation results") {
+                            registry.insert(callback_id, next_pending);
+                        }
+                        return 0;
+                    };
+                    let dont_sort = pending.html_batch.as_ref().map(|b| b.dont_sort).unwrap_or(false);
+                    let transformed = service.transform_response(translated, dont_sort);
+                    // Restore each segment's protected placeholders now, before any of the
+                    // chunking/fallback branches below consume `transformed` - `placeholder_maps`
+                    // is index-aligned with it since both came from the same `plan_request` call.
+                    let transformed: Vec<String> = transformed
+                        .iter()
+                        .enumerate()
+                        .map(|(i, text)| match pending.placeholder_maps.get(i) {
+                            Some(map) => placeholder::restore(text, map),
+                            None => text.clone(),
+                        })
+                        .collect();
+
+                    if let Some(mut batch) = pending.html_batch.take() {
+                        if let Some(chunks) = batch.chunks.clone() {
+                            // One group came back from the free-endpoint chunking split; groups
+                            // are contiguous, in-order slices of the original segments, so
+                            // appending each one's results in group order reconstructs the
+                            // original order without needing to track indices past this point.
+                            let chunk_pos = batch.chunk_pos;
+                            if transformed.len() != chunks[chunk_pos].len() {
+                                drop(reg);
+                                pending.html_batch = Some(batch);
+                                if let Some(next_pending) = advance_chain(
+                                    pending,
+                                    callback_id,
+                                    "segment count mismatch in a chunked free-endpoint request",
+                                ) {
+                                    registry.insert(callback_id, next_pending);
+                                }
+                                return 0;
+                            }
+                            batch.collected.extend(transformed.iter().cloned());
+
+                            let next_pos = chunk_pos + 1;
+                            if next_pos < chunks.len() {
+                                let next_segments: Vec<String> =
+                                    chunks[next_pos].iter().map(|&i| batch.segments[i].clone()).collect();
+                                let plan = plan_request(
+                                    service.as_ref(),
+                                    &pending.service_name,
+                                    &pending.source_lang,
+                                    &pending.target_lang,
+                                    &next_segments,
+                                );
+                                batch.chunk_pos = next_pos;
+                                let next_pending = PendingTranslation {
+                                    service_name: pending.service_name,
+                                    source_lang: pending.source_lang,
+                                    target_lang: pending.target_lang,
+                                    cache_key: plan.cache_key,
+                                    url: plan.url,
+                                    method: plan.method,
+                                    headers_json: plan.headers_json,
+                                    body: plan.body,
+                                    placeholder_maps: plan.placeholder_maps,
+                                    stage: PendingStage::AwaitingHttp,
+                                    revalidating: None,
+                                    attempt: 0,
+                                    html_batch: Some(batch),
+                                    segments: pending.segments,
+                                    chain: pending.chain,
+                                    chain_index: pending.chain_index,
+                                    chain_errors: pending.chain_errors,
+                                };
+                                start_http_request(&next_pending, callback_id);
+                                registry.insert(callback_id, next_pending);
+                                return 0;
+                            }
+
+                            remember_detected_language(detected_lang);
+                            remember_sentence_boundaries(&sentence_boundaries);
+                            return write_out_for(callback_id, &html::splice_translated_segments(&batch.template, &batch.collected));
+                        }
+
+                        if let Some(fallback_idx) = batch.fallback_index {
+                            // One segment came back from the per-segment fallback.
+                            let Some(text) = transformed.first() else {
+                                drop(reg);
+                                pending.html_batch = Some(batch);
+                                if let Some(next_pending) =
+                                    advance_chain(pending, callback_id, "empty translation result during per-segment fallback")
+                                {
+                                    registry.insert(callback_id, next_pending);
+                                }
+                                return 0;
+                            };
+                            batch.collected.push(text.clone());
+
+                            let next_idx = fallback_idx + 1;
+                            if next_idx < batch.segments.len() {
+                                let plan = plan_request(
+                                    service.as_ref(),
+                                    &pending.service_name,
+                                    &pending.source_lang,
+                                    &pending.target_lang,
+                                    std::slice::from_ref(&batch.segments[next_idx]),
+                                );
+                                batch.fallback_index = Some(next_idx);
+                                let next_pending = PendingTranslation {
+                                    service_name: pending.service_name,
+                                    source_lang: pending.source_lang,
+                                    target_lang: pending.target_lang,
+                                    cache_key: plan.cache_key,
+                                    url: plan.url,
+                                    method: plan.method,
+                                    headers_json: plan.headers_json,
+                                    body: plan.body,
+                                    placeholder_maps: plan.placeholder_maps,
+                                    stage: PendingStage::AwaitingHttp,
+                                    revalidating: None,
+                                    attempt: 0,
+                                    html_batch: Some(batch),
+                                    segments: pending.segments,
+                                    chain: pending.chain,
+                                    chain_index: pending.chain_index,
+                                    chain_errors: pending.chain_errors,
+                                };
+                                start_http_request(&next_pending, callback_id);
+                                registry.insert(callback_id, next_pending);
+                                return 0;
+                            }
+
+                            remember_detected_language(detected_lang);
+                            remember_sentence_boundaries(&sentence_boundaries);
+                            return write_out_for(callback_id, &html::splice_translated_segments(&batch.template, &batch.collected));
+                        }
+
+                        if transformed.len() == batch.segments.len() {
+                            remember_detected_language(detected_lang);
+                            remember_sentence_boundaries(&sentence_boundaries);
+                            return write_out_for(callback_id, &html::splice_translated_segments(&batch.template, &transformed));
+                        }
+
+                        // Arity mismatch between what we sent and what came back: fall back to
+                        // translating one segment at a time instead of guessing at an alignment.
+                        let plan = plan_request(
+                            service.as_ref(),
+                            &pending.service_name,
+                            &pending.source_lang,
+                            &pending.target_lang,
+                            std::slice::from_ref(&batch.segments[0]),
+                        );
+                        batch.fallback_index = Some(0);
+                        batch.collected = Vec::new();
+                        let next_pending = PendingTranslation {
+                            service_name: pending.service_name,
+                            source_lang: pending.source_lang,
+                            target_lang: pending.target_lang,
+                            cache_key: plan.cache_key,
+                            url: plan.url,
+                            method: plan.method,
+                            headers_json: plan.headers_json,
+                            body: plan.body,
+                            placeholder_maps: plan.placeholder_maps,
+                            stage: PendingStage::AwaitingHttp,
+                            revalidating: None,
+                            attempt: 0,
+                            html_batch: Some(batch),
+                            segments: pending.segments,
+                            chain: pending.chain,
+                            chain_index: pending.chain_index,
+                            chain_errors: pending.chain_errors,
+                        };
+                        start_http_request(&next_pending, callback_id);
+                        registry.insert(callback_id, next_pending);
+                        return 0;
+                    }
+
+                    let Some(result) = transformed.first() else {
+                        drop(reg);
+                        if let Some(next_pending) = advance_chain(pending, callback_id, "no translation result") {
+                            registry.insert(callback_id, next_pending);
+                        }
+                        return 0;
+                    };
+
+                    let cache_control = header_value("Cache-Control")
+                        .map(|v| cache::parse_cache_control(&v))
+                        .unwrap_or_default();
+
+                    if !cache_control.no_store {
+                        let age_secs = header_value("Age").and_then(|v| cache::parse_age_secs(&v)).unwrap_or(0);
+                        let max_age_secs = cache_control.max_age_secs.map(|m| m.saturating_sub(age_secs));
+                        let cache_entry = cache::CacheEntry {
+                            translated_text: result.clone(),
+                            detected_language: detected_lang.clone().unwrap_or_else(|| "unknown".to_string()),
+                            stored_at: unsafe { now() },
+                            max_age_secs,
+                            etag: header_value("ETag"),
+                        };
+                        if let Ok(entry_json) = serde_json::to_string(&cache_entry) {
+                            let (key_ptr, key_len) = alloc_copy(pending.cache_key.as_bytes());
+                            let (entry_ptr, entry_len) = alloc_copy(entry_json.as_bytes());
+                            unsafe {
+                                cache_set(key_ptr, key_len, entry_ptr, entry_len);
+                            }
+                            ffi::dealloc(key_ptr, key_len);
+                            ffi::dealloc(entry_ptr, entry_len);
+                        }
+                        TRANSLATION_MEMORY.lock().unwrap().insert(pending.cache_key.clone(), cache_entry);
+                    }
+
+                    remember_detected_language(detected_lang);
+                    remember_sentence_boundaries(&sentence_boundaries);
+                    write_out_for(callback_id, result)
+                }
+                Err(e) => {
+                    drop(reg);
+                    if let Some(next_pending) = advance_chain(pending, callback_id, &e) {
+                        registry.insert(callback_id, next_pending);
+                    }
                     0
                 }
-            } else {
-                set_error(1, "Empty translation results");
-                0
             }
         }
-        Err(e) => {
-            set_error(1, &e);
-            0
-        }
+    }
+}
+
+/// Returns the `write_out` pointer for the JSON-encoded `Vec<service::SentenceBoundary>` left by
+/// the most recent `translate_resume`/`parse_translation_response` call that had any, or `0` if
+/// that service didn't report sentence boundaries (or none has completed yet).
+#[no_mangle]
+pub extern "C" fn get_sentence_boundaries() -> u32 {
+    match LAST_SENTENCE_BOUNDARIES.lock().unwrap().clone() {
+        Some(json) => write_out(&json),
+        None => 0,
     }
 }
 
@@ -472,6 +1196,7 @@ pub extern "C" fn parse_translation_response(
     };
     
     // Parse response
+    remember_sentence_boundaries(&service.parse_sentence_boundaries(response));
     match service.parse_response(response) {
         Ok(results) => {
             if let Some((translated, _)) = results.first() {
@@ -494,7 +1219,10 @@ pub extern "C" fn parse_translation_response(
     }
 }
 
-// Legacy function stubs for compatibility
+/// Translates the text content of an HTML fragment while leaving its tags/attributes intact,
+/// via `html::extract_translatable_segments`/`splice_translated_segments` around the same
+/// cache/HTTP state machine `translate_begin` drives. Returns its own `ffi::ctx_new` handle with
+/// the same contract as `translate_begin`'s return value.
 #[no_mangle]
 pub extern "C" fn translate_html(
     service_ptr: u32,
@@ -505,11 +1233,121 @@ pub extern "C" fn translate_html(
     target_lang_len: u32,
     html_ptr: u32,
     html_len: u32,
-    _dont_sort_ptr: u32,
-    _dont_sort_len: u32,
+    dont_sort_ptr: u32,
+    dont_sort_len: u32,
 ) -> u32 {
-    // For HTML, treat as text for now (HTML-aware translation can be added later)
-    translate_text(service_ptr, service_len, source_lang_ptr, source_lang_len, target_lang_ptr, target_lang_len, html_ptr, html_len)
+    init_services();
+    init_config();
+
+    // See translate_begin: minted up front so every exit path, including a validation error,
+    // reports through this call's own handle instead of the shared default one.
+    let callback_id = ffi::ctx_new();
+
+    let service_name = match read_utf8(service_ptr, service_len) {
+        Ok(s) => s,
+        Err(_) => {
+            set_error_for(callback_id, 1, "Invalid service parameter");
+            return callback_id;
+        }
+    };
+    let source_lang = match read_utf8(source_lang_ptr, source_lang_len) {
+        Ok(s) => s,
+        Err(_) => {
+            set_error_for(callback_id, 1, "Invalid source_lang parameter");
+            return callback_id;
+        }
+    };
+    let target_lang = match read_utf8(target_lang_ptr, target_lang_len) {
+        Ok(s) => s,
+        Err(_) => {
+            set_error_for(callback_id, 1, "Invalid target_lang parameter");
+            return callback_id;
+        }
+    };
+    let html = match read_utf8(html_ptr, html_len) {
+        Ok(s) => s,
+        Err(_) => {
+            set_error_for(callback_id, 1, "Invalid html parameter");
+            return callback_id;
+        }
+    };
+    // Controls whether a service's transform_response re-sorts segments back into request order;
+    // only meaningful once there's more than one segment in flight.
+    let dont_sort = read_utf8(dont_sort_ptr, dont_sort_len).unwrap_or("false") == "true";
+
+    let (template, segments) = html::extract_translatable_segments(html);
+    if segments.is_empty() {
+        // Nothing translatable (e.g. an attribute-only fragment or pure markup): hand it back.
+        return write_out_for(callback_id, html);
+    }
+
+    let chain = resolve_service_chain(service_name);
+    let mut chain_errors = Vec::new();
+    let registry = SERVICE_REGISTRY.lock().unwrap();
+    let Some(chain_index) = next_chain_index(&registry, &chain, 0, &mut chain_errors) else {
+        set_error_for(callback_id, 1, &format!("All services failed: {}", chain_errors.join("; ")));
+        return callback_id;
+    };
+    let current_service = &chain[chain_index];
+    let current_source_lang = effective_source_lang(source_lang, chain_index);
+    let service = registry.get(current_service).unwrap();
+
+    // Only group into chunks when the whole batch wouldn't otherwise fit: single-request
+    // behavior for small inputs (and for services with no query-length limit) is unchanged.
+    let chunks: Option<Vec<Vec<usize>>> = service.max_query_bytes().and_then(|budget| {
+        let groups = google::chunk_segment_indices(&segments, budget);
+        if groups.len() > 1 {
+            Some(groups)
+        } else {
+            None
+        }
+    });
+    let first_request_segments: Vec<String> = match &chunks {
+        Some(groups) => groups[0].iter().map(|&i| segments[i].clone()).collect(),
+        None => segments.clone(),
+    };
+
+    let plan = plan_request(service.as_ref(), current_service, &current_source_lang, target_lang, &first_request_segments);
+    drop(registry);
+
+    PENDING_TRANSLATIONS.lock().unwrap().insert(
+        callback_id,
+        PendingTranslation {
+            service_name: current_service.clone(),
+            source_lang: source_lang.to_string(),
+            target_lang: target_lang.to_string(),
+            cache_key: plan.cache_key.clone(),
+            url: plan.url,
+            method: plan.method,
+            headers_json: plan.headers_json,
+            body: plan.body,
+            placeholder_maps: plan.placeholder_maps,
+            stage: PendingStage::AwaitingCache,
+            revalidating: None,
+            attempt: 0,
+            html_batch: Some(HtmlBatch {
+                template,
+                segments: segments.clone(),
+                dont_sort,
+                fallback_index: None,
+                collected: Vec::new(),
+                chunks,
+                chunk_pos: 0,
+            }),
+            segments,
+            chain,
+            chain_index,
+            chain_errors,
+        },
+    );
+
+    let (key_ptr, key_len) = alloc_copy(plan.cache_key.as_bytes());
+    unsafe {
+        cache_get(key_ptr, key_len, callback_id);
+    }
+    ffi::dealloc(key_ptr, key_len);
+
+    callback_id
 }
 
 #[no_mangle]
@@ -523,5 +1361,5 @@ pub extern "C" fn translate_single_text(
     text_ptr: u32,
     text_len: u32,
 ) -> u32 {
-    translate_text(service_ptr, service_len, source_lang_ptr, source_lang_len, target_lang_ptr, target_lang_len, text_ptr, text_len)
+    translate_begin(service_ptr, service_len, source_lang_ptr, source_lang_len, target_lang_ptr, target_lang_len, text_ptr, text_len)
 }
@@ -0,0 +1,268 @@
+// Native stand-ins for the JS host bridge (`http_request`, `cache_get`, `cache_set`, `now`,
+// `schedule_retry`), so the service registry can be exercised end-to-end under `cargo test`
+// without a browser or a real wasm runtime. Only built with `--features mock-host`; lib.rs
+// swaps these in for the real `extern "C"` imports under that feature.
+//
+// Because the bridge is fully synchronous here, a single `translate_begin` call drives the
+// whole cache-miss/fetch/parse/cache-write chain to completion inline, recursing back into
+// `translate_resume` from within the mocked `cache_get`/`http_request` calls instead of waiting
+// for JS to call back later. Tests read the final result via `last_delivered_text()`.
+
+use lazy_static::lazy_static;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+struct MockHost {
+    /// Queued responses per URL; each `http_request` call pops the front entry so a retry test
+    /// can queue a `429` followed by a `200`.
+    responses: HashMap<String, VecDeque<serde_json::Value>>,
+    cache: HashMap<String, String>,
+    clock_millis: u64,
+    /// `(write_out_for pointer, handle it was written under)` for the most recently completed
+    /// `translate_resume` call - the handle is `callback_id` itself now that results are stored
+    /// per-call rather than in the single default slot, so reading it back needs both.
+    delivered_result: Option<(u32, u32)>,
+}
+
+impl MockHost {
+    fn new() -> Self {
+        Self {
+            responses: HashMap::new(),
+            cache: HashMap::new(),
+            clock_millis: 0,
+            delivered_result: None,
+        }
+    }
+}
+
+lazy_static! {
+    static ref MOCK_HOST: Mutex<MockHost> = Mutex::new(MockHost::new());
+}
+
+/// Clears all expectations, the cache, and the clock; call at the start of each test.
+pub fn reset() {
+    *MOCK_HOST.lock().unwrap() = MockHost::new();
+}
+
+/// Sets the value the mocked `now()` bridge returns, so TTL/backoff logic is deterministic.
+pub fn set_clock(millis: u64) {
+    MOCK_HOST.lock().unwrap().clock_millis = millis;
+}
+
+/// Seeds the mocked cache with a pre-serialized `CacheEntry` for `key`.
+pub fn set_cached(key: &str, entry: &crate::cache::CacheEntry) {
+    let json = serde_json::to_string(entry).expect("CacheEntry always serializes");
+    MOCK_HOST.lock().unwrap().cache.insert(key.to_string(), json);
+}
+
+/// Starts building a canned response for requests to `url`.
+pub fn expect_request(url: &str) -> RequestExpectationBuilder {
+    RequestExpectationBuilder {
+        url: url.to_string(),
+        headers: Vec::new(),
+    }
+}
+
+pub struct RequestExpectationBuilder {
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl RequestExpectationBuilder {
+    /// Adds a response header (e.g. `ETag`, `Cache-Control`, `Retry-After`).
+    pub fn with_response_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Queues a canned `(status, body)` response for the next matching `http_request` call.
+    pub fn returns(self, status: u64, body: &str) {
+        let ok = (200..300).contains(&status);
+        let mut headers_map = serde_json::Map::new();
+        for (name, value) in self.headers {
+            headers_map.insert(name, serde_json::Value::String(value));
+        }
+        let response = serde_json::json!({
+            "ok": ok,
+            "status": status,
+            "statusText": if ok { "OK" } else { "Mock Error" },
+            "text": body,
+            "headers": serde_json::Value::Object(headers_map),
+        });
+        MOCK_HOST
+            .lock()
+            .unwrap()
+            .responses
+            .entry(self.url)
+            .or_insert_with(VecDeque::new)
+            .push_back(response);
+    }
+}
+
+/// The `write_out_for` pointer from the most recent completed `translate_resume` call, if any.
+pub fn last_delivered_result() -> Option<u32> {
+    MOCK_HOST.lock().unwrap().delivered_result.map(|(ptr, _)| ptr)
+}
+
+/// Reads the UTF-8 text behind `last_delivered_result()`, using `ffi::last_len_for` (keyed by the
+/// same handle the result was delivered under) for the length, the way JS would after receiving a
+/// non-zero pointer back from an exported call.
+pub fn last_delivered_text() -> Option<String> {
+    let (ptr, handle) = MOCK_HOST.lock().unwrap().delivered_result?;
+    if ptr == 0 {
+        return None;
+    }
+    let len = crate::ffi::last_len_for(handle);
+    let bytes = unsafe { std::slice::from_raw_parts(ptr as *const u8, len as usize) };
+    std::str::from_utf8(bytes).ok().map(|s| s.to_string())
+}
+
+fn record_delivery(callback_id: u32, result: u32) {
+    if result != 0 {
+        MOCK_HOST.lock().unwrap().delivered_result = Some((result, callback_id));
+    }
+}
+
+pub fn http_request(
+    url_ptr: u32,
+    url_len: u32,
+    _method_ptr: u32,
+    _method_len: u32,
+    _headers_ptr: u32,
+    _headers_len: u32,
+    _body_ptr: u32,
+    _body_len: u32,
+    callback_id: u32,
+) {
+    let url = crate::ffi::read_utf8(url_ptr, url_len).unwrap_or("").to_string();
+    let response = {
+        let mut host = MOCK_HOST.lock().unwrap();
+        host.responses
+            .get_mut(&url)
+            .and_then(|queue| queue.pop_front())
+    };
+    let response = response.unwrap_or_else(|| {
+        serde_json::json!({
+            "ok": false,
+            "status": 404,
+            "statusText": "No mock expectation for this URL",
+            "text": "",
+            "headers": {},
+        })
+    });
+    let body = serde_json::to_string(&response).expect("mock response always serializes");
+    let (ptr, len) = crate::alloc_copy(body.as_bytes());
+    let result = crate::translate_resume(callback_id, ptr, len);
+    record_delivery(callback_id, result);
+    crate::ffi::dealloc(ptr, len);
+}
+
+pub fn cache_get(key_ptr: u32, key_len: u32, callback_id: u32) {
+    let key = crate::ffi::read_utf8(key_ptr, key_len).unwrap_or("").to_string();
+    let cached = MOCK_HOST.lock().unwrap().cache.get(&key).cloned();
+    let result = match cached {
+        Some(json) => {
+            let (ptr, len) = crate::alloc_copy(json.as_bytes());
+            let result = crate::translate_resume(callback_id, ptr, len);
+            crate::ffi::dealloc(ptr, len);
+            result
+        }
+        None => crate::translate_resume(callback_id, 0, 0),
+    };
+    record_delivery(callback_id, result);
+}
+
+pub fn cache_set(key_ptr: u32, key_len: u32, value_ptr: u32, value_len: u32) -> u32 {
+    let key = crate::ffi::read_utf8(key_ptr, key_len).unwrap_or("").to_string();
+    let value = crate::ffi::read_utf8(value_ptr, value_len).unwrap_or("").to_string();
+    MOCK_HOST.lock().unwrap().cache.insert(key, value);
+    1
+}
+
+pub fn now() -> u64 {
+    MOCK_HOST.lock().unwrap().clock_millis
+}
+
+pub fn schedule_retry(callback_id: u32, _delay_ms: u32) {
+    // Real JS would `setTimeout`; the mock fires immediately so tests stay synchronous.
+    crate::translate_retry_fire(callback_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn google_round_trip_on_cache_miss() {
+        reset();
+        crate::init_services();
+        expect_request("https://translate-pa.googleapis.com/v1/translateHtml")
+            .returns(200, "<a i=0>Hola</a>");
+
+        crate::translate_begin(
+            b"google".as_ptr() as u32,
+            6,
+            b"en".as_ptr() as u32,
+            2,
+            b"es".as_ptr() as u32,
+            2,
+            b"Hello".as_ptr() as u32,
+            5,
+        );
+
+        assert_eq!(last_delivered_text().as_deref(), Some("Hola"));
+    }
+
+    #[test]
+    fn fresh_cache_entry_short_circuits_the_http_call() {
+        reset();
+        crate::init_services();
+        set_clock(1_000_000);
+        let cache_key = crate::cache::get_cache_key("google", "en", "es", "<pre>Hello</pre>");
+        set_cached(
+            &cache_key,
+            &crate::cache::CacheEntry {
+                translated_text: "Hola (cached)".to_string(),
+                detected_language: "en".to_string(),
+                stored_at: 999_000,
+                max_age_secs: Some(3600),
+                etag: None,
+            },
+        );
+
+        crate::translate_begin(
+            b"google".as_ptr() as u32,
+            6,
+            b"en".as_ptr() as u32,
+            2,
+            b"es".as_ptr() as u32,
+            2,
+            b"Hello".as_ptr() as u32,
+            5,
+        );
+
+        assert_eq!(last_delivered_text().as_deref(), Some("Hola (cached)"));
+    }
+
+    #[test]
+    fn retryable_status_is_retried_until_success() {
+        reset();
+        crate::init_services();
+        let url = "https://translate-pa.googleapis.com/v1/translateHtml";
+        expect_request(url).returns(429, "");
+        expect_request(url).returns(200, "<a i=0>Hola</a>");
+
+        crate::translate_begin(
+            b"google".as_ptr() as u32,
+            6,
+            b"en".as_ptr() as u32,
+            2,
+            b"es".as_ptr() as u32,
+            2,
+            b"Hello".as_ptr() as u32,
+            5,
+        );
+
+        assert_eq!(last_delivered_text().as_deref(), Some("Hola"));
+    }
+}
@@ -0,0 +1,151 @@
+// Local, offline NLLB (No Language Left Behind) translation backend. Unlike every other service
+// in this crate, `LocalNllbService` never touches the network: it runs a bundled sequence-to-
+// sequence model in-process through `run_local`, the escape hatch `service::TranslationService`
+// grows for exactly this case.
+//
+// Memory/download tradeoff: even the smallest usable NLLB checkpoint (the distilled 600M-
+// parameter one) is several hundred MB of weights plus a ~250k-token SentencePiece vocabulary,
+// and keeping it resident for inference takes roughly as much RAM again. None of that is vendored
+// into this crate - a real deployment ships the model+vocab as separate downloadable assets and
+// wires an actual tensor-inference backend into `NllbModel::generate`. Until that backend exists,
+// `run_local` reports "no model loaded" (`None`) so the dispatcher falls through to the next
+// service in the chain instead of silently producing empty translations.
+
+use crate::service::{TranslationInfo, TranslationService};
+
+/// Maps this crate's translation language codes (`en`, `fr`, `zh-CN`, ...) to the FLORES-200
+/// `{lang}_{Script}` tags NLLB's tokenizer prepends to steer generation (`eng_Latn`, `fra_Latn`,
+/// `zho_Hans`, ...). Only the subset of tags this crate's existing UI language codes cover is
+/// listed; extend as more are added.
+fn nllb_tag(lang_code: &str) -> Option<&'static str> {
+    const TABLE: &[(&str, &str)] = &[
+        ("en", "eng_Latn"),
+        ("fr", "fra_Latn"),
+        ("de", "deu_Latn"),
+        ("es", "spa_Latn"),
+        ("it", "ita_Latn"),
+        ("pt", "por_Latn"),
+        ("nl", "nld_Latn"),
+        ("ru", "rus_Cyrl"),
+        ("ja", "jpn_Jpan"),
+        ("ko", "kor_Hang"),
+        ("zh-CN", "zho_Hans"),
+        ("zh-TW", "zho_Hant"),
+        ("ar", "arb_Arab"),
+        ("hi", "hin_Deva"),
+        ("tr", "tur_Latn"),
+        ("pl", "pol_Latn"),
+        ("vi", "vie_Latn"),
+    ];
+    TABLE.iter().find(|(code, _)| *code == lang_code).map(|(_, tag)| *tag)
+}
+
+/// A loaded NLLB model: the SentencePiece-style vocabulary plus whatever tensor backend performs
+/// the forward passes. Not constructible yet - see the module doc for why - so `LocalNllbService`
+/// starts (and, in this build, stays) without one.
+pub struct NllbModel {
+    _private: (),
+}
+
+impl NllbModel {
+    /// Tokenizes `text` into vocabulary ids.
+    fn tokenize(&self, _text: &str) -> Vec<u32> {
+        Vec::new()
+    }
+
+    /// Runs beam-search generation conditioned on `target_tag`'s token, then detokenizes the
+    /// result back into text.
+    fn generate(&self, _target_tag: &str, _input_ids: &[u32]) -> String {
+        String::new()
+    }
+}
+
+pub struct LocalNllbService {
+    model: Option<NllbModel>,
+}
+
+impl LocalNllbService {
+    /// Starts with no model loaded, so `run_local` returns `None` and every translation falls
+    /// through to the next service in the chain until a model-loading API supplies one.
+    pub fn new() -> Self {
+        Self { model: None }
+    }
+}
+
+impl Default for LocalNllbService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranslationService for LocalNllbService {
+    fn service_name(&self) -> &str {
+        "nllb"
+    }
+
+    fn transform_request(&self, source_array: &[String]) -> String {
+        source_array.join("\n")
+    }
+
+    fn parse_response(&self, _response: &str) -> Result<Vec<(String, Option<String>)>, String> {
+        // `run_local` produces every translation this service returns; it never goes through the
+        // HTTP response path `parse_response` serves.
+        Err("LocalNllbService has no HTTP response to parse".to_string())
+    }
+
+    fn transform_response(&self, result: &str, _dont_sort: bool) -> Vec<String> {
+        vec![result.to_string()]
+    }
+
+    fn get_extra_parameters(
+        &self,
+        _source_lang: &str,
+        _target_lang: &str,
+        _requests: &[TranslationInfo],
+    ) -> String {
+        String::new()
+    }
+
+    fn get_request_body(
+        &self,
+        _source_lang: &str,
+        _target_lang: &str,
+        _requests: &[TranslationInfo],
+    ) -> Option<String> {
+        None
+    }
+
+    fn get_extra_headers(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    fn get_base_url(&self) -> String {
+        String::new()
+    }
+
+    fn get_method(&self) -> &str {
+        "LOCAL"
+    }
+
+    fn run_local(
+        &self,
+        source_lang: &str,
+        target_lang: &str,
+        requests: &[TranslationInfo],
+    ) -> Option<Vec<String>> {
+        let model = self.model.as_ref()?;
+        let target_tag = nllb_tag(target_lang)?;
+        // NLLB conditions generation on the target tag only; a real forward pass would also want
+        // the source tag when it's known (`nllb_tag(source_lang)`) to disambiguate the input,
+        // but that's moot until `NllbModel::generate` does real inference.
+        Some(
+            requests
+                .iter()
+                .map(|r| {
+                    let input_ids = model.tokenize(&r.original_text);
+                    model.generate(target_tag, &input_ids)
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,181 @@
+// Placeholder-aware preprocessing: protects ICU/Fluent/printf interpolation tokens in source
+// text from being mangled or reordered by a translation engine, by swapping each one for an
+// opaque sentinel the engine passes through untouched (the same trick Bing's `<b{id}>` wrappers
+// use for segment boundaries - see `bing::transform_request`), then restoring the original spans
+// once the translated text comes back. Sits in `lib.rs`'s `plan_request`/`AwaitingHttp` handling,
+// just outside `TranslationService::transform_request`/`transform_response` - the service itself
+// never sees a raw placeholder.
+
+/// Maps each sentinel `protect` emitted back to the original placeholder span it replaced.
+/// Scoped to one source string (and so, one wire segment) - never shared or merged across
+/// segments, so a batch of several strings can't have one segment's sentinel resolved against
+/// another's placeholder.
+#[derive(Debug, Clone, Default)]
+pub struct PlaceholderMap {
+    entries: Vec<(String, String)>,
+}
+
+const SENTINEL_PREFIX: &str = "QQZPH";
+
+/// Replaces every recognized placeholder span in `text` with a unique sentinel token
+/// (`QQZPH0QQZPH`, `QQZPH1QQZPH`, ...) built only from ASCII letters/digits so a translation
+/// engine has no delimiter to split it on, and returns the map needed to restore them afterward.
+/// Recognizes:
+/// - Fluent: `{ $var }`, `{ -term }` and its `{ $var -> ... }` select blocks
+/// - ICU MessageFormat: `{0}`, `{count, plural, one {...} other {...}}` (braces balanced so a
+///   whole plural/select block becomes one sentinel, not just its opening brace)
+/// - printf: `%s`, `%d`, `%1$s`, `%2$d`, `%%`
+/// Unbalanced `{`/`}` are left as literal text rather than guessed at.
+pub fn protect(text: &str) -> (String, PlaceholderMap) {
+    let mut out = String::with_capacity(text.len());
+    let mut map = PlaceholderMap::default();
+    let bytes = text.as_bytes();
+    let mut i = 0usize;
+
+    while i < bytes.len() {
+        if bytes[i] == b'{' {
+            if let Some(end) = matching_brace_end(text, i) {
+                let span = &text[i..=end];
+                let sentinel = format!("{SENTINEL_PREFIX}{}{SENTINEL_PREFIX}", map.entries.len());
+                map.entries.push((sentinel.clone(), span.to_string()));
+                out.push_str(&sentinel);
+                i = end + 1;
+                continue;
+            }
+        }
+        if bytes[i] == b'%' {
+            if let Some(end) = printf_spec_end(text, i) {
+                let span = &text[i..end];
+                let sentinel = format!("{SENTINEL_PREFIX}{}{SENTINEL_PREFIX}", map.entries.len());
+                map.entries.push((sentinel.clone(), span.to_string()));
+                out.push_str(&sentinel);
+                i = end;
+                continue;
+            }
+        }
+        let ch_len = text[i..].chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+        out.push_str(&text[i..i + ch_len]);
+        i += ch_len;
+    }
+
+    (out, map)
+}
+
+/// Undoes `protect`: every sentinel `map` knows about is swapped back for its original span,
+/// wherever the engine moved it to - this is exactly why a sentinel, not the original text,
+/// crosses the translation boundary, so placeholders survive reordering.
+pub fn restore(text: &str, map: &PlaceholderMap) -> String {
+    let mut out = text.to_string();
+    for (sentinel, original) in &map.entries {
+        out = out.replace(sentinel.as_str(), original);
+    }
+    out
+}
+
+/// Finds the index of the `}` matching the `{` at `start`, scanning for balanced nesting so an
+/// ICU plural/select block's inner `{...}` arms are swallowed into the same span. `None` if the
+/// braces never balance before the string ends, so `protect` leaves that `{` as literal text.
+fn matching_brace_end(text: &str, start: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0i32;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Finds the end (exclusive) of a printf conversion spec starting at `start` (the `%`): an
+/// optional `N$` positional argument, then a single conversion character. `%%` is a two-byte
+/// literal-percent spec. `None` if `start` isn't followed by a recognizable spec, so a bare `%`
+/// in ordinary prose is left untouched.
+fn printf_spec_end(text: &str, start: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut i = start + 1;
+    if bytes.get(i) == Some(&b'%') {
+        return Some(i + 1);
+    }
+    let positional_start = i;
+    while bytes.get(i).is_some_and(|b| b.is_ascii_digit()) {
+        i += 1;
+    }
+    if i > positional_start && bytes.get(i) == Some(&b'$') {
+        i += 1;
+    } else {
+        i = positional_start;
+    }
+    while bytes.get(i).is_some_and(|b| b.is_ascii_digit()) {
+        i += 1;
+    }
+    match bytes.get(i) {
+        Some(b's') | Some(b'd') | Some(b'i') | Some(b'f') | Some(b'u') | Some(b'x') | Some(b'X')
+        | Some(b'@') => Some(i + 1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protects_and_restores_fluent_variable() {
+        let (protected, map) = protect("Hello, { $userName }!");
+        assert!(!protected.contains('$'));
+        assert_eq!(restore(&protected, &map), "Hello, { $userName }!");
+    }
+
+    #[test]
+    fn protects_and_restores_icu_plural_with_nested_braces() {
+        let text = "{count, plural, one {# item} other {# items}}";
+        let (protected, map) = protect(text);
+        assert_eq!(protected, "QQZPH0QQZPH");
+        assert_eq!(restore(&protected, &map), text);
+    }
+
+    #[test]
+    fn protects_and_restores_printf_positional() {
+        let (protected, map) = protect("%1$s scored %2$d points");
+        assert_eq!(restore(&protected, &map), "%1$s scored %2$d points");
+        assert!(!protected.contains('%'));
+    }
+
+    #[test]
+    fn leaves_unbalanced_braces_literal() {
+        let (protected, map) = protect("this { is not closed");
+        assert_eq!(protected, "this { is not closed");
+        assert!(map.entries.is_empty());
+    }
+
+    #[test]
+    fn gives_adjacent_placeholders_distinct_sentinels() {
+        let (protected, map) = protect("{a}{b}");
+        assert_eq!(map.entries.len(), 2);
+        assert_ne!(map.entries[0].0, map.entries[1].0);
+        assert_eq!(restore(&protected, &map), "{a}{b}");
+    }
+
+    #[test]
+    fn survives_sentinel_reordering_by_the_engine() {
+        let (_, map) = protect("{a} then {b}");
+        // Simulate an engine that reorders the two sentinels in its translated output.
+        let reordered = format!("{} before {}", map.entries[1].0, map.entries[0].0);
+        assert_eq!(restore(&reordered, &map), "{b} before {a}");
+    }
+
+    #[test]
+    fn literal_percent_escape_round_trips() {
+        let (protected, map) = protect("100%% done");
+        assert_eq!(restore(&protected, &map), "100%% done");
+    }
+}
@@ -0,0 +1,140 @@
+// Retry policy for transient HTTP failures (429/5xx/408 and network errors). WASM can't sleep,
+// so a retry is expressed as a `schedule_retry(callback_id, delay_ms)` bridge call that asks JS
+// to `setTimeout` before re-driving the request.
+
+/// Default retry ceiling before a retryable failure is surfaced as a hard error.
+pub const MAX_ATTEMPTS: u32 = 3;
+
+const BASE_DELAY_MS: u64 = 250;
+const CAP_DELAY_MS: u64 = 4000;
+
+/// The crate-wide default base delay, exposed for services that want to report their own retry
+/// policy (see `TranslationService::retry_policy`) starting from the same baseline.
+pub const DEFAULT_BASE_DELAY_MS: u64 = BASE_DELAY_MS;
+
+/// Statuses worth retrying: request timeout, rate limiting, and upstream/gateway failures.
+pub fn is_retryable_status(status: u64) -> bool {
+    matches!(status, 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+/// Exponential backoff with full jitter: `delay = rand(0, min(cap, base * 2^attempt))`.
+/// WASM has no RNG import, so jitter is derived from the clock bridge instead of pulling in a
+/// dependency just for this.
+pub fn backoff_delay_ms(attempt: u32, now_millis: u64) -> u64 {
+    backoff_delay_ms_with_base(attempt, now_millis, BASE_DELAY_MS)
+}
+
+/// Same exponential-backoff-with-full-jitter formula as `backoff_delay_ms`, but with a caller
+/// supplied base delay instead of the crate-wide default; used by services that expose their own
+/// `retry_policy` (see `auth::GoogleHelper`).
+pub fn backoff_delay_ms_with_base(attempt: u32, now_millis: u64, base_delay_ms: u64) -> u64 {
+    let upper = base_delay_ms.saturating_mul(1u64 << attempt.min(16)).min(CAP_DELAY_MS);
+    if upper == 0 {
+        return 0;
+    }
+    // xorshift64, seeded from the clock and attempt so back-to-back retries still vary.
+    let mut x = now_millis ^ ((attempt as u64) << 32).wrapping_add(0x9E3779B97F4A7C15);
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    x % upper
+}
+
+/// Parses a `Retry-After` header: either delta-seconds or an HTTP-date (RFC 7231 IMF-fixdate).
+pub fn parse_retry_after(header: &str, now_millis: u64) -> Option<u64> {
+    let header = header.trim();
+    if let Ok(secs) = header.parse::<u64>() {
+        return Some(secs * 1000);
+    }
+    let target_millis = parse_imf_fixdate(header)?;
+    Some(target_millis.saturating_sub(now_millis))
+}
+
+/// Parses `"Sun, 06 Nov 1994 08:49:37 GMT"` into epoch milliseconds. Only the IMF-fixdate
+/// format is handled; the obsolete RFC 850/asctime forms aren't used by the services we talk to.
+fn parse_imf_fixdate(s: &str) -> Option<u64> {
+    let parts: Vec<&str> = s.split_whitespace().collect();
+    if parts.len() != 6 || parts[5] != "GMT" {
+        return None;
+    }
+    let day: i64 = parts[1].parse().ok()?;
+    let month = match parts[2] {
+        "Jan" => 1,
+        "Feb" => 2,
+        "Mar" => 3,
+        "Apr" => 4,
+        "May" => 5,
+        "Jun" => 6,
+        "Jul" => 7,
+        "Aug" => 8,
+        "Sep" => 9,
+        "Oct" => 10,
+        "Nov" => 11,
+        "Dec" => 12,
+        _ => return None,
+    };
+    let year: i64 = parts[3].parse().ok()?;
+    let mut time_parts = parts[4].split(':');
+    let hour: i64 = time_parts.next()?.parse().ok()?;
+    let min: i64 = time_parts.next()?.parse().ok()?;
+    let sec: i64 = time_parts.next()?.parse().ok()?;
+
+    let days = days_from_civil(year, month, day);
+    let epoch_secs = days * 86400 + hour * 3600 + min * 60 + sec;
+    if epoch_secs < 0 {
+        return None;
+    }
+    Some(epoch_secs as u64 * 1000)
+}
+
+/// Days since the Unix epoch for a given civil (proleptic Gregorian) date.
+/// Howard Hinnant's `days_from_civil` algorithm.
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retryable_statuses() {
+        assert!(is_retryable_status(429));
+        assert!(is_retryable_status(503));
+        assert!(!is_retryable_status(404));
+        assert!(!is_retryable_status(200));
+    }
+
+    #[test]
+    fn retry_after_seconds() {
+        assert_eq!(parse_retry_after("120", 0), Some(120_000));
+    }
+
+    #[test]
+    fn retry_after_http_date() {
+        let millis = parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", 0).unwrap();
+        assert_eq!(millis, 784_111_777_000);
+    }
+
+    #[test]
+    fn backoff_is_bounded_by_cap() {
+        for attempt in 0..10 {
+            let delay = backoff_delay_ms(attempt, 123_456);
+            assert!(delay <= CAP_DELAY_MS);
+        }
+    }
+
+    #[test]
+    fn backoff_with_base_still_bounded_by_cap() {
+        for attempt in 0..10 {
+            let delay = backoff_delay_ms_with_base(attempt, 123_456, 50);
+            assert!(delay <= CAP_DELAY_MS);
+        }
+    }
+}
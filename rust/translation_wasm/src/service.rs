@@ -2,7 +2,24 @@
 // Ports TWP's Service class architecture
 
 use crate::cache::{get_cache_key, CacheEntry};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Default number of entries `ServiceManager`'s translation-memory cache holds before it starts
+/// evicting the least-recently-used one, and how long (in seconds) an entry stays valid before
+/// `get_requests` treats it as a miss. Both are overridable via `ServiceManager::with_cache_limits`.
+const DEFAULT_CACHE_CAPACITY: usize = 500;
+const DEFAULT_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// One sentence's character-offset span in the source text paired with its corresponding span in
+/// the translated text, as reported by a service's sentence-segmentation metadata (e.g. Bing's
+/// `includeSentenceLength`). Offsets are exclusive-end, counted the way the upstream API counts
+/// them (UTF-16 code units for Bing).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SentenceBoundary {
+    pub source: (usize, usize),
+    pub translated: (usize, usize),
+}
 
 #[derive(Clone, Debug)]
 pub enum TranslationStatus {
@@ -17,6 +34,9 @@ pub struct TranslationInfo {
     pub translated_text: Option<String>,
     pub detected_language: Option<String>,
     pub status: TranslationStatus,
+    /// Sentence-level alignment for this translation, when `TranslationService::parse_sentence_boundaries`
+    /// found any in the response that produced `translated_text`.
+    pub sentence_boundaries: Option<Vec<SentenceBoundary>>,
 }
 
 pub trait TranslationService {
@@ -43,31 +63,161 @@ pub trait TranslationService {
     ) -> Option<String>;
     
     fn get_extra_headers(&self) -> Vec<(String, String)>;
-    
+
     fn get_base_url(&self) -> String;
-    
+
     fn get_method(&self) -> &str;
+
+    /// Detects the language(s) `text` is written in, as a ranked list of
+    /// `(language_code, confidence)` pairs, without translating it. Every network call in this
+    /// crate goes through JS's async `http_request` bridge (see `lib.rs`'s `translate_begin`/
+    /// `translate_resume`), so a service can't actually perform the round trip from inside this
+    /// synchronous method; implementers return the pieces needed for a caller to drive that
+    /// bridge themselves. Unsupported by default.
+    fn detect_language(&self, _text: &str) -> Result<Vec<(String, f32)>, String> {
+        Err("unsupported".to_string())
+    }
+
+    /// Max attempts and base backoff delay (ms) this service wants for retrying a transient HTTP
+    /// failure, overriding the crate-wide default. Services with no special retry needs (the
+    /// common case) just use that default.
+    fn retry_policy(&self) -> (u32, u64) {
+        (crate::retry::MAX_ATTEMPTS, crate::retry::DEFAULT_BASE_DELAY_MS)
+    }
+
+    /// Called when a response status indicates the credentials this service cached are no longer
+    /// valid (e.g. a `401`), so a service that caches a refreshable token (see `GoogleService`)
+    /// can discard it before the request is retried. Default no-op for services with no such
+    /// state.
+    fn invalidate_auth(&mut self) {}
+
+    /// Whether this service has no usable cached auth token as of `now_millis` and a host should
+    /// fetch one (however it does that for this service - e.g. scraping it from a page, the way
+    /// the JS side of TWP does for Google) and hand it back via `set_fetched_auth`. Default
+    /// `false` for services with no such token to manage.
+    fn needs_auth_refresh(&self, _now_millis: u64) -> bool {
+        false
+    }
+
+    /// Delivers the result of a host-driven auth fetch: `Some(token)` on success, `None` if the
+    /// host couldn't find one. Default no-op for services with no such state.
+    fn set_fetched_auth(&mut self, _auth: Option<String>, _now_millis: u64, _ttl_ms: u64) {}
+
+    /// Maximum url-encoded query-string bytes this service's GET requests can carry, used to
+    /// decide whether a large multi-segment batch needs to be split into several smaller requests
+    /// (see `google::chunk_segment_indices`). `None` means no such limit applies (a POST body, or
+    /// an endpoint without a tight URL length budget).
+    fn max_query_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// Decodes a raw HTTP response body into a `String`, given its declared `Content-Type` (if
+    /// any). Most endpoints reply in UTF-8 and never exercise this, but the free Google endpoint
+    /// (and mirrors of it) can reply in whatever codepage the request implied; see
+    /// `charset::decode_body`. Only reached when the host bridge hands back raw bytes rather than
+    /// an already-decoded string (see `lib.rs`'s `rawText` handling).
+    fn decode_body(&self, bytes: &[u8], content_type: Option<&str>) -> String {
+        crate::charset::decode_body(bytes, content_type)
+    }
+
+    /// Whether this service replies with an incremental `text/event-stream` body (so a caller
+    /// should feed arriving bytes to `parse_stream_chunk` as they land) rather than one
+    /// fully-buffered JSON body for `parse_response`. `false` by default.
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    /// Lets a service bypass the network layer entirely and produce a translation synchronously
+    /// in-process (e.g. `nllb::LocalNllbService` running a bundled model), instead of the
+    /// `get_base_url`/`get_request_body`/`parse_response` HTTP path every other service uses. The
+    /// dispatcher in `lib.rs`'s `translate_begin` tries this first; `None` (the default, and what
+    /// a service with nothing loaded yet should keep returning) falls through to HTTP as before.
+    fn run_local(
+        &self,
+        _source_lang: &str,
+        _target_lang: &str,
+        _requests: &[TranslationInfo],
+    ) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Extracts whatever complete SSE events are present in `buf`, appending incremental text
+    /// fragments to the returned `Vec` and removing the consumed events from `buf` so a partial
+    /// trailing event is carried over to the next call. Only meaningful when
+    /// `supports_streaming` is `true`; the default does nothing and leaves `buf` untouched.
+    fn parse_stream_chunk(&self, _buf: &mut String) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Content codings this service is willing to accept in a compressed response, as an
+    /// `Accept-Encoding` header value. `get_extra_headers` implementations fold this in; the
+    /// actual decompression happens in `lib.rs`'s `AwaitingHttp` handling via
+    /// `compression::decode_body`, keyed off the response's own `Content-Encoding` header, not
+    /// this one - a server is always free to ignore the request and reply uncompressed.
+    fn accept_encoding(&self) -> &str {
+        "gzip, deflate, br, zstd"
+    }
+
+    /// Per-sentence alignment between source and translated text, when the endpoint's response
+    /// carries this information (Bing's `includeSentenceLength` is the only one that currently
+    /// does). Lets a caller display bilingual text or cache at sentence granularity instead of
+    /// only at the whole-segment level. `None` by default.
+    fn parse_sentence_boundaries(&self, _response: &str) -> Option<Vec<SentenceBoundary>> {
+        None
+    }
 }
 
 pub struct ServiceManager {
     translations_in_progress: HashMap<String, TranslationInfo>,
     cache: HashMap<String, CacheEntry>,
+    /// Cache keys in least-to-most-recently-used order, so the front is always the next eviction
+    /// candidate once `cache_capacity` is exceeded.
+    cache_order: VecDeque<String>,
+    cache_capacity: usize,
+    cache_ttl_secs: u64,
 }
 
 impl ServiceManager {
     pub fn new() -> Self {
+        Self::with_cache_limits(DEFAULT_CACHE_CAPACITY, DEFAULT_CACHE_TTL_SECS)
+    }
+
+    /// Like `new`, but with an explicit bound on how many translation-memory entries are kept and
+    /// how long (in seconds) one stays valid, instead of the crate's defaults - lets a host with
+    /// tighter memory limits or fresher-data requirements tune both.
+    pub fn with_cache_limits(cache_capacity: usize, cache_ttl_secs: u64) -> Self {
         Self {
             translations_in_progress: HashMap::new(),
             cache: HashMap::new(),
+            cache_order: VecDeque::new(),
+            cache_capacity,
+            cache_ttl_secs,
         }
     }
-    
+
+    fn is_expired(&self, entry: &CacheEntry, now_millis: u64) -> bool {
+        let age_secs = now_millis.saturating_sub(entry.stored_at) / 1000;
+        age_secs > self.cache_ttl_secs
+    }
+
+    fn evict_over_capacity(&mut self) {
+        while self.cache.len() > self.cache_capacity {
+            match self.cache_order.pop_front() {
+                Some(oldest) => {
+                    self.cache.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
     pub fn get_requests(
         &mut self,
         service: &dyn TranslationService,
         source_lang: &str,
         target_lang: &str,
         source_array_2d: &[Vec<String>],
+        now_millis: u64,
     ) -> (Vec<Vec<TranslationInfo>>, Vec<TranslationInfo>) {
         let mut requests: Vec<Vec<TranslationInfo>> = Vec::new();
         let mut current_translations: Vec<TranslationInfo> = Vec::new();
@@ -78,7 +228,7 @@ impl ServiceManager {
         
         for source_array in source_array_2d {
             let request_string = service.transform_request(source_array);
-            let request_hash = format!("{}:{}:{}", source_lang, target_lang, request_string);
+            let request_hash = request_key(source_lang, target_lang, &request_string);
             
             // Check in-memory cache
             if let Some(cached) = self.translations_in_progress.get(&request_hash) {
@@ -100,14 +250,28 @@ impl ServiceManager {
                 translated_text: None,
                 detected_language: None,
                 status: TranslationStatus::Translating,
+                sentence_boundaries: None,
             };
             
-            // Check cache
-            if let Some(cached_entry) = self.cache.get(&cache_key) {
+            // Check cache, ignoring (and evicting) an entry that's aged past cache_ttl_secs
+            let live_cached = self
+                .cache
+                .get(&cache_key)
+                .cloned()
+                .filter(|entry| !self.is_expired(entry, now_millis));
+
+            if let Some(cached_entry) = live_cached {
                 trans_info.translated_text = Some(cached_entry.translated_text.clone());
                 trans_info.detected_language = Some(cached_entry.detected_language.clone());
                 trans_info.status = TranslationStatus::Complete;
+                // A hit is itself a use - move the key to the back of cache_order so eviction
+                // stays LRU-by-access instead of LRU-by-insertion (same pattern as set_cache_entry).
+                self.cache_order.retain(|k| k != &cache_key);
+                self.cache_order.push_back(cache_key.clone());
             } else {
+                if self.cache.remove(&cache_key).is_some() {
+                    self.cache_order.retain(|k| k != &cache_key);
+                }
                 current_request.push(trans_info.clone());
                 current_size += trans_info.original_text.len();
                 
@@ -148,8 +312,81 @@ impl ServiceManager {
         }
     }
     
-    pub fn set_cache_entry(&mut self, key: String, entry: CacheEntry) {
+    /// Inserts or refreshes a cache entry, stamping `now_millis` as its insertion time (ignoring
+    /// whatever `entry.stored_at` the caller set, so expiry is always measured from when this
+    /// cache actually learned about it) and evicting the least-recently-used entry if `capacity`
+    /// is now exceeded.
+    pub fn set_cache_entry(&mut self, key: String, mut entry: CacheEntry, now_millis: u64) {
+        entry.stored_at = now_millis;
+        if self.cache.contains_key(&key) {
+            self.cache_order.retain(|k| k != &key);
+        }
+        self.cache_order.push_back(key.clone());
         self.cache.insert(key, entry);
+        self.evict_over_capacity();
+    }
+
+    /// Serializes every entry still fresh as of `now_millis` to a JSON object a host can persist
+    /// (IndexedDB, localStorage, a file) and hand back to `import_cache` on the next session, so
+    /// translation memory survives a WASM instance restart instead of starting empty every time.
+    pub fn export_cache(&self, now_millis: u64) -> String {
+        let live: HashMap<&String, &CacheEntry> = self
+            .cache
+            .iter()
+            .filter(|(_, entry)| !self.is_expired(entry, now_millis))
+            .collect();
+        serde_json::to_string(&live).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Loads entries previously produced by `export_cache`, merging them into the live cache
+    /// (an imported entry overwrites one already cached under the same key) and trimming back
+    /// down to `cache_capacity` if the merge pushed it over. Malformed JSON is ignored rather than
+    /// treated as an error, since a corrupt persisted blob shouldn't prevent startup.
+    pub fn import_cache(&mut self, json: &str) {
+        let Ok(entries) = serde_json::from_str::<HashMap<String, CacheEntry>>(json) else {
+            return;
+        };
+        for (key, entry) in entries {
+            if self.cache.contains_key(&key) {
+                self.cache_order.retain(|k| k != &key);
+            }
+            self.cache_order.push_back(key.clone());
+            self.cache.insert(key, entry);
+        }
+        self.evict_over_capacity();
+    }
+
+    /// Feeds a batch of `(translated_text, detected_language)` outcomes - one per entry in
+    /// `requests`, in order - back through `update_translation` and `set_cache_entry`, exactly as
+    /// the real async HTTP response handler in `lib.rs` does once a request lands. Lets a caller
+    /// that already built `requests` via `get_requests` complete the round trip without a network
+    /// call, most usefully in tests driving a `FakeTranslationService`.
+    pub fn apply_responses(
+        &mut self,
+        service: &dyn TranslationService,
+        source_lang: &str,
+        target_lang: &str,
+        requests: &[TranslationInfo],
+        outcomes: Vec<(String, Option<String>)>,
+        now_millis: u64,
+    ) {
+        for (info, (translated_text, detected_language)) in requests.iter().zip(outcomes) {
+            let key = request_key(source_lang, target_lang, &info.original_text);
+            self.update_translation(&key, translated_text.clone(), detected_language.clone());
+
+            let cache_key = get_cache_key(service.service_name(), source_lang, target_lang, &info.original_text);
+            self.set_cache_entry(
+                cache_key,
+                CacheEntry {
+                    translated_text,
+                    detected_language: detected_language.unwrap_or_else(|| "unknown".to_string()),
+                    stored_at: 0,
+                    max_age_secs: None,
+                    etag: None,
+                },
+                now_millis,
+            );
+        }
     }
 }
 
@@ -159,3 +396,289 @@ impl Default for ServiceManager {
     }
 }
 
+/// The key `translations_in_progress`/`update_translation` use for a given already-transformed
+/// request string - the same hash `get_requests` computes internally from
+/// `TranslationInfo::original_text` - so a caller driving a round trip by hand (see
+/// `apply_responses`) can look an entry up without reaching into `ServiceManager`'s private state.
+fn request_key(source_lang: &str, target_lang: &str, transformed_request: &str) -> String {
+    format!("{}:{}:{}", source_lang, target_lang, transformed_request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// A `TranslationService` driven entirely by a caller-supplied closure instead of a real
+    /// network endpoint, so `ServiceManager`'s batching, dedup, and round-trip logic can be
+    /// exercised deterministically. `transform_request` joins segments with a unit separator
+    /// purely so distinct batches hash to distinct keys; every call to it or to
+    /// `get_request_body` is recorded for assertions.
+    struct FakeTranslationService {
+        handler: Box<dyn Fn(&[TranslationInfo]) -> Result<Vec<(String, Option<String>)>, String>>,
+        transform_calls: RefCell<Vec<Vec<String>>>,
+        request_body_calls: RefCell<Vec<Vec<TranslationInfo>>>,
+    }
+
+    impl FakeTranslationService {
+        fn new(
+            handler: impl Fn(&[TranslationInfo]) -> Result<Vec<(String, Option<String>)>, String> + 'static,
+        ) -> Self {
+            Self {
+                handler: Box::new(handler),
+                transform_calls: RefCell::new(Vec::new()),
+                request_body_calls: RefCell::new(Vec::new()),
+            }
+        }
+
+        /// Runs `requests` through the handler, as if the network round trip for them had just
+        /// completed.
+        fn resolve(&self, requests: &[TranslationInfo]) -> Result<Vec<(String, Option<String>)>, String> {
+            (self.handler)(requests)
+        }
+    }
+
+    impl TranslationService for FakeTranslationService {
+        fn service_name(&self) -> &str {
+            "fake"
+        }
+
+        fn transform_request(&self, source_array: &[String]) -> String {
+            self.transform_calls.borrow_mut().push(source_array.to_vec());
+            source_array.join("\u{1f}")
+        }
+
+        fn parse_response(&self, _response: &str) -> Result<Vec<(String, Option<String>)>, String> {
+            Err("FakeTranslationService has no wire format - drive it via resolve() instead".to_string())
+        }
+
+        fn transform_response(&self, result: &str, _dont_sort: bool) -> Vec<String> {
+            vec![result.to_string()]
+        }
+
+        fn get_extra_parameters(
+            &self,
+            _source_lang: &str,
+            _target_lang: &str,
+            _requests: &[TranslationInfo],
+        ) -> String {
+            String::new()
+        }
+
+        fn get_request_body(
+            &self,
+            _source_lang: &str,
+            _target_lang: &str,
+            requests: &[TranslationInfo],
+        ) -> Option<String> {
+            self.request_body_calls.borrow_mut().push(requests.to_vec());
+            None
+        }
+
+        fn get_extra_headers(&self) -> Vec<(String, String)> {
+            Vec::new()
+        }
+
+        fn get_base_url(&self) -> String {
+            "fake://test".to_string()
+        }
+
+        fn get_method(&self) -> &str {
+            "POST"
+        }
+    }
+
+    #[test]
+    fn splits_requests_once_max_size_is_exceeded() {
+        let service = FakeTranslationService::new(|_| Ok(Vec::new()));
+        let mut manager = ServiceManager::new();
+        // MAX_SIZE is 800 bytes/batch; three ~300-byte segments should split into groups of 2 and 1.
+        let segments: Vec<Vec<String>> =
+            (0..3).map(|i| vec![format!("{}{}", "x".repeat(300), i)]).collect();
+
+        let (requests, translations) = manager.get_requests(&service, "en", "fr", &segments, 1_000_000);
+
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].len(), 2);
+        assert_eq!(requests[1].len(), 1);
+        assert_eq!(translations.len(), 3);
+    }
+
+    #[test]
+    fn dedupes_identical_in_flight_requests() {
+        let service = FakeTranslationService::new(|_| Ok(Vec::new()));
+        let mut manager = ServiceManager::new();
+        let segments = vec![vec!["hello".to_string()], vec!["hello".to_string()]];
+
+        let (requests, translations) = manager.get_requests(&service, "en", "fr", &segments, 1_000_000);
+
+        assert_eq!(requests.len(), 1, "both segments transform to the same request, so only one should be sent");
+        assert_eq!(requests[0].len(), 1);
+        assert_eq!(translations.len(), 2, "each caller-facing segment still gets its own TranslationInfo");
+    }
+
+    #[test]
+    fn full_round_trip_populates_cache_and_serves_it_next_time() {
+        let service = FakeTranslationService::new(|reqs| {
+            Ok(reqs
+                .iter()
+                .map(|r| (r.original_text.to_uppercase(), Some("en".to_string())))
+                .collect())
+        });
+        let mut manager = ServiceManager::new();
+        let segments = vec![vec!["hello".to_string()]];
+
+        let (requests, _) = manager.get_requests(&service, "en", "fr", &segments, 1_000_000);
+        let batch = &requests[0];
+        let outcomes = service.resolve(batch).unwrap();
+        manager.apply_responses(&service, "en", "fr", batch, outcomes, 1_000_000);
+
+        assert_eq!(manager.cache.len(), 1);
+
+        let (requests_again, translations_again) = manager.get_requests(&service, "en", "fr", &segments, 1_000_000);
+        assert!(requests_again.is_empty(), "the in-progress entry update_translation just completed should short-circuit this");
+        assert_eq!(translations_again[0].translated_text.as_deref(), Some("HELLO"));
+    }
+
+    #[test]
+    fn remove_translations_with_error_only_drops_errored_entries() {
+        let mut manager = ServiceManager::new();
+        manager.translations_in_progress.insert(
+            "ok".to_string(),
+            TranslationInfo {
+                original_text: "ok".to_string(),
+                translated_text: Some("ok".to_string()),
+                detected_language: None,
+                status: TranslationStatus::Complete,
+                sentence_boundaries: None,
+            },
+        );
+        manager.translations_in_progress.insert(
+            "bad".to_string(),
+            TranslationInfo {
+                original_text: "bad".to_string(),
+                translated_text: None,
+                detected_language: None,
+                status: TranslationStatus::Error,
+                sentence_boundaries: None,
+            },
+        );
+
+        manager.remove_translations_with_error();
+
+        assert_eq!(manager.translations_in_progress.len(), 1);
+        assert!(manager.translations_in_progress.contains_key("ok"));
+    }
+
+    #[test]
+    fn expired_cache_entry_is_treated_as_a_miss() {
+        let service = FakeTranslationService::new(|reqs| {
+            Ok(reqs.iter().map(|r| (r.original_text.to_uppercase(), None)).collect())
+        });
+        let mut manager = ServiceManager::with_cache_limits(DEFAULT_CACHE_CAPACITY, 60);
+        let segments = vec![vec!["hello".to_string()]];
+
+        let (requests, _) = manager.get_requests(&service, "en", "fr", &segments, 1_000_000);
+        let outcomes = service.resolve(&requests[0]).unwrap();
+        manager.apply_responses(&service, "en", "fr", &requests[0], outcomes, 1_000_000);
+
+        // Still within the 60s TTL: served from cache, no request needed.
+        let (still_cached, _) = manager.get_requests(&service, "en", "fr", &segments, 1_030_000);
+        assert!(still_cached.is_empty());
+
+        // 90s later, past the TTL: a fresh request is needed again.
+        let (expired, _) = manager.get_requests(&service, "en", "fr", &segments, 1_090_000);
+        assert_eq!(expired.len(), 1);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_entry_once_over_capacity() {
+        let mut manager = ServiceManager::with_cache_limits(2, DEFAULT_CACHE_TTL_SECS);
+        let entry = |text: &str| CacheEntry {
+            translated_text: text.to_string(),
+            detected_language: "en".to_string(),
+            stored_at: 0,
+            max_age_secs: None,
+            etag: None,
+        };
+
+        manager.set_cache_entry("a".to_string(), entry("A"), 1_000);
+        manager.set_cache_entry("b".to_string(), entry("B"), 1_001);
+        manager.set_cache_entry("c".to_string(), entry("C"), 1_002);
+
+        assert_eq!(manager.cache.len(), 2);
+        assert!(!manager.cache.contains_key("a"), "oldest entry should have been evicted");
+        assert!(manager.cache.contains_key("b"));
+        assert!(manager.cache.contains_key("c"));
+    }
+
+    #[test]
+    fn accessing_the_oldest_entry_saves_it_from_eviction() {
+        let service = FakeTranslationService::new(|_| Ok(Vec::new()));
+        let mut manager = ServiceManager::with_cache_limits(2, DEFAULT_CACHE_TTL_SECS);
+        let entry = |text: &str| CacheEntry {
+            translated_text: text.to_string(),
+            detected_language: "en".to_string(),
+            stored_at: 0,
+            max_age_secs: None,
+            etag: None,
+        };
+
+        let key_a = get_cache_key("fake", "en", "fr", "a");
+        let key_b = get_cache_key("fake", "en", "fr", "b");
+        manager.set_cache_entry(key_a.clone(), entry("A"), 1_000);
+        manager.set_cache_entry(key_b.clone(), entry("B"), 1_001);
+
+        // A cache hit on the oldest entry is itself a use - it should move "a" to the back of
+        // cache_order even though "b" was inserted more recently.
+        let (requests, translations) =
+            manager.get_requests(&service, "en", "fr", &[vec!["a".to_string()]], 1_002);
+        assert!(requests.is_empty(), "a should be served from cache, not requested again");
+        assert_eq!(translations[0].translated_text.as_deref(), Some("A"));
+
+        // Inserting a third entry now should evict "b", the true least-recently-used one, not "a".
+        manager.set_cache_entry(get_cache_key("fake", "en", "fr", "c"), entry("C"), 1_003);
+
+        assert_eq!(manager.cache.len(), 2);
+        assert!(manager.cache.contains_key(&key_a), "recently-accessed entry should survive eviction");
+        assert!(!manager.cache.contains_key(&key_b), "true least-recently-used entry should be evicted");
+    }
+
+    #[test]
+    fn export_then_import_round_trips_live_entries() {
+        let mut manager = ServiceManager::new();
+        manager.set_cache_entry(
+            "k".to_string(),
+            CacheEntry {
+                translated_text: "hola".to_string(),
+                detected_language: "es".to_string(),
+                stored_at: 0,
+                max_age_secs: None,
+                etag: None,
+            },
+            1_000_000,
+        );
+
+        let exported = manager.export_cache(1_000_000);
+
+        let mut restored = ServiceManager::new();
+        restored.import_cache(&exported);
+
+        assert_eq!(restored.cache.get("k").map(|e| e.translated_text.as_str()), Some("hola"));
+    }
+
+    #[test]
+    fn records_transform_and_request_body_calls() {
+        let service = FakeTranslationService::new(|_| Ok(Vec::new()));
+        let mut manager = ServiceManager::new();
+        let segments = vec![vec!["hi".to_string()]];
+
+        let (requests, _) = manager.get_requests(&service, "en", "fr", &segments, 1_000_000);
+        let _ = service.get_request_body("en", "fr", &requests[0]);
+
+        assert_eq!(service.transform_calls.borrow().len(), 1);
+        assert_eq!(service.transform_calls.borrow()[0], vec!["hi".to_string()]);
+        assert_eq!(service.request_body_calls.borrow().len(), 1);
+    }
+}
+
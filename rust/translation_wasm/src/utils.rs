@@ -1,5 +1,9 @@
+/// Encodes `s` for an `application/x-www-form-urlencoded` body, where a space is conventionally
+/// written as `+` rather than `%20` (e.g. `LibreService::get_request_body`'s `q=`/`source=`/
+/// `target=` fields). Not safe for a URL's query string or path: a literal `+` there means
+/// "space" to the server, silently corrupting any prompt that contains one. Use `url_encode_query`
+/// or `url_encode_path` for those.
 pub fn url_encode(s: &str) -> String {
-    // Simple URL encoding - for full implementation, use a proper URL encoding crate
     let mut encoded = String::new();
     for byte in s.bytes() {
         match byte {
@@ -16,3 +20,107 @@ pub fn url_encode(s: &str) -> String {
     encoded
 }
 
+/// Encodes `s` for use as a single query-string component's value (e.g. `GeminiService`'s
+/// `&key=…`). Unlike `url_encode`, a space becomes `%20` rather than `+` (a literal `+` instead
+/// means "space", which would mangle any value that itself contains one), and `&`, `=`, `+`, and
+/// `#` are escaped so a value can't be mistaken for a parameter separator or fragment start.
+pub fn url_encode_query(s: &str) -> String {
+    let mut encoded = String::new();
+    for byte in s.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char);
+            }
+            _ => {
+                encoded.push('%');
+                encoded.push_str(&format!("{:02X}", byte));
+            }
+        }
+    }
+    encoded
+}
+
+/// Encodes `s` for use as one path segment (e.g. `PollinationsService`'s `/{prompt}` endpoint).
+/// Everything `url_encode_query` escapes, plus `/` and `?`, since either would otherwise split the
+/// value into extra path segments or start a query string partway through a prompt.
+pub fn url_encode_path(s: &str) -> String {
+    let mut encoded = String::new();
+    for byte in s.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char);
+            }
+            _ => {
+                encoded.push('%');
+                encoded.push_str(&format!("{:02X}", byte));
+            }
+        }
+    }
+    encoded
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard (padded, `+`/`/`) base64 encoding, used to read the PEM body of a service-account
+/// private key in `rsa::parse_rsa_private_key`.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = *chunk.get(1).unwrap_or(&0);
+        let b2 = *chunk.get(2).unwrap_or(&0);
+        let triple = ((b0 as u32) << 16) | ((b1 as u32) << 8) | b2 as u32;
+        out.push(BASE64_ALPHABET[(triple >> 18 & 0x3f) as usize] as char);
+        out.push(BASE64_ALPHABET[(triple >> 12 & 0x3f) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            BASE64_ALPHABET[(triple >> 6 & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            BASE64_ALPHABET[(triple & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+/// Unpadded, URL-safe (`-`/`_`) base64 encoding, the form a JWT's header/claims/signature segments
+/// use (RFC 7515 Appendix C).
+pub fn base64url_encode_nopad(bytes: &[u8]) -> String {
+    base64_encode(bytes)
+        .trim_end_matches('=')
+        .replace('+', "-")
+        .replace('/', "_")
+}
+
+/// Decodes standard or URL-safe base64, ignoring whitespace/newlines and tolerating missing
+/// padding, so it can read both a PEM key body and a compact JWT segment.
+pub fn base64_decode(s: &str) -> Result<Vec<u8>, String> {
+    let mut bits: u32 = 0;
+    let mut bit_count = 0u32;
+    let mut out = Vec::with_capacity(s.len() / 4 * 3);
+    for c in s.chars() {
+        if c.is_whitespace() || c == '=' {
+            continue;
+        }
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => return Err(format!("invalid base64 character: {}", c)),
+        };
+        bits = (bits << 6) | value;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            out.push((bits >> bit_count) as u8);
+        }
+    }
+    Ok(out)
+}
+
@@ -0,0 +1,228 @@
+// Google Vertex AI translation service, authenticated with a GCP service-account key instead of
+// a raw API key (see `ai::GeminiService` for the API-key-based Gemini path this shares its
+// request/response shape with).
+
+use crate::service::{TranslationInfo, TranslationService};
+use serde::Deserialize;
+use serde_json::json;
+
+/// The fields this crate needs out of a GCP service-account JSON key file; everything else in
+/// that file (`private_key_id`, `client_id`, ...) is irrelevant to signing the OAuth2 assertion.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServiceAccountKey {
+    pub client_email: String,
+    pub private_key: String,
+    pub token_uri: String,
+}
+
+impl ServiceAccountKey {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("invalid service account JSON: {}", e))
+    }
+}
+
+/// Caches the Vertex AI access token between `token_request`/`ingest_token_response` round trips,
+/// the same TTL-cache shape as `auth::GoogleHelper` but without the scrape-retry bookkeeping that
+/// doesn't apply to an OAuth2 token exchange.
+#[derive(Default)]
+struct TokenCache {
+    access_token: Option<String>,
+    expires_at: Option<u64>,
+}
+
+impl TokenCache {
+    fn get(&self, now_millis: u64) -> Option<String> {
+        if self.expires_at.map(|exp| now_millis >= exp).unwrap_or(true) {
+            return None;
+        }
+        self.access_token.clone()
+    }
+
+    fn set(&mut self, access_token: String, now_millis: u64, ttl_ms: u64) {
+        self.access_token = Some(access_token);
+        self.expires_at = Some(now_millis.saturating_add(ttl_ms));
+    }
+
+    fn invalidate(&mut self) {
+        self.access_token = None;
+        self.expires_at = None;
+    }
+}
+
+const OAUTH_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+const JWT_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+/// The JWT assertion's own lifetime, per `build_jwt_assertion`'s `exp` claim.
+const JWT_TTL_SECS: u64 = 3600;
+
+/// Builds and RS256-signs the JWT assertion for a Google OAuth2 service-account token exchange:
+/// header `{"alg":"RS256","typ":"JWT"}`, claims `{iss, scope, aud, iat, exp}` per
+/// [RFC 7523](https://www.rfc-editor.org/rfc/rfc7523).
+fn build_jwt_assertion(key: &ServiceAccountKey, now_millis: u64) -> Result<String, String> {
+    let now_secs = now_millis / 1000;
+    let header = json!({"alg": "RS256", "typ": "JWT"}).to_string();
+    let claims = json!({
+        "iss": key.client_email,
+        "scope": OAUTH_SCOPE,
+        "aud": key.token_uri,
+        "iat": now_secs,
+        "exp": now_secs + JWT_TTL_SECS,
+    })
+    .to_string();
+
+    let signing_input = format!(
+        "{}.{}",
+        crate::utils::base64url_encode_nopad(header.as_bytes()),
+        crate::utils::base64url_encode_nopad(claims.as_bytes()),
+    );
+    let signature = crate::rsa::sign_rs256(&key.private_key, signing_input.as_bytes())?;
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        crate::utils::base64url_encode_nopad(&signature)
+    ))
+}
+
+pub struct VertexAIService {
+    key: Option<ServiceAccountKey>,
+    project_id: String,
+    location: String,
+    model: String,
+    token_cache: TokenCache,
+}
+
+impl VertexAIService {
+    pub fn new(key: Option<ServiceAccountKey>, project_id: String, location: String, model: String) -> Self {
+        Self {
+            key,
+            project_id,
+            location,
+            model,
+            token_cache: TokenCache::default(),
+        }
+    }
+
+    /// Whether `get_extra_headers` has no usable cached token and `token_request`/
+    /// `ingest_token_response` need to run first.
+    pub fn needs_token_refresh(&self, now_millis: u64) -> bool {
+        self.token_cache.get(now_millis).is_none()
+    }
+
+    /// Builds the `(url, body)` for the OAuth2 JWT-bearer token exchange. Like `detect_language`,
+    /// the actual POST has to flow through the caller's async HTTP bridge; feed its response back
+    /// through `ingest_token_response`.
+    pub fn token_request(&self, now_millis: u64) -> Result<(String, String), String> {
+        let key = self.key.as_ref().ok_or("vertex: no service account key configured")?;
+        let assertion = build_jwt_assertion(key, now_millis)?;
+        let body = format!(
+            "grant_type={}&assertion={}",
+            crate::utils::url_encode(JWT_GRANT_TYPE),
+            crate::utils::url_encode(&assertion),
+        );
+        Ok((key.token_uri.clone(), body))
+    }
+
+    /// Parses the token endpoint's `{"access_token": ..., "expires_in": ...}` response and caches
+    /// the token until its expiry.
+    pub fn ingest_token_response(&mut self, response: &str, now_millis: u64) -> Result<(), String> {
+        let json: serde_json::Value =
+            serde_json::from_str(response).map_err(|e| format!("invalid token response: {}", e))?;
+        let access_token = json
+            .get("access_token")
+            .and_then(|v| v.as_str())
+            .ok_or("token response missing access_token")?;
+        let expires_in = json.get("expires_in").and_then(|v| v.as_u64()).unwrap_or(JWT_TTL_SECS);
+        self.token_cache
+            .set(access_token.to_string(), now_millis, expires_in * 1000);
+        Ok(())
+    }
+}
+
+impl TranslationService for VertexAIService {
+    fn service_name(&self) -> &str {
+        "vertex"
+    }
+
+    fn transform_request(&self, source_array: &[String]) -> String {
+        source_array.join("\n")
+    }
+
+    fn parse_response(&self, response: &str) -> Result<Vec<(String, Option<String>)>, String> {
+        use serde_json::Value;
+
+        let json: Value =
+            serde_json::from_str(response).map_err(|e| format!("JSON parse error: {}", e))?;
+
+        if let Some(candidates) = json.get("candidates").and_then(|v| v.as_array()) {
+            if let Some(candidate) = candidates.get(0) {
+                if let Some(content) = candidate.get("content") {
+                    if let Some(parts) = content.get("parts").and_then(|v| v.as_array()) {
+                        if let Some(part) = parts.get(0) {
+                            if let Some(text) = part.get("text").and_then(|v| v.as_str()) {
+                                return Ok(crate::ai::split_marked_response(text));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Err("Invalid response format".to_string())
+    }
+
+    fn transform_response(&self, result: &str, _dont_sort: bool) -> Vec<String> {
+        vec![result.to_string()]
+    }
+
+    fn get_extra_parameters(
+        &self,
+        _source_lang: &str,
+        _target_lang: &str,
+        _requests: &[TranslationInfo],
+    ) -> String {
+        String::new()
+    }
+
+    fn get_request_body(
+        &self,
+        _source_lang: &str,
+        target_lang: &str,
+        requests: &[TranslationInfo],
+    ) -> Option<String> {
+        let prompt = crate::ai::build_marked_prompt(target_lang, requests);
+
+        Some(
+            json!({
+                "contents": [{
+                    "parts": [{
+                        "text": prompt
+                    }]
+                }]
+            })
+            .to_string(),
+        )
+    }
+
+    fn get_extra_headers(&self) -> Vec<(String, String)> {
+        let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+        let now_millis = unsafe { crate::now() };
+        if let Some(token) = self.token_cache.get(now_millis) {
+            headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+        }
+        headers
+    }
+
+    fn get_base_url(&self) -> String {
+        format!(
+            "https://{}-aiplatform.googleapis.com/v1/projects/{}/locations/{}/publishers/google/models/{}:generateContent",
+            self.location, self.project_id, self.location, self.model
+        )
+    }
+
+    fn get_method(&self) -> &str {
+        "POST"
+    }
+
+    fn invalidate_auth(&mut self) {
+        self.token_cache.invalidate();
+    }
+}